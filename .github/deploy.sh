@@ -25,7 +25,7 @@ rm -rf out/master/ || exit 0
 echo "Making the docs for master"
 mkdir out/master/
 cp util/gh-pages/index.html out/master
-python ./util/export.py out/master/lints.json
+./util/dev export-docs --output ../out/master/lints.json
 
 if [ -n "$TRAVIS_TAG" ]; then
     echo "Save the doc for the current tag ($TRAVIS_TAG) and point current/ to it"
@@ -23,6 +23,7 @@ export CARGO_TARGET_DIR=`pwd`/target/
 
 # Perform various checks for lint registration
 ./util/dev update_lints --check
+./util/dev export-docs --check
 ./util/dev --limit-stderr-length
 
 # Check running clippy-driver without cargo
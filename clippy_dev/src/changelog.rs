@@ -0,0 +1,285 @@
+//! Drafts a changelog entry from the changes to the lint list between two git revisions, for
+//! `util/dev changelog`.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::process::Command;
+
+const LINT_LIST: &str = "src/lintlist/mod.rs";
+const LIB_RS: &str = "clippy_lints/src/lib.rs";
+
+lazy_static! {
+    /// A `Lint` of the lint list, as formatted by `update_lints`.
+    static ref LINT_RE: Regex = Regex::new(
+        r#"Lint \{\s*name: "(?P<name>[^"]*)",\s*group: "(?P<group>[^"]*)",\s*desc: "(?P<desc>(?:[^"\\]|\\.)*)",\s*deprecation: (?:None|Some\(\s*"(?P<deprecation>(?:[^"\\]|\\.)*)",?\s*\))"#
+    )
+    .unwrap();
+    static ref RENAMED_RE: Regex =
+        Regex::new(r#"register_renamed\("clippy::(?P<old>\w+)", "clippy::(?P<new>\w+)"\)"#).unwrap();
+}
+
+/// Prints a draft of the changelog entry for the changes to the lints between `from` and `to`.
+/// Changes to the behavior of lints aren't visible in the lint list and have to be added by hand.
+pub fn run(from: &str, to: &str) -> Result<(), String> {
+    let old = Revision::read(from)?;
+    let new = Revision::read(to)?;
+    print!("{}", draft(&old, &new));
+    Ok(())
+}
+
+/// The lints at a git revision.
+#[derive(Debug, Default)]
+struct Revision {
+    hash: String,
+    lints: BTreeMap<String, LintInfo>,
+    /// The old and new names of renamed lints
+    renames: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct LintInfo {
+    group: String,
+    desc: String,
+    deprecation: Option<String>,
+}
+
+impl Revision {
+    fn read(rev: &str) -> Result<Self, String> {
+        let hash = git(&["rev-parse", "--short", &format!("{}^{{commit}}", rev)])?;
+        let lint_list = git(&["show", &format!("{}:{}", hash, LINT_LIST)])?;
+        let lib = git(&["show", &format!("{}:{}", hash, LIB_RS)])?;
+        Ok(Self {
+            hash,
+            lints: parse_lints(&lint_list),
+            renames: parse_renames(&lib),
+        })
+    }
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|err| format!("could not run git: {}", err))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+    } else {
+        Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Parses the lints of `ALL_LINTS` and `DEPRECATED_LINTS` in `src/lintlist/mod.rs`.
+fn parse_lints(lint_list: &str) -> BTreeMap<String, LintInfo> {
+    LINT_RE
+        .captures_iter(lint_list)
+        .map(|caps| {
+            (
+                caps["name"].to_string(),
+                LintInfo {
+                    group: caps["group"].to_string(),
+                    desc: unescape(&caps["desc"]),
+                    deprecation: caps.name("deprecation").map(|reason| unescape(reason.as_str())),
+                },
+            )
+        })
+        .collect()
+}
+
+fn parse_renames(lib: &str) -> Vec<(String, String)> {
+    RENAMED_RE
+        .captures_iter(lib)
+        .map(|caps| (caps["old"].to_string(), caps["new"].to_string()))
+        .collect()
+}
+
+/// Undoes the escaping of a string formatted with `{:?}`.
+fn unescape(s: &str) -> String {
+    let mut result = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(c) => result.push(c),
+            None => {},
+        }
+    }
+    result
+}
+
+/// Writes the changelog entry for the changes between `old` and `new`, in the format of
+/// `CHANGELOG.md`.
+fn draft(old: &Revision, new: &Revision) -> String {
+    let renamed: Vec<_> = new
+        .renames
+        .iter()
+        .filter(|rename| !old.renames.contains(rename))
+        .collect();
+    let is_active = |lint: Option<&LintInfo>| lint.map_or(false, |lint| lint.deprecation.is_none());
+
+    let mut new_lints = Vec::new();
+    let mut moved = Vec::new();
+    let mut deprecated = Vec::new();
+    let mut descriptions = Vec::new();
+    for (name, lint) in &new.lints {
+        let old_lint = old.lints.get(name);
+        if let Some(reason) = &lint.deprecation {
+            if is_active(old_lint) {
+                deprecated.push(format!("* Deprecated [`{}`]: {}", name, reason));
+            }
+            continue;
+        }
+        let old_lint = match old_lint {
+            Some(old_lint) => old_lint,
+            None => {
+                if !renamed.iter().any(|(_, new_name)| new_name == name) {
+                    new_lints.push(format!("  * [`{}`]", name));
+                }
+                continue;
+            },
+        };
+        if old_lint.group != lint.group {
+            moved.push(format!(
+                "* Move [`{}`] from `{}` to `{}`",
+                name, old_lint.group, lint.group
+            ));
+        }
+        if old_lint.desc != lint.desc {
+            descriptions.push(format!(
+                "* Change the description of [`{}`] from \"{}\" to \"{}\"",
+                name, old_lint.desc, lint.desc
+            ));
+        }
+    }
+
+    let mut draft = format!(
+        "[{0}...{1}](https://github.com/rust-lang/rust-clippy/compare/{0}...{1})\n\n",
+        old.hash, new.hash
+    );
+    if !new_lints.is_empty() {
+        draft.push_str("* New Lints:\n");
+    }
+    for line in new_lints
+        .into_iter()
+        .chain(
+            renamed
+                .iter()
+                .map(|(old_name, new_name)| format!("* Renamed Lint: `{}` is now called [`{}`].", old_name, new_name)),
+        )
+        .chain(moved)
+        .chain(deprecated)
+        .chain(descriptions)
+    {
+        draft.push_str(&line);
+        draft.push('\n');
+    }
+    draft
+}
+
+#[test]
+fn test_parse_lints() {
+    let lint_list = r#"pub const ALL_LINTS: [Lint; 1] = [
+    Lint {
+        name: "almost_swapped",
+        group: "correctness",
+        desc: "`foo = bar; bar = foo` sequence",
+        deprecation: None,
+        module: "swap",
+        docs: "**What it does:** Checks for `foo = bar; bar = foo` sequences.",
+    },
+];
+
+pub const DEPRECATED_LINTS: [Lint; 1] = [
+    Lint {
+        name: "assign_ops",
+        group: "Deprecated",
+        desc: "using compound assignment operators (e.g., `+=`) is \"harmless\"",
+        deprecation: Some(
+            "using compound assignment operators (e.g., `+=`) is \"harmless\"",
+        ),
+        module: "deprecated_lints",
+    },
+];
+"#;
+    let lints = parse_lints(lint_list);
+    assert_eq!(2, lints.len());
+    assert_eq!(
+        Some(&LintInfo {
+            group: "correctness".to_string(),
+            desc: "`foo = bar; bar = foo` sequence".to_string(),
+            deprecation: None,
+        }),
+        lints.get("almost_swapped")
+    );
+    assert_eq!(
+        Some("using compound assignment operators (e.g., `+=`) is \"harmless\""),
+        lints["assign_ops"].deprecation.as_ref().map(String::as_str)
+    );
+}
+
+#[test]
+fn test_draft() {
+    fn lint(group: &str, desc: &str, deprecation: Option<&str>) -> LintInfo {
+        LintInfo {
+            group: group.to_string(),
+            desc: desc.to_string(),
+            deprecation: deprecation.map(ToString::to_string),
+        }
+    }
+
+    let mut old = Revision {
+        hash: "e3cb40e".to_string(),
+        ..Revision::default()
+    };
+    old.lints
+        .insert("assign_ops".to_string(), lint("pedantic", "assign ops", None));
+    old.lints
+        .insert("cast_lossless".to_string(), lint("complexity", "lossless cast", None));
+    old.lints
+        .insert("stutter".to_string(), lint("pedantic", "stutter", None));
+    let mut new = Revision {
+        hash: "72da101".to_string(),
+        renames: vec![("stutter".to_string(), "module_name_repetitions".to_string())],
+        ..Revision::default()
+    };
+    new.lints.insert(
+        "assign_ops".to_string(),
+        lint("Deprecated", "assign ops", Some("assign ops are harmless")),
+    );
+    new.lints.insert(
+        "cast_lossless".to_string(),
+        lint("pedantic", "casts using `as` that are known to be lossless", None),
+    );
+    new.lints
+        .insert("get_last_with_len".to_string(), lint("complexity", "last", None));
+    new.lints.insert(
+        "module_name_repetitions".to_string(),
+        lint(
+            "pedantic",
+            "type names prefixed/postfixed with their containing module's name",
+            None,
+        ),
+    );
+
+    assert_eq!(
+        "[e3cb40e...72da101](https://github.com/rust-lang/rust-clippy/compare/e3cb40e...72da101)
+
+* New Lints:
+  * [`get_last_with_len`]
+* Renamed Lint: `stutter` is now called [`module_name_repetitions`].
+* Move [`cast_lossless`] from `complexity` to `pedantic`
+* Deprecated [`assign_ops`]: assign ops are harmless
+* Change the description of [`cast_lossless`] from \"lossless cast\" to \"casts using `as` that are known to be lossless\"
+",
+        draft(&old, &new)
+    );
+}
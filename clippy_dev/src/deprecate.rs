@@ -0,0 +1,322 @@
+//! Turns a lint into a deprecated lint, for `util/dev deprecate`.
+
+use clippy_dev::find_lint_declaration;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const LIB_RS: &str = "../clippy_lints/src/lib.rs";
+const DEPRECATED_LINTS: &str = "../clippy_lints/src/deprecated_lints.rs";
+const DEPRECATED_TEST: &str = "../tests/ui/deprecated.rs";
+
+lazy_static! {
+    /// The lint list of `declare_lint_pass!` and `impl_lint_pass!`.
+    static ref LINT_PASS_RE: Regex =
+        Regex::new(r#"(?P<start>(?:declare|impl)_lint_pass!\s*\([^\[]*\[)(?P<lints>[^\]]*)\]"#).unwrap();
+}
+
+/// Removes the declaration of the lint `name` and declares it as deprecated because of `reason`.
+/// If the module of the lint doesn't contain other lints, the module and its UI tests are removed
+/// as well. `util/dev update_lints` has to run afterwards.
+pub fn run(name: &str, reason: &str) -> Result<(), String> {
+    let upper_name = name.to_uppercase();
+    let (path, content, range) =
+        find_lint_declaration(name).ok_or_else(|| format!("the lint `{}` doesn't exist", name))?;
+
+    let before = content[..range.start].trim_end();
+    let after = content[range.end..].trim_start();
+    let mut content = if before.is_empty() {
+        after.to_string()
+    } else {
+        format!("{}\n\n{}", before, after)
+    };
+    let mut lint_pass_empty = false;
+    content = LINT_PASS_RE
+        .replace_all(&content, |caps: &regex::Captures<'_>| {
+            let lints = remove_lint(&caps["lints"], &upper_name);
+            lint_pass_empty |= lints.trim().is_empty();
+            format!("{}{}]", &caps["start"], lints)
+        })
+        .into_owned();
+
+    let module = path.file_stem().and_then(OsStr::to_str).unwrap_or_default().to_string();
+    let top_level = path.parent() == Some(Path::new("../clippy_lints/src")) && module != "lib";
+    if lint_pass_empty && top_level && !content.contains("declare_clippy_lint!") && !is_used(&module, &path)? {
+        fs::remove_file(&path).map_err(|err| format!("could not remove `{}`: {}", path.display(), err))?;
+        for extension in &["rs", "stderr", "fixed"] {
+            let test = Path::new("../tests/ui").join(format!("{}.{}", name, extension));
+            if test.exists() {
+                fs::remove_file(&test).map_err(|err| format!("could not remove `{}`: {}", test.display(), err))?;
+            }
+        }
+        let lib = read(Path::new(LIB_RS))?;
+        write(Path::new(LIB_RS), &remove_registration(&lib, &module))?;
+    } else {
+        write(&path, &content)?;
+    }
+
+    let deprecated_lints = read(Path::new(DEPRECATED_LINTS))?;
+    write(
+        Path::new(DEPRECATED_LINTS),
+        &(deprecated_lints + &deprecated_declaration(&upper_name, reason)),
+    )?;
+
+    let test = read(Path::new(DEPRECATED_TEST))?;
+    write(Path::new(DEPRECATED_TEST), &add_deprecated_test(&test, name))?;
+
+    Ok(())
+}
+
+/// Returns the places that still refer to the deprecated lint `name` and have to be updated by
+/// hand, like code that emits it or UI tests that enable it.
+pub fn remaining_uses(name: &str) -> Vec<String> {
+    let lint_re = Regex::new(&format!(r"\b{}\b|\bclippy::{}\b", name.to_uppercase(), name)).unwrap();
+    WalkDir::new("../clippy_lints/src")
+        .into_iter()
+        .chain(WalkDir::new("../tests"))
+        .filter_map(Result::ok)
+        .map(walkdir::DirEntry::into_path)
+        .filter(|path| {
+            !path.ends_with("clippy_lints/src/deprecated_lints.rs")
+                && !path.ends_with("clippy_lints/src/lib.rs")
+                && path.file_stem() != Some(OsStr::new("deprecated"))
+                && path.extension() == Some(OsStr::new("rs"))
+        })
+        .flat_map(|path| {
+            let content = fs::read_to_string(&path).unwrap_or_default();
+            content
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| lint_re.is_match(line))
+                .map(|(index, line)| format!("{}:{}: {}", path.display(), index + 1, line.trim()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Removes `upper_name` from the lint list of a lint pass, keeping its layout.
+fn remove_lint(lints: &str, upper_name: &str) -> String {
+    let remaining: Vec<_> = lints
+        .split(',')
+        .map(str::trim)
+        .filter(|lint| !lint.is_empty() && *lint != upper_name)
+        .collect();
+
+    if lints.contains('\n') {
+        let indent = lints
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .map_or("", |line| &line[..line.len() - line.trim_start().len()]);
+        let closing_indent = lints.rsplit('\n').next().unwrap_or("");
+        let mut result = "\n".to_string();
+        for lint in remaining {
+            result.push_str(&format!("{}{},\n", indent, lint));
+        }
+        result + closing_indent
+    } else {
+        remaining.join(", ")
+    }
+}
+
+/// Returns `true` if another module than `lib.rs` uses the module `module` in `path`.
+fn is_used(module: &str, path: &Path) -> Result<bool, String> {
+    let module_re = Regex::new(&format!(r"\b{}::", module)).unwrap();
+    for file in lint_files() {
+        if file != path && !file.ends_with("clippy_lints/src/lib.rs") && module_re.is_match(&read(&file)?) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn lint_files() -> Vec<PathBuf> {
+    WalkDir::new("../clippy_lints/src")
+        .into_iter()
+        .filter_map(Result::ok)
+        .map(walkdir::DirEntry::into_path)
+        .filter(|path| path.extension() == Some(OsStr::new("rs")))
+        .collect()
+}
+
+/// Removes the calls in `register_plugins` and `register_pre_expansion_lints` that register a
+/// lint pass of `module`. The calls can span several lines.
+fn remove_registration(lib: &str, module: &str) -> String {
+    let registration = format!("(box {}::", module);
+    let mut result = String::with_capacity(lib.len());
+    let mut rest = lib;
+    while !rest.is_empty() {
+        let line = &rest[..rest.find('\n').map_or(rest.len(), |end| end + 1)];
+        let trimmed = line.trim_start();
+        let len = if trimmed.starts_with("reg.register_") || trimmed.starts_with("store.register_") {
+            statement_len(rest).unwrap_or(line.len())
+        } else {
+            line.len()
+        };
+
+        let statement = &rest[..len];
+        if !statement.contains(&registration) {
+            result.push_str(statement);
+        }
+        rest = &rest[len..];
+    }
+    result
+}
+
+/// Returns the length of the call statement that `code` starts with, including the `;` and the
+/// line break after it.
+fn statement_len(code: &str) -> Option<usize> {
+    let open = code.find('(')?;
+    let mut depth = 0;
+    for (i, c) in code[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => continue,
+        }
+        if depth == 0 {
+            let end = open + i + 1;
+            if !code[end..].starts_with(';') {
+                return None;
+            }
+            return Some(end + 1 + usize::from(code[end + 1..].starts_with('\n')));
+        }
+    }
+    None
+}
+
+fn deprecated_declaration(upper_name: &str, reason: &str) -> String {
+    let mut docs = String::new();
+    let mut line = "/// **Deprecation reason:**".to_string();
+    for word in reason.split_whitespace() {
+        if line.len() + 1 + word.len() > 80 {
+            docs.push_str(&line);
+            docs.push('\n');
+            line = "///".to_string();
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+    docs.push_str(&line);
+
+    format!(
+        "
+/// **What it does:** Nothing. This lint has been deprecated.
+///
+{}
+declare_deprecated_lint! {{
+    pub {},
+    \"{}\"
+}}
+",
+        docs,
+        upper_name,
+        reason.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Adds `name` to the lints enabled in `tests/ui/deprecated.rs`.
+fn add_deprecated_test(test: &str, name: &str) -> String {
+    let attr = format!("#[warn(clippy::{})]\n", name);
+    let index = test.rfind("#[warn(clippy::").map_or(0, |start| {
+        start + test[start..].find('\n').map_or(test.len() - start, |end| end + 1)
+    });
+    [&test[..index], &attr, &test[index..]].concat()
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|err| format!("could not read `{}`: {}", path.display(), err))
+}
+
+fn write(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|err| format!("could not write `{}`: {}", path.display(), err))
+}
+
+#[test]
+fn test_remove_lint() {
+    assert_eq!("FOO, BAZ", remove_lint("FOO, BAR, BAZ", "BAR"));
+    assert_eq!("", remove_lint("BAR", "BAR"));
+    assert_eq!(
+        "\n    FOO,\n    BAZ,\n",
+        remove_lint("\n    FOO,\n    BAR,\n    BAZ,\n", "BAR")
+    );
+}
+
+#[test]
+fn test_remove_registration() {
+    let lib = "    reg.register_late_lint_pass(box foo::Foo);
+    reg.register_late_lint_pass(box foo_bar::FooBar);
+    reg.register_early_lint_pass(box foo::FooEarly::new(conf.foo));
+    store.register_pre_expansion_pass(Some(session), true, false, timings.wrap_pre_expansion(box foo::Foo));
+";
+    assert_eq!(
+        "    reg.register_late_lint_pass(box foo_bar::FooBar);\n",
+        remove_registration(lib, "foo")
+    );
+}
+
+#[test]
+fn test_remove_multi_line_registration() {
+    let lib = "    reg.register_late_lint_pass(box foo::Foo::new(
+            conf.foo_names.iter().cloned().collect()
+    ));
+    reg.register_late_lint_pass(box bar::Bar::new(conf));
+
+    store.register_pre_expansion_pass(
+        Some(session),
+        true,
+        false,
+        timings.wrap_pre_expansion(box foo::FooEarly {
+            threshold: conf.foo_threshold,
+        }),
+    );
+    store.register_pre_expansion_pass(
+        Some(session),
+        true,
+        false,
+        timings.wrap_pre_expansion(box bar::BarEarly),
+    );
+}
+";
+    let expected = "    reg.register_late_lint_pass(box bar::Bar::new(conf));
+
+    store.register_pre_expansion_pass(
+        Some(session),
+        true,
+        false,
+        timings.wrap_pre_expansion(box bar::BarEarly),
+    );
+}
+";
+    assert_eq!(expected, remove_registration(lib, "foo"));
+}
+
+#[test]
+fn test_deprecated_declaration() {
+    assert_eq!(
+        "
+/// **What it does:** Nothing. This lint has been deprecated.
+///
+/// **Deprecation reason:** this lint has been superseded by the `invalid_value`
+/// rustc lint
+declare_deprecated_lint! {
+    pub INVALID_REF,
+    \"this lint has been superseded by the `invalid_value` rustc lint\"
+}
+",
+        deprecated_declaration(
+            "INVALID_REF",
+            "this lint has been superseded by the `invalid_value` rustc lint"
+        )
+    );
+}
+
+#[test]
+fn test_add_deprecated_test() {
+    assert_eq!(
+        "#[warn(clippy::str_to_string)]\n#[warn(clippy::foo)]\n\nfn main() {}\n",
+        add_deprecated_test("#[warn(clippy::str_to_string)]\n\nfn main() {}\n", "foo")
+    );
+}
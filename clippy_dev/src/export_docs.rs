@@ -0,0 +1,200 @@
+//! Generates the `lints.json` that the lint list in `util/gh-pages/index.html` is rendered from.
+
+use clippy_dev::{gather_all, gather_conf, is_rust_block, ConfKey, Level, Lint, LINT_LEVELS};
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::process;
+
+lazy_static! {
+    /// The first line of a section of the docs, e.g. `**Why is this bad?** It is slow.`
+    static ref SECTION_RE: Regex = Regex::new(r#"^\*\*([\w\s]+?)[:?.!]?\*\*(.*)"#).unwrap();
+}
+
+pub fn run(output: &str, check: bool) {
+    let conf = gather_conf();
+    let mut lints: Vec<Lint> = gather_all().filter(|lint| !lint.is_internal()).collect();
+    lints.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut errors = Vec::new();
+    for key in &conf {
+        for name in &key.lints {
+            if !lints.iter().any(|lint| lint.name == *name) {
+                errors.push(format!(
+                    "`{}` in `define_Conf!` refers to the unknown lint `{}`",
+                    key.name, name
+                ));
+            }
+        }
+    }
+
+    let exported: Vec<Value> = lints
+        .iter()
+        .filter_map(|lint| match export_lint(lint, &conf) {
+            Ok(exported) => Some(exported),
+            Err(err) => {
+                errors.push(format!("lint `{}`: {}", lint.name, err));
+                None
+            },
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        for error in errors {
+            eprintln!("error: {}", error);
+        }
+        process::exit(1);
+    }
+
+    if !check {
+        let json = serde_json::to_string_pretty(&exported).expect("lints can be serialized");
+        if let Err(err) = fs::write(output, json + "\n") {
+            eprintln!("error: could not write `{}`: {}", output, err);
+            process::exit(1);
+        }
+        println!("wrote the documentation of {} lints to `{}`", exported.len(), output);
+    }
+}
+
+/// Converts a lint into the format expected by `index.html`.
+fn export_lint(lint: &Lint, conf: &[ConfKey]) -> Result<Value, String> {
+    let (group, level) = if lint.deprecation.is_some() {
+        ("deprecated", "Deprecated")
+    } else {
+        let level = match LINT_LEVELS.iter().find(|(group, _)| *group == lint.group) {
+            Some((_, Level::Deny)) => "Deny",
+            Some((_, Level::Warn)) => "Warn",
+            Some((_, Level::Allow)) => "Allow",
+            None => return Err(format!("unknown lint group `{}`", lint.group)),
+        };
+        (lint.group.as_str(), level)
+    };
+
+    let mut docs = doc_sections(&lint.docs)?;
+    let keys: Vec<_> = conf.iter().filter(|key| key.lints.contains(&lint.name)).collect();
+    if !keys.is_empty() {
+        let variables = keys
+            .iter()
+            .map(|key| {
+                format!(
+                    "* `{}: {}`: {} (defaults to `{}`).",
+                    key.name, key.ty, key.doc, key.default
+                )
+            })
+            .join("\n");
+        docs.insert(
+            "Configuration".to_string(),
+            json!(format!(
+                "This lint has the following configuration variables:\n\n{}",
+                variables
+            )),
+        );
+    }
+
+    Ok(json!({
+        "id": lint.name,
+        "group": group,
+        "level": level,
+        "docs": docs,
+    }))
+}
+
+/// Splits the docs of a lint into the sections that start with a bold heading, like
+/// `**What it does:**`. Lines of Rust code blocks that start with `# ` are hidden, like in rustdoc.
+fn doc_sections(docs: &str) -> Result<Map<String, Value>, String> {
+    let mut sections: Vec<(String, String)> = Vec::new();
+    let mut rust_block = None;
+
+    for line in docs.lines() {
+        let mut text = line;
+        if line.starts_with("```") {
+            rust_block = match rust_block {
+                Some(_) => None,
+                None => Some(is_rust_block(&line[3..])),
+            };
+        } else if rust_block == Some(true) && (line == "#" || line.starts_with("# ")) {
+            continue;
+        } else if let (None, Some(caps)) = (rust_block, SECTION_RE.captures(line)) {
+            sections.push((caps[1].to_string(), String::new()));
+            text = caps.get(2).map_or("", |m| m.as_str());
+        }
+
+        match sections.last_mut() {
+            Some((_, section)) => {
+                section.push_str(text);
+                section.push('\n');
+            },
+            None if line.trim().is_empty() => {},
+            None => return Err("the documentation must start with a section like `**What it does:**`".to_string()),
+        }
+    }
+
+    if sections.is_empty() {
+        return Err("the lint has no documentation".to_string());
+    }
+
+    Ok(sections
+        .into_iter()
+        .map(|(title, text)| (title, json!(text.trim())))
+        .collect())
+}
+
+#[test]
+fn test_doc_sections() {
+    let docs = "**What it does:** Checks for `foo`.\n\
+                \n\
+                **Why is this bad?** It's\nconfusing.\n\
+                \n\
+                **Example:**\n\
+                ```rust\n\
+                # let foo = 1;\n\
+                foo;\n\
+                ```\n\
+                ```toml\n\
+                # a comment\n\
+                ```";
+    let sections = doc_sections(docs).unwrap();
+    assert_eq!(
+        vec!["What it does", "Why is this bad", "Example"],
+        sections.keys().collect::<Vec<_>>()
+    );
+    assert_eq!("Checks for `foo`.", sections["What it does"]);
+    assert_eq!("It's\nconfusing.", sections["Why is this bad"]);
+    assert_eq!("```rust\nfoo;\n```\n```toml\n# a comment\n```", sections["Example"]);
+
+    assert!(doc_sections("Checks for `foo`.").is_err());
+}
+
+#[test]
+fn test_export_lint() {
+    let lint = Lint::new(
+        "too_many_lines",
+        "pedantic",
+        "functions with too many lines",
+        None,
+        "functions",
+    )
+    .with_docs("/// **What it does:** Checks for long functions.");
+    let conf = vec![ConfKey {
+        name: "too-many-lines-threshold".to_string(),
+        lints: vec!["too_many_lines".to_string()],
+        doc: "The maximum number of lines a function or method can have".to_string(),
+        ty: "u64".to_string(),
+        default: "100".to_string(),
+    }];
+
+    let expected = json!({
+        "id": "too_many_lines",
+        "group": "pedantic",
+        "level": "Allow",
+        "docs": {
+            "What it does": "Checks for long functions.",
+            "Configuration": "This lint has the following configuration variables:\n\n\
+                * `too-many-lines-threshold: u64`: The maximum number of lines a function or method can have \
+                (defaults to `100`).",
+        },
+    });
+    assert_eq!(expected, export_lint(&lint, &conf).unwrap());
+}
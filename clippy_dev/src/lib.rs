@@ -1,17 +1,23 @@
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fs;
 use std::io::prelude::*;
+use std::ops::Range;
+use std::path::PathBuf;
 use walkdir::WalkDir;
 
+#[path = "../../src/lintlist/shared.rs"]
+mod shared;
+pub use shared::{is_rust_block, Level, LINT_LEVELS};
+
 lazy_static! {
     static ref DEC_CLIPPY_LINT_RE: Regex = Regex::new(
         r#"(?x)
         declare_clippy_lint!\s*[\{(]
-        (?:\s+///.*)*
+        (?P<docs>(?:\s+///.*)*)
         \s+pub\s+(?P<name>[A-Z_][A-Z_0-9]*)\s*,\s*
         (?P<cat>[a-z_]+)\s*,\s*
         "(?P<desc>(?:[^"\\]+|\\(?s).(?-s))*)"\s*[})]
@@ -20,6 +26,7 @@ lazy_static! {
     .unwrap();
     static ref DEC_DEPRECATED_LINT_RE: Regex = Regex::new(
         r#"(?x)
+        (?P<docs>(?:[\ \t]*///.*\n)*)
         declare_deprecated_lint!\s*[{(]\s*
         (?:\s+///.*)*
         \s+pub\s+(?P<name>[A-Z_][A-Z_0-9]*)\s*,\s*
@@ -28,6 +35,14 @@ lazy_static! {
     )
     .unwrap();
     static ref NL_ESCAPE_RE: Regex = Regex::new(r#"\\\n\s*"#).unwrap();
+    static ref REGISTERED_LINT_RE: Regex = Regex::new(r#"\w+::(?P<name>[A-Z][A-Z0-9_]*)\b"#).unwrap();
+    static ref CONF_RE: Regex = Regex::new(
+        r#"(?xm)
+        ^\s*///\ Lint:\ (?P<lints>[\w,\ ]+)\.\ (?P<doc>.*)\n
+        \s*\(\w+,\s*"(?P<name>\w+)",\s*(?P<default>[^=]+)=>\s*(?P<ty>.*)\),
+    "#
+    )
+    .unwrap();
     pub static ref DOCS_LINK: String = "https://rust-lang.github.io/rust-clippy/master/index.html".to_string();
 }
 
@@ -39,6 +54,8 @@ pub struct Lint {
     pub desc: String,
     pub deprecation: Option<String>,
     pub module: String,
+    /// The markdown documentation from the doc comments of the lint declaration.
+    pub docs: String,
 }
 
 impl Lint {
@@ -49,9 +66,28 @@ impl Lint {
             desc: NL_ESCAPE_RE.replace(&desc.replace("\\\"", "\""), "").to_string(),
             deprecation: deprecation.map(std::string::ToString::to_string),
             module: module.to_string(),
+            docs: String::new(),
         }
     }
 
+    /// Sets the documentation from the `///` lines preceding the lint name.
+    pub fn with_docs(mut self, doc_comments: &str) -> Self {
+        self.docs = doc_comments
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("///"))
+            .map(|line| {
+                let line = &line[3..];
+                if line.starts_with(' ') {
+                    &line[1..]
+                } else {
+                    line
+                }
+            })
+            .join("\n");
+        self
+    }
+
     /// Returns all non-deprecated lints and non-internal lints
     pub fn usable_lints(lints: impl Iterator<Item = Self>) -> impl Iterator<Item = Self> {
         lints.filter(|l| l.deprecation.is_none() && !l.is_internal())
@@ -136,6 +172,52 @@ pub fn gen_deprecated(lints: &[Lint]) -> Vec<String> {
         .collect::<Vec<String>>()
 }
 
+/// A `clippy.toml` key from `define_Conf!` in `clippy_lints/src/utils/conf.rs`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfKey {
+    /// The key as it is written in `clippy.toml`, e.g. `too-many-lines-threshold`.
+    pub name: String,
+    /// The lowercase names of the lints the key applies to.
+    pub lints: Vec<String>,
+    pub doc: String,
+    pub ty: String,
+    /// The default value as Rust code.
+    pub default: String,
+}
+
+/// Generates the `ConfKey` items of `CONF_KEYS` in `src/lintlist/mod.rs`.
+pub fn gen_conf_list(keys: &[ConfKey]) -> Vec<String> {
+    let mut lines = vec![format!("pub const CONF_KEYS: [ConfKey; {}] = [", keys.len())];
+    for key in keys {
+        let lints = key.lints.iter().map(|lint| format!("{:?}", lint)).join(", ");
+        lines.push("    ConfKey {".to_string());
+        lines.push(format!("        name: {:?},", key.name));
+        lines.push(format!("        lints: &[{}],", lints));
+        lines.push(format!("        doc: {:?},", key.doc));
+        lines.push("    },".to_string());
+    }
+    lines.push("];".to_string());
+    lines
+}
+
+/// Gathers the configuration keys from `clippy_lints/src/utils/conf.rs`
+pub fn gather_conf() -> Vec<ConfKey> {
+    parse_conf(&fs::read_to_string("../clippy_lints/src/utils/conf.rs").unwrap())
+}
+
+fn parse_conf(content: &str) -> Vec<ConfKey> {
+    CONF_RE
+        .captures_iter(content)
+        .map(|m| ConfKey {
+            name: m["name"].replace('_', "-"),
+            lints: m["lints"].split(',').map(|lint| lint.trim().to_lowercase()).collect(),
+            doc: m["doc"].trim().to_string(),
+            ty: m["ty"].trim().to_string(),
+            default: m["default"].split_whitespace().join(" "),
+        })
+        .collect()
+}
+
 /// Gathers all files in `src/clippy_lints` and gathers all lints inside
 pub fn gather_all() -> impl Iterator<Item = Lint> {
     lint_files().flat_map(|f| gather_from_file(&f))
@@ -161,13 +243,43 @@ fn gather_from_file(dir_entry: &walkdir::DirEntry) -> impl Iterator<Item = Lint>
     parse_contents(&content, filename)
 }
 
+/// Returns the groups of the lints that `register_plugins` in `lib_rs` registers in `clippy::all`.
+pub fn groups_in_all(lib_rs: &str, lints: &[Lint]) -> Vec<String> {
+    let start = lib_rs
+        .find(r#"reg.register_lint_group("clippy::all""#)
+        .expect("`clippy::all` is not registered in `clippy_lints/src/lib.rs`");
+    let call = &lib_rs[start..];
+    let call = &call[..call.find("]);").unwrap()];
+
+    let names: HashSet<_> = REGISTERED_LINT_RE
+        .captures_iter(call)
+        .map(|m| m["name"].to_lowercase())
+        .collect();
+    lints
+        .iter()
+        .filter(|lint| names.contains(&lint.name))
+        .map(|lint| lint.group.clone())
+        .unique()
+        .sorted()
+        .collect()
+}
+
+/// Generates `ALL_GROUPS` in `src/lintlist/mod.rs`.
+pub fn gen_all_groups(groups: &[String]) -> Vec<String> {
+    vec![format!(
+        "pub const ALL_GROUPS: [&str; {}] = [{}];",
+        groups.len(),
+        groups.iter().map(|group| format!("{:?}", group)).join(", ")
+    )]
+}
+
 fn parse_contents(content: &str, filename: &str) -> impl Iterator<Item = Lint> {
     let lints = DEC_CLIPPY_LINT_RE
         .captures_iter(content)
-        .map(|m| Lint::new(&m["name"], &m["cat"], &m["desc"], None, filename));
+        .map(|m| Lint::new(&m["name"], &m["cat"], &m["desc"], None, filename).with_docs(&m["docs"]));
     let deprecated = DEC_DEPRECATED_LINT_RE
         .captures_iter(content)
-        .map(|m| Lint::new(&m["name"], "Deprecated", &m["desc"], Some(&m["desc"]), filename));
+        .map(|m| Lint::new(&m["name"], "Deprecated", &m["desc"], Some(&m["desc"]), filename).with_docs(&m["docs"]));
     // Removing the `.collect::<Vec<Lint>>().into_iter()` causes some lifetime issues due to the map
     lints.chain(deprecated).collect::<Vec<Lint>>().into_iter()
 }
@@ -182,6 +294,43 @@ fn lint_files() -> impl Iterator<Item = walkdir::DirEntry> {
         .filter(|f| f.path().extension() == Some(OsStr::new("rs")))
 }
 
+/// Returns `true` if `name` can be the name of a lint, i.e. it is in snake_case.
+pub fn is_valid_lint_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Converts a lint or module name to the name of its lint pass, e.g. `foo_bar` to `FooBar`.
+pub fn to_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Finds the `declare_clippy_lint!` of the lint `name`. Returns the file it is in, the content of
+/// that file and the byte range of the declaration.
+pub fn find_lint_declaration(name: &str) -> Option<(PathBuf, String, Range<usize>)> {
+    let upper_name = name.to_uppercase();
+    lint_files().find_map(|entry| {
+        let content = fs::read_to_string(entry.path()).ok()?;
+        let range = DEC_CLIPPY_LINT_RE
+            .captures_iter(&content)
+            .find(|caps| caps["name"] == upper_name)?
+            .get(0)?
+            .range();
+        Some((entry.path().to_path_buf(), content, range))
+    })
+}
+
 /// Whether a file has had its text changed or not
 #[derive(PartialEq, Debug)]
 pub struct FileChange {
@@ -294,6 +443,12 @@ fn test_parse_contents() {
     let result: Vec<Lint> = parse_contents(
         r#"
 declare_clippy_lint! {
+    /// **What it does:** Checks for `&Vec` arguments.
+    ///
+    /// **Example:**
+    /// ```rust
+    /// fn foo(v: &Vec<u8>) {}
+    /// ```
     pub PTR_ARG,
     style,
     "really long \
@@ -306,7 +461,7 @@ declare_clippy_lint!{
     "single line"
 }
 
-/// some doc comment
+/// **What it does:** Nothing. This lint has been deprecated.
 declare_deprecated_lint! {
     pub SHOULD_ASSERT_EQ,
     "`assert!()` will be more flexible with RFC 2011"
@@ -317,7 +472,10 @@ declare_deprecated_lint! {
     .collect();
 
     let expected = vec![
-        Lint::new("ptr_arg", "style", "really long text", None, "module_name"),
+        Lint::new("ptr_arg", "style", "really long text", None, "module_name").with_docs(
+            "/// **What it does:** Checks for `&Vec` arguments.\n///\n/// **Example:**\n\
+             /// ```rust\n/// fn foo(v: &Vec<u8>) {}\n/// ```",
+        ),
         Lint::new("doc_markdown", "pedantic", "single line", None, "module_name"),
         Lint::new(
             "should_assert_eq",
@@ -325,9 +483,95 @@ declare_deprecated_lint! {
             "`assert!()` will be more flexible with RFC 2011",
             Some("`assert!()` will be more flexible with RFC 2011"),
             "module_name",
-        ),
+        )
+        .with_docs("/// **What it does:** Nothing. This lint has been deprecated."),
     ];
     assert_eq!(expected, result);
+    assert_eq!(
+        "**What it does:** Checks for `&Vec` arguments.\n\n**Example:**\n```rust\nfn foo(v: &Vec<u8>) {}\n```",
+        result[0].docs
+    );
+}
+
+#[test]
+fn test_groups_in_all() {
+    let lib_rs = r#"
+    reg.register_lint_group("clippy::pedantic", Some("clippy_pedantic"), vec![
+        types::LINKEDLIST,
+    ]);
+
+    reg.register_lint_group("clippy::all", Some("clippy"), vec![
+        approx_const::APPROX_CONSTANT,
+        misc::SHORT_CIRCUIT_STATEMENT,
+    ]);
+"#;
+    let lints = vec![
+        Lint::new("approx_constant", "correctness", "abc", None, "approx_const"),
+        Lint::new("linkedlist", "pedantic", "abc", None, "types"),
+        Lint::new("short_circuit_statement", "complexity", "abc", None, "misc"),
+    ];
+    assert_eq!(vec!["complexity", "correctness"], groups_in_all(lib_rs, &lints));
+    assert_eq!(
+        vec![r#"pub const ALL_GROUPS: [&str; 2] = ["complexity", "correctness"];"#],
+        gen_all_groups(&groups_in_all(lib_rs, &lints))
+    );
+}
+
+#[test]
+fn test_parse_conf() {
+    let content = r#"
+define_Conf! {
+    /// Lint: BLACKLISTED_NAME. The list of blacklisted names to lint about
+    (blacklisted_names, "blacklisted_names", [
+        "foo", "bar",
+    ] => Vec<String>),
+    /// DEPRECATED LINT: CYCLOMATIC_COMPLEXITY. Use the Cognitive Complexity lint instead.
+    (cyclomatic_complexity_threshold, "cyclomatic_complexity_threshold", None => Option<u64>),
+    /// Lint: QUESTION_MARK, REDUNDANT_STATIC_LIFETIMES. The minimum rust version that the project supports
+    (msrv, "msrv", None => Option<String>),
+}
+"#;
+    let expected = vec![
+        ConfKey {
+            name: "blacklisted-names".to_string(),
+            lints: vec!["blacklisted_name".to_string()],
+            doc: "The list of blacklisted names to lint about".to_string(),
+            ty: "Vec<String>".to_string(),
+            default: "[ \"foo\", \"bar\", ]".to_string(),
+        },
+        ConfKey {
+            name: "msrv".to_string(),
+            lints: vec!["question_mark".to_string(), "redundant_static_lifetimes".to_string()],
+            doc: "The minimum rust version that the project supports".to_string(),
+            ty: "Option<String>".to_string(),
+            default: "None".to_string(),
+        },
+    ];
+    assert_eq!(expected, parse_conf(content));
+    assert_eq!(
+        vec![
+            "pub const CONF_KEYS: [ConfKey; 2] = [",
+            "    ConfKey {",
+            "        name: \"blacklisted-names\",",
+            "        lints: &[\"blacklisted_name\"],",
+            "        doc: \"The list of blacklisted names to lint about\",",
+            "    },",
+            "    ConfKey {",
+            "        name: \"msrv\",",
+            "        lints: &[\"question_mark\", \"redundant_static_lifetimes\"],",
+            "        doc: \"The minimum rust version that the project supports\",",
+            "    },",
+            "];",
+        ],
+        gen_conf_list(&expected)
+    );
+}
+
+#[test]
+fn test_camel_case() {
+    assert_eq!("FooFunctions", to_camel_case("foo_functions"));
+    assert_eq!("Foo", to_camel_case("foo"));
+    assert_eq!("Int2Float", to_camel_case("int2_float"));
 }
 
 #[test]
@@ -0,0 +1,383 @@
+//! Runs Clippy over a corpus of crates and reports its warnings, for `util/dev lintcheck`.
+//!
+//! The crates are listed in a TOML file, as local directories or `.crate` files downloaded from
+//! crates.io. Nothing is fetched from the network, so dependencies of the crates have to be in the
+//! local cargo cache. The report contains one sorted line per warning, so reports of two Clippy
+//! versions can be compared with `--diff`.
+
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The lint groups enabled for the corpus.
+const LINT_GROUPS: [&str; 5] = [
+    "clippy::all",
+    "clippy::pedantic",
+    "clippy::nursery",
+    "clippy::cargo",
+    "clippy::restriction",
+];
+
+/// The directory the crate files are extracted to and the crates are built in.
+const WORK_DIR: &str = "../target/lintcheck";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CrateList {
+    crates: BTreeMap<String, CrateSource>,
+}
+
+/// Where to find a crate, relative to the crate list.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CrateSource {
+    path: Option<PathBuf>,
+    crate_file: Option<PathBuf>,
+}
+
+impl CrateSource {
+    /// Returns the directory of the crate, extracting it first if it is a `.crate` file.
+    fn dir(&self, name: &str, list_dir: &Path) -> Result<PathBuf, String> {
+        match (&self.path, &self.crate_file) {
+            (Some(path), None) => canonicalize(&list_dir.join(path)),
+            (None, Some(crate_file)) => extract(&list_dir.join(crate_file), &Path::new(WORK_DIR).join("sources")),
+            _ => Err(format!("crate `{}` needs exactly one of `path` and `crate_file`", name)),
+        }
+    }
+}
+
+/// A warning emitted while linting the corpus.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Warning {
+    lint: String,
+    /// The file the warning points to, prefixed with the name of the crate.
+    file: String,
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}:{} {}",
+            self.lint,
+            self.file,
+            self.line,
+            self.message.replace('\n', "\\n")
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    message: String,
+    code: Option<DiagnosticCode>,
+    spans: Vec<DiagnosticSpan>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticSpan {
+    file_name: String,
+    line_start: usize,
+    is_primary: bool,
+}
+
+/// Lints all crates of the crate list `crates` and writes the report to `output`. If `diff` is
+/// given, the warnings added and removed since that report are printed.
+pub fn run(crates: &str, output: &str, diff: Option<&str>) -> Result<(), String> {
+    let list_path = Path::new(crates);
+    let list: CrateList =
+        toml::from_str(&read(list_path)?).map_err(|err| format!("could not parse `{}`: {}", crates, err))?;
+    let list_dir = list_path.parent().unwrap_or_else(|| Path::new("."));
+    let old_report = diff.map(|path| read(Path::new(path))).transpose()?;
+
+    build_clippy()?;
+    let cargo_clippy = canonicalize(Path::new("../target/debug/cargo-clippy"))?;
+    fs::create_dir_all(WORK_DIR).map_err(|err| format!("could not create `{}`: {}", WORK_DIR, err))?;
+    let target_dir = canonicalize(Path::new(WORK_DIR))?.join("target");
+
+    let mut warnings = Vec::new();
+    for (name, source) in &list.crates {
+        println!("linting {}", name);
+        let dir = source.dir(name, list_dir)?;
+        warnings.extend(lint_crate(&cargo_clippy, name, &dir, &target_dir.join(name))?);
+    }
+    // A line can have the same warning several times, e.g. in code generated by a macro.
+    warnings.sort();
+
+    let report: String = warnings.iter().map(|warning| format!("{}\n", warning)).collect();
+    if let Some(parent) = Path::new(output).parent() {
+        fs::create_dir_all(parent).map_err(|err| format!("could not create `{}`: {}", parent.display(), err))?;
+    }
+    fs::write(output, &report).map_err(|err| format!("could not write `{}`: {}", output, err))?;
+    println!("wrote {} warnings to `{}`", warnings.len(), output);
+
+    if let Some(old_report) = old_report {
+        let (added, removed) = diff_reports(&old_report, &report);
+        for line in &added {
+            println!("+ {}", line);
+        }
+        for line in &removed {
+            println!("- {}", line);
+        }
+        println!("{} warnings added, {} warnings removed", added.len(), removed.len());
+    }
+
+    Ok(())
+}
+
+fn build_clippy() -> Result<(), String> {
+    let status = Command::new("cargo")
+        .current_dir("..")
+        .arg("build")
+        .status()
+        .map_err(|err| format!("could not run cargo: {}", err))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("could not build Clippy".to_string())
+    }
+}
+
+/// Runs `cargo-clippy` in `dir` and collects the Clippy warnings. A crate that fails to compile
+/// only produces a warning, since the rest of the corpus can still be linted.
+fn lint_crate(cargo_clippy: &Path, name: &str, dir: &Path, target_dir: &Path) -> Result<Vec<Warning>, String> {
+    // Clippy only lints crates that cargo considers out of date.
+    for package in workspace_packages(dir)? {
+        Command::new("cargo")
+            .current_dir(dir)
+            .args(&["clean", "-p", &package, "--target-dir"])
+            .arg(target_dir)
+            .output()
+            .map_err(|err| format!("could not run cargo: {}", err))?;
+    }
+
+    let mut cmd = Command::new(cargo_clippy);
+    cmd.current_dir(dir)
+        .env("CARGO_TARGET_DIR", target_dir)
+        .env("CARGO_NET_OFFLINE", "true")
+        .args(&["clippy", "--message-format=json", "--"]);
+    for group in &LINT_GROUPS {
+        cmd.args(&["-W", group]);
+    }
+    let output = cmd
+        .output()
+        .map_err(|err| format!("could not run `{}`: {}", cargo_clippy.display(), err))?;
+
+    if !output.status.success() {
+        eprintln!(
+            "warning: `{}` failed to compile:\n{}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_messages(name, &String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Returns the names of the packages in the workspace in `dir`.
+fn workspace_packages(dir: &Path) -> Result<Vec<String>, String> {
+    let output = Command::new("cargo")
+        .current_dir(dir)
+        .args(&["metadata", "--no-deps", "--format-version", "1"])
+        .output()
+        .map_err(|err| format!("could not run cargo: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "`cargo metadata` failed in `{}`: {}",
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("could not parse the output of `cargo metadata`: {}", err))?;
+    Ok(metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|package| package["name"].as_str())
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Collects the Clippy warnings from the output of `cargo clippy --message-format=json`.
+fn parse_messages(name: &str, stdout: &str) -> Vec<Warning> {
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoMessage>(line).ok())
+        .filter(|message| message.reason == "compiler-message")
+        .filter_map(|message| message.message)
+        .filter_map(|diagnostic| {
+            let lint = diagnostic.code.as_ref()?.code.clone();
+            if !lint.starts_with("clippy::") {
+                return None;
+            }
+            let span = diagnostic.spans.iter().find(|span| span.is_primary)?;
+            Some(Warning {
+                lint: lint["clippy::".len()..].to_string(),
+                file: format!("{}/{}", name, span.file_name),
+                line: span.line_start,
+                message: diagnostic.message.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Extracts the `.crate` file `crate_file` into `sources`, unless it has already been extracted.
+fn extract(crate_file: &Path, sources: &Path) -> Result<PathBuf, String> {
+    let stem = crate_file
+        .file_stem()
+        .ok_or_else(|| format!("`{}` is not a `.crate` file", crate_file.display()))?;
+    let dir = sources.join(stem);
+    if !dir.exists() {
+        let file =
+            File::open(crate_file).map_err(|err| format!("could not open `{}`: {}", crate_file.display(), err))?;
+        tar::Archive::new(GzDecoder::new(file))
+            .unpack(sources)
+            .map_err(|err| format!("could not extract `{}`: {}", crate_file.display(), err))?;
+    }
+    if dir.is_dir() {
+        canonicalize(&dir)
+    } else {
+        Err(format!(
+            "`{}` doesn't contain the directory `{}`",
+            crate_file.display(),
+            stem.to_string_lossy()
+        ))
+    }
+}
+
+/// Returns the lines that were added and removed between two reports. A line that occurs more
+/// often in one of the reports is added or removed as often as the counts differ.
+fn diff_reports<'a>(old: &'a str, new: &'a str) -> (Vec<&'a str>, Vec<&'a str>) {
+    let mut old: Vec<_> = old.lines().collect();
+    let mut new: Vec<_> = new.lines().collect();
+    old.sort();
+    new.sort();
+
+    let mut old = old.into_iter().peekable();
+    let mut new = new.into_iter().peekable();
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    loop {
+        match (old.peek(), new.peek()) {
+            (Some(old_line), Some(new_line)) if old_line == new_line => {
+                old.next();
+                new.next();
+            },
+            (Some(old_line), Some(new_line)) if old_line < new_line => removed.extend(old.next()),
+            (Some(_), None) => removed.extend(old.next()),
+            (_, Some(_)) => added.extend(new.next()),
+            (None, None) => break,
+        }
+    }
+    (added, removed)
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|err| format!("could not read `{}`: {}", path.display(), err))
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, String> {
+    fs::canonicalize(path).map_err(|err| format!("could not resolve `{}`: {}", path.display(), err))
+}
+
+#[test]
+fn test_parse_messages() {
+    let stdout = r#"{"reason":"compiler-artifact","package_id":"foo 0.1.0"}
+{"reason":"compiler-message","message":{"message":"unneeded return statement","code":{"code":"clippy::needless_return"},"spans":[{"file_name":"src/main.rs","line_start":3,"is_primary":true}]}}
+{"reason":"compiler-message","message":{"message":"unused variable: `x`","code":{"code":"unused_variables"},"spans":[{"file_name":"src/main.rs","line_start":2,"is_primary":true}]}}
+Compiling foo v0.1.0"#;
+    let warnings = parse_messages("foo", stdout);
+    assert_eq!(1, warnings.len());
+    assert_eq!(
+        "needless_return foo/src/main.rs:3 unneeded return statement",
+        warnings[0].to_string()
+    );
+}
+
+#[test]
+fn test_warning_order() {
+    let warning = |lint: &str, line| Warning {
+        lint: lint.to_string(),
+        file: "foo/src/lib.rs".to_string(),
+        line,
+        message: String::new(),
+    };
+    let mut warnings = vec![
+        warning("needless_return", 10),
+        warning("needless_return", 9),
+        warning("box_vec", 20),
+    ];
+    warnings.sort();
+    assert_eq!(
+        vec![
+            warning("box_vec", 20),
+            warning("needless_return", 9),
+            warning("needless_return", 10)
+        ],
+        warnings
+    );
+}
+
+#[test]
+fn test_diff_reports() {
+    let old = "box_vec foo/src/lib.rs:1 a\nneedless_return foo/src/lib.rs:2 b\n";
+    let new = "needless_return foo/src/lib.rs:2 b\nneedless_return foo/src/lib.rs:5 c\n";
+    assert_eq!(
+        (
+            vec!["needless_return foo/src/lib.rs:5 c"],
+            vec!["box_vec foo/src/lib.rs:1 a"]
+        ),
+        diff_reports(old, new)
+    );
+
+    let twice = "needless_return foo/src/lib.rs:2 b\nneedless_return foo/src/lib.rs:2 b\n";
+    assert_eq!(
+        (vec!["needless_return foo/src/lib.rs:2 b"], vec![]),
+        diff_reports(old.lines().nth(1).unwrap(), twice)
+    );
+    assert_eq!(
+        (vec![], vec!["needless_return foo/src/lib.rs:2 b"]),
+        diff_reports(twice, old.lines().nth(1).unwrap())
+    );
+}
+
+#[test]
+fn test_crate_list() {
+    let list: CrateList = toml::from_str(
+        r#"
+        [crates]
+        regex = { path = "../regex" }
+        bitflags = { crate_file = "crates/bitflags-1.2.1.crate" }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(vec!["bitflags", "regex"], list.crates.keys().collect::<Vec<_>>());
+    assert_eq!(
+        Some(Path::new("crates/bitflags-1.2.1.crate")),
+        list.crates["bitflags"].crate_file.as_ref().map(PathBuf::as_path)
+    );
+
+    let both = CrateSource {
+        path: Some(PathBuf::from("a")),
+        crate_file: Some(PathBuf::from("b.crate")),
+    };
+    assert!(both.dir("foo", Path::new(".")).is_err());
+}
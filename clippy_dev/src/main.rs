@@ -5,7 +5,13 @@ extern crate regex;
 use clap::{App, Arg, SubCommand};
 use clippy_dev::*;
 
+mod changelog;
+mod deprecate;
+mod export_docs;
 mod fmt;
+mod lintcheck;
+mod new_lint;
+mod rename_lint;
 mod stderr_length_check;
 
 #[derive(PartialEq)]
@@ -40,7 +46,9 @@ fn main() {
                      * the changelog contains markdown link references at the bottom\n \
                      * all lint groups include the correct lints\n \
                      * lint modules in `clippy_lints/*` are visible in `src/lib.rs` via `pub mod`\n \
-                     * all lints are registered in the lint store",
+                     * all lints are registered in the lint store\n \
+                     * the lint list in `src/lintlist` contains the documentation of all lints\n \
+                     * the configuration keys in `src/lintlist` match `clippy_lints/src/utils/conf.rs`",
                 )
                 .arg(Arg::with_name("print-only").long("print-only").help(
                     "Print a table of lints to STDOUT. \
@@ -53,6 +61,117 @@ fn main() {
                         .help("Checks that util/dev update_lints has been run. Used on CI."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("new_lint")
+                .about("Creates a new lint from a template and registers it")
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The name of the lint in snake_case, e.g. `foo_functions`"),
+                )
+                .arg(
+                    Arg::with_name("category")
+                        .long("category")
+                        .takes_value(true)
+                        .default_value("nursery")
+                        .possible_values(&new_lint::CATEGORIES)
+                        .help("The lint group of the lint"),
+                )
+                .arg(
+                    Arg::with_name("pass")
+                        .long("pass")
+                        .takes_value(true)
+                        .required(true)
+                        .possible_values(&["early", "late"])
+                        .help("Whether the lint runs on the AST or on the HIR"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rename_lint")
+                .about("Renames a lint and registers the old name as renamed")
+                .arg(
+                    Arg::with_name("old")
+                        .required(true)
+                        .help("The current name of the lint"),
+                )
+                .arg(Arg::with_name("new").required(true).help("The new name of the lint")),
+        )
+        .subcommand(
+            SubCommand::with_name("deprecate")
+                .about("Deprecates a lint and removes its declaration")
+                .arg(Arg::with_name("name").required(true).help("The name of the lint"))
+                .arg(
+                    Arg::with_name("reason")
+                        .long("reason")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Why the lint is deprecated, shown to users that still enable it"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lintcheck")
+                .about("Runs Clippy over a corpus of crates and writes a report of its warnings")
+                .arg(
+                    Arg::with_name("crates")
+                        .long("crates")
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .default_value("lintcheck_crates.toml")
+                        .help("The TOML file listing the crates to lint"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .default_value("../target/lintcheck/report.txt")
+                        .help("The file to write the report to"),
+                )
+                .arg(
+                    Arg::with_name("diff")
+                        .long("diff")
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .help("A previous report to print the added and removed warnings against"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("changelog")
+                .about("Drafts a changelog entry from the changes to the lint list between two git revisions")
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .value_name("REV")
+                        .required(true)
+                        .help("The Clippy commit of the previous release"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .value_name("REV")
+                        .default_value("HEAD")
+                        .help("The Clippy commit of the release to write the changelog for"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export-docs")
+                .about("Exports the documentation of all lints for the lint list website")
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .default_value("../util/gh-pages/lints.json")
+                        .help("The file to write the documentation to"),
+                )
+                .arg(Arg::with_name("check").long("check").help(
+                    "Checks that the documentation of all lints can be exported without writing it. Used on CI.",
+                )),
+        )
         .arg(
             Arg::with_name("limit-stderr-length")
                 .long("limit-stderr-length")
@@ -77,10 +196,74 @@ fn main() {
                 update_lints(&UpdateMode::Change);
             }
         },
+        ("new_lint", Some(matches)) => {
+            let name = matches.value_of("name").expect("is required");
+            exit_on_error(new_lint::create(
+                name,
+                matches.value_of("category").expect("has a default value"),
+                matches.value_of("pass").expect("is required"),
+            ));
+            update_lints(&UpdateMode::Change);
+            println!("created the lint `{}`", name);
+        },
+        ("rename_lint", Some(matches)) => {
+            let old = matches.value_of("old").expect("is required");
+            let new = matches.value_of("new").expect("is required");
+            exit_on_error(rename_lint::run(old, new));
+            update_lints(&UpdateMode::Change);
+            println!(
+                "renamed `{}` to `{}`, run `TESTNAME=rename cargo uitest` and \
+                 `tests/ui/update-all-references.sh` to update the tests of renamed lints",
+                old, new
+            );
+        },
+        ("deprecate", Some(matches)) => {
+            let name = matches.value_of("name").expect("is required");
+            exit_on_error(deprecate::run(name, matches.value_of("reason").expect("is required")));
+            update_lints(&UpdateMode::Change);
+            println!(
+                "deprecated `{}`, run `TESTNAME=deprecated cargo uitest` and \
+                 `tests/ui/update-all-references.sh` to update the tests of deprecated lints",
+                name
+            );
+            let uses = deprecate::remaining_uses(name);
+            if !uses.is_empty() {
+                println!("\nthe lint is still used here, these uses have to be removed by hand:");
+                for line in uses {
+                    println!("{}", line);
+                }
+            }
+        },
+        ("lintcheck", Some(matches)) => {
+            exit_on_error(lintcheck::run(
+                matches.value_of("crates").expect("has a default value"),
+                matches.value_of("output").expect("has a default value"),
+                matches.value_of("diff"),
+            ));
+        },
+        ("changelog", Some(matches)) => {
+            exit_on_error(changelog::run(
+                matches.value_of("from").expect("is required"),
+                matches.value_of("to").expect("has a default value"),
+            ));
+        },
+        ("export-docs", Some(matches)) => {
+            export_docs::run(
+                matches.value_of("output").expect("has a default value"),
+                matches.is_present("check"),
+            );
+        },
         _ => {},
     }
 }
 
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
 fn print_lints() {
     let lint_list = gather_all();
     let usable_lints: Vec<Lint> = Lint::usable_lints(lint_list).collect();
@@ -137,6 +320,38 @@ fn update_lints(update_mode: &UpdateMode) {
     )
     .changed;
 
+    let mut deprecated_lints: Vec<Lint> = lint_list.iter().filter(|l| l.deprecation.is_some()).cloned().collect();
+    deprecated_lints.sort_by_key(|lint| lint.name.clone());
+
+    file_change |= replace_region_in_file(
+        "../src/lintlist/mod.rs",
+        "begin deprecated lint list",
+        "end deprecated lint list",
+        false,
+        update_mode == &UpdateMode::Change,
+        || {
+            format!(
+                "pub const DEPRECATED_LINTS: [Lint; {}] = {:#?};",
+                deprecated_lints.len(),
+                deprecated_lints
+            )
+            .lines()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+        },
+    )
+    .changed;
+
+    file_change |= replace_region_in_file(
+        "../src/lintlist/mod.rs",
+        "begin conf list",
+        "end conf list",
+        false,
+        update_mode == &UpdateMode::Change,
+        || gen_conf_list(&gather_conf()),
+    )
+    .changed;
+
     file_change |= replace_region_in_file(
         "../README.md",
         r#"\[There are \d+ lints included in this crate!\]\(https://rust-lang.github.io/rust-clippy/master/index.html\)"#,
@@ -215,6 +430,18 @@ fn update_lints(update_mode: &UpdateMode) {
         .changed;
     }
 
+    // In check mode, this reads the registration that is checked above.
+    let lib_rs = std::fs::read_to_string("../clippy_lints/src/lib.rs").unwrap();
+    file_change |= replace_region_in_file(
+        "../src/lintlist/mod.rs",
+        "begin clippy::all groups",
+        "end clippy::all groups",
+        false,
+        update_mode == &UpdateMode::Change,
+        || gen_all_groups(&groups_in_all(&lib_rs, &usable_lints)),
+    )
+    .changed;
+
     if update_mode == &UpdateMode::Check && file_change {
         println!(
             "Not all lints defined properly. \
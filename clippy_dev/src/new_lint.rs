@@ -0,0 +1,164 @@
+//! Creates the files of a new lint and registers its lint pass, for `util/dev new_lint`.
+
+use clippy_dev::{gather_all, is_valid_lint_name, to_camel_case};
+use std::fs::{self, OpenOptions};
+use std::io::prelude::*;
+use std::io::ErrorKind;
+use std::path::Path;
+
+pub const CATEGORIES: [&str; 8] = [
+    "correctness",
+    "style",
+    "complexity",
+    "perf",
+    "pedantic",
+    "restriction",
+    "nursery",
+    "cargo",
+];
+
+/// The comment in `register_plugins` that new lint passes are registered before.
+const REGISTER_LAST: &str = "    // must be registered last";
+
+/// Creates `clippy_lints/src/<name>.rs`, the UI test stubs `tests/ui/<name>.{rs,stderr}` and
+/// registers the lint pass. `util/dev update_lints` has to run afterwards to add the module and
+/// the lint groups.
+pub fn create(name: &str, category: &str, pass: &str) -> Result<(), String> {
+    if !is_valid_lint_name(name) {
+        return Err(format!("`{}` is not a valid lint name, use snake_case", name));
+    }
+    if gather_all().any(|lint| lint.name == name) {
+        return Err(format!("the lint `{}` already exists", name));
+    }
+
+    let lib_path = Path::new("../clippy_lints/src/lib.rs");
+    let lib =
+        fs::read_to_string(lib_path).map_err(|err| format!("could not read `{}`: {}", lib_path.display(), err))?;
+    let lib = register_pass(&lib, name, pass)
+        .ok_or_else(|| format!("could not find `{}` in `{}`", REGISTER_LAST.trim(), lib_path.display()))?;
+
+    create_file(
+        &Path::new("../clippy_lints/src").join(format!("{}.rs", name)),
+        &lint_contents(name, category, pass),
+    )?;
+    create_file(
+        &Path::new("../tests/ui").join(format!("{}.rs", name)),
+        &test_contents(name),
+    )?;
+    create_file(&Path::new("../tests/ui").join(format!("{}.stderr", name)), "")?;
+    fs::write(lib_path, lib).map_err(|err| format!("could not write `{}`: {}", lib_path.display(), err))
+}
+
+fn create_file(path: &Path, contents: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|err| match err.kind() {
+            ErrorKind::AlreadyExists => format!("`{}` already exists", path.display()),
+            _ => format!("could not create `{}`: {}", path.display(), err),
+        })?;
+    file.write_all(contents.as_bytes())
+        .map_err(|err| format!("could not write `{}`: {}", path.display(), err))
+}
+
+/// Adds the registration of the lint pass of `name` to the `clippy_lints/src/lib.rs` in `lib`.
+fn register_pass(lib: &str, name: &str, pass: &str) -> Option<String> {
+    let index = lib.find(REGISTER_LAST)?;
+    let registration = format!(
+        "    reg.register_{}_lint_pass(box {}::{});\n",
+        pass,
+        name,
+        to_camel_case(name)
+    );
+    Some([&lib[..index], &registration, &lib[index..]].concat())
+}
+
+fn lint_contents(name: &str, category: &str, pass: &str) -> String {
+    let (imports, pass_impl) = if pass == "early" {
+        (
+            "EarlyLintPass",
+            format!("impl EarlyLintPass for {} {{}}", to_camel_case(name)),
+        )
+    } else {
+        (
+            "LateLintPass",
+            format!("impl<'a, 'tcx> LateLintPass<'a, 'tcx> for {} {{}}", to_camel_case(name)),
+        )
+    };
+
+    format!(
+        "use rustc::lint::{{{imports}, LintArray, LintPass}};
+use rustc::{{declare_lint_pass, declare_tool_lint}};
+
+declare_clippy_lint! {{
+    /// **What it does:**
+    ///
+    /// **Why is this bad?**
+    ///
+    /// **Known problems:** None.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// // example code
+    /// ```
+    pub {upper_name},
+    {category},
+    \"default lint description\"
+}}
+
+declare_lint_pass!({camel_name} => [{upper_name}]);
+
+{pass_impl}
+",
+        imports = imports,
+        upper_name = name.to_uppercase(),
+        category = category,
+        camel_name = to_camel_case(name),
+        pass_impl = pass_impl,
+    )
+}
+
+fn test_contents(name: &str) -> String {
+    format!(
+        "#![warn(clippy::{})]
+
+fn main() {{
+    // test code goes here
+}}
+",
+        name
+    )
+}
+
+#[test]
+fn test_register_pass() {
+    let lib = "pub fn register_plugins() {
+    reg.register_late_lint_pass(box types::Types);
+    // must be registered last, it checks which lints were emitted by all other passes
+    reg.register_late_lint_pass(box attrs::UnusedClippyAllow::default());
+}
+";
+    let expected = "pub fn register_plugins() {
+    reg.register_late_lint_pass(box types::Types);
+    reg.register_early_lint_pass(box foo_functions::FooFunctions);
+    // must be registered last, it checks which lints were emitted by all other passes
+    reg.register_late_lint_pass(box attrs::UnusedClippyAllow::default());
+}
+";
+    assert_eq!(Some(expected.to_string()), register_pass(lib, "foo_functions", "early"));
+    assert_eq!(
+        None,
+        register_pass("pub fn register_plugins() {}", "foo_functions", "early")
+    );
+}
+
+#[test]
+fn test_lint_contents() {
+    let contents = lint_contents("foo_functions", "pedantic", "late");
+    assert!(contents.starts_with("use rustc::lint::{LateLintPass, LintArray, LintPass};\n"));
+    assert!(contents.contains("    pub FOO_FUNCTIONS,\n    pedantic,\n"));
+    assert!(contents.contains("declare_lint_pass!(FooFunctions => [FOO_FUNCTIONS]);"));
+    assert!(contents.ends_with("impl<'a, 'tcx> LateLintPass<'a, 'tcx> for FooFunctions {}\n"));
+}
@@ -0,0 +1,316 @@
+//! Renames a lint and registers the old name as renamed, for `util/dev rename_lint`.
+
+use clippy_dev::{gather_all, is_valid_lint_name, to_camel_case};
+use regex::Regex;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const LIB_RS: &str = "../clippy_lints/src/lib.rs";
+const RENAME_TEST: &str = "../tests/ui/rename.rs";
+
+/// Renames the lint `old` to `new` in the lint declaration, the code of Clippy and the tests, and
+/// registers `old` as renamed. `util/dev update_lints` has to run afterwards.
+pub fn run(old: &str, new: &str) -> Result<(), String> {
+    if !is_valid_lint_name(new) {
+        return Err(format!("`{}` is not a valid lint name, use snake_case", new));
+    }
+    let lints: Vec<_> = gather_all().collect();
+    let lint = lints
+        .iter()
+        .find(|lint| lint.name == old)
+        .ok_or_else(|| format!("the lint `{}` doesn't exist", old))?;
+    if lint.deprecation.is_some() {
+        return Err(format!("the lint `{}` is deprecated and can't be renamed", old));
+    }
+    if lints.iter().any(|lint| lint.name == new) {
+        return Err(format!("the lint `{}` already exists", new));
+    }
+    let lib = read(Path::new(LIB_RS))?;
+    if lib.contains(&format!("register_renamed(\"clippy::{}\"", new)) {
+        return Err(format!("`{}` is the old name of a renamed lint", new));
+    }
+
+    // Files named after the lint are renamed along with it.
+    let mut renames = Vec::new();
+    let module = Path::new("../clippy_lints/src").join(format!("{}.rs", old));
+    let rename_module = lint.module == old && module.exists();
+    if rename_module {
+        renames.push((module, Path::new("../clippy_lints/src").join(format!("{}.rs", new))));
+    }
+    for extension in &["rs", "stderr", "fixed"] {
+        let test = Path::new("../tests/ui").join(format!("{}.{}", old, extension));
+        if test.exists() {
+            renames.push((test, Path::new("../tests/ui").join(format!("{}.{}", new, extension))));
+        }
+    }
+    if let Some((_, to)) = renames.iter().find(|(_, to)| to.exists()) {
+        return Err(format!("`{}` already exists", to.display()));
+    }
+    for (from, to) in &renames {
+        fs::rename(from, to).map_err(|err| format!("could not rename `{}`: {}", from.display(), err))?;
+    }
+
+    let replacements = Replacements::new(old, new, rename_module);
+    for path in files_to_update() {
+        let content = read(&path)?;
+        let updated = replacements.apply(&content, &path);
+        if updated != content {
+            write(&path, &updated)?;
+        }
+    }
+
+    let lib = read(Path::new(LIB_RS))?;
+    let lib =
+        register_renamed(&lib, old, new).ok_or_else(|| format!("could not find `register_renamed` in `{}`", LIB_RS))?;
+    write(Path::new(LIB_RS), &lib)?;
+
+    let test = read(Path::new(RENAME_TEST))?;
+    write(Path::new(RENAME_TEST), &add_rename_test(&test, old, new))?;
+
+    Ok(())
+}
+
+/// The rewrites of the code that refers to the renamed lint.
+struct Replacements {
+    old: String,
+    new: String,
+    lint_const: (Regex, String),
+    /// Matches any lint path, optionally preceded by a command line flag.
+    lint_path: Regex,
+    test_path: (String, String),
+    /// The module and lint pass named after the lint are renamed too.
+    module: Option<(Regex, String)>,
+    pass: Option<(Regex, String)>,
+}
+
+impl Replacements {
+    fn new(old: &str, new: &str, rename_module: bool) -> Self {
+        Self {
+            old: old.to_string(),
+            new: new.to_string(),
+            lint_const: (
+                Regex::new(&format!(r"\b{}\b", old.to_uppercase())).unwrap(),
+                new.to_uppercase(),
+            ),
+            lint_path: Regex::new(r"(?P<flag>-[ADFW] )?\bclippy::(?P<name>[\w-]+)").unwrap(),
+            test_path: (format!("$DIR/{}.rs", old), format!("$DIR/{}.rs", new)),
+            module: if rename_module {
+                Some((Regex::new(&format!(r"\b{}::", old)).unwrap(), format!("{}::", new)))
+            } else {
+                None
+            },
+            pass: if rename_module {
+                Some((
+                    Regex::new(&format!(r"\b{}\b", to_camel_case(old))).unwrap(),
+                    to_camel_case(new),
+                ))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn apply(&self, content: &str, path: &Path) -> String {
+        let mut content = self
+            .lint_const
+            .0
+            .replace_all(content, self.lint_const.1.as_str())
+            .into_owned();
+        content = self
+            .lint_path
+            .replace_all(&content, |caps: &regex::Captures<'_>| self.lint_path_replacement(caps))
+            .into_owned();
+        if path.extension() == Some(OsStr::new("stderr")) {
+            content = content.replace(&self.test_path.0, &self.test_path.1);
+        }
+        if let (Some((re, replacement)), true) = (&self.module, path.ends_with("clippy_lints/src/lib.rs")) {
+            content = re.replace_all(&content, replacement.as_str()).into_owned();
+        }
+        if let (Some((re, replacement)), true) = (&self.pass, path.starts_with("../clippy_lints/src")) {
+            content = re.replace_all(&content, replacement.as_str()).into_owned();
+        }
+        content
+    }
+
+    /// Renames a match of `lint_path` if it refers to the old lint. Lints are written in
+    /// kebab-case in the notes of UI tests, e.g. `-D clippy::module-name-repetitions`.
+    fn lint_path_replacement(&self, caps: &regex::Captures<'_>) -> String {
+        let name = &caps["name"];
+        if name.replace('-', "_") != self.old {
+            return caps[0].to_string();
+        }
+
+        // Without underscores, only the flag tells the styles apart.
+        let flag = caps.name("flag").map_or("", |flag| flag.as_str());
+        let kebab_case = name.contains('-') || (!self.old.contains('_') && !flag.is_empty());
+        let new = if kebab_case {
+            self.new.replace('_', "-")
+        } else {
+            self.new.clone()
+        };
+        format!("{}clippy::{}", flag, new)
+    }
+}
+
+/// The files that can refer to a lint. The test of renamed lints keeps the old names.
+fn files_to_update() -> Vec<PathBuf> {
+    WalkDir::new("../clippy_lints/src")
+        .into_iter()
+        .chain(WalkDir::new("../tests"))
+        .filter_map(Result::ok)
+        .map(walkdir::DirEntry::into_path)
+        .filter(|path| {
+            path.extension()
+                .and_then(OsStr::to_str)
+                .map_or(false, |ext| ["rs", "stderr", "fixed", "toml"].contains(&ext))
+        })
+        .filter(|path| path.file_stem() != Some(OsStr::new("rename")))
+        .collect()
+}
+
+/// Adds `old` to the end of `register_renamed`.
+fn register_renamed(lib: &str, old: &str, new: &str) -> Option<String> {
+    let start = lib.find("pub fn register_renamed(")?;
+    let end = start + lib[start..].find("\n}")? + 1;
+    let registration = format!("    ls.register_renamed(\"clippy::{}\", \"clippy::{}\");\n", old, new);
+    Some([&lib[..end], &registration, &lib[end..]].concat())
+}
+
+/// Adds `new` to the allowed lints and `old` to the warned lints of `tests/ui/rename.rs`.
+fn add_rename_test(test: &str, old: &str, new: &str) -> String {
+    let mut lines: Vec<String> = test.lines().map(ToString::to_string).collect();
+    insert_attr(
+        &mut lines,
+        "// allow the new lint name here",
+        format!("#![allow(clippy::{})]", new),
+    );
+    insert_attr(
+        &mut lines,
+        "// warn for the old lint name here",
+        format!("#![warn(clippy::{})]", old),
+    );
+    lines.join("\n") + "\n"
+}
+
+/// Inserts `attr` after the attributes following the comment `section`, or before the first item
+/// if there is no such comment.
+fn insert_attr(lines: &mut Vec<String>, section: &str, attr: String) {
+    let index = match lines.iter().position(|line| line.starts_with(section)) {
+        Some(start) => {
+            start
+                + 1
+                + lines[start + 1..]
+                    .iter()
+                    .take_while(|line| line.starts_with("#!["))
+                    .count()
+        },
+        None => lines
+            .iter()
+            .position(|line| !line.is_empty() && !line.starts_with("//"))
+            .unwrap_or(lines.len()),
+    };
+    lines.insert(index, attr);
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|err| format!("could not read `{}`: {}", path.display(), err))
+}
+
+fn write(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|err| format!("could not write `{}`: {}", path.display(), err))
+}
+
+#[test]
+fn test_replacements() {
+    let replacements = Replacements::new("stutter", "module_name_repetitions", true);
+    let lib = "pub mod stutter;\n\
+               reg.register_late_lint_pass(box stutter::Stutter);\n\
+               stutter::STUTTER,\n\
+               stutter::STUTTER_TOO,\n";
+    assert_eq!(
+        "pub mod stutter;\n\
+         reg.register_late_lint_pass(box module_name_repetitions::ModuleNameRepetitions);\n\
+         module_name_repetitions::MODULE_NAME_REPETITIONS,\n\
+         module_name_repetitions::STUTTER_TOO,\n",
+        replacements.apply(lib, Path::new("../clippy_lints/src/lib.rs"))
+    );
+
+    let stderr = "error: item name starts with its containing module's name\n  \
+                  --> $DIR/stutter.rs:8:5\n   \
+                  |\n\
+                  LL |     #![warn(clippy::stutter)]\n   \
+                  |\n   \
+                  = note: `-D clippy::stutter` implied by `-D warnings`\n";
+    assert_eq!(
+        "error: item name starts with its containing module's name\n  \
+         --> $DIR/module_name_repetitions.rs:8:5\n   \
+         |\n\
+         LL |     #![warn(clippy::module_name_repetitions)]\n   \
+         |\n   \
+         = note: `-D clippy::module-name-repetitions` implied by `-D warnings`\n",
+        replacements.apply(stderr, Path::new("../tests/ui/stutter.stderr"))
+    );
+
+    let replacements = Replacements::new("needless_lifetimes", "redundant_lifetimes", false);
+    let stderr = "LL | #[allow(clippy::needless_lifetimes)]\n   \
+                  = note: `-D clippy::needless-lifetimes` implied by `-D warnings`\n   \
+                  = note: `-D clippy::needless-lifetimes-too` implied by `-D warnings`\n";
+    assert_eq!(
+        "LL | #[allow(clippy::redundant_lifetimes)]\n   \
+         = note: `-D clippy::redundant-lifetimes` implied by `-D warnings`\n   \
+         = note: `-D clippy::needless-lifetimes-too` implied by `-D warnings`\n",
+        replacements.apply(stderr, Path::new("../tests/ui/needless_lifetimes.stderr"))
+    );
+    assert_eq!(
+        "#![warn(clippy::stutter_too)]\nstutter::foo();\n",
+        replacements.apply(
+            "#![warn(clippy::stutter_too)]\nstutter::foo();\n",
+            Path::new("../tests/ui/stutter.rs")
+        )
+    );
+}
+
+#[test]
+fn test_register_renamed() {
+    let lib = "pub fn register_renamed(ls: &mut rustc::lint::LintStore) {
+    ls.register_renamed(\"clippy::stutter\", \"clippy::module_name_repetitions\");
+}
+
+pub fn foo() {}
+";
+    let expected = "pub fn register_renamed(ls: &mut rustc::lint::LintStore) {
+    ls.register_renamed(\"clippy::stutter\", \"clippy::module_name_repetitions\");
+    ls.register_renamed(\"clippy::foo\", \"clippy::bar\");
+}
+
+pub fn foo() {}
+";
+    assert_eq!(Some(expected.to_string()), register_renamed(lib, "foo", "bar"));
+}
+
+#[test]
+fn test_add_rename_test() {
+    let test = "// run-rustfix
+
+// allow the new lint name here, to test if the new name works
+#![allow(clippy::new_without_default)]
+// warn for the old lint name here, to test if the renaming worked
+#![warn(clippy::cyclomatic_complexity)]
+
+fn main() {}
+";
+    let expected = "// run-rustfix
+
+// allow the new lint name here, to test if the new name works
+#![allow(clippy::new_without_default)]
+#![allow(clippy::bar)]
+// warn for the old lint name here, to test if the renaming worked
+#![warn(clippy::cyclomatic_complexity)]
+#![warn(clippy::foo)]
+
+fn main() {}
+";
+    assert_eq!(expected, add_rename_test(test, "foo", "bar"));
+}
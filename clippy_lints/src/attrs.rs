@@ -2,17 +2,17 @@
 
 use crate::reexport::*;
 use crate::utils::{
-    is_present_in_source, last_line_of_span, match_def_path, paths, snippet_opt, span_lint, span_lint_and_sugg,
-    span_lint_and_then, without_block_comments,
+    is_present_in_source, last_line_of_span, match_def_path, paths, snippet, snippet_opt, span_lint,
+    span_lint_and_sugg, span_lint_and_then, span_lint_hir_and_then, take_recorded_lints, without_block_comments,
 };
 use if_chain::if_chain;
 use rustc::hir::*;
 use rustc::lint::{
     in_external_macro, CheckLintNameResult, EarlyContext, EarlyLintPass, LateContext, LateLintPass, LintArray,
-    LintContext, LintPass,
+    LintContext, LintId, LintPass,
 };
 use rustc::ty;
-use rustc::{declare_lint_pass, declare_tool_lint};
+use rustc::{declare_lint_pass, declare_tool_lint, impl_lint_pass};
 use rustc_errors::Applicability;
 use semver::Version;
 use syntax::ast::{AttrStyle, Attribute, Lit, LitKind, MetaItemKind, NestedMetaItem};
@@ -188,6 +188,29 @@ declare_clippy_lint! {
     "usage of `cfg_attr(rustfmt)` instead of `tool_attributes`"
 }
 
+declare_clippy_lint! {
+    /// **What it does:** Checks for `#[allow(clippy::...)]` attributes that don't suppress any
+    /// Clippy lint.
+    ///
+    /// **Why is this bad?** These attributes are usually left over after the code they were
+    /// added for changed. They clutter the code and hide new occurrences of the lint.
+    ///
+    /// **Known problems:** Only the code compiled in the current configuration is checked, so an
+    /// attribute for a lint that only triggers with e.g. `--all-features` is reported when the
+    /// feature is disabled. Attributes for lint groups, like `clippy::pedantic`, are ignored.
+    ///
+    /// **Example:**
+    /// ```rust
+    /// #[allow(clippy::too_many_arguments)]
+    /// fn add(a: u32, b: u32) -> u32 {
+    ///     a + b
+    /// }
+    /// ```
+    pub UNUSED_CLIPPY_ALLOW,
+    restriction,
+    "`#[allow]` attributes for Clippy lints that never trigger"
+}
+
 declare_lint_pass!(Attributes => [
     INLINE_ALWAYS,
     DEPRECATED_SEMVER,
@@ -518,3 +541,217 @@ impl EarlyLintPass for DeprecatedCfgAttribute {
         }
     }
 }
+
+/// A lint level attribute that mentions Clippy lints.
+struct LevelAttr {
+    /// The node the attribute is attached to.
+    hir_id: HirId,
+    /// The code the attribute applies to, empty for crate level attributes.
+    scope: Vec<Span>,
+    span: Span,
+    style: AttrStyle,
+    is_allow: bool,
+    items: Vec<LevelAttrItem>,
+}
+
+struct LevelAttrItem {
+    span: Span,
+    /// `None` if the item is not a Clippy lint, e.g. a lint group or a rustc lint.
+    lint: Option<LintId>,
+    /// `true` if the lint was checked in the scope of the attribute.
+    used: bool,
+}
+
+impl LevelAttr {
+    /// Returns the size of the smallest span of the scope containing `span`, or `None` if `span`
+    /// is outside of the scope.
+    fn scope_size(&self, span: Span) -> Option<u32> {
+        if self.scope.is_empty() {
+            return Some(u32::max_value());
+        }
+        self.scope
+            .iter()
+            .filter_map(|scope| {
+                if scope.contains(span) {
+                    Some(scope.hi().0 - scope.lo().0)
+                } else {
+                    None
+                }
+            })
+            .min()
+    }
+}
+
+#[derive(Default)]
+pub struct UnusedClippyAllow {
+    attrs: Vec<LevelAttr>,
+}
+
+impl_lint_pass!(UnusedClippyAllow => [UNUSED_CLIPPY_ALLOW]);
+
+impl UnusedClippyAllow {
+    fn collect(&mut self, cx: &LateContext<'_, '_>, hir_id: HirId, scope: &[Span], attrs: &[Attribute]) {
+        for attr in attrs {
+            if attr.span.from_expansion() {
+                continue;
+            }
+            let is_allow = match attr.ident() {
+                Some(ident) if ident.name == sym!(allow) => true,
+                Some(ident) if ident.name == sym!(warn) || ident.name == sym!(deny) || ident.name == sym!(forbid) => {
+                    false
+                },
+                _ => continue,
+            };
+            let items: Vec<_> = attr
+                .meta_item_list()
+                .unwrap_or_default()
+                .iter()
+                .map(|item| LevelAttrItem {
+                    span: item.span(),
+                    lint: clippy_lint_id(cx, item),
+                    used: false,
+                })
+                .collect();
+            if items.iter().any(|item| item.lint.is_some()) {
+                self.attrs.push(LevelAttr {
+                    hir_id,
+                    scope: scope.to_vec(),
+                    span: attr.span,
+                    style: attr.style,
+                    is_allow,
+                    items,
+                });
+            }
+        }
+    }
+
+    /// Marks the items of `allow` attributes that suppressed a lint.
+    ///
+    /// A lint is controlled by the innermost lint level attribute mentioning it, so an outer
+    /// `allow` is unused if an inner attribute overrides it.
+    fn mark_used(&mut self, lint: LintId, span: Span) {
+        let mut innermost: Option<(usize, usize, u32)> = None;
+        for (i, attr) in self.attrs.iter().enumerate() {
+            if let Some(size) = attr.scope_size(span) {
+                for (j, item) in attr.items.iter().enumerate() {
+                    if item.lint == Some(lint) && innermost.map_or(true, |(.., best)| size <= best) {
+                        innermost = Some((i, j, size));
+                    }
+                }
+            }
+        }
+
+        if let Some((i, j, _)) = innermost {
+            self.attrs[i].items[j].used = true;
+        }
+    }
+}
+
+/// Returns the lint if `item` names a single Clippy lint.
+fn clippy_lint_id(cx: &LateContext<'_, '_>, item: &NestedMetaItem) -> Option<LintId> {
+    let meta_item = item.meta_item()?;
+    if meta_item.path.segments.len() != 2 || meta_item.path.segments[0].ident.as_str() != "clippy" {
+        return None;
+    }
+
+    let tool_name = meta_item.path.segments[0].ident.name;
+    let name = meta_item.path.segments[1].ident.as_str();
+    let ids = match cx.lints().check_lint_name(&name, Some(tool_name)) {
+        CheckLintNameResult::Ok(ids) | CheckLintNameResult::Tool(Ok(ids)) => ids,
+        _ => return None,
+    };
+    match *ids {
+        // lint groups can contain a single lint as well
+        [id] if id.to_string() == format!("clippy::{}", name) => {
+            if id == LintId::of(UNUSED_CLIPPY_ALLOW) {
+                None
+            } else {
+                Some(id)
+            }
+        },
+        _ => None,
+    }
+}
+
+impl<'a, 'tcx> LateLintPass<'a, 'tcx> for UnusedClippyAllow {
+    fn check_crate(&mut self, cx: &LateContext<'a, 'tcx>, krate: &'tcx Crate) {
+        self.collect(cx, CRATE_HIR_ID, &[], &krate.attrs);
+    }
+
+    fn check_item(&mut self, cx: &LateContext<'a, 'tcx>, item: &'tcx Item) {
+        let scope = if let ItemKind::Mod(ref module) = item.node {
+            // the contents of out-of-line modules are in another file
+            vec![item.span, module.inner]
+        } else {
+            vec![item.span]
+        };
+        self.collect(cx, item.hir_id, &scope, &item.attrs);
+    }
+
+    fn check_impl_item(&mut self, cx: &LateContext<'a, 'tcx>, item: &'tcx ImplItem) {
+        self.collect(cx, item.hir_id, &[item.span], &item.attrs);
+    }
+
+    fn check_trait_item(&mut self, cx: &LateContext<'a, 'tcx>, item: &'tcx TraitItem) {
+        self.collect(cx, item.hir_id, &[item.span], &item.attrs);
+    }
+
+    fn check_foreign_item(&mut self, cx: &LateContext<'a, 'tcx>, item: &'tcx ForeignItem) {
+        self.collect(cx, item.hir_id, &[item.span], &item.attrs);
+    }
+
+    fn check_struct_field(&mut self, cx: &LateContext<'a, 'tcx>, field: &'tcx StructField) {
+        self.collect(cx, field.hir_id, &[field.span], &field.attrs);
+    }
+
+    fn check_variant(&mut self, cx: &LateContext<'a, 'tcx>, variant: &'tcx Variant) {
+        self.collect(cx, variant.id, &[variant.span], &variant.attrs);
+    }
+
+    fn check_local(&mut self, cx: &LateContext<'a, 'tcx>, local: &'tcx Local) {
+        self.collect(cx, local.hir_id, &[local.span], &local.attrs);
+    }
+
+    fn check_arm(&mut self, cx: &LateContext<'a, 'tcx>, arm: &'tcx Arm) {
+        self.collect(cx, arm.hir_id, &[arm.span], &arm.attrs);
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'a, 'tcx>, expr: &'tcx Expr) {
+        self.collect(cx, expr.hir_id, &[expr.span], &expr.attrs);
+    }
+
+    // This pass is registered last, so all other lints were checked at this point.
+    fn check_crate_post(&mut self, cx: &LateContext<'a, 'tcx>, _: &'tcx Crate) {
+        for (lint, span) in take_recorded_lints() {
+            self.mark_used(LintId::of(lint), span.source_callsite());
+        }
+
+        for attr in self.attrs.iter().filter(|attr| attr.is_allow) {
+            let (unused, used): (Vec<_>, Vec<_>) =
+                attr.items.iter().partition(|item| item.lint.is_some() && !item.used);
+            if unused.is_empty() {
+                continue;
+            }
+
+            let bang = if let AttrStyle::Inner = attr.style { "!" } else { "" };
+            let (msg, help, sugg) = if used.is_empty() {
+                (
+                    "this `allow` attribute doesn't suppress any Clippy lint".to_string(),
+                    "remove the attribute",
+                    String::new(),
+                )
+            } else {
+                let unused: Vec<_> = unused.iter().map(|item| snippet(cx, item.span, "..")).collect();
+                let used: Vec<_> = used.iter().map(|item| snippet(cx, item.span, "..")).collect();
+                (
+                    format!("`{}` doesn't suppress any Clippy lint here", unused.join("`, `")),
+                    "remove the unused lints",
+                    format!("#{}[allow({})]", bang, used.join(", ")),
+                )
+            };
+            span_lint_hir_and_then(cx, UNUSED_CLIPPY_ALLOW, attr.hir_id, attr.span, &msg, |db| {
+                db.span_suggestion(attr.span, help, sugg, Applicability::MachineApplicable);
+            });
+        }
+    }
+}
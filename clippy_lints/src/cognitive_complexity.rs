@@ -9,6 +9,7 @@ use rustc::{declare_tool_lint, impl_lint_pass};
 use syntax::ast::Attribute;
 use syntax::source_map::Span;
 
+use crate::utils::conf::{Conf, Scoped};
 use crate::utils::{is_allowed, match_type, paths, span_help_and_lint, LimitStack};
 
 declare_clippy_lint! {
@@ -28,12 +29,14 @@ declare_clippy_lint! {
 
 pub struct CognitiveComplexity {
     limit: LimitStack,
+    default_limit: Scoped<u64>,
 }
 
 impl CognitiveComplexity {
-    pub fn new(limit: u64) -> Self {
+    pub fn new(conf: &Conf) -> Self {
         Self {
-            limit: LimitStack::new(limit),
+            limit: LimitStack::new(conf.cognitive_complexity_threshold),
+            default_limit: Scoped::new(conf, |conf| conf.cognitive_complexity_threshold),
         }
     }
 }
@@ -95,16 +98,18 @@ impl CognitiveComplexity {
             if rust_cc >= ret_adjust {
                 rust_cc -= ret_adjust;
             }
-            if rust_cc > self.limit.limit() {
+            // attributes take precedence over the configuration file
+            let limit = if self.limit.is_set_by_attr() {
+                self.limit.limit()
+            } else {
+                *self.default_limit.get(cx.sess(), span)
+            };
+            if rust_cc > limit {
                 span_help_and_lint(
                     cx,
                     COGNITIVE_COMPLEXITY,
                     span,
-                    &format!(
-                        "the function has a cognitive complexity of ({}/{})",
-                        rust_cc,
-                        self.limit.limit()
-                    ),
+                    &format!("the function has a cognitive complexity of ({}/{})", rust_cc, limit),
                     "you could split it up into multiple smaller functions",
                 );
             }
@@ -1,13 +1,15 @@
 #![allow(clippy::float_cmp)]
 
-use crate::utils::{clip, higher, sext, unsext};
-use if_chain::if_chain;
+use crate::utils::{clip, higher, int_bits, match_def_path, paths, sext, unsext};
 use rustc::hir::def::{DefKind, Res};
+use rustc::hir::def_id::DefId;
 use rustc::hir::*;
 use rustc::lint::LateContext;
+use rustc::mir;
 use rustc::ty::subst::{Subst, SubstsRef};
 use rustc::ty::{self, Instance, Ty, TyCtxt};
 use rustc::{bug, span_bug};
+use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::sync::Lrc;
 use std::cmp::Ordering::{self, Equal};
 use std::cmp::PartialOrd;
@@ -41,6 +43,8 @@ pub enum Constant {
     Tuple(Vec<Constant>),
     /// A raw pointer.
     RawPtr(u128),
+    /// A reference to a constant.
+    Ref(Box<Constant>),
     /// A literal with syntax error.
     Err(Symbol),
 }
@@ -67,6 +71,7 @@ impl PartialEq for Constant {
             (&Self::Bool(l), &Self::Bool(r)) => l == r,
             (&Self::Vec(ref l), &Self::Vec(ref r)) | (&Self::Tuple(ref l), &Self::Tuple(ref r)) => l == r,
             (&Self::Repeat(ref lv, ref ls), &Self::Repeat(ref rv, ref rs)) => ls == rs && lv == rv,
+            (&Self::Ref(ref l), &Self::Ref(ref r)) => l == r,
             // TODO: are there inter-type equalities?
             _ => false,
         }
@@ -111,6 +116,9 @@ impl Hash for Constant {
             Self::RawPtr(u) => {
                 u.hash(state);
             },
+            Self::Ref(ref c) => {
+                c.hash(state);
+            },
             Self::Err(ref s) => {
                 s.hash(state);
             },
@@ -145,6 +153,13 @@ impl Constant {
                     x => x,
                 }
             },
+            (&Self::Ref(ref l), &Self::Ref(ref r)) => {
+                let cmp_type = match cmp_type.sty {
+                    ty::Ref(_, ty, _) => ty,
+                    _ => cmp_type,
+                };
+                Self::partial_cmp(tcx, cmp_type, l, r)
+            },
             // TODO: are there any useful inter-type orderings?
             _ => None,
         }
@@ -182,6 +197,8 @@ pub fn constant<'c, 'cc>(
         param_env: lcx.param_env,
         needed_resolution: false,
         substs: lcx.tcx.intern_substs(&[]),
+        locals: FxHashMap::default(),
+        call_depth: 0,
     };
     cx.expr(e).map(|cst| (cst, cx.needed_resolution))
 }
@@ -205,15 +222,23 @@ pub fn constant_context<'c, 'cc>(
         param_env: lcx.param_env,
         needed_resolution: false,
         substs: lcx.tcx.intern_substs(&[]),
+        locals: FxHashMap::default(),
+        call_depth: 0,
     }
 }
 
+/// How deeply calls of `const fn`s are evaluated, to give up on (infinite) recursion.
+const MAX_CALL_DEPTH: usize = 16;
+
 pub struct ConstEvalLateContext<'a, 'tcx> {
     lcx: &'a LateContext<'a, 'tcx>,
     tables: &'a ty::TypeckTables<'tcx>,
     param_env: ty::ParamEnv<'tcx>,
     needed_resolution: bool,
     substs: SubstsRef<'tcx>,
+    /// The values of the local bindings and `const fn` parameters in scope.
+    locals: FxHashMap<HirId, Constant>,
+    call_depth: usize,
 }
 
 impl<'c, 'cc> ConstEvalLateContext<'c, 'cc> {
@@ -238,47 +263,200 @@ impl<'c, 'cc> ConstEvalLateContext<'c, 'cc> {
             ExprKind::Unary(op, ref operand) => self.expr(operand).and_then(|o| match op {
                 UnNot => self.constant_not(&o, self.tables.expr_ty(e)),
                 UnNeg => self.constant_negate(&o, self.tables.expr_ty(e)),
-                UnDeref => Some(o),
+                UnDeref => match o {
+                    Constant::Ref(value) => Some(*value),
+                    o => Some(o),
+                },
             }),
+            ExprKind::AddrOf(_, ref operand) => self.expr(operand).map(|o| Constant::Ref(Box::new(o))),
             ExprKind::Binary(op, ref left, ref right) => self.binop(op, left, right),
-            ExprKind::Call(ref callee, ref args) => {
-                // We only handle a few const functions for now.
-                if_chain! {
-                    if args.is_empty();
-                    if let ExprKind::Path(qpath) = &callee.node;
-                    let res = self.tables.qpath_res(qpath, callee.hir_id);
-                    if let Some(def_id) = res.opt_def_id();
-                    let get_def_path = self.lcx.get_def_path(def_id, );
-                    let def_path = get_def_path
-                        .iter()
-                        .copied()
-                        .map(Symbol::as_str)
-                        .collect::<Vec<_>>();
-                    if def_path[0] == "core";
-                    if def_path[1] == "num";
-                    if def_path[3] == "max_value";
-                    if def_path.len() == 4;
-                    then {
-                       let value = match &*def_path[2] {
-                           "<impl i8>" => i8::max_value() as u128,
-                           "<impl i16>" => i16::max_value() as u128,
-                           "<impl i32>" => i32::max_value() as u128,
-                           "<impl i64>" => i64::max_value() as u128,
-                           "<impl i128>" => i128::max_value() as u128,
-                           _ => return None,
-                       };
-                       Some(Constant::Int(value))
-                    }
-                    else {
-                        None
-                    }
-                }
+            ExprKind::Cast(ref operand, _) => {
+                let value = self.expr(operand)?;
+                self.cast(value, self.tables.expr_ty(operand), self.tables.expr_ty(e))
+            },
+            ExprKind::Field(ref base, _) => match self.expr(base) {
+                Some(Constant::Tuple(fields)) => fields.into_iter().nth(self.tables.field_indices()[e.hir_id]),
+                Some(_) => None,
+                None => self.projection(e),
+            },
+            ExprKind::Index(ref base, ref index) => match self.expr(base) {
+                Some(base) => match self.expr(index)? {
+                    Constant::Int(index) => Self::index(base, index),
+                    _ => None,
+                },
+                None => self.projection(e),
             },
+            ExprKind::Call(ref callee, ref args) => self.call(e, callee, args),
             // TODO: add other expressions.
             _ => None,
         }
     }
 
+    fn call(&mut self, e: &Expr, callee: &Expr, args: &[Expr]) -> Option<Constant> {
+        let def_id = match callee.node {
+            ExprKind::Path(ref qpath) => self.tables.qpath_res(qpath, callee.hir_id).opt_def_id()?,
+            _ => return None,
+        };
+
+        if args.is_empty() {
+            let def_path: Vec<_> = self.lcx.get_def_path(def_id).into_iter().map(Symbol::as_str).collect();
+            if def_path.len() == 4 && def_path[0] == "core" && def_path[1] == "num" && def_path[3] == "max_value" {
+                let value = match &*def_path[2] {
+                    "<impl i8>" => i8::max_value() as u128,
+                    "<impl i16>" => i16::max_value() as u128,
+                    "<impl i32>" => i32::max_value() as u128,
+                    "<impl i64>" => i64::max_value() as u128,
+                    "<impl i128>" => i128::max_value() as u128,
+                    _ => return None,
+                };
+                return Some(Constant::Int(value));
+            }
+        }
+
+        // `From` conversions between primitive types are lossless casts.
+        if match_def_path(self.lcx, def_id, &paths::FROM_FROM) {
+            let (from, to) = (self.tables.expr_ty(&args[0]), self.tables.expr_ty(e));
+            if from.is_primitive() && to.is_primitive() {
+                let value = self.expr(&args[0])?;
+                return self.cast(value, from, to);
+            }
+            return None;
+        }
+
+        self.const_fn_call(def_id, callee, args)
+    }
+
+    /// Evaluates a call of a `const fn` of the current crate by evaluating its body with the
+    /// parameters bound to the values of the arguments.
+    fn const_fn_call(&mut self, def_id: DefId, callee: &Expr, args: &[Expr]) -> Option<Constant> {
+        let tcx = self.lcx.tcx;
+        if !def_id.is_local() || !tcx.is_const_fn(def_id) || self.call_depth >= MAX_CALL_DEPTH {
+            return None;
+        }
+        let args = self.multi(args)?;
+        // Constructors of tuple structs and variants are `const fn`s without a body.
+        let body = tcx
+            .hir()
+            .body(tcx.hir().maybe_body_owned_by(tcx.hir().as_local_hir_id(def_id)?)?);
+
+        let mut locals = FxHashMap::default();
+        for (param, arg) in body.params.iter().zip(args) {
+            if let PatKind::Binding(_, hir_id, _, None) = param.pat.node {
+                locals.insert(hir_id, arg);
+            } else {
+                return None;
+            }
+        }
+        let substs = self.tables.node_substs(callee.hir_id);
+        let mut cx = ConstEvalLateContext {
+            lcx: self.lcx,
+            tables: tcx.typeck_tables_of(def_id),
+            param_env: tcx.param_env(def_id),
+            needed_resolution: false,
+            substs: if self.substs.is_empty() {
+                substs
+            } else {
+                substs.subst(tcx, self.substs)
+            },
+            locals,
+            call_depth: self.call_depth + 1,
+        };
+        let result = cx.expr(&body.value)?;
+        self.needed_resolution = true;
+        Some(result)
+    }
+
+    /// Casts `value` of type `from` to the type `to` like `as` does.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn cast(&self, value: Constant, from: Ty<'_>, to: Ty<'_>) -> Option<Constant> {
+        use self::Constant::*;
+        let tcx = self.lcx.tcx;
+
+        // The bits of integer-like values, sign extended to 128 bits
+        let bits = match (&value, &from.sty) {
+            (&Int(n), &ty::Int(ity)) => Some(sext(tcx, n, ity) as u128),
+            (&Int(n), &ty::Uint(_)) => Some(n),
+            (&Bool(b), _) => Some(u128::from(b)),
+            (&Char(c), _) => Some(u128::from(u32::from(c))),
+            _ => None,
+        };
+        if let Some(bits) = bits {
+            let signed = if let ty::Int(_) = from.sty { true } else { false };
+            return match to.sty {
+                ty::Int(ity) => Some(Int(unsext(tcx, bits as i128, ity))),
+                ty::Uint(uty) => Some(Int(clip(tcx, bits, uty))),
+                // Only `u8` can be cast to `char`.
+                ty::Char => Some(Char(char::from(bits as u8))),
+                ty::Float(FloatTy::F32) if signed => Some(F32(bits as i128 as f32)),
+                ty::Float(FloatTy::F32) => Some(F32(bits as f32)),
+                ty::Float(FloatTy::F64) if signed => Some(F64(bits as i128 as f64)),
+                ty::Float(FloatTy::F64) => Some(F64(bits as f64)),
+                _ => None,
+            };
+        }
+
+        match (value, &to.sty) {
+            (F32(f), &ty::Float(FloatTy::F32)) => Some(F32(f)),
+            (F32(f), &ty::Float(FloatTy::F64)) => Some(F64(f64::from(f))),
+            (F64(f), &ty::Float(FloatTy::F32)) => Some(F32(f as f32)),
+            (F64(f), &ty::Float(FloatTy::F64)) => Some(F64(f)),
+            (F32(f), _) => self.float_to_int(f64::from(f), to),
+            (F64(f), _) => self.float_to_int(f, to),
+            _ => None,
+        }
+    }
+
+    /// Casts a float to an integer type. Floats that don't fit into the integer type after
+    /// truncation are not evaluated, casting them is undefined behavior.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    fn float_to_int(&self, f: f64, to: Ty<'_>) -> Option<Constant> {
+        let tcx = self.lcx.tcx;
+        let f = f.trunc();
+        match to.sty {
+            ty::Int(ity) => {
+                let limit = 2_f64.powi(int_bits(tcx, ity) as i32 - 1);
+                if f >= -limit && f < limit {
+                    Some(Constant::Int(unsext(tcx, f as i128, ity)))
+                } else {
+                    None
+                }
+            },
+            ty::Uint(uty) => {
+                let limit = 2_f64.powi(clip(tcx, u128::max_value(), uty).count_ones() as i32);
+                if f >= 0.0 && f < limit {
+                    Some(Constant::Int(f as u128))
+                } else {
+                    None
+                }
+            },
+            _ => None,
+        }
+    }
+
+    fn index(mut base: Constant, index: u128) -> Option<Constant> {
+        let index: usize = index.try_into().ok()?;
+        while let Constant::Ref(inner) = base {
+            base = *inner;
+        }
+        match base {
+            Constant::Vec(values) => values.into_iter().nth(index),
+            Constant::Repeat(value, n) => {
+                if (index as u64) < n {
+                    Some(*value)
+                } else {
+                    None
+                }
+            },
+            Constant::Binary(bytes) => bytes.get(index).map(|&b| Constant::Int(u128::from(b))),
+            _ => None,
+        }
+    }
+
     #[allow(clippy::cast_possible_wrap)]
     fn constant_not(&self, o: &Constant, ty: Ty<'_>) -> Option<Constant> {
         use self::Constant::*;
@@ -324,6 +502,19 @@ impl<'c, 'cc> ConstEvalLateContext<'c, 'cc> {
 
     /// Lookup a possibly constant expression from a ExprKind::Path.
     fn fetch_path(&mut self, qpath: &QPath, id: HirId) -> Option<Constant> {
+        if let Res::Local(hir_id) = self.tables.qpath_res(qpath, id) {
+            return self.locals.get(&hir_id).cloned();
+        }
+        let result = miri_to_const(self.const_eval(qpath, id)?);
+        if result.is_some() {
+            self.needed_resolution = true;
+        }
+        result
+    }
+
+    /// Evaluates the `const` or associated constant `qpath` refers to with the const evaluator of
+    /// rustc.
+    fn const_eval(&self, qpath: &QPath, id: HirId) -> Option<&'cc ty::Const<'cc>> {
         use rustc::mir::interpret::GlobalId;
 
         let res = self.tables.qpath_res(qpath, id);
@@ -341,25 +532,73 @@ impl<'c, 'cc> ConstEvalLateContext<'c, 'cc> {
                     promoted: None,
                 };
 
-                let result = self.lcx.tcx.const_eval(self.param_env.and(gid)).ok()?;
-                let result = miri_to_const(&result);
-                if result.is_some() {
-                    self.needed_resolution = true;
-                }
-                result
+                self.lcx.tcx.const_eval(self.param_env.and(gid)).ok()
             },
             // FIXME: cover all usable cases.
             _ => None,
         }
     }
 
-    /// A block can only yield a constant if it only has one constant expression.
+    /// Evaluates a field or array element of a constant whose value `Constant` can't represent,
+    /// e.g. `CONFIG.len` or `TABLE[2]`.
+    fn projection(&mut self, e: &Expr) -> Option<Constant> {
+        let result = miri_to_const(self.projected_const(e)?);
+        if result.is_some() {
+            self.needed_resolution = true;
+        }
+        result
+    }
+
+    fn projected_const(&mut self, e: &Expr) -> Option<&'cc ty::Const<'cc>> {
+        let (base, field) = match e.node {
+            ExprKind::Path(ref qpath) => return self.const_eval(qpath, e.hir_id),
+            ExprKind::Field(ref base, _) => {
+                let base = self.projected_const(base)?;
+                match base.ty.sty {
+                    ty::Adt(adt, _) if adt.is_struct() => {},
+                    ty::Tuple(_) => {},
+                    _ => return None,
+                }
+                (base, self.tables.field_indices()[e.hir_id])
+            },
+            ExprKind::Index(ref base, ref index) => {
+                let index = match self.expr(index)? {
+                    Constant::Int(index) => index,
+                    _ => return None,
+                };
+                let base = self.projected_const(base)?;
+                match base.ty.sty {
+                    ty::Array(_, len) if index < u128::from(len.eval_usize(self.lcx.tcx, self.param_env)) => {},
+                    _ => return None,
+                }
+                (base, index.try_into().ok()?)
+            },
+            _ => return None,
+        };
+        Some(
+            self.lcx
+                .tcx
+                .const_field(self.param_env.and((base, mir::Field::from_usize(field)))),
+        )
+    }
+
+    /// A block can only yield a constant if its statements only bind constants to local
+    /// variables and it ends with a constant expression.
     fn block(&mut self, block: &Block) -> Option<Constant> {
-        if block.stmts.is_empty() {
-            block.expr.as_ref().and_then(|b| self.expr(b))
-        } else {
-            None
+        for stmt in &block.stmts {
+            match stmt.node {
+                StmtKind::Local(ref local) => {
+                    if let (PatKind::Binding(_, hir_id, _, None), Some(init)) = (&local.pat.node, &local.init) {
+                        let value = self.expr(init)?;
+                        self.locals.insert(*hir_id, value);
+                    } else {
+                        return None;
+                    }
+                },
+                _ => return None,
+            }
         }
+        block.expr.as_ref().and_then(|b| self.expr(b))
     }
 
     fn ifthenelse(&mut self, cond: &Expr, then: &Expr, otherwise: Option<&Expr>) -> Option<Constant> {
@@ -374,23 +613,51 @@ impl<'c, 'cc> ConstEvalLateContext<'c, 'cc> {
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     fn binop(&mut self, op: BinOp, left: &Expr, right: &Expr) -> Option<Constant> {
         let l = self.expr(left)?;
         let r = self.expr(right);
         match (l, r) {
+            // References are compared by the values they point to.
+            (Constant::Ref(ref l), Some(Constant::Ref(ref r))) if op.node.is_comparison() => {
+                let ty = match self.tables.expr_ty(left).sty {
+                    ty::Ref(_, ty, _) => ty,
+                    _ => return None,
+                };
+                let ordering = Constant::partial_cmp(self.lcx.tcx, ty, l, r)?;
+                Some(Constant::Bool(match op.node {
+                    BinOpKind::Eq => ordering == Ordering::Equal,
+                    BinOpKind::Ne => ordering != Ordering::Equal,
+                    BinOpKind::Lt => ordering == Ordering::Less,
+                    BinOpKind::Le => ordering != Ordering::Greater,
+                    BinOpKind::Ge => ordering != Ordering::Less,
+                    _ => ordering == Ordering::Greater,
+                }))
+            },
+            // Arithmetic that overflows is an error in constants. Shifts only overflow if the shift
+            // amount is too large, the bits shifted out are dropped.
             (Constant::Int(l), Some(Constant::Int(r))) => match self.tables.expr_ty(left).sty {
                 ty::Int(ity) => {
-                    let l = sext(self.lcx.tcx, l, ity);
-                    let r = sext(self.lcx.tcx, r, ity);
-                    let zext = |n: i128| Constant::Int(unsext(self.lcx.tcx, n, ity));
+                    let tcx = self.lcx.tcx;
+                    let l = sext(tcx, l, ity);
+                    let r = sext(tcx, r, ity);
+                    let bits = i128::from(int_bits(tcx, ity));
+                    let zext = |n: i128| Constant::Int(unsext(tcx, n, ity));
+                    let in_range = |n: i128| {
+                        if sext(tcx, unsext(tcx, n, ity), ity) == n {
+                            Some(zext(n))
+                        } else {
+                            None
+                        }
+                    };
                     match op.node {
-                        BinOpKind::Add => l.checked_add(r).map(zext),
-                        BinOpKind::Sub => l.checked_sub(r).map(zext),
-                        BinOpKind::Mul => l.checked_mul(r).map(zext),
-                        BinOpKind::Div if r != 0 => l.checked_div(r).map(zext),
-                        BinOpKind::Rem if r != 0 => l.checked_rem(r).map(zext),
-                        BinOpKind::Shr => l.checked_shr(r.try_into().expect("invalid shift")).map(zext),
-                        BinOpKind::Shl => l.checked_shl(r.try_into().expect("invalid shift")).map(zext),
+                        BinOpKind::Add => l.checked_add(r).and_then(in_range),
+                        BinOpKind::Sub => l.checked_sub(r).and_then(in_range),
+                        BinOpKind::Mul => l.checked_mul(r).and_then(in_range),
+                        BinOpKind::Div if r != 0 => l.checked_div(r).and_then(in_range),
+                        BinOpKind::Rem if r != 0 => l.checked_rem(r).and_then(in_range),
+                        BinOpKind::Shr if 0 <= r && r < bits => Some(zext(l >> r)),
+                        BinOpKind::Shl if 0 <= r && r < bits => Some(zext(l << r)),
                         BinOpKind::BitXor => Some(zext(l ^ r)),
                         BinOpKind::BitOr => Some(zext(l | r)),
                         BinOpKind::BitAnd => Some(zext(l & r)),
@@ -403,24 +670,35 @@ impl<'c, 'cc> ConstEvalLateContext<'c, 'cc> {
                         _ => None,
                     }
                 },
-                ty::Uint(_) => match op.node {
-                    BinOpKind::Add => l.checked_add(r).map(Constant::Int),
-                    BinOpKind::Sub => l.checked_sub(r).map(Constant::Int),
-                    BinOpKind::Mul => l.checked_mul(r).map(Constant::Int),
-                    BinOpKind::Div => l.checked_div(r).map(Constant::Int),
-                    BinOpKind::Rem => l.checked_rem(r).map(Constant::Int),
-                    BinOpKind::Shr => l.checked_shr(r.try_into().expect("shift too large")).map(Constant::Int),
-                    BinOpKind::Shl => l.checked_shl(r.try_into().expect("shift too large")).map(Constant::Int),
-                    BinOpKind::BitXor => Some(Constant::Int(l ^ r)),
-                    BinOpKind::BitOr => Some(Constant::Int(l | r)),
-                    BinOpKind::BitAnd => Some(Constant::Int(l & r)),
-                    BinOpKind::Eq => Some(Constant::Bool(l == r)),
-                    BinOpKind::Ne => Some(Constant::Bool(l != r)),
-                    BinOpKind::Lt => Some(Constant::Bool(l < r)),
-                    BinOpKind::Le => Some(Constant::Bool(l <= r)),
-                    BinOpKind::Ge => Some(Constant::Bool(l >= r)),
-                    BinOpKind::Gt => Some(Constant::Bool(l > r)),
-                    _ => None,
+                ty::Uint(uty) => {
+                    let tcx = self.lcx.tcx;
+                    let bits = u128::from(clip(tcx, u128::max_value(), uty).count_ones());
+                    let in_range = |n: u128| {
+                        if clip(tcx, n, uty) == n {
+                            Some(Constant::Int(n))
+                        } else {
+                            None
+                        }
+                    };
+                    match op.node {
+                        BinOpKind::Add => l.checked_add(r).and_then(in_range),
+                        BinOpKind::Sub => l.checked_sub(r).map(Constant::Int),
+                        BinOpKind::Mul => l.checked_mul(r).and_then(in_range),
+                        BinOpKind::Div => l.checked_div(r).map(Constant::Int),
+                        BinOpKind::Rem => l.checked_rem(r).map(Constant::Int),
+                        BinOpKind::Shr if r < bits => Some(Constant::Int(l >> r)),
+                        BinOpKind::Shl if r < bits => Some(Constant::Int(clip(tcx, l << r, uty))),
+                        BinOpKind::BitXor => Some(Constant::Int(l ^ r)),
+                        BinOpKind::BitOr => Some(Constant::Int(l | r)),
+                        BinOpKind::BitAnd => Some(Constant::Int(l & r)),
+                        BinOpKind::Eq => Some(Constant::Bool(l == r)),
+                        BinOpKind::Ne => Some(Constant::Bool(l != r)),
+                        BinOpKind::Lt => Some(Constant::Bool(l < r)),
+                        BinOpKind::Le => Some(Constant::Bool(l <= r)),
+                        BinOpKind::Ge => Some(Constant::Bool(l >= r)),
+                        BinOpKind::Gt => Some(Constant::Bool(l > r)),
+                        _ => None,
+                    }
                 },
                 _ => None,
             },
@@ -127,7 +127,8 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for CopyAndPaste {
 
 /// Implementation of `IF_SAME_THEN_ELSE`.
 fn lint_same_then_else(cx: &LateContext<'_, '_>, blocks: &[&Block]) {
-    let eq: &dyn Fn(&&Block, &&Block) -> bool = &|&lhs, &rhs| -> bool { SpanlessEq::new(cx).eq_block(lhs, rhs) };
+    let eq: &dyn Fn(&&Block, &&Block) -> bool =
+        &|&lhs, &rhs| -> bool { SpanlessEq::new(cx).commutative().eq_block(lhs, rhs) };
 
     if let Some((i, j)) = search_same_sequenced(blocks, eq) {
         span_note_and_lint(
@@ -144,13 +145,13 @@ fn lint_same_then_else(cx: &LateContext<'_, '_>, blocks: &[&Block]) {
 /// Implementation of `IFS_SAME_COND`.
 fn lint_same_cond(cx: &LateContext<'_, '_>, conds: &[&Expr]) {
     let hash: &dyn Fn(&&Expr) -> u64 = &|expr| -> u64 {
-        let mut h = SpanlessHash::new(cx, cx.tables);
+        let mut h = SpanlessHash::new(cx, cx.tables).commutative();
         h.hash_expr(expr);
         h.finish()
     };
 
     let eq: &dyn Fn(&&Expr, &&Expr) -> bool =
-        &|&lhs, &rhs| -> bool { SpanlessEq::new(cx).ignore_fn().eq_expr(lhs, rhs) };
+        &|&lhs, &rhs| -> bool { SpanlessEq::new(cx).ignore_fn().commutative().eq_expr(lhs, rhs) };
 
     for (i, j) in search_same(conds, hash, eq) {
         span_note_and_lint(
@@ -179,7 +180,7 @@ fn lint_match_arms<'tcx>(cx: &LateContext<'_, 'tcx>, expr: &Expr) {
 
     if let ExprKind::Match(_, ref arms, MatchSource::Normal) = expr.node {
         let hash = |&(_, arm): &(usize, &Arm)| -> u64 {
-            let mut h = SpanlessHash::new(cx, cx.tables);
+            let mut h = SpanlessHash::new(cx, cx.tables).commutative();
             h.hash_expr(&arm.body);
             h.finish()
         };
@@ -191,7 +192,7 @@ fn lint_match_arms<'tcx>(cx: &LateContext<'_, 'tcx>, expr: &Expr) {
             // Arms with a guard are ignored, those can’t always be merged together
             // This is also the case for arms in-between each there is an arm with a guard
             (min_index..=max_index).all(|index| arms[index].guard.is_none()) &&
-                SpanlessEq::new(cx).eq_expr(&lhs.body, &rhs.body) &&
+                SpanlessEq::new(cx).commutative().eq_expr(&lhs.body, &rhs.body) &&
                 // all patterns should have the same bindings
                 same_bindings(cx, &bindings(cx, &lhs.pats[0]), &bindings(cx, &rhs.pats[0]))
         };
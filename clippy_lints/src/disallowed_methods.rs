@@ -0,0 +1,116 @@
+use crate::utils::conf::DisallowedPath;
+use crate::utils::{path_to_res, span_lint_and_then};
+use rustc::hir::def::Res;
+use rustc::hir::def_id::DefId;
+use rustc::hir::*;
+use rustc::lint::{LateContext, LateLintPass, LintArray, LintPass};
+use rustc::{declare_tool_lint, impl_lint_pass};
+use rustc_data_structures::fx::FxHashMap;
+
+declare_clippy_lint! {
+    /// **What it does:** Denies the functions and methods configured in the
+    /// `disallowed-methods` list of `clippy.toml`.
+    ///
+    /// **Why is this bad?** Some functions are undesirable in certain contexts,
+    /// e.g. `std::thread::sleep` in async code or `std::env::set_var` in
+    /// multithreaded programs.
+    ///
+    /// **Known problems:** Paths can only refer to items of the crates the linted
+    /// crate depends on. Paths that can't be resolved are ignored.
+    ///
+    /// **Example:**
+    ///
+    /// With the following configuration:
+    /// ```toml
+    /// disallowed-methods = [
+    ///     { path = "std::env::set_var" },
+    ///     { path = "std::thread::sleep", reason = "use `Timer::delay` in async code" },
+    /// ]
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// std::env::set_var("RUST_LOG", "info");
+    /// ```
+    pub DISALLOWED_METHODS,
+    restriction,
+    "use of a function or method that is disallowed in `clippy.toml`"
+}
+
+pub struct DisallowedMethods {
+    conf: Vec<DisallowedPath>,
+    /// Maps the resolved items to their entry in `conf`.
+    disallowed: FxHashMap<DefId, usize>,
+}
+
+impl DisallowedMethods {
+    pub fn new(conf: Vec<DisallowedPath>) -> Self {
+        Self {
+            conf,
+            disallowed: FxHashMap::default(),
+        }
+    }
+}
+
+impl_lint_pass!(DisallowedMethods => [DISALLOWED_METHODS]);
+
+impl<'a, 'tcx> LateLintPass<'a, 'tcx> for DisallowedMethods {
+    fn check_crate(&mut self, cx: &LateContext<'a, 'tcx>, _: &'tcx Crate) {
+        for (index, conf) in self.conf.iter().enumerate() {
+            let segments: Vec<_> = conf.path().split("::").collect();
+            for def_id in resolve(cx, &segments) {
+                self.disallowed.insert(def_id, index);
+            }
+        }
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'a, 'tcx>, expr: &'tcx Expr) {
+        let def_id = match expr.node {
+            // also catches functions that are passed as values, e.g. `.map(std::env::var)`
+            ExprKind::Path(ref qpath) => cx.tables.qpath_res(qpath, expr.hir_id).opt_def_id(),
+            ExprKind::MethodCall(..) => cx.tables.type_dependent_def_id(expr.hir_id),
+            _ => None,
+        };
+
+        if let Some(&index) = def_id.and_then(|def_id| self.disallowed.get(&def_id)) {
+            let conf = &self.conf[index];
+            span_lint_and_then(
+                cx,
+                DISALLOWED_METHODS,
+                expr.span,
+                &format!("use of a disallowed method `{}`", conf.path()),
+                |db| {
+                    if let Some(reason) = conf.reason() {
+                        db.note(reason);
+                    }
+                },
+            );
+        }
+    }
+}
+
+/// Resolves the path of a function or method.
+///
+/// Methods of inherent impls aren't children of the module of their type, so they are looked
+/// up in the inherent impls of the type the rest of the path resolves to.
+fn resolve(cx: &LateContext<'_, '_>, path: &[&str]) -> Vec<DefId> {
+    if let Some(res) = path_to_res(cx, path) {
+        return res.opt_def_id().into_iter().collect();
+    }
+
+    if let Some((name, ty_path)) = path.split_last() {
+        if !ty_path.is_empty() {
+            if let Some(Res::Def(_, ty)) = path_to_res(cx, ty_path) {
+                return cx
+                    .tcx
+                    .inherent_impls(ty)
+                    .iter()
+                    .flat_map(|&impl_id| cx.tcx.associated_item_def_ids(impl_id).iter())
+                    .filter(|&&def_id| cx.tcx.item_name(def_id).as_str() == *name)
+                    .copied()
+                    .collect();
+            }
+        }
+    }
+
+    Vec::new()
+}
@@ -0,0 +1,103 @@
+use crate::utils::conf::DisallowedPath;
+use crate::utils::{path_to_res, span_lint_and_then};
+use rustc::hir::def::Res;
+use rustc::hir::def_id::DefId;
+use rustc::hir::*;
+use rustc::lint::{LateContext, LateLintPass, LintArray, LintPass};
+use rustc::{declare_tool_lint, impl_lint_pass};
+use rustc_data_structures::fx::FxHashMap;
+use syntax::source_map::Span;
+
+declare_clippy_lint! {
+    /// **What it does:** Denies the types configured in the `disallowed-types`
+    /// list of `clippy.toml`.
+    ///
+    /// **Why is this bad?** Some types are undesirable in certain contexts, e.g.
+    /// `HashMap` in crates whose output must not depend on the iteration order.
+    ///
+    /// **Known problems:** Paths can only refer to items of the crates the linted
+    /// crate depends on. Paths that can't be resolved are ignored.
+    ///
+    /// **Example:**
+    ///
+    /// With the following configuration:
+    /// ```toml
+    /// disallowed-types = [
+    ///     { path = "std::collections::HashMap", reason = "use `BTreeMap` for a deterministic output" },
+    /// ]
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// use std::collections::HashMap;
+    ///
+    /// struct Index {
+    ///     words: HashMap<String, usize>,
+    /// }
+    /// ```
+    pub DISALLOWED_TYPES,
+    restriction,
+    "use of a type that is disallowed in `clippy.toml`"
+}
+
+pub struct DisallowedTypes {
+    conf: Vec<DisallowedPath>,
+    /// Maps the resolved types to their entry in `conf`.
+    disallowed: FxHashMap<DefId, usize>,
+}
+
+impl DisallowedTypes {
+    pub fn new(conf: Vec<DisallowedPath>) -> Self {
+        Self {
+            conf,
+            disallowed: FxHashMap::default(),
+        }
+    }
+
+    fn check_res(&self, cx: &LateContext<'_, '_>, res: Res, span: Span) {
+        let index = match res {
+            Res::Def(_, def_id) => self.disallowed.get(&def_id),
+            _ => None,
+        };
+
+        if let Some(&index) = index {
+            let conf = &self.conf[index];
+            span_lint_and_then(
+                cx,
+                DISALLOWED_TYPES,
+                span,
+                &format!("use of a disallowed type `{}`", conf.path()),
+                |db| {
+                    if let Some(reason) = conf.reason() {
+                        db.note(reason);
+                    }
+                },
+            );
+        }
+    }
+}
+
+impl_lint_pass!(DisallowedTypes => [DISALLOWED_TYPES]);
+
+impl<'a, 'tcx> LateLintPass<'a, 'tcx> for DisallowedTypes {
+    fn check_crate(&mut self, cx: &LateContext<'a, 'tcx>, _: &'tcx Crate) {
+        for (index, conf) in self.conf.iter().enumerate() {
+            let segments: Vec<_> = conf.path().split("::").collect();
+            if let Some(def_id) = path_to_res(cx, &segments).and_then(|res| res.opt_def_id()) {
+                self.disallowed.insert(def_id, index);
+            }
+        }
+    }
+
+    fn check_item(&mut self, cx: &LateContext<'a, 'tcx>, item: &'tcx Item) {
+        if let ItemKind::Use(ref path, UseKind::Single) = item.node {
+            self.check_res(cx, path.res, path.span);
+        }
+    }
+
+    // Covers signatures, fields, `let` bindings with a type and paths like `HashMap::new`.
+    fn check_ty(&mut self, cx: &LateContext<'a, 'tcx>, ty: &'tcx Ty) {
+        if let TyKind::Path(QPath::Resolved(_, ref path)) = ty.node {
+            self.check_res(cx, path.res, path.span);
+        }
+    }
+}
@@ -53,7 +53,7 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for EqOp {
             if e.span.from_expansion() {
                 return;
             }
-            if is_valid_operator(op) && SpanlessEq::new(cx).ignore_fn().eq_expr(left, right) {
+            if is_valid_operator(op) && SpanlessEq::new(cx).ignore_fn().commutative().eq_expr(left, right) {
                 span_lint(
                     cx,
                     EQ_OP,
@@ -1,5 +1,6 @@
 use std::convert::TryFrom;
 
+use crate::utils::conf::{Conf, Scoped};
 use crate::utils::{iter_input_pats, snippet, snippet_opt, span_lint, type_is_unsafe_function};
 use matches::matches;
 use rustc::hir;
@@ -84,15 +85,17 @@ declare_clippy_lint! {
     "public functions dereferencing raw pointer arguments but not marked `unsafe`"
 }
 
-#[derive(Copy, Clone)]
 pub struct Functions {
-    threshold: u64,
-    max_lines: u64,
+    threshold: Scoped<u64>,
+    max_lines: Scoped<u64>,
 }
 
 impl Functions {
-    pub fn new(threshold: u64, max_lines: u64) -> Self {
-        Self { threshold, max_lines }
+    pub fn new(conf: &Conf) -> Self {
+        Self {
+            threshold: Scoped::new(conf, |conf| conf.too_many_arguments_threshold),
+            max_lines: Scoped::new(conf, |conf| conf.too_many_lines_threshold),
+        }
     }
 }
 
@@ -160,7 +163,7 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for Functions {
 }
 
 impl<'a, 'tcx> Functions {
-    fn check_arg_number(self, cx: &LateContext<'_, '_>, decl: &hir::FnDecl, span: Span) {
+    fn check_arg_number(&self, cx: &LateContext<'_, '_>, decl: &hir::FnDecl, span: Span) {
         // Remove the function body from the span. We can't use `SourceMap::def_span` because the
         // argument list might span multiple lines.
         let span = if let Some(snippet) = snippet_opt(cx, span) {
@@ -175,17 +178,18 @@ impl<'a, 'tcx> Functions {
         };
 
         let args = decl.inputs.len() as u64;
-        if args > self.threshold {
+        let threshold = *self.threshold.get(cx.sess(), span);
+        if args > threshold {
             span_lint(
                 cx,
                 TOO_MANY_ARGUMENTS,
                 span,
-                &format!("this function has too many arguments ({}/{})", args, self.threshold),
+                &format!("this function has too many arguments ({}/{})", args, threshold),
             );
         }
     }
 
-    fn check_line_number(self, cx: &LateContext<'_, '_>, span: Span, body: &'tcx hir::Body) {
+    fn check_line_number(&self, cx: &LateContext<'_, '_>, span: Span, body: &'tcx hir::Body) {
         if in_external_macro(cx.sess(), span) {
             return;
         }
@@ -234,13 +238,13 @@ impl<'a, 'tcx> Functions {
             }
         }
 
-        if line_count > self.max_lines {
+        if line_count > *self.max_lines.get(cx.sess(), span) {
             span_lint(cx, TOO_MANY_LINES, span, "This function has a large number of lines.")
         }
     }
 
     fn check_raw_ptr(
-        self,
+        &self,
         cx: &LateContext<'a, 'tcx>,
         unsafety: hir::Unsafety,
         decl: &'tcx hir::FnDecl,
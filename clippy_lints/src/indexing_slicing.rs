@@ -4,10 +4,15 @@ use crate::consts::{constant, Constant};
 use crate::utils;
 use crate::utils::higher;
 use crate::utils::higher::Range;
+use crate::utils::ranges::{expr_range, is_bounded_by, is_immutable_local};
+use crate::utils::SpanlessEq;
+use if_chain::if_chain;
+use rustc::hir::def::Res;
 use rustc::hir::*;
 use rustc::lint::{LateContext, LateLintPass, LintArray, LintPass};
 use rustc::ty;
 use rustc::{declare_lint_pass, declare_tool_lint};
+use std::convert::TryFrom;
 use syntax::ast::RangeLimits;
 
 declare_clippy_lint! {
@@ -62,7 +67,9 @@ declare_clippy_lint! {
     /// x.get(2..100);
     /// x.get(2..);
     /// x.get(..100);
+    /// ```
     ///
+    /// ```rust,no_run
     /// // Array
     /// let y = [0, 1, 2, 3];
     ///
@@ -139,12 +146,24 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for IndexingSlicing {
                 utils::span_help_and_lint(cx, INDEXING_SLICING, expr.span, "slicing may panic.", help_msg);
             } else {
                 // Catchall non-range index, i.e., [n] or [n << m]
-                if let ty::Array(..) = ty.sty {
+                if let ty::Array(_, s) = ty.sty {
                     // Index is a constant uint.
                     if let Some(..) = constant(cx, cx.tables, index) {
                         // Let rustc's `const_err` lint handle constant `usize` indexing on arrays.
                         return;
                     }
+
+                    // Index is known to be in bounds, e.g., `x[i % 4]`.
+                    let size: u128 = s.eval_usize(cx.tcx, cx.param_env).into();
+                    let max = expr_range(cx, index).and_then(|range| u128::try_from(range.max).ok());
+                    if max.map_or(false, |max| max < size) {
+                        return;
+                    }
+                }
+
+                // Index is checked against the length, e.g., `if i < x.len() { x[i] }`.
+                if is_bounded_by(cx, index, |bound| is_len_of(cx, bound, array)) {
+                    return;
                 }
 
                 utils::span_help_and_lint(
@@ -159,6 +178,24 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for IndexingSlicing {
     }
 }
 
+/// Checks if `expr` is `array.len()` and the length of `array` can't change, because it's an
+/// immutable local that isn't a mutable reference.
+fn is_len_of(cx: &LateContext<'_, '_>, expr: &Expr, array: &Expr) -> bool {
+    if_chain! {
+        if let ExprKind::MethodCall(ref path, _, ref args) = expr.node;
+        if path.ident.name == sym!(len) && args.len() == 1;
+        if let ExprKind::Path(QPath::Resolved(None, ref array_path)) = array.node;
+        if let Res::Local(local) = array_path.res;
+        if is_immutable_local(cx, local);
+        if let ty::Ref(_, _, MutImmutable) | ty::Array(..) | ty::Adt(..) = cx.tables.expr_ty(array).sty;
+        then {
+            SpanlessEq::new(cx).eq_expr(&args[0], array)
+        } else {
+            false
+        }
+    }
+}
+
 /// Returns a tuple of options with the start and end (exclusive) values of
 /// the range. If the start or end is not constant, None is returned.
 fn to_const_range<'a, 'tcx>(
@@ -164,6 +164,8 @@ pub mod copy_iterator;
 pub mod dbg_macro;
 pub mod default_trait_access;
 pub mod derive;
+pub mod disallowed_methods;
+pub mod disallowed_types;
 pub mod doc;
 pub mod double_comparison;
 pub mod double_parens;
@@ -282,6 +284,7 @@ pub mod zero_div_zero;
 // end lints modules, do not remove this comment, it’s used in `update_lints`
 
 pub use crate::utils::conf::Conf;
+pub use crate::utils::timings::Timings;
 
 mod reexport {
     crate use syntax::ast::Name;
@@ -299,24 +302,30 @@ pub fn register_pre_expansion_lints(
     session: &rustc::session::Session,
     store: &mut rustc::lint::LintStore,
     conf: &Conf,
+    timings: &mut Timings,
 ) {
-    store.register_pre_expansion_pass(Some(session), true, false, box write::Write);
+    store.register_pre_expansion_pass(Some(session), true, false, timings.wrap_pre_expansion(box write::Write));
     store.register_pre_expansion_pass(
         Some(session),
         true,
         false,
-        box redundant_field_names::RedundantFieldNames,
+        timings.wrap_pre_expansion(box redundant_field_names::RedundantFieldNames),
     );
     store.register_pre_expansion_pass(
         Some(session),
         true,
         false,
-        box non_expressive_names::NonExpressiveNames {
+        timings.wrap_pre_expansion(box non_expressive_names::NonExpressiveNames {
             single_char_binding_names_threshold: conf.single_char_binding_names_threshold,
-        },
+        }),
+    );
+    store.register_pre_expansion_pass(
+        Some(session),
+        true,
+        false,
+        timings.wrap_pre_expansion(box attrs::DeprecatedCfgAttribute),
     );
-    store.register_pre_expansion_pass(Some(session), true, false, box attrs::DeprecatedCfgAttribute);
-    store.register_pre_expansion_pass(Some(session), true, false, box dbg_macro::DbgMacro);
+    store.register_pre_expansion_pass(Some(session), true, false, timings.wrap_pre_expansion(box dbg_macro::DbgMacro));
 }
 
 #[doc(hidden)]
@@ -377,6 +386,22 @@ pub fn read_conf(reg: &rustc_driver::plugin::Registry<'_>) -> Conf {
     }
 }
 
+/// Returns the command line flags for the lint levels set in the `[lints]` table of `clippy.toml`.
+///
+/// Errors in the configuration file are ignored here, they are reported by `read_conf`.
+/// `group_lints` returns the lints of a Clippy lint group.
+///
+/// Used in `./src/driver.rs`.
+#[doc(hidden)]
+pub fn conf_lint_level_args(group_lints: impl Fn(&str) -> Option<Vec<String>>) -> Vec<String> {
+    match utils::conf::lookup_conf_file() {
+        Ok(Some(file_name)) => {
+            utils::conf::lint_level_args(&utils::conf::read(Some(file_name.as_path())).0, group_lints)
+        },
+        _ => Vec::new(),
+    }
+}
+
 /// Register all lints and lint groups with the rustc plugin registry
 ///
 /// Used in `./src/driver.rs`.
@@ -441,6 +466,8 @@ pub fn register_plugins(reg: &mut rustc_driver::plugin::Registry<'_>, conf: &Con
     );
     // end deprecated lints, do not remove this comment, it’s used in `update_lints`
 
+    let msrv = conf.msrv.as_ref().map(String::as_str);
+
     reg.register_late_lint_pass(box serde_api::SerdeAPI);
     reg.register_early_lint_pass(box utils::internal_lints::ClippyLintsInternal);
     reg.register_late_lint_pass(box utils::internal_lints::CompilerLintFunctions::new());
@@ -477,7 +504,7 @@ pub fn register_plugins(reg: &mut rustc_driver::plugin::Registry<'_>, conf: &Con
     reg.register_late_lint_pass(box strings::StringAdd);
     reg.register_early_lint_pass(box returns::Return);
     reg.register_late_lint_pass(box implicit_return::ImplicitReturn);
-    reg.register_late_lint_pass(box methods::Methods);
+    reg.register_late_lint_pass(box methods::Methods::new(msrv));
     reg.register_late_lint_pass(box map_clone::MapClone);
     reg.register_late_lint_pass(box shadow::Shadow);
     reg.register_late_lint_pass(box types::LetUnitValue);
@@ -500,9 +527,7 @@ pub fn register_plugins(reg: &mut rustc_driver::plugin::Registry<'_>, conf: &Con
     reg.register_late_lint_pass(box no_effect::NoEffect);
     reg.register_late_lint_pass(box temporary_assignment::TemporaryAssignment);
     reg.register_late_lint_pass(box transmute::Transmute);
-    reg.register_late_lint_pass(
-        box cognitive_complexity::CognitiveComplexity::new(conf.cognitive_complexity_threshold)
-    );
+    reg.register_late_lint_pass(box cognitive_complexity::CognitiveComplexity::new(conf));
     reg.register_late_lint_pass(box escape::BoxedLocal{too_large_for_stack: conf.too_large_for_stack});
     reg.register_early_lint_pass(box misc_early::MiscEarlyLints);
     reg.register_late_lint_pass(box panic_unimplemented::PanicUnimplemented);
@@ -531,7 +556,7 @@ pub fn register_plugins(reg: &mut rustc_driver::plugin::Registry<'_>, conf: &Con
     reg.register_late_lint_pass(box blacklisted_name::BlacklistedName::new(
             conf.blacklisted_names.iter().cloned().collect()
     ));
-    reg.register_late_lint_pass(box functions::Functions::new(conf.too_many_arguments_threshold, conf.too_many_lines_threshold));
+    reg.register_late_lint_pass(box functions::Functions::new(conf));
     reg.register_early_lint_pass(box doc::DocMarkdown::new(conf.doc_valid_idents.iter().cloned().collect()));
     reg.register_late_lint_pass(box neg_multiply::NegMultiply);
     reg.register_early_lint_pass(box unsafe_removed_from_name::UnsafeNameRemoval);
@@ -569,12 +594,12 @@ pub fn register_plugins(reg: &mut rustc_driver::plugin::Registry<'_>, conf: &Con
     reg.register_late_lint_pass(box inline_fn_without_body::InlineFnWithoutBody);
     reg.register_late_lint_pass(box identity_conversion::IdentityConversion::default());
     reg.register_late_lint_pass(box types::ImplicitHasher);
-    reg.register_early_lint_pass(box redundant_static_lifetimes::RedundantStaticLifetimes);
+    reg.register_early_lint_pass(box redundant_static_lifetimes::RedundantStaticLifetimes::new(msrv));
     reg.register_late_lint_pass(box fallible_impl_from::FallibleImplFrom);
     reg.register_late_lint_pass(box replace_consts::ReplaceConsts);
     reg.register_late_lint_pass(box types::UnitArg);
     reg.register_late_lint_pass(box double_comparison::DoubleComparisons);
-    reg.register_late_lint_pass(box question_mark::QuestionMark);
+    reg.register_late_lint_pass(box question_mark::QuestionMark::new(msrv));
     reg.register_late_lint_pass(box suspicious_trait_impl::SuspiciousImpl);
     reg.register_early_lint_pass(box cargo_common_metadata::CargoCommonMetadata);
     reg.register_early_lint_pass(box multiple_crate_versions::MultipleCrateVersions);
@@ -600,11 +625,18 @@ pub fn register_plugins(reg: &mut rustc_driver::plugin::Registry<'_>, conf: &Con
     reg.register_late_lint_pass(box integer_division::IntegerDivision);
     reg.register_late_lint_pass(box inherent_to_string::InherentToString);
     reg.register_late_lint_pass(box trait_bounds::TraitBounds);
+    reg.register_late_lint_pass(box disallowed_methods::DisallowedMethods::new(conf.disallowed_methods.clone()));
+    reg.register_late_lint_pass(box disallowed_types::DisallowedTypes::new(conf.disallowed_types.clone()));
+    // must be registered last, it checks which lints were emitted by all other passes
+    reg.register_late_lint_pass(box attrs::UnusedClippyAllow::default());
 
     reg.register_lint_group("clippy::restriction", Some("clippy_restriction"), vec![
         arithmetic::FLOAT_ARITHMETIC,
         arithmetic::INTEGER_ARITHMETIC,
+        attrs::UNUSED_CLIPPY_ALLOW,
         dbg_macro::DBG_MACRO,
+        disallowed_methods::DISALLOWED_METHODS,
+        disallowed_types::DISALLOWED_TYPES,
         else_if_without_else::ELSE_IF_WITHOUT_ELSE,
         implicit_return::IMPLICIT_RETURN,
         indexing_slicing::INDEXING_SLICING,
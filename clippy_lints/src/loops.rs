@@ -27,9 +27,10 @@ use syntax_pos::BytePos;
 
 use crate::utils::paths;
 use crate::utils::{
-    get_enclosing_block, get_parent_expr, has_iter_method, higher, is_integer_literal, is_refutable, last_path_segment,
-    match_trait_method, match_type, match_var, multispan_sugg, snippet, snippet_opt, snippet_with_applicability,
-    span_help_and_lint, span_lint, span_lint_and_sugg, span_lint_and_then, SpanlessEq,
+    get_enclosing_block, get_parent_expr, get_trait_def_id, has_iter_method, higher, implements_trait,
+    is_integer_literal, is_refutable, last_path_segment, match_trait_method, match_type, match_var, multispan_sugg,
+    snippet, snippet_opt, snippet_with_applicability, span_help_and_lint, span_lint, span_lint_and_sugg,
+    span_lint_and_then, SpanlessEq,
 };
 
 declare_clippy_lint! {
@@ -1160,17 +1161,12 @@ fn check_for_loop_range<'a, 'tcx>(
                         expr.span,
                         &format!("the loop variable `{}` is used to index `{}`", ident.name, indexed),
                         |db| {
-                            multispan_sugg(
-                                db,
-                                "consider using an iterator".to_string(),
-                                vec![
-                                    (pat.span, format!("({}, <item>)", ident.name)),
-                                    (
-                                        arg.span,
-                                        format!("{}.{}().enumerate(){}{}", indexed, method, method_1, method_2),
-                                    ),
-                                ],
+                            let mut sugg = sugg::SuggestionBuilder::new(cx, Applicability::HasPlaceholders);
+                            sugg.replace(pat.span, format!("({}, <item>)", ident.name)).replace(
+                                arg.span,
+                                format!("{}.{}().enumerate(){}{}", indexed, method, method_1, method_2),
                             );
+                            sugg.emit(db, "consider using an iterator");
                         },
                     );
                 } else {
@@ -1189,11 +1185,9 @@ fn check_for_loop_range<'a, 'tcx>(
                             ident.name, indexed
                         ),
                         |db| {
-                            multispan_sugg(
-                                db,
-                                "consider using an iterator".to_string(),
-                                vec![(pat.span, "<item>".to_string()), (arg.span, repl)],
-                            );
+                            let mut sugg = sugg::SuggestionBuilder::new(cx, Applicability::HasPlaceholders);
+                            sugg.replace(pat.span, "<item>".to_string()).replace(arg.span, repl);
+                            sugg.emit(db, "consider using an iterator");
                         },
                     );
                 }
@@ -1448,6 +1442,7 @@ fn check_for_loop_explicit_counter<'a, 'tcx>(
                 var_id: *id,
                 state: VarState::IncrOnce,
                 name: None,
+                decl: None,
                 depth: 0,
                 past_loop: false,
             };
@@ -1455,30 +1450,46 @@ fn check_for_loop_explicit_counter<'a, 'tcx>(
 
             if visitor2.state == VarState::Warn {
                 if let Some(name) = visitor2.name {
-                    let mut applicability = Applicability::MachineApplicable;
-                    span_lint_and_sugg(
+                    span_lint_and_then(
                         cx,
                         EXPLICIT_COUNTER_LOOP,
                         expr.span,
                         &format!("the variable `{}` is used as a loop counter.", name),
-                        "consider using",
-                        format!(
-                            "for ({}, {}) in {}.enumerate()",
-                            name,
-                            snippet_with_applicability(cx, pat.span, "item", &mut applicability),
-                            if higher::range(cx, arg).is_some() {
-                                format!(
-                                    "({})",
-                                    snippet_with_applicability(cx, arg.span, "_", &mut applicability)
-                                )
+                        |db| {
+                            let mut sugg = sugg::SuggestionBuilder::new(cx, Applicability::MachineApplicable);
+                            let pat_snippet = sugg.snippet(pat.span, "item");
+                            let arg_snippet = if higher::range(cx, arg).is_some() {
+                                format!("({})", sugg.snippet(arg.span, "_"))
                             } else {
-                                format!(
-                                    "{}",
-                                    sugg::Sugg::hir_with_applicability(cx, arg, "_", &mut applicability).maybe_par()
-                                )
+                                iterator_snippet(cx, &mut sugg, arg)
+                            };
+                            sugg.replace(pat.span, format!("({}, {})", name, pat_snippet))
+                                .replace(arg.span, format!("{}.enumerate()", arg_snippet));
+
+                            // the pattern of the loop shadows the counter
+                            if let Some(decl) = visitor2.decl {
+                                sugg.remove(decl);
+                            } else {
+                                sugg.weaken(Applicability::MaybeIncorrect);
                             }
-                        ),
-                        applicability,
+
+                            if let Some(increment) = counter_increment(cx, body, *id) {
+                                sugg.remove(increment.span);
+                                // the counter changes its value in the middle of the iteration
+                                if !increment.is_last {
+                                    sugg.weaken(Applicability::MaybeIncorrect);
+                                }
+                                // `enumerate` counts with `usize`
+                                if increment.counter_used
+                                    && cx.tables.node_type(*id).sty != ty::Uint(ast::UintTy::Usize)
+                                {
+                                    sugg.weaken(Applicability::MaybeIncorrect);
+                                }
+                            } else {
+                                sugg.weaken(Applicability::MaybeIncorrect);
+                            }
+                            sugg.emit(db, "consider using");
+                        },
                     );
                 }
             }
@@ -1486,6 +1497,86 @@ fn check_for_loop_explicit_counter<'a, 'tcx>(
     }
 }
 
+/// The snippet of an iterator over the values the loop argument `arg` yields, e.g., `v.iter()`
+/// for `&v`. `enumerate` needs an iterator, while `for` loops take anything that implements
+/// `IntoIterator`.
+fn iterator_snippet(
+    cx: &LateContext<'_, '_>,
+    sugg: &mut sugg::SuggestionBuilder<'_, LateContext<'_, '_>>,
+    arg: &Expr,
+) -> String {
+    let is_iterator = get_trait_def_id(cx, &paths::ITERATOR).map_or(false, |iterator_trait| {
+        implements_trait(cx, cx.tables.expr_ty(arg), iterator_trait, &[])
+    });
+    if is_iterator {
+        return sugg.hir(arg, "_").maybe_par().to_string();
+    }
+    match arg.node {
+        ExprKind::AddrOf(mutbl, ref inner) if has_iter_method(cx, cx.tables.expr_ty(inner)).is_some() => format!(
+            "{}.{}()",
+            sugg.hir(inner, "_").maybe_par(),
+            if mutbl == MutMutable { "iter_mut" } else { "iter" }
+        ),
+        _ => format!("{}.into_iter()", sugg.hir(arg, "_").maybe_par()),
+    }
+}
+
+/// The statement incrementing a loop counter, see `counter_increment`.
+struct CounterIncrement {
+    span: Span,
+    /// Whether it's the last statement of the loop body
+    is_last: bool,
+    /// Whether the counter is used by the other statements of the loop body
+    counter_used: bool,
+}
+
+/// Finds the statement incrementing the loop counter `var` among the statements of the loop
+/// body.
+fn counter_increment<'a, 'tcx>(
+    cx: &'a LateContext<'a, 'tcx>,
+    body: &'tcx Expr,
+    var: HirId,
+) -> Option<CounterIncrement> {
+    let is_increment = |expr: &Expr| {
+        if let ExprKind::AssignOp(op, ref lhs, _) = expr.node {
+            op.node == BinOpKind::Add && var_def_id(cx, lhs) == Some(var)
+        } else {
+            false
+        }
+    };
+
+    let block = match body.node {
+        ExprKind::Block(ref block, _) => block,
+        _ => return None,
+    };
+    let mut increment = None;
+    let mut visitor = LocalUsedVisitor {
+        cx,
+        local: var,
+        used: false,
+    };
+    for (i, stmt) in block.stmts.iter().enumerate() {
+        match stmt.node {
+            StmtKind::Expr(ref expr) | StmtKind::Semi(ref expr) if increment.is_none() && is_increment(expr) => {
+                increment = Some((stmt.span, block.expr.is_none() && i + 1 == block.stmts.len()));
+            },
+            _ => visitor.visit_stmt(stmt),
+        }
+    }
+    if let Some(ref expr) = block.expr {
+        if increment.is_none() && is_increment(expr) {
+            increment = Some((expr.span, true));
+        } else {
+            visitor.visit_expr(expr);
+        }
+    }
+    increment.map(|(span, is_last)| CounterIncrement {
+        span,
+        is_last,
+        counter_used: visitor.used,
+    })
+}
+
 /// Checks for the `FOR_KV_MAP` lint.
 fn check_for_loop_over_map_kv<'a, 'tcx>(
     cx: &LateContext<'a, 'tcx>,
@@ -2081,6 +2172,9 @@ struct InitializeVisitor<'a, 'tcx> {
     var_id: HirId,
     state: VarState,
     name: Option<Name>,
+    /// The `let` statement initializing the variable to zero, unless the variable is used
+    /// between it and the loop.
+    decl: Option<Span>,
     depth: u32, // depth of conditional expressions
     past_loop: bool,
 }
@@ -2101,7 +2195,12 @@ impl<'a, 'tcx> Visitor<'tcx> for InitializeVisitor<'a, 'tcx> {
                         }
                     } else {
                         VarState::Declared
-                    }
+                    };
+                    self.decl = if self.state == VarState::Warn {
+                        Some(stmt.span)
+                    } else {
+                        None
+                    };
                 }
             }
         }
@@ -2124,6 +2223,7 @@ impl<'a, 'tcx> Visitor<'tcx> for InitializeVisitor<'a, 'tcx> {
 
         // If node is the desired variable, see how it's used
         if var_def_id(self.cx, expr) == Some(self.var_id) {
+            self.decl = None;
             if let Some(parent) = get_parent_expr(self.cx, expr) {
                 match parent.node {
                     ExprKind::AssignOp(_, ref lhs, _) if lhs.hir_id == expr.hir_id => {
@@ -6,6 +6,9 @@ use rustc_errors::Applicability;
 use rustc_target::abi::LayoutOf;
 use syntax::ast;
 
+/// `saturating_mul` is stable since Rust 1.7.
+pub const MSRV: (u64, u64, u64) = (1, 7, 0);
+
 pub fn lint(cx: &LateContext<'_, '_>, expr: &hir::Expr, args: &[&[hir::Expr]], arith: &str) {
     let unwrap_arg = &args[0][1];
     let arith_lhs = &args[1][0];
@@ -12,7 +12,7 @@ use rustc::hir;
 use rustc::hir::intravisit::{self, Visitor};
 use rustc::lint::{in_external_macro, LateContext, LateLintPass, Lint, LintArray, LintContext, LintPass};
 use rustc::ty::{self, Predicate, Ty};
-use rustc::{declare_lint_pass, declare_tool_lint};
+use rustc::{declare_tool_lint, impl_lint_pass};
 use rustc_errors::Applicability;
 use syntax::ast;
 use syntax::source_map::Span;
@@ -25,7 +25,7 @@ use crate::utils::{
     is_ctor_function, is_expn_of, iter_input_pats, last_path_segment, match_def_path, match_qpath, match_trait_method,
     match_type, match_var, method_calls, method_chain_args, remove_blocks, return_ty, same_tys, single_segment_path,
     snippet, snippet_with_applicability, snippet_with_macro_callsite, span_lint, span_lint_and_sugg,
-    span_lint_and_then, span_note_and_lint, walk_ptrs_ty, walk_ptrs_ty_depth, SpanlessEq,
+    span_lint_and_then, span_note_and_lint, walk_ptrs_ty, walk_ptrs_ty_depth, MsrvStack, SpanlessEq,
 };
 use crate::utils::{paths, span_help_and_lint};
 
@@ -1011,7 +1011,19 @@ declare_clippy_lint! {
     "`.chcked_add/sub(x).unwrap_or(MAX/MIN)`"
 }
 
-declare_lint_pass!(Methods => [
+pub struct Methods {
+    msrv: MsrvStack,
+}
+
+impl Methods {
+    pub fn new(msrv: Option<&str>) -> Self {
+        Self {
+            msrv: MsrvStack::new(msrv),
+        }
+    }
+}
+
+impl_lint_pass!(Methods => [
     OPTION_UNWRAP_USED,
     RESULT_UNWRAP_USED,
     SHOULD_IMPLEMENT_TRAIT,
@@ -1107,7 +1119,9 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for Methods {
             ["assume_init"] => lint_maybe_uninit(cx, &arg_lists[0][0], expr),
             ["unwrap_or", arith @ "checked_add"]
             | ["unwrap_or", arith @ "checked_sub"]
-            | ["unwrap_or", arith @ "checked_mul"] => {
+            | ["unwrap_or", arith @ "checked_mul"]
+                if self.msrv.meets(manual_saturating_arithmetic::MSRV) =>
+            {
                 manual_saturating_arithmetic::lint(cx, expr, &arg_lists, &arith["checked_".len()..])
             },
             _ => {},
@@ -1261,6 +1275,13 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for Methods {
             }
         }
     }
+
+    fn enter_lint_attrs(&mut self, cx: &LateContext<'a, 'tcx>, attrs: &'tcx [ast::Attribute]) {
+        self.msrv.push_attrs(cx.sess(), attrs);
+    }
+    fn exit_lint_attrs(&mut self, cx: &LateContext<'a, 'tcx>, attrs: &'tcx [ast::Attribute]) {
+        self.msrv.pop_attrs(cx.sess(), attrs);
+    }
 }
 
 /// Checks for the `OR_FUN_CALL` lint.
@@ -44,7 +44,7 @@ impl EarlyLintPass for MultipleCrateVersions {
         };
 
         let mut packages = metadata.packages;
-        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
 
         for (name, group) in &packages.into_iter().group_by(|p| p.name.clone()) {
             let group: Vec<cargo_metadata::Package> = group.collect();
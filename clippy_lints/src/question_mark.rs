@@ -2,13 +2,14 @@ use if_chain::if_chain;
 use rustc::hir::def::{DefKind, Res};
 use rustc::hir::ptr::P;
 use rustc::hir::*;
-use rustc::lint::{LateContext, LateLintPass, LintArray, LintPass};
-use rustc::{declare_lint_pass, declare_tool_lint};
+use rustc::lint::{LateContext, LateLintPass, LintArray, LintContext, LintPass};
+use rustc::{declare_tool_lint, impl_lint_pass};
 use rustc_errors::Applicability;
+use syntax::ast::Attribute;
 
 use crate::utils::paths::*;
 use crate::utils::sugg::Sugg;
-use crate::utils::{higher, match_def_path, match_type, span_lint_and_then, SpanlessEq};
+use crate::utils::{higher, match_def_path, match_type, span_lint_and_then, MsrvStack, SpanlessEq};
 
 declare_clippy_lint! {
     /// **What it does:** Checks for expressions that could be replaced by the question mark operator.
@@ -34,7 +35,22 @@ declare_clippy_lint! {
     "checks for expressions that could be replaced by the question mark operator"
 }
 
-declare_lint_pass!(QuestionMark => [QUESTION_MARK]);
+/// `Option` implements `Try` since Rust 1.22.
+const QUESTION_MARK_MSRV: (u64, u64, u64) = (1, 22, 0);
+
+pub struct QuestionMark {
+    msrv: MsrvStack,
+}
+
+impl QuestionMark {
+    pub fn new(msrv: Option<&str>) -> Self {
+        Self {
+            msrv: MsrvStack::new(msrv),
+        }
+    }
+}
+
+impl_lint_pass!(QuestionMark => [QUESTION_MARK]);
 
 impl QuestionMark {
     /// Checks if the given expression on the given context matches the following structure:
@@ -158,6 +174,15 @@ impl QuestionMark {
 
 impl<'a, 'tcx> LateLintPass<'a, 'tcx> for QuestionMark {
     fn check_expr(&mut self, cx: &LateContext<'a, 'tcx>, expr: &'tcx Expr) {
-        Self::check_is_none_and_early_return_none(cx, expr);
+        if self.msrv.meets(QUESTION_MARK_MSRV) {
+            Self::check_is_none_and_early_return_none(cx, expr);
+        }
+    }
+
+    fn enter_lint_attrs(&mut self, cx: &LateContext<'a, 'tcx>, attrs: &'tcx [Attribute]) {
+        self.msrv.push_attrs(cx.sess(), attrs);
+    }
+    fn exit_lint_attrs(&mut self, cx: &LateContext<'a, 'tcx>, attrs: &'tcx [Attribute]) {
+        self.msrv.pop_attrs(cx.sess(), attrs);
     }
 }
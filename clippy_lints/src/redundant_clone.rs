@@ -1,3 +1,4 @@
+use crate::utils::mir::{lint_root, LocalLiveness, PossibleBorrowerMap};
 use crate::utils::{
     has_drop, is_copy, match_def_path, match_type, paths, snippet_opt, span_lint_hir, span_lint_hir_and_then,
     walk_ptrs_ty_depth,
@@ -7,11 +8,7 @@ use matches::matches;
 use rustc::hir::intravisit::FnKind;
 use rustc::hir::{def_id, Body, FnDecl, HirId};
 use rustc::lint::{LateContext, LateLintPass, LintArray, LintPass};
-use rustc::mir::{
-    self, traversal,
-    visit::{MutatingUseContext, PlaceContext, Visitor},
-    TerminatorKind,
-};
+use rustc::mir::{self, TerminatorKind};
 use rustc::ty::{self, Ty};
 use rustc::{declare_lint_pass, declare_tool_lint};
 use rustc_errors::Applicability;
@@ -34,19 +31,7 @@ declare_clippy_lint! {
     /// **Why is this bad?** It is not always possible for the compiler to eliminate useless
     /// allocations and deallocations generated by redundant `clone()`s.
     ///
-    /// **Known problems:**
-    ///
-    /// * Suggestions made by this lint could require NLL to be enabled.
-    /// * False-positive if there is a borrow preventing the value from moving out.
-    ///
-    /// ```rust
-    /// # fn foo(x: String) {}
-    /// let x = String::new();
-    ///
-    /// let y = &x;
-    ///
-    /// foo(x.clone()); // This lint suggests to remove this `clone()`
-    /// ```
+    /// **Known problems:** Suggestions made by this lint could require NLL to be enabled.
     ///
     /// **Example:**
     /// ```rust
@@ -87,6 +72,8 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for RedundantClone {
     ) {
         let def_id = cx.tcx.hir().body_owner_def_id(body.id());
         let mir = cx.tcx.optimized_mir(def_id);
+        let liveness = LocalLiveness::new(mir);
+        let mut possible_borrower = PossibleBorrowerMap::new(cx, mir);
 
         for (bb, bbdata) in mir.basic_blocks().iter_enumerated() {
             let terminator = bbdata.terminator();
@@ -95,11 +82,6 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for RedundantClone {
                 continue;
             }
 
-            // Give up on loops
-            if terminator.successors().any(|s| *s == bb) {
-                continue;
-            }
-
             let (fn_def_id, arg, arg_ty, _) = unwrap_or_continue!(is_call_with_ref_arg(cx, mir, &terminator.kind));
 
             let from_borrow = match_def_path(cx, fn_def_id, &paths::CLONE_TRAIT_METHOD)
@@ -125,7 +107,13 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for RedundantClone {
                 bbdata.statements.iter()
             ));
 
-            if from_borrow && cannot_move_out {
+            let loc = mir::Location {
+                block: bb,
+                statement_index: bbdata.statements.len(),
+            };
+
+            if from_borrow && (cannot_move_out || !possible_borrower.only_borrowers(&[arg], cloned, loc)) {
+                // `cloned` can only be moved out if `arg` is its only borrow at this point.
                 continue;
             }
 
@@ -158,7 +146,7 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for RedundantClone {
                     true,
                     mir[ps[0]].statements.iter()
                 ));
-                if cannot_move_out {
+                if cannot_move_out || !possible_borrower.only_borrowers(&[arg, cloned], local, loc) {
                     continue;
                 }
                 local
@@ -166,27 +154,9 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for RedundantClone {
                 cloned
             };
 
-            let used_later = traversal::ReversePostorder::new(&mir, bb).skip(1).any(|(tbb, tdata)| {
-                // Give up on loops
-                if tdata.terminator().successors().any(|s| *s == bb) {
-                    return true;
-                }
-
-                let mut vis = LocalUseVisitor {
-                    local: referent,
-                    used_other_than_drop: false,
-                };
-                vis.visit_basic_block_data(tbb, tdata);
-                vis.used_other_than_drop
-            });
-
-            if !used_later {
+            if !liveness.is_used_after(referent, loc) {
                 let span = terminator.source_info.span;
-                let node = if let mir::ClearCrossCrate::Set(scope_local_data) = &mir.source_scope_local_data {
-                    scope_local_data[terminator.source_info.scope].lint_root
-                } else {
-                    unreachable!()
-                };
+                let node = lint_root(mir, terminator.source_info);
 
                 if_chain! {
                     if let Some(snip) = snippet_opt(cx, span);
@@ -316,41 +286,3 @@ fn base_local_and_movability<'tcx>(
         None
     }
 }
-
-struct LocalUseVisitor {
-    local: mir::Local,
-    used_other_than_drop: bool,
-}
-
-impl<'tcx> mir::visit::Visitor<'tcx> for LocalUseVisitor {
-    fn visit_basic_block_data(&mut self, block: mir::BasicBlock, data: &mir::BasicBlockData<'tcx>) {
-        let statements = &data.statements;
-        for (statement_index, statement) in statements.iter().enumerate() {
-            self.visit_statement(statement, mir::Location { block, statement_index });
-
-            // Once flagged, skip remaining statements
-            if self.used_other_than_drop {
-                return;
-            }
-        }
-
-        self.visit_terminator(
-            data.terminator(),
-            mir::Location {
-                block,
-                statement_index: statements.len(),
-            },
-        );
-    }
-
-    fn visit_local(&mut self, local: &mir::Local, ctx: PlaceContext, _: mir::Location) {
-        match ctx {
-            PlaceContext::MutatingUse(MutatingUseContext::Drop) | PlaceContext::NonUse(_) => return,
-            _ => {},
-        }
-
-        if *local == self.local {
-            self.used_other_than_drop = true;
-        }
-    }
-}
@@ -1,6 +1,6 @@
-use crate::utils::{snippet, span_lint_and_then};
-use rustc::lint::{EarlyContext, EarlyLintPass, LintArray, LintPass};
-use rustc::{declare_lint_pass, declare_tool_lint};
+use crate::utils::{snippet, span_lint_and_then, MsrvStack};
+use rustc::lint::{EarlyContext, EarlyLintPass, LintArray, LintContext, LintPass};
+use rustc::{declare_tool_lint, impl_lint_pass};
 use rustc_errors::Applicability;
 use syntax::ast::*;
 
@@ -29,7 +29,22 @@ declare_clippy_lint! {
     "Using explicit `'static` lifetime for constants or statics when elision rules would allow omitting them."
 }
 
-declare_lint_pass!(RedundantStaticLifetimes => [REDUNDANT_STATIC_LIFETIMES]);
+/// Constants and statics have an implicit `'static` lifetime since Rust 1.17.
+const REDUNDANT_STATIC_LIFETIMES_MSRV: (u64, u64, u64) = (1, 17, 0);
+
+pub struct RedundantStaticLifetimes {
+    msrv: MsrvStack,
+}
+
+impl RedundantStaticLifetimes {
+    pub fn new(msrv: Option<&str>) -> Self {
+        Self {
+            msrv: MsrvStack::new(msrv),
+        }
+    }
+}
+
+impl_lint_pass!(RedundantStaticLifetimes => [REDUNDANT_STATIC_LIFETIMES]);
 
 impl RedundantStaticLifetimes {
     // Recursively visit types
@@ -78,6 +93,10 @@ impl RedundantStaticLifetimes {
 
 impl EarlyLintPass for RedundantStaticLifetimes {
     fn check_item(&mut self, cx: &EarlyContext<'_>, item: &Item) {
+        if !self.msrv.meets(REDUNDANT_STATIC_LIFETIMES_MSRV) {
+            return;
+        }
+
         if !item.span.from_expansion() {
             if let ItemKind::Const(ref var_type, _) = item.node {
                 self.visit_type(var_type, cx, "Constants have by default a `'static` lifetime");
@@ -90,4 +109,11 @@ impl EarlyLintPass for RedundantStaticLifetimes {
             }
         }
     }
+
+    fn enter_lint_attrs(&mut self, cx: &EarlyContext<'_>, attrs: &[Attribute]) {
+        self.msrv.push_attrs(cx.sess(), attrs);
+    }
+    fn exit_lint_attrs(&mut self, cx: &EarlyContext<'_>, attrs: &[Attribute]) {
+        self.msrv.pop_attrs(cx.sess(), attrs);
+    }
 }
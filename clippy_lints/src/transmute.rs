@@ -102,6 +102,8 @@ declare_clippy_lint! {
     /// **Example:**
     /// ```rust
     /// let x = 1_u32;
+    ///
+    /// // Bad
     /// unsafe {
     ///     let _: char = std::mem::transmute(x); // where x: u32
     /// }
@@ -133,6 +135,8 @@ declare_clippy_lint! {
     /// **Example:**
     /// ```rust
     /// let b: &[u8] = &[1_u8, 2_u8];
+    ///
+    /// // Bad
     /// unsafe {
     ///     let _: &str = std::mem::transmute(b); // where b: &[u8]
     /// }
@@ -155,6 +159,8 @@ declare_clippy_lint! {
     /// **Example:**
     /// ```rust
     /// let x = 1_u8;
+    ///
+    /// // Bad
     /// unsafe {
     ///     let _: bool = std::mem::transmute(x); // where x: u8
     /// }
@@ -201,6 +207,8 @@ declare_clippy_lint! {
     /// **Example:**
     /// ```rust
     /// let ptr = &1u32 as *const u32;
+    ///
+    /// // Bad
     /// unsafe {
     ///     // pointer-to-pointer transmute
     ///     let _: *const f32 = std::mem::transmute(ptr);
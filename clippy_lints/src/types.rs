@@ -22,6 +22,7 @@ use syntax::symbol::sym;
 
 use crate::consts::{constant, Constant};
 use crate::utils::paths;
+use crate::utils::ranges::{expr_range, IntRange};
 use crate::utils::{
     clip, comparisons, differing_macro_contexts, higher, in_constant, int_bits, last_path_segment, match_def_path,
     match_path, multispan_sugg, same_tys, sext, snippet, snippet_opt, snippet_with_applicability,
@@ -695,7 +696,8 @@ declare_clippy_lint! {
 declare_clippy_lint! {
     /// **What it does:** Checks for casts between numerical types that may
     /// truncate large values. This is expected behavior, so the cast is `Allow` by
-    /// default.
+    /// default. Values that are known to fit into the target type, like in
+    /// `(x & 0xff) as u8`, are not linted.
     ///
     /// **Why is this bad?** In some problem domains, it is good practice to avoid
     /// truncation. This lint can be activated to help assess where additional
@@ -974,16 +976,9 @@ fn check_loss_of_sign(cx: &LateContext<'_, '_>, expr: &Expr, op: &Expr, cast_fro
         return;
     }
 
-    // don't lint for positive constants
-    let const_val = constant(cx, &cx.tables, op);
-    if_chain! {
-        if let Some((const_val, _)) = const_val;
-        if let Constant::Int(n) = const_val;
-        if let ty::Int(ity) = cast_from.sty;
-        if sext(cx.tcx, n, ity) >= 0;
-        then {
-            return
-        }
+    // don't lint for values that are never negative, e.g., positive constants
+    if expr_range(cx, op).map_or(false, |range| range.min >= 0) {
+        return;
     }
 
     span_lint(
@@ -994,7 +989,62 @@ fn check_loss_of_sign(cx: &LateContext<'_, '_>, expr: &Expr, op: &Expr, cast_fro
     );
 }
 
-fn check_truncation_and_wrapping(cx: &LateContext<'_, '_>, expr: &Expr, cast_from: Ty<'_>, cast_to: Ty<'_>) {
+/// Whether the values of `op` are known to fit into `cast_to`, e.g., because of a mask like in
+/// `(x & 0xff) as u8`, for every size of `isize` and `usize`.
+fn fits_on_all_targets(cx: &LateContext<'_, '_>, op: &Expr, cast_to: Ty<'_>) -> bool {
+    // Casting a constant still casts its type, `1i32 as u8` should be `1u8`.
+    if constant(cx, cx.tables, op).is_some() {
+        return false;
+    }
+    // The values of `isize` and `usize`, and of anything computed from them, depend on the target.
+    if uses_pointer_sized_value(cx, op) {
+        return false;
+    }
+    let range = match expr_range(cx, op) {
+        Some(range) => range,
+        None => return false,
+    };
+    let target_range = match cast_to.sty {
+        ty::Int(IntTy::Isize) => IntRange::new(i32::min_value().into(), i32::max_value().into()),
+        ty::Uint(UintTy::Usize) => IntRange::new(0, u32::max_value().into()),
+        _ => match IntRange::of_type(cx.tcx, cast_to) {
+            Some(target_range) => target_range,
+            None => return false,
+        },
+    };
+    target_range.contains(range)
+}
+
+/// Whether `expr` or any of its subexpressions is an `isize` or `usize`.
+fn uses_pointer_sized_value(cx: &LateContext<'_, '_>, expr: &Expr) -> bool {
+    let mut visitor = PointerSizedVisitor { cx, found: false };
+    visitor.visit_expr(expr);
+    visitor.found
+}
+
+struct PointerSizedVisitor<'a, 'b, 'tcx> {
+    cx: &'a LateContext<'b, 'tcx>,
+    found: bool,
+}
+
+impl<'a, 'b, 'tcx, 'v> Visitor<'v> for PointerSizedVisitor<'a, 'b, 'tcx> {
+    fn visit_expr(&mut self, expr: &'v Expr) {
+        if self.found {
+            return;
+        }
+        if self.cx.tables.expr_ty_opt(expr).map_or(false, is_isize_or_usize) {
+            self.found = true;
+            return;
+        }
+        walk_expr(self, expr);
+    }
+
+    fn nested_visit_map<'this>(&'this mut self) -> NestedVisitorMap<'this, 'v> {
+        NestedVisitorMap::None
+    }
+}
+
+fn check_truncation_and_wrapping(cx: &LateContext<'_, '_>, expr: &Expr, op: &Expr, cast_from: Ty<'_>, cast_to: Ty<'_>) {
     let arch_64_suffix = " on targets with 64-bit wide pointers";
     let arch_32_suffix = " on targets with 32-bit wide pointers";
     let cast_unsigned_to_signed = !cast_from.is_signed() && cast_to.is_signed();
@@ -1029,6 +1079,9 @@ fn check_truncation_and_wrapping(cx: &LateContext<'_, '_>, expr: &Expr, cast_fro
                 },
             ),
         };
+    if (span_truncation || span_wrap) && fits_on_all_targets(cx, op, cast_to) {
+        return;
+    }
     if span_truncation {
         span_lint(
             cx,
@@ -1206,7 +1259,7 @@ fn lint_numeric_casts<'tcx>(
         },
         (true, true) => {
             check_loss_of_sign(cx, expr, cast_expr, cast_from, cast_to);
-            check_truncation_and_wrapping(cx, expr, cast_from, cast_to);
+            check_truncation_and_wrapping(cx, expr, cast_expr, cast_from, cast_to);
             check_lossless(cx, expr, cast_expr, cast_from, cast_to);
         },
         (false, false) => {
@@ -1638,6 +1691,14 @@ fn detect_absurd_comparison<'a, 'tcx>(
 fn detect_extreme_expr<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, expr: &'tcx Expr) -> Option<ExtremeExpr<'tcx>> {
     use crate::types::ExtremeType::*;
 
+    // The extreme values of target sized integers depend on the target, casting them to fixed
+    // size types isn't necessarily an extreme value.
+    if let ExprKind::Cast(ref operand, _) = expr.node {
+        if let ty::Int(IntTy::Isize) | ty::Uint(UintTy::Usize) = cx.tables.expr_ty(operand).sty {
+            return None;
+        }
+    }
+
     let ty = cx.tables.expr_ty(expr);
 
     let cv = constant(cx, cx.tables, expr)?.0;
@@ -1,5 +1,6 @@
 use rustc::session::Session;
 use rustc_errors::Applicability;
+use semver::Version;
 use std::str::FromStr;
 use syntax::ast;
 
@@ -21,6 +22,7 @@ pub const BUILTIN_ATTRIBUTES: &[(&str, DeprecationStatus)] = &[
         DeprecationStatus::Replaced("cognitive_complexity"),
     ),
     ("dump", DeprecationStatus::None),
+    ("msrv", DeprecationStatus::None),
 ];
 
 pub struct LimitStack {
@@ -40,6 +42,10 @@ impl LimitStack {
     pub fn limit(&self) -> u64 {
         *self.stack.last().expect("there should always be a value in the stack")
     }
+    /// Returns `true` if an attribute changed the limit from its default value.
+    pub fn is_set_by_attr(&self) -> bool {
+        self.stack.len() > 1
+    }
     pub fn push_attrs(&mut self, sess: &Session, attrs: &[ast::Attribute], name: &'static str) {
         let stack = &mut self.stack;
         parse_attrs(sess, attrs, name, |val| stack.push(val));
@@ -50,6 +56,64 @@ impl LimitStack {
     }
 }
 
+/// The minimum supported Rust version of the code being linted, as set by the `msrv`
+/// configuration value and `#[clippy::msrv = "1.30"]` attributes.
+///
+/// Lints that suggest APIs or syntax introduced in a later version stay silent when
+/// `meets` returns `false`.
+pub struct MsrvStack {
+    stack: Vec<Option<Version>>,
+}
+
+impl MsrvStack {
+    pub fn new(msrv: Option<&str>) -> Self {
+        Self {
+            stack: vec![msrv.and_then(parse_msrv)],
+        }
+    }
+    /// Returns `true` if code written for the current MSRV can use features that were
+    /// stabilized in `major.minor.patch`. Without a MSRV, every feature can be used.
+    pub fn meets(&self, (major, minor, patch): (u64, u64, u64)) -> bool {
+        self.stack
+            .last()
+            .and_then(Option::as_ref)
+            .map_or(true, |msrv| *msrv >= Version::new(major, minor, patch))
+    }
+    pub fn push_attrs(&mut self, sess: &Session, attrs: &[ast::Attribute]) {
+        let mut msrv = None;
+        for attr in get_attr(sess, attrs, "msrv") {
+            if let Some(ref value) = attr.value_str() {
+                if let Some(version) = parse_msrv(&value.as_str()) {
+                    msrv = Some(version);
+                } else {
+                    sess.span_err(attr.span, "`msrv` is not a valid Rust version");
+                }
+            } else {
+                sess.span_err(attr.span, "bad clippy attribute");
+            }
+        }
+        if let Some(msrv) = msrv {
+            self.stack.push(Some(msrv));
+        }
+    }
+    pub fn pop_attrs(&mut self, sess: &Session, attrs: &[ast::Attribute]) {
+        let has_msrv = get_attr(sess, attrs, "msrv")
+            .filter_map(ast::Attribute::value_str)
+            .any(|value| parse_msrv(&value.as_str()).is_some());
+        if has_msrv {
+            self.stack.pop();
+        }
+    }
+}
+
+/// Parses a Rust version like `1.30` or `1.30.1`.
+pub fn parse_msrv(msrv: &str) -> Option<Version> {
+    Version::parse(msrv)
+        .or_else(|_| Version::parse(&format!("{}.0", msrv)))
+        .or_else(|_| Version::parse(&format!("{}.0.0", msrv)))
+        .ok()
+}
+
 pub fn get_attr<'a>(
     sess: &'a Session,
     attrs: &'a [ast::Attribute],
@@ -3,12 +3,16 @@
 #![deny(clippy::missing_docs_in_private_items)]
 
 use lazy_static::lazy_static;
+use rustc::session::Session;
+use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::default::Default;
-use std::io::Read;
 use std::sync::Mutex;
 use std::{env, fmt, fs, io, path};
+use syntax::source_map::{FileName, Span};
 use syntax::{ast, source_map};
 use toml;
+use toml::value::{Table, Value};
 
 /// Gets the configuration file from arguments.
 pub fn file_from_args(args: &[ast::NestedMetaItem]) -> Result<Option<path::PathBuf>, (&'static str, source_map::Span)> {
@@ -74,6 +78,18 @@ macro_rules! define_Conf {
                 #[allow(dead_code)]
                 #[serde(default)]
                 third_party: Option<::toml::Value>,
+                /// Whether the configuration of the closest `clippy.toml` in a parent directory
+                /// is inherited. Handled by `read_table`.
+                #[allow(dead_code)]
+                #[serde(default)]
+                inherit: bool,
+                /// The `[[override]]` tables. Handled by `read_overrides`.
+                #[allow(dead_code)]
+                #[serde(default, rename = "override")]
+                raw_overrides: Vec<::toml::Value>,
+                /// Configuration that only applies to some files.
+                #[serde(skip)]
+                pub overrides: Vec<super::PathOverride>,
             }
             $(
                 mod $rust_name {
@@ -150,6 +166,14 @@ define_Conf! {
     (trivial_copy_size_limit, "trivial_copy_size_limit", None => Option<u64>),
     /// Lint: TOO_MANY_LINES. The maximum number of lines a function or method can have
     (too_many_lines_threshold, "too_many_lines_threshold", 100 => u64),
+    /// Lint: MANUAL_SATURATING_ARITHMETIC, QUESTION_MARK, REDUNDANT_STATIC_LIFETIMES. The minimum rust version that the project supports
+    (msrv, "msrv", None => Option<String>),
+    /// Lint: DISALLOWED_METHODS. The list of disallowed functions and methods, written as fully qualified paths. Entries can also be tables with a `path` and a `reason` key, but all entries of a list have to be of the same kind
+    (disallowed_methods, "disallowed_methods", Vec::new() => Vec<crate::utils::conf::DisallowedPath>),
+    /// Lint: DISALLOWED_TYPES. The list of disallowed types, written as fully qualified paths. Entries can also be tables with a `path` and a `reason` key, but all entries of a list have to be of the same kind
+    (disallowed_types, "disallowed_types", Vec::new() => Vec<crate::utils::conf::DisallowedPath>),
+    /// The levels of lints and lint groups, e.g. `pedantic = "warn"`. These apply as if they were passed on the command line
+    (lints, "lints", ::std::collections::BTreeMap::new() => ::std::collections::BTreeMap<String, String>),
 }
 
 impl Default for Conf {
@@ -158,18 +182,171 @@ impl Default for Conf {
     }
 }
 
+/// An entry of the `disallowed-methods` or `disallowed-types` lists.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum DisallowedPath {
+    /// Only the path, e.g. `"std::env::set_var"`.
+    Simple(String),
+    /// The path and the reason why it is disallowed, e.g.
+    /// `{ path = "std::env::set_var", reason = "not thread safe" }`.
+    WithReason {
+        /// The fully qualified path.
+        path: String,
+        /// Explains why the path is disallowed, shown as a note of the lint.
+        reason: Option<String>,
+    },
+}
+
+impl DisallowedPath {
+    /// Returns the fully qualified path.
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Simple(path) | Self::WithReason { path, .. } => path,
+        }
+    }
+
+    /// Returns the reason why the path is disallowed, if any.
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::WithReason {
+                reason: Some(reason), ..
+            } => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+/// A `[[override]]` table, which changes the configuration for the files matching one of its
+/// `paths`.
+pub struct PathOverride {
+    /// Absolute glob patterns of the files the override applies to.
+    patterns: Vec<String>,
+    /// The configuration that applies to these files.
+    conf: Conf,
+}
+
+/// The configuration keys that can be set in `[[override]]` tables. These must be read with
+/// `Scoped`, everything else only has a value for the whole crate.
+const SCOPED_KEYS: [&str; 3] = [
+    "cognitive-complexity-threshold",
+    "too-many-arguments-threshold",
+    "too-many-lines-threshold",
+];
+
+/// A configuration value that can be overridden for some files with `[[override]]` tables.
+pub struct Scoped<T> {
+    /// The value for files that don't match any override.
+    default: T,
+    /// The overridden values and the patterns of the files they apply to.
+    overrides: Vec<(Vec<String>, T)>,
+}
+
+impl<T> Scoped<T> {
+    /// Extracts a value from the configuration and all of its overrides.
+    pub fn new(conf: &Conf, value: impl Fn(&Conf) -> T) -> Self {
+        Self {
+            default: value(conf),
+            overrides: conf
+                .overrides
+                .iter()
+                .map(|o| (o.patterns.clone(), value(&o.conf)))
+                .collect(),
+        }
+    }
+
+    /// Returns the value for the file containing `span`. If several overrides match the file, the
+    /// last one wins.
+    pub fn get(&self, sess: &Session, span: Span) -> &T {
+        if self.overrides.is_empty() {
+            return &self.default;
+        }
+
+        if let FileName::Real(file) = sess.source_map().span_to_filename(span) {
+            let file = absolute_path(&file);
+            if let Some((_, value)) = self
+                .overrides
+                .iter()
+                .rev()
+                .find(|(patterns, _)| patterns.iter().any(|pattern| glob_match(pattern, &file)))
+            {
+                return value;
+            }
+        }
+
+        &self.default
+    }
+}
+
+/// Makes a path absolute and uses `/` as separator, so it can be matched against the patterns of
+/// `[[override]]` tables. `.` and `..` components are removed without resolving symbolic links.
+fn absolute_path(path: &path::Path) -> String {
+    let path = if path.is_relative() {
+        env::current_dir().unwrap_or_default().join(path)
+    } else {
+        path.to_path_buf()
+    };
+    let mut normalized = path::PathBuf::new();
+    for component in path.components() {
+        match component {
+            path::Component::CurDir => {},
+            path::Component::ParentDir => {
+                normalized.pop();
+            },
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+    normalized.to_string_lossy().replace('\\', "/")
+}
+
+/// Matches a path against a glob pattern. `*` and `?` match any characters except `/`, while `**`
+/// matches any number of directories.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    /// Matches the remaining characters of the pattern against the remaining path.
+    fn matches(pattern: &[char], path: &[char]) -> bool {
+        if pattern.is_empty() {
+            return path.is_empty();
+        }
+
+        if pattern.starts_with(&['*', '*']) {
+            let rest = &pattern[2..];
+            // `a/**/b` also matches `a/b`
+            if rest.first() == Some(&'/') && matches(&rest[1..], path) {
+                return true;
+            }
+            return (0..=path.len()).any(|i| matches(rest, &path[i..]));
+        }
+
+        match pattern[0] {
+            '*' => (0..=path.len())
+                .take_while(|&i| i == 0 || path[i - 1] != '/')
+                .any(|i| matches(&pattern[1..], &path[i..])),
+            '?' => !path.is_empty() && path[0] != '/' && matches(&pattern[1..], &path[1..]),
+            c => !path.is_empty() && path[0] == c && matches(&pattern[1..], &path[1..]),
+        }
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    matches(&pattern, &path)
+}
+
 /// Search for the configuration file.
 pub fn lookup_conf_file() -> io::Result<Option<path::PathBuf>> {
-    /// Possible filename to search for.
-    const CONFIG_FILE_NAMES: [&str; 2] = [".clippy.toml", "clippy.toml"];
-
     // Start looking for a config file in CLIPPY_CONF_DIR, or failing that, CARGO_MANIFEST_DIR.
     // If neither of those exist, use ".".
-    let mut current = path::PathBuf::from(
+    find_conf_file(path::PathBuf::from(
         env::var("CLIPPY_CONF_DIR")
             .or_else(|_| env::var("CARGO_MANIFEST_DIR"))
             .unwrap_or_else(|_| ".".to_string()),
-    );
+    ))
+}
+
+/// Search for the configuration file in `current` and its parent directories.
+fn find_conf_file(mut current: path::PathBuf) -> io::Result<Option<path::PathBuf>> {
+    /// Possible filename to search for.
+    const CONFIG_FILE_NAMES: [&str; 2] = [".clippy.toml", "clippy.toml"];
+
     loop {
         for config_file_name in &CONFIG_FILE_NAMES {
             let config_file = current.join(config_file_name);
@@ -202,6 +379,11 @@ fn default(errors: Vec<Error>) -> (Conf, Vec<Error>) {
     (Conf::default(), errors)
 }
 
+/// Takes the errors reported by the deserializers of the configuration values.
+fn take_errors() -> Vec<Error> {
+    ERRORS.lock().expect("no threading -> mutex always safe").split_off(0)
+}
+
 /// Read the `toml` configuration file.
 ///
 /// In case of error, the function tries to continue as much as possible.
@@ -212,40 +394,375 @@ pub fn read(path: Option<&path::Path>) -> (Conf, Vec<Error>) {
         return default(Vec::new());
     };
 
-    let file = match fs::File::open(path) {
-        Ok(mut file) => {
-            let mut buf = String::new();
+    assert!(ERRORS.lock().expect("no threading -> mutex always safe").is_empty());
+    let mut errors = Vec::new();
+    let table = if let Some(table) = read_table(path, &mut errors) {
+        table
+    } else {
+        return default(errors);
+    };
+
+    // Errors in the individual files were already reported by `read_table`.
+    let mut toml = conf_from_table(table.clone()).unwrap_or_default();
+    take_errors();
+
+    let cyc_field: Option<u64> = toml.cyclomatic_complexity_threshold;
 
-            if let Err(err) = file.read_to_string(&mut buf) {
-                return default(vec![err.into()]);
+    if cyc_field.is_some() {
+        let cyc_err = "found deprecated field `cyclomatic-complexity-threshold`. Please use `cognitive-complexity-threshold` instead.".to_string();
+        errors.push(Error::Toml(cyc_err));
+    }
+
+    let invalid: Vec<String> = toml
+        .lints
+        .iter()
+        .filter_map(|(lint, level)| {
+            if LINT_LEVEL_FLAGS.iter().any(|(name, _)| name == level) {
+                None
+            } else {
+                Some(lint.clone())
             }
+        })
+        .collect();
+    for lint in invalid {
+        let level = toml.lints.remove(&lint).unwrap();
+        errors.push(Error::Toml(format!(
+            "invalid level `{}` for lint `{}`, expected one of `allow`, `warn`, `deny`, `forbid`",
+            level, lint
+        )));
+    }
 
-            buf
+    if let Some(msrv) = &toml.msrv {
+        if super::attrs::parse_msrv(msrv).is_none() {
+            errors.push(Error::Toml(format!("`{}` is not a valid Rust version", msrv)));
+            toml.msrv = None;
+        }
+    }
+
+    toml.overrides = read_overrides(table, &mut errors);
+
+    (toml, errors)
+}
+
+/// Deserializes a `Conf` from a TOML table.
+fn conf_from_table(table: Table) -> Result<Conf, Error> {
+    Value::Table(table)
+        .try_into()
+        .map_err(|e: toml::de::Error| Error::Toml(e.to_string()))
+}
+
+/// Reads a configuration file into a TOML table.
+///
+/// If the file sets `inherit = true`, its values are merged into the configuration of the closest
+/// configuration file in a parent directory. The `paths` of its `[[override]]` tables are made
+/// absolute, since they are relative to the file that declares them.
+fn read_table(path: &path::Path, errors: &mut Vec<Error>) -> Option<Table> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            errors.push(err.into());
+            return None;
         },
-        Err(err) => return default(vec![err.into()]),
     };
 
-    assert!(ERRORS.lock().expect("no threading -> mutex always safe").is_empty());
-    match toml::from_str(&file) {
-        Ok(toml) => {
-            let mut errors = ERRORS.lock().expect("no threading -> mutex always safe").split_off(0);
+    // Deserializing each file on its own reports errors together with their position.
+    let result = toml::from_str::<Conf>(&contents);
+    errors.extend(take_errors());
+    if let Err(e) = result {
+        errors.push(Error::Toml(e.to_string()));
+        return None;
+    }
+
+    let mut table = match toml::from_str(&contents) {
+        Ok(Value::Table(table)) => table,
+        _ => return None,
+    };
+
+    let dir = path::PathBuf::from(absolute_path(path.parent().unwrap_or_else(|| path::Path::new(""))));
+    if let Some(Value::Array(overrides)) = table.get_mut("override") {
+        for paths in overrides.iter_mut().filter_map(|o| o.get_mut("paths")) {
+            for path in paths.as_array_mut().into_iter().flatten() {
+                if let Value::String(pattern) = path {
+                    *pattern = absolute_path(&dir.join(pattern.as_str()));
+                }
+            }
+        }
+    }
+
+    if table.remove("inherit") == Some(Value::Boolean(true)) {
+        let parent = dir.parent().map_or(Ok(None), |dir| find_conf_file(dir.to_path_buf()));
+        match parent {
+            Ok(Some(parent)) => {
+                if let Some(parent) = read_table(&parent, errors) {
+                    table = merge_tables(parent, table);
+                }
+            },
+            Ok(None) => {},
+            Err(err) => errors.push(err.into()),
+        }
+    }
+
+    Some(table)
+}
+
+/// Merges the values of `table` into `base`. Nested tables are merged as well and the
+/// `[[override]]` tables of both are kept.
+fn merge_tables(mut base: Table, table: Table) -> Table {
+    for (key, value) in table {
+        let value = match (base.remove(&key), value) {
+            (Some(Value::Table(base)), Value::Table(table)) => Value::Table(merge_tables(base, table)),
+            (Some(Value::Array(mut base)), Value::Array(overrides)) => {
+                if key == "override" {
+                    base.extend(overrides);
+                    Value::Array(base)
+                } else {
+                    Value::Array(overrides)
+                }
+            },
+            (_, value) => value,
+        };
+        base.insert(key, value);
+    }
+    base
+}
+
+/// Resolves the `[[override]]` tables of a configuration into a `Conf` each.
+fn read_overrides(mut table: Table, errors: &mut Vec<Error>) -> Vec<PathOverride> {
+    let overrides = match table.remove("override") {
+        Some(Value::Array(overrides)) => overrides,
+        _ => return Vec::new(),
+    };
+
+    let mut result = Vec::new();
+    for value in overrides {
+        let mut o = if let Value::Table(o) = value {
+            o
+        } else {
+            errors.push(Error::Toml("`override` must be an array of tables".to_string()));
+            continue;
+        };
 
-            let toml_ref: &Conf = &toml;
+        let patterns: Option<Vec<String>> = match o.remove("paths") {
+            Some(Value::Array(paths)) => paths
+                .into_iter()
+                .map(|path| path.as_str().map(ToString::to_string))
+                .collect(),
+            _ => None,
+        };
+        let patterns = if let Some(patterns) = patterns {
+            patterns
+        } else {
+            errors.push(Error::Toml(
+                "`[[override]]` tables need a `paths` array of glob patterns".to_string(),
+            ));
+            continue;
+        };
 
-            let cyc_field: Option<u64> = toml_ref.cyclomatic_complexity_threshold;
+        if o.contains_key("inherit") || o.contains_key("override") {
+            errors.push(Error::Toml(
+                "`[[override]]` tables can't contain `inherit` or `override`".to_string(),
+            ));
+            continue;
+        }
+        if o.remove("lints").is_some() {
+            errors.push(Error::Toml(
+                "lint levels can't be set in `[[override]]` tables, use attributes instead".to_string(),
+            ));
+        }
+        let unscoped: Vec<_> = o
+            .keys()
+            .filter(|key| !SCOPED_KEYS.contains(&key.as_str()))
+            .cloned()
+            .collect();
+        for key in unscoped {
+            errors.push(Error::Toml(format!(
+                "`{}` can't be set in `[[override]]` tables, only `{}` can",
+                key,
+                SCOPED_KEYS.join("`, `")
+            )));
+            o.remove(&key);
+        }
+
+        let conf = conf_from_table(merge_tables(table.clone(), o));
+        let mut new_errors = take_errors();
+        match conf {
+            Ok(conf) => result.push(PathOverride { patterns, conf }),
+            Err(err) => new_errors.push(err),
+        }
 
-            if cyc_field.is_some() {
-                let cyc_err = "found deprecated field `cyclomatic-complexity-threshold`. Please use `cognitive-complexity-threshold` instead.".to_string();
-                errors.push(Error::Toml(cyc_err));
+        // Errors in the base configuration were already reported.
+        for error in new_errors {
+            if !errors.iter().any(|e| e.to_string() == error.to_string()) {
+                errors.push(error);
             }
+        }
+    }
+    result
+}
 
-            (toml, errors)
-        },
-        Err(e) => {
-            let mut errors = ERRORS.lock().expect("no threading -> mutex always safe").split_off(0);
-            errors.push(Error::Toml(e.to_string()));
+/// The lint levels accepted in the `[lints]` table, and the corresponding command line flags.
+const LINT_LEVEL_FLAGS: [(&str, &str); 4] = [("allow", "-A"), ("warn", "-W"), ("deny", "-D"), ("forbid", "-F")];
 
-            default(errors)
-        },
+/// The Clippy lint groups, which have to be passed on the command line before single lints.
+const LINT_GROUPS: [&str; 9] = [
+    "all",
+    "cargo",
+    "complexity",
+    "correctness",
+    "nursery",
+    "pedantic",
+    "perf",
+    "restriction",
+    "style",
+];
+
+/// Returns the command line flags for the lint levels in the `[lints]` table.
+///
+/// rustc applies all `-A` flags before the `-W` flags and so on, whatever their order, so the level
+/// of a Clippy lint group is passed for each lint that `group_lints` returns for it instead.
+/// `clippy::all` comes first, then the other lint groups, so that the level of a group overrides
+/// `clippy::all` and the level of a single lint overrides the level of its group. Lints without a
+/// tool name are assumed to be Clippy lints.
+pub fn lint_level_args(conf: &Conf, group_lints: impl Fn(&str) -> Option<Vec<String>>) -> Vec<String> {
+    let mut levels: Vec<_> = conf
+        .lints
+        .iter()
+        .filter_map(|(lint, level)| {
+            let flag = LINT_LEVEL_FLAGS.iter().find(|(name, _)| name == level)?.1;
+            let lint = if lint.contains("::") {
+                lint.clone()
+            } else {
+                format!("clippy::{}", lint)
+            };
+            let is_group = LINT_GROUPS.iter().any(|group| lint == format!("clippy::{}", group));
+            Some((!is_group, lint != "clippy::all", lint, flag))
+        })
+        .collect();
+    levels.sort();
+
+    let mut flags = BTreeMap::new();
+    for (_, _, lint, flag) in levels {
+        if let Some(lints) = group_lints(&lint) {
+            for lint in lints {
+                flags.insert(lint, flag);
+            }
+        } else {
+            flags.insert(lint, flag);
+        }
+    }
+    flags
+        .into_iter()
+        .flat_map(|(lint, flag)| vec![flag.to_string(), lint])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{absolute_path, glob_match, lint_level_args, merge_tables, Conf};
+    use toml::value::{Table, Value};
+
+    /// Parses a TOML table.
+    fn table(s: &str) -> Table {
+        match toml::from_str(s).unwrap() {
+            Value::Table(table) => table,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("/a/src/*.rs", "/a/src/lib.rs"));
+        assert!(!glob_match("/a/src/*.rs", "/a/src/foo/lib.rs"));
+        assert!(glob_match("/a/src/generated/**", "/a/src/generated/foo/bar.rs"));
+        assert!(glob_match("/a/**/tests/*.rs", "/a/tests/main.rs"));
+        assert!(glob_match("/a/**/tests/*.rs", "/a/b/c/tests/main.rs"));
+        assert!(glob_match("/a/src/mod?.rs", "/a/src/mod1.rs"));
+        assert!(!glob_match("/a/src/mod?.rs", "/a/src/mod.rs"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_absolute_path() {
+        use std::path::Path;
+
+        assert_eq!("/a/src/**", absolute_path(Path::new("/a/./src/**")));
+        assert_eq!("/a/x/*.rs", absolute_path(Path::new("/a/b/../x/*.rs")));
+        assert_eq!("/x", absolute_path(Path::new("/a/../../x")));
+    }
+
+    #[test]
+    fn test_merge_tables() {
+        let parent = table(
+            r#"
+            too-many-lines-threshold = 50
+            cognitive-complexity-threshold = 20
+            [lints]
+            pedantic = "warn"
+            [[override]]
+            paths = ["/a/**"]
+            "#,
+        );
+        let child = table(
+            r#"
+            too-many-lines-threshold = 80
+            [lints]
+            similar_names = "allow"
+            [[override]]
+            paths = ["/a/b/**"]
+            "#,
+        );
+        let expected = table(
+            r#"
+            too-many-lines-threshold = 80
+            cognitive-complexity-threshold = 20
+            [lints]
+            pedantic = "warn"
+            similar_names = "allow"
+            [[override]]
+            paths = ["/a/**"]
+            [[override]]
+            paths = ["/a/b/**"]
+            "#,
+        );
+        assert_eq!(expected, merge_tables(parent, child));
+    }
+
+    #[test]
+    fn test_lint_level_args() {
+        let conf: Conf = toml::from_str(
+            r#"
+            [lints]
+            similar_names = "allow"
+            correctness = "allow"
+            all = "warn"
+            pedantic = "deny"
+            "rustc::internal" = "warn"
+            "#,
+        )
+        .unwrap();
+        let group_lints = |group: &str| {
+            let lints: &[&str] = match group {
+                "clippy::all" => &["clippy::eq_op", "clippy::needless_return"],
+                "clippy::correctness" => &["clippy::eq_op"],
+                "clippy::pedantic" => &["clippy::doc_markdown", "clippy::similar_names"],
+                _ => return None,
+            };
+            Some(lints.iter().map(ToString::to_string).collect())
+        };
+        assert_eq!(
+            lint_level_args(&conf, group_lints),
+            [
+                "-D",
+                "clippy::doc_markdown",
+                "-A",
+                "clippy::eq_op",
+                "-W",
+                "clippy::needless_return",
+                "-A",
+                "clippy::similar_names",
+                "-W",
+                "rustc::internal",
+            ]
+        );
     }
 }
@@ -1,23 +1,72 @@
 //! Clippy wrappers around rustc's diagnostic functions.
 
+use lazy_static::lazy_static;
 use rustc::hir::HirId;
 use rustc::lint::{LateContext, Lint, LintContext};
+use rustc_data_structures::fx::FxHashMap;
 use rustc_errors::{Applicability, CodeSuggestion, Substitution, SubstitutionPart, SuggestionStyle};
+use std::cell::RefCell;
 use std::env;
+use std::sync::Mutex;
 use syntax::errors::DiagnosticBuilder;
 use syntax::source_map::{MultiSpan, Span};
 
+thread_local! {
+    /// The lints Clippy tried to emit so far, including the allowed ones.
+    static RECORDED_LINTS: RefCell<Vec<(&'static Lint, Span)>> = RefCell::new(Vec::new());
+}
+
+/// Records that `lint` was checked at `span`, whether or not it is allowed there.
+///
+/// The recorded lints are used to find `#[allow]` attributes that don't suppress anything, see
+/// `attrs::UNUSED_CLIPPY_ALLOW`.
+pub fn record_lint(lint: &'static Lint, span: Span) {
+    RECORDED_LINTS.with(|lints| lints.borrow_mut().push((lint, span)));
+}
+
+/// Returns the lints recorded by `record_lint` since the last call.
+pub fn take_recorded_lints() -> Vec<(&'static Lint, Span)> {
+    RECORDED_LINTS.with(|lints| lints.replace(Vec::new()))
+}
+
+lazy_static! {
+    /// The number of diagnostics each lint emitted, keyed by `Lint::name`.
+    ///
+    /// This is global rather than thread local because the driver reads it once the compiler
+    /// thread finished, see `timings::Timings::lints`.
+    static ref EMITTED_LINTS: Mutex<FxHashMap<&'static str, usize>> = Mutex::default();
+}
+
+/// Returns the number of diagnostics each lint emitted so far, sorted by lint name.
+pub fn emitted_lint_counts() -> Vec<(String, usize)> {
+    let emitted = EMITTED_LINTS.lock().expect("lint counts poisoned");
+    let mut counts: Vec<_> = emitted
+        .iter()
+        .map(|(name, &count)| (name.to_lowercase(), count))
+        .collect();
+    counts.sort();
+    counts
+}
+
 /// Wrapper around `DiagnosticBuilder` that adds a link to Clippy documentation for the emitted lint
-struct DiagnosticWrapper<'a>(DiagnosticBuilder<'a>);
+struct DiagnosticWrapper<'a>(DiagnosticBuilder<'a>, &'static Lint);
 
 impl<'a> Drop for DiagnosticWrapper<'a> {
     fn drop(&mut self) {
+        // The diagnostics of allowed lints are cancelled.
+        if !self.0.cancelled() {
+            *EMITTED_LINTS
+                .lock()
+                .expect("lint counts poisoned")
+                .entry(self.1.name)
+                .or_insert(0) += 1;
+        }
         self.0.emit();
     }
 }
 
 impl<'a> DiagnosticWrapper<'a> {
-    fn docs_link(&mut self, lint: &'static Lint) {
+    fn docs_link(&mut self) {
         if env::var("CLIPPY_DISABLE_DOCS_LINKS").is_err() {
             self.0.help(&format!(
                 "for further information visit https://rust-lang.github.io/rust-clippy/{}/index.html#{}",
@@ -25,7 +74,7 @@ impl<'a> DiagnosticWrapper<'a> {
                     // extract just major + minor version and ignore patch versions
                     format!("rust-{}", n.rsplitn(2, '.').nth(1).unwrap())
                 }),
-                lint.name_lower().replacen("clippy::", "", 1)
+                self.1.name_lower().replacen("clippy::", "", 1)
             ));
         }
     }
@@ -49,7 +98,11 @@ impl<'a> DiagnosticWrapper<'a> {
 ///    |     ^^^^^^^^^^^^^^^^^^^^^^^
 /// ```
 pub fn span_lint<T: LintContext>(cx: &T, lint: &'static Lint, sp: impl Into<MultiSpan>, msg: &str) {
-    DiagnosticWrapper(cx.struct_span_lint(lint, sp, msg)).docs_link(lint);
+    let sp = sp.into();
+    if let Some(primary) = sp.primary_span() {
+        record_lint(lint, primary);
+    }
+    DiagnosticWrapper(cx.struct_span_lint(lint, sp, msg), lint).docs_link();
 }
 
 /// Same as `span_lint` but with an extra `help` message.
@@ -71,9 +124,10 @@ pub fn span_lint<T: LintContext>(cx: &T, lint: &'static Lint, sp: impl Into<Mult
 ///    = help: Consider using `std::f64::NAN` if you would like a constant representing NaN
 /// ```
 pub fn span_help_and_lint<'a, T: LintContext>(cx: &'a T, lint: &'static Lint, span: Span, msg: &str, help: &str) {
-    let mut db = DiagnosticWrapper(cx.struct_span_lint(lint, span, msg));
+    record_lint(lint, span);
+    let mut db = DiagnosticWrapper(cx.struct_span_lint(lint, span, msg), lint);
     db.0.help(help);
-    db.docs_link(lint);
+    db.docs_link();
 }
 
 /// Like `span_lint` but with a `note` section instead of a `help` message.
@@ -105,26 +159,29 @@ pub fn span_note_and_lint<'a, T: LintContext>(
     note_span: Span,
     note: &str,
 ) {
-    let mut db = DiagnosticWrapper(cx.struct_span_lint(lint, span, msg));
+    record_lint(lint, span);
+    let mut db = DiagnosticWrapper(cx.struct_span_lint(lint, span, msg), lint);
     if note_span == span {
         db.0.note(note);
     } else {
         db.0.span_note(note_span, note);
     }
-    db.docs_link(lint);
+    db.docs_link();
 }
 
 pub fn span_lint_and_then<'a, T: LintContext, F>(cx: &'a T, lint: &'static Lint, sp: Span, msg: &str, f: F)
 where
     F: for<'b> FnOnce(&mut DiagnosticBuilder<'b>),
 {
-    let mut db = DiagnosticWrapper(cx.struct_span_lint(lint, sp, msg));
+    record_lint(lint, sp);
+    let mut db = DiagnosticWrapper(cx.struct_span_lint(lint, sp, msg), lint);
     f(&mut db.0);
-    db.docs_link(lint);
+    db.docs_link();
 }
 
 pub fn span_lint_hir(cx: &LateContext<'_, '_>, lint: &'static Lint, hir_id: HirId, sp: Span, msg: &str) {
-    DiagnosticWrapper(cx.tcx.struct_span_lint_hir(lint, hir_id, sp, msg)).docs_link(lint);
+    record_lint(lint, sp);
+    DiagnosticWrapper(cx.tcx.struct_span_lint_hir(lint, hir_id, sp, msg), lint).docs_link();
 }
 
 pub fn span_lint_hir_and_then(
@@ -135,9 +192,10 @@ pub fn span_lint_hir_and_then(
     msg: &str,
     f: impl FnOnce(&mut DiagnosticBuilder<'_>),
 ) {
-    let mut db = DiagnosticWrapper(cx.tcx.struct_span_lint_hir(lint, hir_id, sp, msg));
+    record_lint(lint, sp);
+    let mut db = DiagnosticWrapper(cx.tcx.struct_span_lint_hir(lint, hir_id, sp, msg), lint);
     f(&mut db.0);
-    db.docs_link(lint);
+    db.docs_link();
 }
 
 /// Add a span lint with a suggestion on how to fix it.
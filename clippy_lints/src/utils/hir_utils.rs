@@ -1,5 +1,6 @@
 use crate::consts::{constant_context, constant_simple};
 use crate::utils::differing_macro_contexts;
+use rustc::hir::intravisit::{walk_expr, NestedVisitorMap, Visitor};
 use rustc::hir::ptr::P;
 use rustc::hir::*;
 use rustc::lint::LateContext;
@@ -21,6 +22,9 @@ pub struct SpanlessEq<'a, 'tcx> {
     /// If is true, never consider as equal expressions containing function
     /// calls.
     ignore_fn: bool,
+    /// If is true, compare the operands of commutative operators regardless of
+    /// their order, see `commutative`.
+    commutative: bool,
 }
 
 impl<'a, 'tcx> SpanlessEq<'a, 'tcx> {
@@ -29,14 +33,27 @@ impl<'a, 'tcx> SpanlessEq<'a, 'tcx> {
             cx,
             tables: cx.tables,
             ignore_fn: false,
+            commutative: false,
         }
     }
 
     pub fn ignore_fn(self) -> Self {
         Self {
-            cx: self.cx,
-            tables: self.cx.tables,
             ignore_fn: true,
+            ..self
+        }
+    }
+
+    /// Normalizes binary operations before comparing them: the operands of
+    /// commutative operators are compared in any order, chains of the same
+    /// built-in bitwise operator are flattened and `>`/`>=` are compared as
+    /// flipped `<`/`<=`. This is only done if no operand has side effects, so
+    /// calls are never reordered, not even by the operand swapping of the
+    /// default mode. Use `SpanlessHash::commutative` for a matching hash.
+    pub fn commutative(self) -> Self {
+        Self {
+            commutative: true,
+            ..self
         }
     }
 
@@ -92,9 +109,13 @@ impl<'a, 'tcx> SpanlessEq<'a, 'tcx> {
             (&ExprKind::Block(ref l, _), &ExprKind::Block(ref r, _)) => self.eq_block(l, r),
             (&ExprKind::Binary(l_op, ref ll, ref lr), &ExprKind::Binary(r_op, ref rl, ref rr)) => {
                 l_op.node == r_op.node && self.eq_expr(ll, rl) && self.eq_expr(lr, rr)
-                    || swap_binop(l_op.node, ll, lr).map_or(false, |(l_op, ll, lr)| {
-                        l_op == r_op.node && self.eq_expr(ll, rl) && self.eq_expr(lr, rr)
-                    })
+                    || if self.commutative {
+                        self.eq_normalized_binop(left, right)
+                    } else {
+                        swap_binop(l_op.node, ll, lr).map_or(false, |(l_op, ll, lr)| {
+                            l_op == r_op.node && self.eq_expr(ll, rl) && self.eq_expr(lr, rr)
+                        })
+                    }
             },
             (&ExprKind::Break(li, ref le), &ExprKind::Break(ri, ref re)) => {
                 both(&li.label, &ri.label, |l, r| l.ident.as_str() == r.ident.as_str())
@@ -157,6 +178,29 @@ impl<'a, 'tcx> SpanlessEq<'a, 'tcx> {
         over(left, right, |l, r| self.eq_expr(l, r))
     }
 
+    fn eq_normalized_binop(&mut self, left: &Expr, right: &Expr) -> bool {
+        let (left, right) = match (normalize_binop(self.tables, left), normalize_binop(self.tables, right)) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return false,
+        };
+        if left.op != right.op || left.operands.len() != right.operands.len() {
+            return false;
+        }
+        if !left.commutative {
+            return over(&left.operands, &right.operands, |l, r| self.eq_expr(l, r));
+        }
+
+        let mut matched = vec![false; right.operands.len()];
+        for l in &left.operands {
+            let found = (0..right.operands.len()).find(|&i| !matched[i] && self.eq_expr(l, right.operands[i]));
+            match found {
+                Some(i) => matched[i] = true,
+                None => return false,
+            }
+        }
+        true
+    }
+
     fn eq_field(&mut self, left: &Field, right: &Field) -> bool {
         left.ident.name == right.ident.name && self.eq_expr(&left.expr, &right.expr)
     }
@@ -321,6 +365,113 @@ fn swap_binop<'a>(binop: BinOpKind, lhs: &'a Expr, rhs: &'a Expr) -> Option<(Bin
     }
 }
 
+/// A binary operation as normalized by the commutative mode of `SpanlessEq`
+/// and `SpanlessHash`.
+struct NormalizedBinop<'e> {
+    op: BinOpKind,
+    operands: Vec<&'e Expr>,
+    /// If is true, the order of `operands` doesn't matter.
+    commutative: bool,
+}
+
+/// Normalizes a binary operation for the commutative mode. Returns `None` if
+/// `expr` is not a binary operation that can be normalized, e.g. because one
+/// of its operands has side effects.
+fn normalize_binop<'e>(tables: &TypeckTables<'_>, expr: &'e Expr) -> Option<NormalizedBinop<'e>> {
+    let (op, lhs, rhs) = match expr.node {
+        ExprKind::Binary(op, ref lhs, ref rhs) => (op.node, lhs, rhs),
+        _ => return None,
+    };
+    let (op, operands, commutative) = match op {
+        BinOpKind::Eq | BinOpKind::Ne => (op, vec![&**lhs, &**rhs], true),
+        BinOpKind::Lt | BinOpKind::Le => (op, vec![&**lhs, &**rhs], false),
+        BinOpKind::Gt => (BinOpKind::Lt, vec![&**rhs, &**lhs], false),
+        BinOpKind::Ge => (BinOpKind::Le, vec![&**rhs, &**lhs], false),
+        // overloaded operators don't have to be commutative
+        _ if tables.is_method_call(expr) => return None,
+        // `Add` and `Mul` aren't regrouped, as that can change overflow and
+        // rounding
+        BinOpKind::Add | BinOpKind::Mul => (op, vec![&**lhs, &**rhs], true),
+        BinOpKind::BitAnd | BinOpKind::BitOr | BinOpKind::BitXor => {
+            let mut operands = Vec::new();
+            flatten_bitop(tables, op, expr, &mut operands);
+            (op, operands, true)
+        },
+        BinOpKind::Sub
+        | BinOpKind::Div
+        | BinOpKind::Rem
+        | BinOpKind::Shl
+        | BinOpKind::Shr
+        | BinOpKind::And
+        | BinOpKind::Or => return None,
+    };
+    if is_reorderable(tables, expr) {
+        Some(NormalizedBinop {
+            op,
+            operands,
+            commutative,
+        })
+    } else {
+        None
+    }
+}
+
+/// Collects the operands of a chain of the built-in bitwise operator `op`,
+/// e.g. `a`, `b` and `c` for `a & (b & c)`.
+fn flatten_bitop<'e>(tables: &TypeckTables<'_>, op: BinOpKind, expr: &'e Expr, operands: &mut Vec<&'e Expr>) {
+    match expr.node {
+        ExprKind::Binary(expr_op, ref lhs, ref rhs) if expr_op.node == op && !tables.is_method_call(expr) => {
+            flatten_bitop(tables, op, lhs, operands);
+            flatten_bitop(tables, op, rhs, operands);
+        },
+        _ => operands.push(expr),
+    }
+}
+
+/// Checks that evaluating the parts of `expr` in another order can't change
+/// the result, i.e. that it doesn't contain calls, assignments or overloaded
+/// operators other than comparisons.
+fn is_reorderable(tables: &TypeckTables<'_>, expr: &Expr) -> bool {
+    let mut visitor = ReorderableVisitor {
+        tables,
+        reorderable: true,
+    };
+    visitor.visit_expr(expr);
+    visitor.reorderable
+}
+
+struct ReorderableVisitor<'a, 'tcx> {
+    tables: &'a TypeckTables<'tcx>,
+    reorderable: bool,
+}
+
+impl<'a, 'tcx, 'e> Visitor<'e> for ReorderableVisitor<'a, 'tcx> {
+    fn visit_expr(&mut self, expr: &'e Expr) {
+        if !self.reorderable {
+            return;
+        }
+        match expr.node {
+            ExprKind::Call(..)
+            | ExprKind::MethodCall(..)
+            | ExprKind::Assign(..)
+            | ExprKind::AssignOp(..)
+            | ExprKind::Yield(..)
+            | ExprKind::InlineAsm(..) => self.reorderable = false,
+            ExprKind::Binary(op, ..) if !op.node.is_comparison() && self.tables.is_method_call(expr) => {
+                self.reorderable = false;
+            },
+            ExprKind::Unary(..) | ExprKind::Index(..) if self.tables.is_method_call(expr) => {
+                self.reorderable = false;
+            },
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn nested_visit_map<'this>(&'this mut self) -> NestedVisitorMap<'this, 'e> {
+        NestedVisitorMap::None
+    }
+}
+
 /// Checks if the two `Option`s are both `None` or some equal values as per
 /// `eq_fn`.
 fn both<X, F>(l: &Option<X>, r: &Option<X>, mut eq_fn: F) -> bool
@@ -349,6 +500,9 @@ pub struct SpanlessHash<'a, 'tcx> {
     cx: &'a LateContext<'a, 'tcx>,
     tables: &'a TypeckTables<'tcx>,
     s: DefaultHasher,
+    /// If is true, hash binary operations like the commutative mode of
+    /// `SpanlessEq`.
+    commutative: bool,
 }
 
 impl<'a, 'tcx> SpanlessHash<'a, 'tcx> {
@@ -357,6 +511,16 @@ impl<'a, 'tcx> SpanlessHash<'a, 'tcx> {
             cx,
             tables,
             s: DefaultHasher::new(),
+            commutative: false,
+        }
+    }
+
+    /// Hashes expressions which are equal as per `SpanlessEq::commutative` to
+    /// the same value.
+    pub fn commutative(self) -> Self {
+        Self {
+            commutative: true,
+            ..self
         }
     }
 
@@ -419,9 +583,41 @@ impl<'a, 'tcx> SpanlessHash<'a, 'tcx> {
                 self.hash_block(b);
             },
             ExprKind::Binary(op, ref l, ref r) => {
-                op.node.hash(&mut self.s);
-                self.hash_expr(l);
-                self.hash_expr(r);
+                let normalized = if self.commutative {
+                    normalize_binop(self.tables, e)
+                } else {
+                    None
+                };
+                match normalized {
+                    Some(NormalizedBinop {
+                        op,
+                        operands,
+                        commutative: true,
+                    }) => {
+                        op.hash(&mut self.s);
+                        let mut hashes: Vec<u64> = operands
+                            .iter()
+                            .map(|operand| {
+                                let mut hasher = SpanlessHash::new(self.cx, self.tables).commutative();
+                                hasher.hash_expr(operand);
+                                hasher.finish()
+                            })
+                            .collect();
+                        hashes.sort_unstable();
+                        hashes.hash(&mut self.s);
+                    },
+                    Some(NormalizedBinop { op, operands, .. }) => {
+                        op.hash(&mut self.s);
+                        for operand in operands {
+                            self.hash_expr(operand);
+                        }
+                    },
+                    None => {
+                        op.node.hash(&mut self.s);
+                        self.hash_expr(l);
+                        self.hash_expr(r);
+                    },
+                }
             },
             ExprKind::Break(i, ref j) => {
                 if let Some(i) = i.label {
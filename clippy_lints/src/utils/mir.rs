@@ -0,0 +1,416 @@
+//! Dataflow analyses over the MIR of a function, for lints about moves and borrows.
+//!
+//! * `LocalLiveness`: whether the value of a local may be used after a location.
+//! * `PossibleBorrowerMap`: which locals may borrow a local at a location.
+
+#![deny(clippy::missing_docs_in_private_items)]
+
+use crate::utils::is_copy;
+use rustc::hir::HirId;
+use rustc::lint::LateContext;
+use rustc::mir::visit::{MutatingUseContext, NonUseContext, PlaceContext, Visitor};
+use rustc::mir::{self, traversal};
+use rustc::ty;
+use rustc::ty::fold::TypeVisitor;
+use rustc_data_structures::bit_set::{BitSet, HybridBitSet};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::indexed_vec::IndexVec;
+use rustc_data_structures::transitive_relation::TransitiveRelation;
+
+/// Returns the `HirId` whose lint levels apply to the MIR at `source_info`, to be used with
+/// `span_lint_hir`.
+pub fn lint_root(body: &mir::Body<'_>, source_info: mir::SourceInfo) -> HirId {
+    if let mir::ClearCrossCrate::Set(scope_local_data) = &body.source_scope_local_data {
+        scope_local_data[source_info.scope].lint_root
+    } else {
+        unreachable!("the local scope data of MIR built for the local crate is always set")
+    }
+}
+
+/// Whether the value of a local may be used after a location, computed by a backward dataflow
+/// analysis. Drops don't count as uses, so a local is dead after its last use even if it is
+/// dropped later.
+pub struct LocalLiveness<'a, 'tcx> {
+    /// The body the locals belong to
+    body: &'a mir::Body<'tcx>,
+    /// The locals that are live at the start of each basic block
+    live_in: IndexVec<mir::BasicBlock, BitSet<mir::Local>>,
+}
+
+impl<'a, 'tcx> LocalLiveness<'a, 'tcx> {
+    /// Computes the liveness of the locals of `body`.
+    pub fn new(body: &'a mir::Body<'tcx>) -> Self {
+        let locals = body.local_decls.len();
+        let effects: IndexVec<mir::BasicBlock, GenKill> = body
+            .basic_blocks()
+            .iter_enumerated()
+            .map(|(block, data)| {
+                let mut effect = GenKill::new(locals);
+                for statement_index in (0..=data.statements.len()).rev() {
+                    effect.add_location(body, mir::Location { block, statement_index });
+                }
+                effect
+            })
+            .collect();
+
+        let mut liveness = Self {
+            body,
+            live_in: IndexVec::from_elem_n(BitSet::new_empty(locals), body.basic_blocks().len()),
+        };
+        // Blocks are visited in postorder, so that most successors are visited before their
+        // predecessors.
+        let blocks: Vec<_> = traversal::postorder(body).map(|(block, _)| block).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in &blocks {
+                let mut live = liveness.live_out(block);
+                effects[block].apply(&mut live);
+                if live != liveness.live_in[block] {
+                    liveness.live_in[block] = live;
+                    changed = true;
+                }
+            }
+        }
+        liveness
+    }
+
+    /// Returns the locals that are live at the end of `block`.
+    fn live_out(&self, block: mir::BasicBlock) -> BitSet<mir::Local> {
+        let mut live = BitSet::new_empty(self.body.local_decls.len());
+        for successor in self.body[block].terminator().successors() {
+            live.union(&self.live_in[*successor]);
+        }
+        live
+    }
+
+    /// Whether the value `local` has after `location` may be used later.
+    pub fn is_used_after(&self, local: mir::Local, location: mir::Location) -> bool {
+        let mut live = self.live_out(location.block);
+        let mut effect = GenKill::new(self.body.local_decls.len());
+        let statements = self.body[location.block].statements.len();
+        for statement_index in (location.statement_index + 1..=statements).rev() {
+            effect.add_location(
+                self.body,
+                mir::Location {
+                    block: location.block,
+                    statement_index,
+                },
+            );
+        }
+        effect.apply(&mut live);
+        live.contains(local)
+    }
+}
+
+/// The effect of a sequence of statements on the live locals.
+struct GenKill {
+    /// Locals that are used before they are assigned to
+    gen: BitSet<mir::Local>,
+    /// Locals that are assigned to before they are used
+    kill: BitSet<mir::Local>,
+}
+
+impl GenKill {
+    /// Creates the effect of an empty sequence.
+    fn new(locals: usize) -> Self {
+        Self {
+            gen: BitSet::new_empty(locals),
+            kill: BitSet::new_empty(locals),
+        }
+    }
+
+    /// Adds the effect of the statement or terminator at `location`, which comes before the
+    /// statements added so far.
+    fn add_location(&mut self, body: &mir::Body<'_>, location: mir::Location) {
+        let mut visitor = DefUseVisitor {
+            defs: Vec::new(),
+            uses: Vec::new(),
+        };
+        let data = &body[location.block];
+        if location.statement_index == data.statements.len() {
+            visitor.visit_terminator(data.terminator(), location);
+        } else {
+            visitor.visit_statement(&data.statements[location.statement_index], location);
+        }
+
+        // The operands are evaluated before the result is assigned.
+        for local in visitor.defs {
+            self.gen.remove(local);
+            self.kill.insert(local);
+        }
+        for local in visitor.uses {
+            self.kill.remove(local);
+            self.gen.insert(local);
+        }
+    }
+
+    /// Applies the effect to the locals that are live after the sequence.
+    fn apply(&self, live: &mut BitSet<mir::Local>) {
+        live.subtract(&self.kill);
+        live.union(&self.gen);
+    }
+}
+
+/// Collects the locals a statement assigns to and the locals it uses, ignoring drops.
+struct DefUseVisitor {
+    /// Locals whose whole value is overwritten or whose storage starts or ends
+    defs: Vec<mir::Local>,
+    /// Locals whose value is read, borrowed, or partially overwritten
+    uses: Vec<mir::Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for DefUseVisitor {
+    fn visit_local(&mut self, local: &mir::Local, context: PlaceContext, _: mir::Location) {
+        match context {
+            PlaceContext::MutatingUse(MutatingUseContext::Store)
+            | PlaceContext::MutatingUse(MutatingUseContext::Call)
+            | PlaceContext::NonUse(NonUseContext::StorageLive)
+            | PlaceContext::NonUse(NonUseContext::StorageDead) => self.defs.push(*local),
+            PlaceContext::MutatingUse(MutatingUseContext::Drop) | PlaceContext::NonUse(_) => {},
+            _ => self.uses.push(*local),
+        }
+    }
+}
+
+/// Whether a local may have storage at a location, computed by a forward dataflow analysis of
+/// the `StorageLive` and `StorageDead` statements.
+struct MaybeStorageLive<'a, 'tcx> {
+    /// The body the locals belong to
+    body: &'a mir::Body<'tcx>,
+    /// The locals that may have storage at the start of each basic block
+    live_in: IndexVec<mir::BasicBlock, BitSet<mir::Local>>,
+}
+
+impl<'a, 'tcx> MaybeStorageLive<'a, 'tcx> {
+    /// Computes the locals of `body` that may have storage.
+    fn new(body: &'a mir::Body<'tcx>) -> Self {
+        let locals = body.local_decls.len();
+        let mut storage = Self {
+            body,
+            live_in: IndexVec::from_elem_n(BitSet::new_empty(locals), body.basic_blocks().len()),
+        };
+        for arg in body.args_iter() {
+            storage.live_in[mir::START_BLOCK].insert(arg);
+        }
+        // Blocks are visited in reverse postorder, so that most predecessors are visited before
+        // their successors.
+        let blocks: Vec<_> = traversal::reverse_postorder(body).map(|(block, _)| block).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in &blocks {
+                let mut live = storage.live_in[block].clone();
+                storage.apply_statements(&mut live, block, body[block].statements.len());
+                for successor in body[block].terminator().successors() {
+                    changed |= storage.live_in[*successor].union(&live);
+                }
+            }
+        }
+        storage
+    }
+
+    /// Applies the effects of the first `statements` statements of `block` to `live`.
+    fn apply_statements(&self, live: &mut BitSet<mir::Local>, block: mir::BasicBlock, statements: usize) {
+        for statement in &self.body[block].statements[..statements] {
+            match statement.kind {
+                mir::StatementKind::StorageLive(local) => {
+                    live.insert(local);
+                },
+                mir::StatementKind::StorageDead(local) => {
+                    live.remove(local);
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Stores the locals that may have storage right after the statement at `location` in
+    /// `live`.
+    fn seek(&self, live: &mut BitSet<mir::Local>, location: mir::Location) {
+        live.overwrite(&self.live_in[location.block]);
+        let statements = self.body[location.block].statements.len();
+        self.apply_statements(
+            live,
+            location.block,
+            std::cmp::min(location.statement_index + 1, statements),
+        );
+    }
+}
+
+/// The locals that may borrow each local, i.e., references to it and values that contain such
+/// references. For example, `b = &a; c = Some(b);` makes `b` and `c` possible borrowers of `a`.
+pub struct PossibleBorrowerMap<'a, 'tcx> {
+    /// The possible borrowers of each local
+    map: FxHashMap<mir::Local, HybridBitSet<mir::Local>>,
+    /// Whether a local may have storage at a location
+    maybe_live: MaybeStorageLive<'a, 'tcx>,
+    /// Caches to avoid allocating a `BitSet` on every query
+    bitset: (BitSet<mir::Local>, BitSet<mir::Local>, BitSet<mir::Local>),
+}
+
+impl<'a, 'tcx> PossibleBorrowerMap<'a, 'tcx> {
+    /// Collects the possible borrowers of the locals of `body`.
+    pub fn new(cx: &LateContext<'a, 'tcx>, body: &'a mir::Body<'tcx>) -> Self {
+        let mut visitor = BorrowerVisitor {
+            body,
+            relation: TransitiveRelation::default(),
+        };
+        visitor.visit_body(body);
+        let locals = body.local_decls.len();
+
+        let mut map = FxHashMap::default();
+        for borrowed in (1..locals).map(mir::Local::from_usize) {
+            // Copies can't be moved out from, so nobody cares about their borrowers.
+            if is_copy(cx, body.local_decls[borrowed].ty) {
+                continue;
+            }
+
+            let mut borrowers = HybridBitSet::new_empty(locals);
+            for &borrower in visitor.relation.reachable_from(&borrowed) {
+                if borrower != mir::RETURN_PLACE {
+                    borrowers.insert(borrower);
+                }
+            }
+            if !borrowers.is_empty() {
+                map.insert(borrowed, borrowers);
+            }
+        }
+
+        let bitset = BitSet::new_empty(locals);
+        Self {
+            map,
+            maybe_live: MaybeStorageLive::new(body),
+            bitset: (bitset.clone(), bitset.clone(), bitset),
+        }
+    }
+
+    /// Whether the possible borrowers of `borrowed` that are live at `at` are exactly
+    /// `borrowers`.
+    pub fn only_borrowers(&mut self, borrowers: &[mir::Local], borrowed: mir::Local, at: mir::Location) -> bool {
+        let (ref mut live, ref mut actual, ref mut expected) = self.bitset;
+        self.maybe_live.seek(live, at);
+
+        actual.clear();
+        if let Some(bitset) = self.map.get(&borrowed) {
+            for b in bitset.iter().filter(|b| live.contains(*b)) {
+                actual.insert(b);
+            }
+        } else {
+            return false;
+        }
+
+        expected.clear();
+        for b in borrowers {
+            expected.insert(*b);
+        }
+
+        actual == expected
+    }
+}
+
+/// Collects the relation from borrowed locals to the locals they are assigned to, i.e., their
+/// possible borrowers.
+struct BorrowerVisitor<'a, 'tcx> {
+    /// The body the locals belong to
+    body: &'a mir::Body<'tcx>,
+    /// The relation being built
+    relation: TransitiveRelation<mir::Local>,
+}
+
+impl<'a, 'tcx> BorrowerVisitor<'a, 'tcx> {
+    /// Records that `lhs` is assigned from `rhs`.
+    fn add(&mut self, lhs: mir::Local, rhs: mir::Local) {
+        if lhs != rhs {
+            self.relation.add(rhs, lhs);
+        }
+    }
+
+    /// Whether values of the type of `local` may contain references.
+    fn contains_region(&self, local: mir::Local) -> bool {
+        ContainsRegion.visit_ty(self.body.local_decls[local].ty)
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for BorrowerVisitor<'a, 'tcx> {
+    fn visit_assign(&mut self, place: &mir::Place<'tcx>, rvalue: &mir::Rvalue<'tcx>, _: mir::Location) {
+        if let mir::PlaceBase::Local(lhs) = place.base {
+            match rvalue {
+                mir::Rvalue::Ref(_, _, borrowed) => {
+                    if let mir::PlaceBase::Local(borrowed) = borrowed.base {
+                        self.add(lhs, borrowed);
+                    }
+                },
+                other => {
+                    // Only values with references can borrow something.
+                    if self.contains_region(lhs) {
+                        rvalue_locals(other, |rhs| self.add(lhs, rhs));
+                    }
+                },
+            }
+        }
+    }
+
+    fn visit_terminator_kind(&mut self, kind: &mir::TerminatorKind<'tcx>, _: mir::Location) {
+        if let mir::TerminatorKind::Call {
+            args,
+            destination:
+                Some((
+                    mir::Place {
+                        base: mir::PlaceBase::Local(dest),
+                        ..
+                    },
+                    _,
+                )),
+            ..
+        } = kind
+        {
+            // If the call returns something with lifetimes, conservatively assume that the
+            // returned value contains the lifetimes of all the arguments. For example, given
+            // `let y: Foo<'a> = foo(x)`, `y` is considered to be a possible borrower of `x`.
+            if !self.contains_region(*dest) {
+                return;
+            }
+
+            for op in args {
+                if let mir::Operand::Copy(place) | mir::Operand::Move(place) = op {
+                    if let mir::PlaceBase::Local(arg) = place.base {
+                        self.add(*dest, arg);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Finds out whether a type contains regions, i.e., whether its values may contain references.
+struct ContainsRegion;
+
+impl TypeVisitor<'_> for ContainsRegion {
+    fn visit_region(&mut self, _: ty::Region<'_>) -> bool {
+        true
+    }
+}
+
+/// Calls `visit` with the locals the operands of `rvalue` use.
+fn rvalue_locals(rvalue: &mir::Rvalue<'_>, mut visit: impl FnMut(mir::Local)) {
+    use rustc::mir::Rvalue::*;
+
+    let mut visit_op = |op: &mir::Operand<'_>| {
+        if let mir::Operand::Copy(place) | mir::Operand::Move(place) = op {
+            if let mir::PlaceBase::Local(local) = place.base {
+                visit(local)
+            }
+        }
+    };
+
+    match rvalue {
+        Use(op) | Repeat(op, _) | Cast(_, op, _) | UnaryOp(_, op) => visit_op(op),
+        Aggregate(_, ops) => ops.iter().for_each(visit_op),
+        BinaryOp(_, lhs, rhs) | CheckedBinaryOp(_, lhs, rhs) => {
+            visit_op(lhs);
+            visit_op(rhs);
+        },
+        _ => {},
+    }
+}
@@ -12,9 +12,12 @@ pub mod higher;
 mod hir_utils;
 pub mod inspector;
 pub mod internal_lints;
+pub mod mir;
 pub mod paths;
 pub mod ptr;
+pub mod ranges;
 pub mod sugg;
+pub mod timings;
 pub mod usage;
 pub use self::attrs::*;
 pub use self::diagnostics::*;
@@ -253,6 +256,10 @@ pub fn path_to_res(cx: &LateContext<'_, '_>, path: &[&str]) -> Option<(def::Res)
                     if path_it.peek().is_none() {
                         return Some(item.res);
                     }
+                    // e.g. `std::vec` is both a module and a macro
+                    if let Res::Def(DefKind::Macro(_), _) = item.res {
+                        continue;
+                    }
 
                     items = cx.tcx.item_children(item.res.def_id());
                     break;
@@ -915,7 +922,12 @@ pub fn is_try(expr: &Expr) -> Option<&Expr> {
 ///
 /// Useful for skipping long running code when it's unnecessary
 pub fn is_allowed(cx: &LateContext<'_, '_>, lint: &'static Lint, id: HirId) -> bool {
-    cx.tcx.lint_level_at_node(lint, id).0 == Level::Allow
+    let allowed = cx.tcx.lint_level_at_node(lint, id).0 == Level::Allow;
+    if allowed {
+        // Lints check this to avoid needless work, so the `allow` attribute is in use.
+        record_lint(lint, cx.tcx.hir().span(id));
+    }
+    allowed
 }
 
 pub fn get_arg_name(pat: &Pat) -> Option<ast::Name> {
@@ -0,0 +1,548 @@
+//! A conservative analysis of the values integer expressions can have.
+//!
+//! The values are approximated by an interval computed from constants, arithmetic and bit
+//! operations, casts and the conditions of `if`s, `while`s and `match`es that enclose the use of
+//! an immutable local.
+
+#![deny(clippy::missing_docs_in_private_items)]
+
+use crate::consts::{constant, Constant};
+use crate::utils::comparisons::{normalize_comparison, Rel};
+use crate::utils::{clip, int_bits, sext};
+use rustc::hir::def::Res;
+use rustc::hir::ptr::P;
+use rustc::hir::*;
+use rustc::lint::LateContext;
+use rustc::ty::{self, Ty, TyCtxt};
+use std::cmp;
+
+/// An interval of integer values, including both bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntRange {
+    /// The smallest value
+    pub min: i128,
+    /// The largest value
+    pub max: i128,
+}
+
+impl IntRange {
+    /// Creates the range `min..=max`.
+    pub fn new(min: i128, max: i128) -> Self {
+        debug_assert!(min <= max);
+        Self { min, max }
+    }
+
+    /// Creates the range that only contains `value`.
+    pub fn exact(value: i128) -> Self {
+        Self::new(value, value)
+    }
+
+    /// The values of the integer type `ty`, unless they don't fit into `i128` (i.e., for `u128`).
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn of_type(tcx: TyCtxt<'_>, ty: Ty<'_>) -> Option<Self> {
+        match ty.sty {
+            ty::Int(ity) => {
+                let bits = int_bits(tcx, ity);
+                Some(Self::new(
+                    i128::min_value() >> (128 - bits),
+                    i128::max_value() >> (128 - bits),
+                ))
+            },
+            ty::Uint(uty) => {
+                let max = clip(tcx, u128::max_value(), uty);
+                if max <= i128::max_value() as u128 {
+                    Some(Self::new(0, max as i128))
+                } else {
+                    None
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether all values of `other` are in this range.
+    pub fn contains(self, other: Self) -> bool {
+        self.min <= other.min && other.max <= self.max
+    }
+
+    /// The values that are in both ranges, or `None` if there are none.
+    pub fn intersect(self, other: Self) -> Option<Self> {
+        let min = cmp::max(self.min, other.min);
+        let max = cmp::min(self.max, other.max);
+        if min <= max {
+            Some(Self::new(min, max))
+        } else {
+            None
+        }
+    }
+
+    /// The smallest range containing the values of both ranges.
+    pub fn union(self, other: Self) -> Self {
+        Self::new(cmp::min(self.min, other.min), cmp::max(self.max, other.max))
+    }
+
+    /// The values of `a + b`, or `None` if the result doesn't fit into `i128`.
+    pub fn add(self, other: Self) -> Option<Self> {
+        Some(Self::new(
+            self.min.checked_add(other.min)?,
+            self.max.checked_add(other.max)?,
+        ))
+    }
+
+    /// The values of `a - b`, or `None` if the result doesn't fit into `i128`.
+    pub fn sub(self, other: Self) -> Option<Self> {
+        Some(Self::new(
+            self.min.checked_sub(other.max)?,
+            self.max.checked_sub(other.min)?,
+        ))
+    }
+
+    /// The values of `a * b`, or `None` if the result doesn't fit into `i128`.
+    pub fn mul(self, other: Self) -> Option<Self> {
+        let products = [
+            self.min.checked_mul(other.min)?,
+            self.min.checked_mul(other.max)?,
+            self.max.checked_mul(other.min)?,
+            self.max.checked_mul(other.max)?,
+        ];
+        Some(Self::new(
+            *products.iter().min().unwrap(),
+            *products.iter().max().unwrap(),
+        ))
+    }
+
+    /// The values of `a / b` for non-negative operands.
+    pub fn div(self, other: Self) -> Option<Self> {
+        if self.min < 0 || other.min <= 0 {
+            return None;
+        }
+        Some(Self::new(self.min / other.max, self.max / other.min))
+    }
+
+    /// The values of `a % b`. The result has the sign of `a` and is smaller than the largest
+    /// magnitude of `b`.
+    pub fn rem(self, other: Self) -> Option<Self> {
+        let limit = cmp::max(other.min.checked_abs()?, other.max.checked_abs()?) - 1;
+        if limit < 0 {
+            // `b` is always zero, which panics.
+            return None;
+        }
+        if self.min >= 0 {
+            Some(Self::new(0, cmp::min(self.max, limit)))
+        } else if self.max <= 0 {
+            Some(Self::new(cmp::max(self.min, -limit), 0))
+        } else {
+            Some(Self::new(cmp::max(self.min, -limit), cmp::min(self.max, limit)))
+        }
+    }
+
+    /// The values of `a & b`, if one of the operands is never negative.
+    pub fn bit_and(self, other: Self) -> Option<Self> {
+        match (self.min >= 0, other.min >= 0) {
+            (true, true) => Some(Self::new(0, cmp::min(self.max, other.max))),
+            (true, false) => Some(Self::new(0, self.max)),
+            (false, true) => Some(Self::new(0, other.max)),
+            (false, false) => None,
+        }
+    }
+
+    /// The values of `a >> b`, if `b` is a single value less than 128.
+    pub fn shr(self, other: Self) -> Option<Self> {
+        if other.min != other.max || other.min < 0 || other.min >= 128 {
+            return None;
+        }
+        Some(Self::new(self.min >> other.min, self.max >> other.min))
+    }
+
+    /// The values of `-a`, or `None` if the result doesn't fit into `i128`.
+    pub fn neg(self) -> Option<Self> {
+        Some(Self::new(self.max.checked_neg()?, self.min.checked_neg()?))
+    }
+}
+
+/// Computes the values the integer expression `expr` can have. Returns `None` if nothing is known
+/// about `expr` and its type is `u128` or not an integer type.
+pub fn expr_range<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, expr: &Expr) -> Option<IntRange> {
+    let ty = cx.tables.expr_ty(expr);
+    if !ty.is_integral() {
+        return None;
+    }
+    let ty_range = IntRange::of_type(cx.tcx, ty);
+    match (compute_range(cx, expr, ty), ty_range) {
+        // An empty intersection means that the expression is unreachable.
+        (Some(range), Some(ty_range)) => Some(range.intersect(ty_range).unwrap_or(ty_range)),
+        (range, ty_range) => range.or(ty_range),
+    }
+}
+
+/// Computes the values of `expr` from its kind, ignoring the range of its type `ty`.
+fn compute_range<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, expr: &Expr, ty: Ty<'tcx>) -> Option<IntRange> {
+    if let Some(value) = constant_value(cx, expr, ty) {
+        return Some(IntRange::exact(value));
+    }
+
+    let range = match expr.node {
+        ExprKind::Binary(op, ref left, ref right) => {
+            let (left, right) = (expr_range(cx, left)?, expr_range(cx, right)?);
+            match op.node {
+                BinOpKind::Add => left.add(right),
+                BinOpKind::Sub => left.sub(right),
+                BinOpKind::Mul => left.mul(right),
+                BinOpKind::Div => left.div(right),
+                BinOpKind::Rem => left.rem(right),
+                BinOpKind::BitAnd => left.bit_and(right),
+                BinOpKind::Shr => left.shr(right),
+                _ => None,
+            }
+        },
+        ExprKind::Unary(UnNeg, ref operand) => expr_range(cx, operand)?.neg(),
+        ExprKind::Cast(ref operand, _) => {
+            let operand_ty = cx.tables.expr_ty(operand);
+            match operand_ty.sty {
+                ty::Bool => Some(IntRange::new(0, 1)),
+                ty::Int(_) | ty::Uint(_) => expr_range(cx, operand),
+                _ => None,
+            }
+        },
+        ExprKind::MethodCall(ref path, _, ref args) if args.len() == 2 => {
+            let (left, right) = (expr_range(cx, &args[0])?, expr_range(cx, &args[1])?);
+            match &*path.ident.as_str() {
+                "min" => Some(IntRange::new(
+                    cmp::min(left.min, right.min),
+                    cmp::min(left.max, right.max),
+                )),
+                "max" => Some(IntRange::new(
+                    cmp::max(left.min, right.min),
+                    cmp::max(left.max, right.max),
+                )),
+                _ => None,
+            }
+        },
+        ExprKind::Block(ref block, _) if block.stmts.is_empty() => block.expr.as_ref().and_then(|e| expr_range(cx, e)),
+        ExprKind::Path(QPath::Resolved(None, ref path)) => match path.res {
+            Res::Local(local) => local_range(cx, expr, local),
+            _ => None,
+        },
+        _ => None,
+    }?;
+
+    // Operations that overflow the type of `expr` wrap around or panic.
+    if fits_into(cx.tcx, range, ty) {
+        Some(range)
+    } else {
+        None
+    }
+}
+
+/// The value of `expr` if it's a constant that fits into `i128`.
+#[allow(clippy::cast_possible_wrap)]
+fn constant_value<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, expr: &Expr, ty: Ty<'tcx>) -> Option<i128> {
+    match (constant(cx, cx.tables, expr)?.0, &ty.sty) {
+        (Constant::Int(value), &ty::Int(ity)) => Some(sext(cx.tcx, value, ity)),
+        (Constant::Int(value), &ty::Uint(_)) if value <= i128::max_value() as u128 => Some(value as i128),
+        _ => None,
+    }
+}
+
+/// Whether all values of `range` are values of the integer type `ty`.
+fn fits_into(tcx: TyCtxt<'_>, range: IntRange, ty: Ty<'_>) -> bool {
+    match (IntRange::of_type(tcx, ty), &ty.sty) {
+        (Some(ty_range), _) => ty_range.contains(range),
+        // `u128`
+        (None, &ty::Uint(_)) => range.min >= 0,
+        _ => false,
+    }
+}
+
+/// Computes the values of the local `local` used in `expr`. Only the values of immutable locals
+/// can be narrowed down by their initializer and the conditions the use is guarded by.
+fn local_range<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, expr: &Expr, local: HirId) -> Option<IntRange> {
+    let map = cx.tcx.hir();
+    let pat = match map.find(local) {
+        Some(Node::Binding(pat)) => pat,
+        _ => return None,
+    };
+    let mut range = match pat.node {
+        PatKind::Binding(BindingAnnotation::Unannotated, _, _, Some(ref sub)) => pat_range(cx, sub),
+        PatKind::Binding(BindingAnnotation::Unannotated, _, _, None) => match map.find(map.get_parent_node(local)) {
+            Some(Node::Local(&Local {
+                init: Some(ref init), ..
+            })) => expr_range(cx, init),
+            _ => None,
+        },
+        _ => return None,
+    };
+    // Without an initializer, the local can have any value of its type.
+    range = range.or_else(|| IntRange::of_type(cx.tcx, cx.tables.expr_ty(expr)));
+
+    for fact in enclosing_facts(cx, expr) {
+        let narrowed = match fact {
+            Fact::Condition(cond, holds) => condition_range(cx, cond, holds, local),
+            Fact::Match(scrutinee, pats) if is_local(scrutinee, local) => {
+                // The local matches one of the patterns.
+                let mut ranges = pats.iter().map(|pat| pat_range(cx, pat));
+                let first = ranges.next().and_then(|range| range);
+                ranges.fold(first, |acc, range| Some(acc?.union(range?)))
+            },
+            Fact::Match(..) => None,
+        };
+        range = match (range, narrowed) {
+            // An empty intersection means that the use is unreachable.
+            (Some(range), Some(narrowed)) => Some(range.intersect(narrowed).unwrap_or(range)),
+            (range, narrowed) => range.or(narrowed),
+        };
+    }
+    range
+}
+
+/// What is known at a use of a local because of an enclosing expression.
+#[derive(Clone, Copy)]
+pub enum Fact<'tcx> {
+    /// The condition holds (or doesn't hold, if the `bool` is `false`).
+    Condition(&'tcx Expr, bool),
+    /// The scrutinee matches one of the patterns.
+    Match(&'tcx Expr, &'tcx [P<Pat>]),
+}
+
+/// Collects the facts that hold at `expr` because of the conditions of the enclosing `if`s and
+/// `while`s, the left operands of enclosing `&&` and `||` and the arms of enclosing `match`es.
+pub fn enclosing_facts<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, expr: &Expr) -> Vec<Fact<'tcx>> {
+    let map = cx.tcx.hir();
+    let mut facts = Vec::new();
+    let mut child = expr.hir_id;
+    loop {
+        let parent = map.get_parent_node(child);
+        if parent == child {
+            break;
+        }
+        match map.find(parent) {
+            Some(Node::Expr(parent_expr)) => match parent_expr.node {
+                ExprKind::Match(ref scrutinee, ref arms, source) => {
+                    let arm = arms
+                        .iter()
+                        .position(|arm| arm.hir_id == child || arm.body.hir_id == child);
+                    match (arm, source) {
+                        (Some(0), MatchSource::IfDesugar { .. }) | (Some(0), MatchSource::WhileDesugar) => {
+                            facts.push(Fact::Condition(scrutinee, true));
+                        },
+                        (Some(1), MatchSource::IfDesugar { .. }) => facts.push(Fact::Condition(scrutinee, false)),
+                        (Some(i), MatchSource::Normal) => {
+                            facts.push(Fact::Match(scrutinee, &arms[i].pats));
+                            if let Some(Guard::If(ref guard)) = arms[i].guard {
+                                if arms[i].body.hir_id == child {
+                                    facts.push(Fact::Condition(guard, true));
+                                }
+                            }
+                        },
+                        _ => {},
+                    }
+                },
+                ExprKind::Binary(op, ref left, ref right) if right.hir_id == child => match op.node {
+                    BinOpKind::And => facts.push(Fact::Condition(left, true)),
+                    BinOpKind::Or => facts.push(Fact::Condition(left, false)),
+                    _ => {},
+                },
+                _ => {},
+            },
+            Some(Node::Arm(_)) | Some(Node::Block(_)) | Some(Node::Stmt(_)) | Some(Node::Local(_)) => {},
+            _ => break,
+        }
+        child = parent;
+    }
+    facts
+}
+
+/// Computes the values `local` can have if `cond` evaluates to `holds`.
+fn condition_range<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, cond: &Expr, holds: bool, local: HirId) -> Option<IntRange> {
+    match cond.node {
+        ExprKind::DropTemps(ref cond) => condition_range(cx, cond, holds, local),
+        ExprKind::Unary(UnNot, ref cond) => condition_range(cx, cond, !holds, local),
+        ExprKind::Binary(op, ref left, ref right) => match (op.node, holds) {
+            // Both operands hold, so both narrow down the values.
+            (BinOpKind::And, true) | (BinOpKind::Or, false) => {
+                match (
+                    condition_range(cx, left, holds, local),
+                    condition_range(cx, right, holds, local),
+                ) {
+                    (Some(left), Some(right)) => left.intersect(right).or(Some(left)),
+                    (left, right) => left.or(right),
+                }
+            },
+            // Only one of the operands holds.
+            (BinOpKind::And, false) | (BinOpKind::Or, true) => {
+                Some(condition_range(cx, left, holds, local)?.union(condition_range(cx, right, holds, local)?))
+            },
+            _ => {
+                let (rel, left, right) = normalize_comparison(op.node, left, right)?;
+                if is_local(left, local) {
+                    // `local < right`, `local <= right` or `local == right`
+                    let right = expr_range(cx, right)?;
+                    match (rel, holds) {
+                        (Rel::Lt, true) => Some(IntRange::new(i128::min_value(), right.max.checked_sub(1)?)),
+                        (Rel::Le, true) => Some(IntRange::new(i128::min_value(), right.max)),
+                        (Rel::Lt, false) => Some(IntRange::new(right.min, i128::max_value())),
+                        (Rel::Le, false) => Some(IntRange::new(right.min.checked_add(1)?, i128::max_value())),
+                        (Rel::Eq, true) | (Rel::Ne, false) => Some(right),
+                        _ => None,
+                    }
+                } else if is_local(right, local) {
+                    // `left < local`, `left <= local` or `left == local`
+                    let left = expr_range(cx, left)?;
+                    match (rel, holds) {
+                        (Rel::Lt, true) => Some(IntRange::new(left.min.checked_add(1)?, i128::max_value())),
+                        (Rel::Le, true) => Some(IntRange::new(left.min, i128::max_value())),
+                        (Rel::Lt, false) => Some(IntRange::new(i128::min_value(), left.max)),
+                        (Rel::Le, false) => Some(IntRange::new(i128::min_value(), left.max.checked_sub(1)?)),
+                        (Rel::Eq, true) | (Rel::Ne, false) => Some(left),
+                        _ => None,
+                    }
+                } else {
+                    None
+                }
+            },
+        },
+        _ => None,
+    }
+}
+
+/// Computes the values matched by the pattern `pat`.
+fn pat_range<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, pat: &Pat) -> Option<IntRange> {
+    match pat.node {
+        PatKind::Lit(ref lit) => expr_range(cx, lit),
+        PatKind::Range(ref lo, ref hi, end) => {
+            let (lo, hi) = (expr_range(cx, lo)?, expr_range(cx, hi)?);
+            let max = match end {
+                RangeEnd::Included => hi.max,
+                RangeEnd::Excluded => hi.max.checked_sub(1)?,
+            };
+            if lo.min <= max {
+                Some(IntRange::new(lo.min, max))
+            } else {
+                None
+            }
+        },
+        PatKind::Binding(_, _, _, Some(ref sub)) => pat_range(cx, sub),
+        _ => None,
+    }
+}
+
+/// Whether `expr` is a path to the local `local`.
+pub fn is_local(expr: &Expr, local: HirId) -> bool {
+    if let ExprKind::Path(QPath::Resolved(None, ref path)) = expr.node {
+        if let Res::Local(id) = path.res {
+            return id == local;
+        }
+    }
+    false
+}
+
+/// Whether `expr` is an immutable local that is known to be less than an expression `bound`
+/// returns `true` for, because of a condition that guards `expr`. This is used to find accesses
+/// guarded by length checks like `if i < v.len() { v[i] }`.
+pub fn is_bounded_by<'a, 'tcx>(cx: &LateContext<'a, 'tcx>, expr: &Expr, bound: impl Fn(&Expr) -> bool) -> bool {
+    let local = match expr.node {
+        ExprKind::Path(QPath::Resolved(None, ref path)) => match path.res {
+            Res::Local(local) if is_immutable_local(cx, local) => local,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    enclosing_facts(cx, expr).into_iter().any(|fact| match fact {
+        Fact::Condition(cond, holds) => condition_bounds(cond, holds, local, &bound),
+        Fact::Match(..) => false,
+    })
+}
+
+/// Whether `cond` evaluating to `holds` implies that `local` is less than an expression `bound`
+/// returns `true` for.
+fn condition_bounds(cond: &Expr, holds: bool, local: HirId, bound: &impl Fn(&Expr) -> bool) -> bool {
+    match cond.node {
+        ExprKind::DropTemps(ref cond) => condition_bounds(cond, holds, local, bound),
+        ExprKind::Unary(UnNot, ref cond) => condition_bounds(cond, !holds, local, bound),
+        ExprKind::Binary(op, ref left, ref right) => match (op.node, holds) {
+            (BinOpKind::And, true) | (BinOpKind::Or, false) => {
+                condition_bounds(left, holds, local, bound) || condition_bounds(right, holds, local, bound)
+            },
+            (BinOpKind::And, false) | (BinOpKind::Or, true) => {
+                condition_bounds(left, holds, local, bound) && condition_bounds(right, holds, local, bound)
+            },
+            _ => match normalize_comparison(op.node, left, right) {
+                // `local < bound`
+                Some((Rel::Lt, left, right)) if holds => is_local(left, local) && bound(right),
+                // `!(bound <= local)`
+                Some((Rel::Le, left, right)) if !holds => is_local(right, local) && bound(left),
+                _ => false,
+            },
+        },
+        _ => false,
+    }
+}
+
+/// Whether `local` is bound by a pattern that doesn't allow to change its value.
+pub fn is_immutable_local(cx: &LateContext<'_, '_>, local: HirId) -> bool {
+    match cx.tcx.hir().find(local) {
+        Some(Node::Binding(pat)) => match pat.node {
+            PatKind::Binding(BindingAnnotation::Unannotated, ..) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::IntRange;
+
+    #[test]
+    fn test_arithmetic() {
+        let small = IntRange::new(0, 10);
+        let signed = IntRange::new(-5, 5);
+        assert_eq!(Some(IntRange::new(-5, 15)), small.add(signed));
+        assert_eq!(Some(IntRange::new(-5, 15)), small.sub(signed));
+        assert_eq!(Some(IntRange::new(-50, 50)), small.mul(signed));
+        assert_eq!(None, IntRange::exact(i128::max_value()).add(IntRange::exact(1)));
+        assert_eq!(Some(IntRange::new(-5, 5)), signed.neg());
+        assert_eq!(None, IntRange::exact(i128::min_value()).neg());
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let value = IntRange::new(10, 100);
+        assert_eq!(Some(IntRange::new(2, 50)), value.div(IntRange::new(2, 5)));
+        assert_eq!(None, value.div(IntRange::new(0, 5)));
+        assert_eq!(Some(IntRange::new(0, 15)), value.rem(IntRange::exact(16)));
+        assert_eq!(Some(IntRange::new(0, 15)), value.rem(IntRange::new(-16, 3)));
+        assert_eq!(
+            Some(IntRange::new(-15, 0)),
+            IntRange::new(-100, -1).rem(IntRange::exact(16))
+        );
+        assert_eq!(
+            Some(IntRange::new(-3, 3)),
+            IntRange::new(-100, 100).rem(IntRange::exact(4))
+        );
+        assert_eq!(None, value.rem(IntRange::exact(0)));
+    }
+
+    #[test]
+    fn test_bits() {
+        let any = IntRange::new(i128::from(i32::min_value()), i128::from(i32::max_value()));
+        assert_eq!(Some(IntRange::new(0, 0xff)), any.bit_and(IntRange::exact(0xff)));
+        assert_eq!(None, any.bit_and(any));
+        assert_eq!(
+            Some(IntRange::new(0, 0xff)),
+            IntRange::new(0, 0xffff).shr(IntRange::exact(8))
+        );
+        assert_eq!(None, any.shr(IntRange::new(1, 2)));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = IntRange::new(0, 10);
+        let b = IntRange::new(5, 20);
+        assert_eq!(Some(IntRange::new(5, 10)), a.intersect(b));
+        assert_eq!(None, a.intersect(IntRange::new(11, 12)));
+        assert_eq!(IntRange::new(0, 20), a.union(b));
+        assert!(b.contains(IntRange::new(6, 20)));
+        assert!(!a.contains(b));
+    }
+}
@@ -1,7 +1,7 @@
 //! Contains utility functions to generate suggestions.
 #![deny(clippy::missing_docs_in_private_items)]
 
-use crate::utils::{higher, snippet, snippet_opt, snippet_with_macro_callsite};
+use crate::utils::{higher, in_macro, snippet, snippet_opt, snippet_with_macro_callsite};
 use matches::matches;
 use rustc::hir;
 use rustc::lint::{EarlyContext, LateContext, LintContext};
@@ -496,6 +496,159 @@ fn indentation<T: LintContext>(cx: &T, span: Span) -> Option<String> {
     }
 }
 
+/// Builds a suggestion made of several edits that only make sense together, like changing a
+/// loop header and removing a statement from its body. The edits are emitted as a single
+/// multipart suggestion, so they are applied all at once or not at all.
+///
+/// Edits inside of macro expansions are widened to the whole macro call, which makes the
+/// suggestion `MaybeIncorrect`. If edits overlap, no suggestion is emitted.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut sugg = SuggestionBuilder::new(cx, Applicability::MachineApplicable);
+/// let pat_snippet = sugg.snippet(pat.span, "item");
+/// let arg_sugg = sugg.hir(arg, "_");
+/// sugg.replace(pat.span, format!("({}, {})", name, pat_snippet))
+///     .replace(arg.span, format!("{}.enumerate()", arg_sugg.maybe_par()))
+///     .remove(increment_span);
+/// sugg.emit(db, "consider using");
+/// ```
+pub struct SuggestionBuilder<'a, T: LintContext> {
+    /// The context the snippets are taken from.
+    cx: &'a T,
+    /// The spans to replace and their replacements.
+    parts: Vec<(Span, String)>,
+    /// The applicability of the edits taken together.
+    applicability: Applicability,
+}
+
+impl<'a, T: LintContext> SuggestionBuilder<'a, T> {
+    /// Creates a builder without any edits. The applicability can only be weakened afterwards.
+    pub fn new(cx: &'a T, applicability: Applicability) -> Self {
+        Self {
+            cx,
+            parts: Vec::new(),
+            applicability,
+        }
+    }
+
+    /// Gets the snippet of `span`, like `snippet_with_applicability`. Unlike it, spans of
+    /// desugarings such as `for` loops don't weaken the applicability.
+    pub fn snippet<'b>(&mut self, span: Span, default: &'b str) -> Cow<'b, str> {
+        self.check_span(span);
+        if let Some(snippet) = snippet_opt(self.cx, span) {
+            Cow::Owned(snippet)
+        } else {
+            self.weaken(Applicability::HasPlaceholders);
+            Cow::Borrowed(default)
+        }
+    }
+
+    /// Replaces `span` with `replacement`.
+    pub fn replace(&mut self, span: Span, replacement: String) -> &mut Self {
+        let span = self.callsite(span);
+        self.parts.push((span, replacement));
+        self
+    }
+
+    /// Removes `span`. If there's nothing but whitespace on its lines otherwise, the whole lines
+    /// are removed.
+    pub fn remove(&mut self, span: Span) -> &mut Self {
+        let span = self.callsite(span);
+        let span = whole_lines(self.cx, span).unwrap_or(span);
+        self.parts.push((span, String::new()));
+        self
+    }
+
+    /// Lowers the applicability of the suggestion to `applicability`, unless it's already lower.
+    pub fn weaken(&mut self, applicability: Applicability) -> &mut Self {
+        if applicability_rank(applicability) > applicability_rank(self.applicability) {
+            self.applicability = applicability;
+        }
+        self
+    }
+
+    /// Adds the suggestion to `db`. Returns `false` without adding anything if there are no
+    /// edits or if edits overlap.
+    pub fn emit(self, db: &mut rustc_errors::DiagnosticBuilder<'_>, msg: &str) -> bool {
+        let mut parts = self.parts;
+        parts.sort_by_key(|&(span, _)| (span.lo(), span.hi()));
+        parts.dedup();
+        if parts.is_empty() || parts.windows(2).any(|pair| pair[0].0.hi() > pair[1].0.lo()) {
+            return false;
+        }
+        db.multipart_suggestion(msg, parts, self.applicability);
+        true
+    }
+
+    /// Widens a span inside of a macro expansion to the whole macro call.
+    fn callsite(&mut self, span: Span) -> Span {
+        if self.check_span(span) {
+            span.source_callsite()
+        } else {
+            span
+        }
+    }
+
+    /// Weakens the applicability if `span` is inside of a macro expansion, and returns whether it
+    /// is.
+    fn check_span(&mut self, span: Span) -> bool {
+        let in_macro = in_macro(span);
+        if in_macro {
+            self.weaken(Applicability::MaybeIncorrect);
+        }
+        in_macro
+    }
+}
+
+impl<'a, 'b, 'tcx> SuggestionBuilder<'a, LateContext<'b, 'tcx>> {
+    /// Gets the `Sugg` of `expr`, like `Sugg::hir_with_applicability`. Unlike it, spans of
+    /// desugarings such as `for` loops don't weaken the applicability.
+    pub fn hir<'c>(&mut self, expr: &hir::Expr, default: &'c str) -> Sugg<'c> {
+        self.check_span(expr.span);
+        Sugg::hir_opt(self.cx, expr).unwrap_or_else(|| {
+            self.weaken(Applicability::HasPlaceholders);
+            Sugg::NonParen(Cow::Borrowed(default))
+        })
+    }
+}
+
+/// Orders the applicabilities from certain to uncertain.
+fn applicability_rank(applicability: Applicability) -> u8 {
+    match applicability {
+        Applicability::MachineApplicable => 0,
+        Applicability::HasPlaceholders => 1,
+        Applicability::MaybeIncorrect => 2,
+        Applicability::Unspecified => 3,
+    }
+}
+
+/// Extends `span` to the whole lines it's on, including the preceding line break, if there's
+/// nothing but whitespace on them otherwise.
+///
+/// The span starts at the end of the previous line rather than at the start of the first line:
+/// rustfix can't apply suggestions starting in the indentation of a line.
+fn whole_lines<T: LintContext>(cx: &T, span: Span) -> Option<Span> {
+    let source_map = cx.sess().source_map();
+    let lo = source_map.lookup_byte_offset(span.lo());
+    let hi = source_map.lookup_byte_offset(span.hi());
+    let src = lo.sf.src.as_ref()?;
+    let line_start = src[..lo.pos.to_usize()].rfind('\n')?;
+    let before = &src[line_start..lo.pos.to_usize()];
+    let rest = &src[hi.pos.to_usize()..];
+    let after = &rest[..rest.find('\n').unwrap_or_else(|| rest.len())];
+    if !before.trim().is_empty() || !after.trim().is_empty() {
+        return None;
+    }
+    let before: u32 = before.len().try_into().ok()?;
+    let after: u32 = after.len().try_into().ok()?;
+    Some(
+        span.with_lo(span.lo() - BytePos(before))
+            .with_hi(span.hi() + BytePos(after)),
+    )
+}
+
 /// Convenience extension trait for `DiagnosticBuilder`.
 pub trait DiagnosticBuilderExt<'a, T: LintContext> {
     /// Suggests to add an attribute to an item.
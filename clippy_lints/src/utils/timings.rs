@@ -0,0 +1,168 @@
+//! Measures the wall time spent in each of Clippy's lint passes, for `clippy-driver --timings`.
+//!
+//! Every pass is wrapped in a `TimedPass` that forwards all `check_*` methods to the actual pass.
+//! The forwarding methods are generated from rustc's lists of lint pass methods, so they don't
+//! have to be kept in sync by hand.
+
+use crate::utils::emitted_lint_counts;
+use rustc::hir;
+use rustc::lint::{
+    EarlyContext, EarlyLintPass, EarlyLintPassObject, LateContext, LateLintPass, LateLintPassObject, LintArray,
+    LintPass,
+};
+use rustc::{early_lint_methods, late_lint_methods};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use syntax::ast;
+use syntax::source_map::Span;
+
+/// When a pass runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PassKind {
+    PreExpansion,
+    Early,
+    Late,
+}
+
+impl PassKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PreExpansion => "pre-expansion",
+            Self::Early => "early",
+            Self::Late => "late",
+        }
+    }
+}
+
+/// The wall time spent in the passes of one crate.
+///
+/// If timing is disabled, the passes are registered as-is.
+#[derive(Default)]
+pub struct Timings {
+    enabled: bool,
+    passes: Vec<(&'static str, PassKind, Arc<AtomicU64>)>,
+}
+
+impl Timings {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            passes: Vec::new(),
+        }
+    }
+
+    pub fn wrap_pre_expansion(&mut self, pass: EarlyLintPassObject) -> EarlyLintPassObject {
+        self.wrap_early_kind(pass, PassKind::PreExpansion)
+    }
+
+    pub fn wrap_early(&mut self, pass: EarlyLintPassObject) -> EarlyLintPassObject {
+        self.wrap_early_kind(pass, PassKind::Early)
+    }
+
+    fn wrap_early_kind(&mut self, pass: EarlyLintPassObject, kind: PassKind) -> EarlyLintPassObject {
+        if self.enabled {
+            let elapsed = self.track(pass.name(), kind);
+            box TimedPass { pass, elapsed }
+        } else {
+            pass
+        }
+    }
+
+    pub fn wrap_late(&mut self, pass: LateLintPassObject) -> LateLintPassObject {
+        if self.enabled {
+            let elapsed = self.track(pass.name(), PassKind::Late);
+            box TimedPass { pass, elapsed }
+        } else {
+            pass
+        }
+    }
+
+    fn track(&mut self, name: &'static str, kind: PassKind) -> Arc<AtomicU64> {
+        let elapsed = Arc::new(AtomicU64::new(0));
+        self.passes.push((name, kind, Arc::clone(&elapsed)));
+        elapsed
+    }
+
+    /// Returns the time spent in each pass, slowest first.
+    pub fn passes(&self) -> Vec<(&'static str, PassKind, Duration)> {
+        let mut passes: Vec<_> = self
+            .passes
+            .iter()
+            .map(|(name, kind, elapsed)| (*name, *kind, Duration::from_nanos(elapsed.load(Ordering::Relaxed))))
+            .collect();
+        passes.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(b.0)));
+        passes
+    }
+
+    /// Returns the number of diagnostics each lint emitted, e.g. `("clippy::needless_return", 2)`.
+    pub fn lints(&self) -> Vec<(String, usize)> {
+        emitted_lint_counts()
+    }
+}
+
+struct TimedPass<P> {
+    pass: P,
+    /// The total time spent in `pass` in nanoseconds.
+    elapsed: Arc<AtomicU64>,
+}
+
+impl<P> TimedPass<P> {
+    fn record(&self, start: Instant) {
+        let elapsed = start.elapsed();
+        let nanos = elapsed.as_secs() * 1_000_000_000 + u64::from(elapsed.subsec_nanos());
+        self.elapsed.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+#[allow(rustc::lint_pass_impl_without_macro)]
+impl LintPass for TimedPass<EarlyLintPassObject> {
+    fn name(&self) -> &'static str {
+        self.pass.name()
+    }
+
+    fn get_lints(&self) -> LintArray {
+        self.pass.get_lints()
+    }
+}
+
+#[allow(rustc::lint_pass_impl_without_macro)]
+impl LintPass for TimedPass<LateLintPassObject> {
+    fn name(&self) -> &'static str {
+        self.pass.name()
+    }
+
+    fn get_lints(&self) -> LintArray {
+        self.pass.get_lints()
+    }
+}
+
+macro_rules! timed_methods {
+    ($context:ty, [$($(#[$attr:meta])* fn $name:ident($($param:ident: $arg:ty),*);)*]) => {
+        $(fn $name(&mut self, cx: $context, $($param: $arg),*) {
+            let start = Instant::now();
+            self.pass.$name(cx, $($param),*);
+            self.record(start);
+        })*
+    };
+}
+
+macro_rules! timed_early_methods {
+    ([], $methods:tt) => {
+        timed_methods!(&EarlyContext<'_>, $methods);
+    };
+}
+
+macro_rules! timed_late_methods {
+    ([], [$hir:tt], $methods:tt) => {
+        timed_methods!(&LateContext<'a, $hir>, $methods);
+    };
+}
+
+impl EarlyLintPass for TimedPass<EarlyLintPassObject> {
+    early_lint_methods!(timed_early_methods, []);
+}
+
+impl<'a, 'tcx> LateLintPass<'a, 'tcx> for TimedPass<LateLintPassObject> {
+    late_lint_methods!(timed_late_methods, [], ['tcx]);
+}
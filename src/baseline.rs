@@ -0,0 +1,170 @@
+//! Baseline files record the Clippy warnings that already exist in a code base, so that only new
+//! occurrences are reported.
+//!
+//! Each line of a baseline file has the form `lint<TAB>file<TAB>fingerprint`. The fingerprint is
+//! computed from the lint message and the source code the lint points at, but not from line or
+//! column numbers. Unrelated edits elsewhere in a file therefore do not invalidate the baseline.
+
+use crate::diagnostics::Diagnostic;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+const HEADER: &str = "# Clippy baseline, generated by `cargo clippy --write-baseline`";
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fingerprint {
+    lint: String,
+    file: String,
+    hash: String,
+}
+
+impl Fingerprint {
+    /// Computes the fingerprint of a Clippy diagnostic. Returns `None` for all other diagnostics.
+    pub fn new(diag: &Diagnostic) -> Option<Self> {
+        let lint = diag.clippy_lint()?;
+        let span = diag.primary_span()?;
+
+        // Whitespace is ignored so that reformatting code doesn't invalidate the baseline.
+        let snippet: String = span.snippet().chars().filter(|c| !c.is_whitespace()).collect();
+
+        Some(Self {
+            lint: lint.to_string(),
+            file: span.file_name.replace('\\', "/"),
+            hash: format!("{:016x}", fnv1a(&[diag.message.as_bytes(), snippet.as_bytes()])),
+        })
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.split('\t');
+        let fingerprint = Self {
+            lint: parts.next()?.to_string(),
+            file: parts.next()?.to_string(),
+            hash: parts.next()?.to_string(),
+        };
+
+        if parts.next().is_none() {
+            Some(fingerprint)
+        } else {
+            None
+        }
+    }
+}
+
+/// A 64 bit FNV-1a hash. Unlike `DefaultHasher`, it is guaranteed to be stable across Rust
+/// releases, which matters because baselines are committed to version control.
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in parts.iter().flat_map(|part| part.iter().chain(&[0])) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// The warnings recorded in a baseline file.
+///
+/// The same fingerprint can occur several times in a file, e.g. if the same expression is linted
+/// in several places. Only as many occurrences as were recorded are suppressed.
+#[derive(Default)]
+pub struct Baseline {
+    remaining: HashMap<Fingerprint, usize>,
+}
+
+impl Baseline {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let mut baseline = Self::default();
+
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fingerprint = Fingerprint::parse(line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: malformed baseline entry", path.display(), i + 1),
+                )
+            })?;
+            *baseline.remaining.entry(fingerprint).or_insert(0) += 1;
+        }
+
+        Ok(baseline)
+    }
+
+    /// Returns `true` if the diagnostic is recorded in the baseline and should not be reported.
+    pub fn suppresses(&mut self, fingerprint: &Fingerprint) -> bool {
+        if let Some(count) = self.remaining.get_mut(fingerprint) {
+            if *count > 0 {
+                *count -= 1;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Writes a baseline file containing the given fingerprints.
+pub fn write(path: &Path, mut fingerprints: Vec<Fingerprint>) -> io::Result<()> {
+    fingerprints.sort();
+
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    writeln!(file, "{}", HEADER)?;
+    for fingerprint in fingerprints {
+        writeln!(file, "{}\t{}\t{}", fingerprint.lint, fingerprint.file, fingerprint.hash)?;
+    }
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::CargoMessage;
+
+    const MESSAGE: &str = r#"{
+        "reason": "compiler-message",
+        "message": {
+            "message": "this let-binding has unit value",
+            "code": { "code": "clippy::let_unit_value", "explanation": null },
+            "level": "warning",
+            "spans": [{
+                "file_name": "src/main.rs",
+                "line_start": LINE,
+                "line_end": LINE,
+                "column_start": 5,
+                "column_end": 23,
+                "is_primary": true,
+                "text": [{ "text": "    let x = SNIPPET;", "highlight_start": 5, "highlight_end": 23 }]
+            }],
+            "children": [],
+            "rendered": "warning: this let-binding has unit value"
+        }
+    }"#;
+
+    fn fingerprint(line: usize, snippet: &str) -> Fingerprint {
+        let json = MESSAGE.replace("LINE", &line.to_string()).replace("SNIPPET", snippet);
+        let message = CargoMessage::parse(&json).unwrap();
+        Fingerprint::new(message.diagnostic().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn fingerprint_ignores_position() {
+        assert_eq!(fingerprint(3, "foo()"), fingerprint(42, "foo()"));
+        assert_eq!(fingerprint(3, "foo()"), fingerprint(3, "foo( )"));
+        assert_ne!(fingerprint(3, "foo()"), fingerprint(3, "bar()"));
+    }
+
+    #[test]
+    fn suppresses_recorded_occurrences() {
+        let fingerprint = fingerprint(3, "foo()");
+        let line = format!("{}\t{}\t{}", fingerprint.lint, fingerprint.file, fingerprint.hash);
+        assert_eq!(Some(fingerprint.clone()), Fingerprint::parse(&line));
+
+        let mut baseline = Baseline::default();
+        baseline.remaining.insert(fingerprint.clone(), 1);
+        assert!(baseline.suppresses(&fingerprint));
+        assert!(!baseline.suppresses(&fingerprint));
+    }
+}
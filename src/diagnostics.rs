@@ -0,0 +1,109 @@
+//! The subset of cargo's and rustc's JSON message format that `cargo-clippy` post-processes.
+
+use serde::Deserialize;
+
+/// A line printed by cargo when invoked with `--message-format=json`.
+#[derive(Deserialize)]
+pub struct CargoMessage {
+    pub reason: String,
+    pub message: Option<Diagnostic>,
+}
+
+impl CargoMessage {
+    /// Parses a line of cargo's stdout. Returns `None` for anything that isn't a JSON message.
+    pub fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str(line).ok()
+    }
+
+    /// Returns the diagnostic if this is a `compiler-message`.
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
+        if self.reason == "compiler-message" {
+            self.message.as_ref()
+        } else {
+            None
+        }
+    }
+}
+
+/// A diagnostic emitted by rustc.
+#[derive(Deserialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub code: Option<DiagnosticCode>,
+    pub level: String,
+    pub spans: Vec<DiagnosticSpan>,
+    pub children: Vec<Diagnostic>,
+    pub rendered: Option<String>,
+}
+
+impl Diagnostic {
+    /// Returns the name of the Clippy lint that emitted this diagnostic, without the `clippy::`
+    /// prefix.
+    pub fn clippy_lint(&self) -> Option<&str> {
+        self.code.as_ref().and_then(|code| {
+            if code.code.starts_with("clippy::") {
+                Some(&code.code["clippy::".len()..])
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns `true` if this diagnostic was emitted by a lint, as opposed to a compiler error
+    /// like `E0308`.
+    pub fn is_lint(&self) -> bool {
+        self.code.as_ref().map_or(false, |code| {
+            !(code.code.starts_with('E') && code.code[1..].chars().all(|c| c.is_ascii_digit()))
+        })
+    }
+
+    pub fn primary_span(&self) -> Option<&DiagnosticSpan> {
+        self.spans.iter().find(|span| span.is_primary)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.level == "error" && !self.message.starts_with("aborting due to")
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DiagnosticCode {
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub struct DiagnosticSpan {
+    pub file_name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+    pub is_primary: bool,
+    pub text: Vec<DiagnosticSpanLine>,
+    pub suggested_replacement: Option<String>,
+    pub suggestion_applicability: Option<String>,
+}
+
+impl DiagnosticSpan {
+    /// Returns the source code covered by this span.
+    pub fn snippet(&self) -> String {
+        self.text
+            .iter()
+            .map(|line| {
+                line.text
+                    .chars()
+                    .skip(line.highlight_start.saturating_sub(1))
+                    .take(line.highlight_end.saturating_sub(line.highlight_start))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DiagnosticSpanLine {
+    pub text: String,
+    pub highlight_start: usize,
+    pub highlight_end: usize,
+}
@@ -0,0 +1,241 @@
+//! The lines changed relative to a git revision, for `--diff-base`.
+
+use crate::diagnostics::DiagnosticSpan;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The changed line ranges of each file. Both ends of a range are inclusive.
+pub struct ChangedLines {
+    files: HashMap<PathBuf, Vec<(usize, usize)>>,
+    /// The directory the file names of diagnostics are relative to.
+    workspace_root: PathBuf,
+}
+
+impl ChangedLines {
+    /// Collects the lines that differ between `rev` and the working tree of the repository that
+    /// contains `workspace_root`, including untracked files.
+    pub fn from_git(rev: &str, workspace_root: &Path) -> Result<Self, String> {
+        let workspace_root = canonicalize(workspace_root)?;
+        let git = |args: &[&str]| git(&workspace_root, args);
+
+        let git_root = canonicalize(Path::new(git(&["rev-parse", "--show-toplevel"])?.trim()))?;
+        // `diff.noprefix` and `diff.mnemonicPrefix` change the `a/` and `b/` prefixes.
+        let diff = git(&[
+            "diff",
+            "-U0",
+            "--no-color",
+            "--no-ext-diff",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            rev,
+            "--",
+            ".",
+        ])?;
+        let mut changed = Self::parse(&git_root, &diff);
+
+        let untracked = git(&["ls-files", "-z", "--others", "--exclude-standard", "--full-name"])?;
+        for file in untracked.split('\0').filter(|file| !file.is_empty()) {
+            changed.files.insert(git_root.join(file), vec![(1, usize::max_value())]);
+        }
+
+        changed.workspace_root = workspace_root;
+        Ok(changed)
+    }
+
+    /// Parses the output of `git diff -U0`. The file names are relative to `git_root`.
+    fn parse(git_root: &Path, diff: &str) -> Self {
+        let mut files: HashMap<_, Vec<_>> = HashMap::new();
+        let mut current = None;
+
+        for line in diff.lines() {
+            if line.starts_with("+++ ") {
+                // `+++ /dev/null` for deleted files, `+++ b/src/lib.rs` otherwise
+                current = parse_file_name(&line["+++ ".len()..])
+                    .filter(|name| name.starts_with("b/"))
+                    .map(|name| git_root.join(&name["b/".len()..]));
+            } else if line.starts_with("@@ ") {
+                if let (Some(file), Some(range)) = (&current, parse_hunk_header(line)) {
+                    files.entry(file.clone()).or_default().push(range);
+                }
+            }
+        }
+
+        Self {
+            files,
+            workspace_root: git_root.to_path_buf(),
+        }
+    }
+
+    /// Returns `true` if any line of `span` was changed.
+    pub fn contains(&self, span: &DiagnosticSpan) -> bool {
+        self.files
+            .get(&self.workspace_root.join(&span.file_name))
+            .map_or(false, |ranges| {
+                ranges
+                    .iter()
+                    .any(|&(start, end)| start <= span.line_end && span.line_start <= end)
+            })
+    }
+}
+
+/// Returns the file name of a `+++ ` line of a diff. Git quotes names with special characters like
+/// a C string, e.g. `"b/caf\303\251.rs"`, and appends a tab to names with spaces.
+fn parse_file_name(name: &str) -> Option<String> {
+    let name = name.trim_end_matches('\t');
+    if !name.starts_with('"') {
+        return Some(name.to_string());
+    }
+    if name.len() < 2 || !name.ends_with('"') {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    let mut chars = name[1..name.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let byte = match chars.next()? {
+            'a' => 0x07,
+            'b' => 0x08,
+            't' => b'\t',
+            'n' => b'\n',
+            'v' => 0x0b,
+            'f' => 0x0c,
+            'r' => b'\r',
+            digit @ '0'..='3' => {
+                let mut byte = digit.to_digit(8)?;
+                for _ in 0..2 {
+                    byte = byte * 8 + chars.next()?.to_digit(8)?;
+                }
+                u8::try_from(byte).ok()?
+            },
+            c @ '"' | c @ '\\' => c as u8,
+            _ => return None,
+        };
+        bytes.push(byte);
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Returns the range of new lines of a hunk header like `@@ -12,3 +14,5 @@ fn main() {`, or
+/// `None` if the hunk only removes lines.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let new_range = line.split_whitespace().nth(2)?;
+    if !new_range.starts_with('+') {
+        return None;
+    }
+
+    let mut parts = new_range[1..].split(',');
+    let start: usize = parts.next()?.parse().ok()?;
+    let count: usize = match parts.next() {
+        Some(count) => count.parse().ok()?,
+        None => 1,
+    };
+
+    if count == 0 {
+        None
+    } else {
+        Some((start, start + count - 1))
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|err| format!("could not run git: {}", err))?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|_| "git printed invalid UTF-8".to_string())
+    } else {
+        Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, String> {
+    fs::canonicalize(path).map_err(|err| format!("could not resolve `{}`: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 3b18e51..a2c8f2e 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -3,0 +4,2 @@ fn main() {
++    let a = 1;
++    let b = 2;
+@@ -10 +12 @@ fn foo() {
+-    bar();
++    baz();
+@@ -20,2 +22,0 @@ fn foo() {
+-    removed();
+-    removed();
+diff --git a/old.rs b/old.rs
+deleted file mode 100644
+--- a/old.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn old() {}
+";
+
+    fn span(file_name: &str, line_start: usize, line_end: usize) -> DiagnosticSpan {
+        DiagnosticSpan {
+            file_name: file_name.to_string(),
+            line_start,
+            line_end,
+            column_start: 1,
+            column_end: 1,
+            is_primary: true,
+            text: Vec::new(),
+            suggested_replacement: None,
+            suggestion_applicability: None,
+        }
+    }
+
+    #[test]
+    fn changed_lines() {
+        let changed = ChangedLines::parse(Path::new("/repo"), DIFF);
+        assert_eq!(
+            Some(&vec![(4, 5), (12, 12)]),
+            changed.files.get(Path::new("/repo/src/lib.rs"))
+        );
+        assert_eq!(1, changed.files.len());
+
+        assert!(changed.contains(&span("src/lib.rs", 5, 5)));
+        assert!(changed.contains(&span("src/lib.rs", 1, 4)));
+        assert!(changed.contains(&span("/repo/src/lib.rs", 12, 12)));
+        assert!(!changed.contains(&span("src/lib.rs", 6, 11)));
+        assert!(!changed.contains(&span("src/main.rs", 4, 4)));
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(Some("b/src/lib.rs".to_string()), parse_file_name("b/src/lib.rs"));
+        assert_eq!(Some("b/my file.rs".to_string()), parse_file_name("b/my file.rs\t"));
+        assert_eq!(
+            Some("b/caf\u{e9} \"\\.rs".to_string()),
+            parse_file_name(r#""b/caf\303\251 \"\\.rs""#)
+        );
+        assert_eq!(None, parse_file_name(r#""b/\377.rs""#));
+
+        let diff = "+++ \"b/src/caf\\303\\251.rs\"\n@@ -1 +1 @@\n+++ b/src/my file.rs\t\n@@ -1 +1 @@\n";
+        let changed = ChangedLines::parse(Path::new("/repo"), diff);
+        assert!(changed.contains(&span("src/caf\u{e9}.rs", 1, 1)));
+        assert!(changed.contains(&span("src/my file.rs", 1, 1)));
+    }
+}
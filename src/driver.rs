@@ -10,11 +10,24 @@ extern crate rustc_interface;
 
 use rustc_interface::interface;
 use rustc_tools_util::*;
+use serde_json::json;
 
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::process::{exit, Command};
-
+use std::process::{exit, Command, Stdio};
+use std::time::Duration;
+
+mod baseline;
+// Shared with `cargo-clippy`, which uses more of them.
+#[allow(dead_code)]
+mod diagnostics;
+#[allow(dead_code)]
+mod diff;
 mod lintlist;
+#[allow(dead_code)]
+mod output;
+#[allow(dead_code)]
+mod sarif;
 
 /// If a command-line option matches `find_arg`, then apply the predicate `pred` on its value. If
 /// true, then return it. The parameter is assumed to be either `--arg=value` or `--arg value`.
@@ -56,9 +69,260 @@ fn test_arg_value() {
     assert_eq!(arg_value(&args, "--foo", |_| true), None);
 }
 
-#[allow(clippy::too_many_lines)]
+/// How `--timings` reports the time spent in each lint pass.
+#[derive(Clone, Copy, PartialEq)]
+enum TimingsFormat {
+    Table,
+    Json,
+}
+
+/// Removes `--timings` and `--timings=json` from `args`. Without them, the `CLIPPY_PROFILE`
+/// environment variable is used, which can be set to `1` or `json`.
+fn timings_format(args: &mut Vec<String>) -> Option<TimingsFormat> {
+    let mut format = match std::env::var("CLIPPY_PROFILE").as_ref().map(String::as_str) {
+        Ok("json") => Some(TimingsFormat::Json),
+        Ok("") | Ok("0") | Err(_) => None,
+        Ok(_) => Some(TimingsFormat::Table),
+    };
+
+    args.retain(|arg| match arg.as_str() {
+        "--timings" => {
+            format = Some(TimingsFormat::Table);
+            false
+        },
+        "--timings=json" => {
+            format = Some(TimingsFormat::Json);
+            false
+        },
+        _ => true,
+    });
+
+    format
+}
+
+#[test]
+fn test_timings_format() {
+    let mut args: Vec<_> = ["rustc", "--timings=json", "src/lib.rs"]
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect();
+
+    assert!(timings_format(&mut args) == Some(TimingsFormat::Json));
+    assert_eq!(args, ["rustc", "src/lib.rs"]);
+}
+
+/// Removes `--baseline <FILE>` and `--write-baseline <FILE>` from `args` and returns their values.
+fn baseline_args(args: &mut Vec<String>) -> (Option<PathBuf>, Option<PathBuf>) {
+    let mut baseline = None;
+    let mut write_baseline = None;
+
+    let mut iter = std::mem::replace(args, Vec::new()).into_iter();
+    while let Some(arg) = iter.next() {
+        let (name, value) = match arg.find('=') {
+            Some(i) => (arg[..i].to_string(), Some(arg[i + 1..].to_string())),
+            None => (arg.clone(), None),
+        };
+        let target = match name.as_str() {
+            "--baseline" => &mut baseline,
+            "--write-baseline" => &mut write_baseline,
+            _ => {
+                args.push(arg);
+                continue;
+            },
+        };
+        *target = value.or_else(|| iter.next()).map(PathBuf::from);
+    }
+
+    (baseline, write_baseline)
+}
+
+#[test]
+fn test_baseline_args() {
+    let mut args: Vec<_> = [
+        "clippy-driver",
+        "--baseline",
+        "old.baseline",
+        "src/lib.rs",
+        "--write-baseline=new.baseline",
+    ]
+    .iter()
+    .map(std::string::ToString::to_string)
+    .collect();
+
+    let (baseline, write_baseline) = baseline_args(&mut args);
+    assert_eq!(baseline, Some(PathBuf::from("old.baseline")));
+    assert_eq!(write_baseline, Some(PathBuf::from("new.baseline")));
+    assert_eq!(args, ["clippy-driver", "src/lib.rs"]);
+}
+
+/// Returns the lint levels from `clippy.toml` followed by the arguments in `CLIPPY_ARGS`.
+///
+/// Lint levels from `clippy.toml` are dropped for the lints set in `args` or in `CLIPPY_ARGS`, so
+/// that the latter take precedence.
+fn lint_args(args: &[String]) -> Vec<String> {
+    let extra_args: Vec<String> = std::env::var("CLIPPY_ARGS")
+        .map(|extra_args| {
+            extra_args
+                .split("__CLIPPY_HACKERY__")
+                .filter_map(|s| if s.is_empty() { None } else { Some(s.to_string()) })
+                .collect()
+        })
+        .unwrap_or_default();
+    let cli_lints = lints_set_by(args.iter().chain(&extra_args));
+
+    let mut lint_args = Vec::new();
+    for flag in clippy_lints::conf_lint_level_args(group_lints).chunks(2) {
+        if !cli_lints.contains(&flag[1]) {
+            lint_args.extend_from_slice(flag);
+        }
+    }
+    lint_args.extend(extra_args);
+    lint_args
+}
+
+/// Returns the lints of the Clippy lint group `group`, e.g. `clippy::style` or `clippy::all`.
+fn group_lints(group: &str) -> Option<Vec<String>> {
+    let lints: Vec<_> = lintlist::ALL_LINTS
+        .iter()
+        .filter_map(|lint| {
+            if lint.groups().iter().any(|lint_group| lint_group == group) {
+                Some(format!("clippy::{}", lint.name))
+            } else {
+                None
+            }
+        })
+        .collect();
+    if lints.is_empty() {
+        None
+    } else {
+        Some(lints)
+    }
+}
+
+/// Returns the lints whose level is set by the `-A`, `-W`, `-D` and `-F` flags in `args`. Lint
+/// groups are replaced with their lints.
+fn lints_set_by<'a>(mut args: impl Iterator<Item = &'a String>) -> Vec<String> {
+    const FLAGS: [&str; 8] = ["-A", "-W", "-D", "-F", "--allow", "--warn", "--deny", "--forbid"];
+
+    let mut lints = Vec::new();
+    while let Some(arg) = args.next() {
+        let lint = if FLAGS.contains(&arg.as_str()) {
+            args.next().cloned()
+        } else {
+            FLAGS.iter().find_map(|flag| {
+                if arg.starts_with(flag) {
+                    Some(arg[flag.len()..].trim_start_matches('=').to_string())
+                } else {
+                    None
+                }
+            })
+        };
+        if let Some(lint) = lint {
+            let lint = lint.replace('-', "_");
+            match group_lints(&lint) {
+                Some(group) => lints.extend(group),
+                None => lints.push(lint),
+            }
+        }
+    }
+    lints
+}
+
+#[test]
+fn test_lints_set_by() {
+    let args: Vec<_> = [
+        "src/lib.rs",
+        "-A",
+        "clippy::needless-return",
+        "-Dclippy::style",
+        "--warn=clippy::similar_names",
+        "--cap-lints",
+        "warn",
+    ]
+    .iter()
+    .map(std::string::ToString::to_string)
+    .collect();
+
+    let lints = lints_set_by(args.iter());
+    assert!(lints.contains(&"clippy::needless_return".to_string()));
+    assert!(lints.contains(&"clippy::len_zero".to_string()));
+    assert!(lints.contains(&"clippy::similar_names".to_string()));
+    assert!(!lints.contains(&"clippy::eq_op".to_string()));
+}
+
+/// Removes `--error-format` and `--message-format` from `args`, since `run_with_baseline` needs the
+/// JSON diagnostics and renders them itself.
+fn without_error_format(args: &[String]) -> Vec<String> {
+    let mut kept = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--error-format" | "--message-format" => {
+                iter.next();
+            },
+            _ if arg.starts_with("--error-format=") || arg.starts_with("--message-format=") => {},
+            _ => kept.push(arg.clone()),
+        }
+    }
+    kept
+}
 
-struct ClippyCallbacks;
+#[test]
+fn test_without_error_format() {
+    let args: Vec<_> = [
+        "src/lib.rs",
+        "--error-format",
+        "short",
+        "--cap-lints=warn",
+        "--message-format=human",
+        "--error-format=json",
+    ]
+    .iter()
+    .map(std::string::ToString::to_string)
+    .collect();
+
+    assert_eq!(without_error_format(&args), ["src/lib.rs", "--cap-lints=warn"]);
+}
+
+/// Runs `clippy-driver` again with `args` and filters the diagnostics it emits through the
+/// baseline, like `cargo clippy --baseline` does. Returns the exit code.
+fn run_with_baseline(args: &[String], baseline: Option<&Path>, write_baseline: Option<PathBuf>) -> i32 {
+    let baseline = match baseline {
+        Some(path) => match baseline::Baseline::read(path) {
+            Ok(baseline) => Some(baseline),
+            Err(err) => {
+                eprintln!("error: could not read baseline `{}`: {}", path.display(), err);
+                return 1;
+            },
+        },
+        None => None,
+    };
+    let mut output = output::Output::new(output::Format::Human, baseline, write_baseline, None);
+
+    let mut child = Command::new(std::env::current_exe().expect("could not find the path of clippy-driver"))
+        .args(without_error_format(&args[1..]))
+        .arg("--error-format=json")
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("could not run clippy-driver");
+    let stderr = BufReader::new(child.stderr.take().expect("clippy-driver's stderr is piped"));
+    for line in stderr.lines() {
+        output.rustc_line(&line.expect("could not read clippy-driver's output"));
+    }
+
+    let exit_status = child.wait().expect("failed to wait for clippy-driver?");
+    match output.finish(exit_status) {
+        Ok(()) => 0,
+        Err(code) => code,
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+#[derive(Default)]
+struct ClippyCallbacks {
+    timings_format: Option<TimingsFormat>,
+    timings: clippy_lints::Timings,
+}
 
 impl rustc_driver::Callbacks for ClippyCallbacks {
     fn after_parsing(&mut self, compiler: &interface::Compiler) -> rustc_driver::Compilation {
@@ -78,6 +342,7 @@ impl rustc_driver::Callbacks for ClippyCallbacks {
 
         let conf = clippy_lints::read_conf(&registry);
         clippy_lints::register_plugins(&mut registry, &conf);
+        self.timings = clippy_lints::Timings::new(self.timings_format.is_some());
 
         let rustc_driver::plugin::registry::Registry {
             early_lint_passes,
@@ -89,16 +354,16 @@ impl rustc_driver::Callbacks for ClippyCallbacks {
         } = registry;
         let mut ls = sess.lint_store.borrow_mut();
         for pass in early_lint_passes {
-            ls.register_early_pass(Some(sess), true, false, pass);
+            ls.register_early_pass(Some(sess), true, false, self.timings.wrap_early(pass));
         }
         for pass in late_lint_passes {
-            ls.register_late_pass(Some(sess), true, false, false, pass);
+            ls.register_late_pass(Some(sess), true, false, false, self.timings.wrap_late(pass));
         }
 
         for (name, (to, deprecated_name)) in lint_groups {
             ls.register_group(Some(sess), true, name, deprecated_name, to);
         }
-        clippy_lints::register_pre_expansion_lints(sess, &mut ls, &conf);
+        clippy_lints::register_pre_expansion_lints(sess, &mut ls, &conf, &mut self.timings);
         clippy_lints::register_renamed(&mut ls);
 
         sess.plugin_llvm_passes.borrow_mut().extend(llvm_passes);
@@ -216,6 +481,83 @@ Available lint options:
     print_lint_groups();
 }
 
+/// Prints all lints as a JSON array, for `--print-lints=json`.
+fn print_lints_json() {
+    use lintlist::*;
+
+    let lints: Vec<_> = ALL_LINTS
+        .iter()
+        .chain(DEPRECATED_LINTS.iter())
+        .map(|lint| {
+            let level = if lint.deprecation.is_some() {
+                None
+            } else {
+                LINT_LEVELS.iter().find_map(|(group, level)| {
+                    if *group == lint.group {
+                        Some(level.as_str())
+                    } else {
+                        None
+                    }
+                })
+            };
+            let conf_keys: Vec<_> = lint.conf_keys().map(|key| key.name).collect();
+
+            json!({
+                "name": format!("clippy::{}", lint.name),
+                "group": lint.group.to_lowercase(),
+                "groups": lint.groups(),
+                "level": level,
+                "desc": lint.desc,
+                "deprecation": lint.deprecation,
+                "config": conf_keys,
+                "machine_applicable": lint.is_machine_applicable(),
+            })
+        })
+        .collect();
+
+    println!("{:#}", serde_json::Value::Array(lints));
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
+
+/// Prints the time spent in each lint pass and the number of diagnostics each lint emitted.
+fn print_timings(crate_name: &str, timings: &clippy_lints::Timings, format: TimingsFormat) {
+    let passes = timings.passes();
+    let lints = timings.lints();
+
+    if format == TimingsFormat::Json {
+        let passes: Vec<_> = passes
+            .iter()
+            .map(|(name, kind, elapsed)| json!({ "name": name, "kind": kind.as_str(), "millis": millis(*elapsed) }))
+            .collect();
+        let lints: serde_json::Map<_, _> = lints.into_iter().map(|(name, count)| (name, json!(count))).collect();
+        eprintln!("{}", json!({ "crate": crate_name, "passes": passes, "lints": lints }));
+        return;
+    }
+
+    eprintln!("Clippy lint passes for crate `{}`:\n", crate_name);
+    eprintln!("    {:>10}  {:13}  pass", "time (ms)", "kind");
+    eprintln!("    {:>10}  {:13}  ----", "---------", "----");
+    for (name, kind, elapsed) in &passes {
+        eprintln!("    {:>10.3}  {:13}  {}", millis(*elapsed), kind.as_str(), name);
+    }
+    let total: Duration = passes.iter().map(|(_, _, elapsed)| *elapsed).sum();
+    eprintln!("    {:>10.3}  {:13}  total\n", millis(total), "");
+
+    if !lints.is_empty() {
+        eprintln!("Diagnostics emitted by Clippy lints:\n");
+        eprintln!("    {:>5}  lint", "count");
+        eprintln!("    {:>5}  ----", "-----");
+        for (name, count) in &lints {
+            eprintln!("    {:>5}  {}", count, name);
+        }
+        eprintln!();
+    }
+}
+
 fn display_help() {
     println!(
         "\
@@ -227,6 +569,10 @@ Usage:
 Common options:
     -h, --help               Print this message
     -V, --version            Print version info and exit
+    --explain <LINT>         Print the documentation of a lint
+    --print-lints=json       Print all lints with their groups and configuration as JSON
+    --baseline <FILE>        Only report warnings that are not recorded in the baseline file
+    --write-baseline <FILE>  Record all current warnings in a baseline file
 
 Other options are the same as `cargo check`.
 
@@ -245,6 +591,7 @@ You can use tool lints to allow or deny lints from your code, eg.:
     );
 }
 
+#[allow(clippy::too_many_lines)]
 pub fn main() {
     rustc_driver::init_rustc_env_logger();
     exit(
@@ -321,6 +668,36 @@ pub fn main() {
                 })
             };
 
+            // `rustc --explain` only knows about error codes, so Clippy's lints are explained here.
+            let lint_to_explain = arg_value(&orig_args, "--explain", |_| true).and_then(lintlist::Lint::find);
+            if let (false, Some(lint)) = (wrapper_mode, lint_to_explain) {
+                print!("{}", lint.explain());
+                exit(0);
+            }
+
+            if let (false, Some(format)) = (wrapper_mode, arg_value(&orig_args, "--print-lints", |_| true)) {
+                if format != "json" {
+                    eprintln!(
+                        "error: unknown format `{}` for `--print-lints`, expected `json`",
+                        format
+                    );
+                    exit(1);
+                }
+                print_lints_json();
+                exit(0);
+            }
+
+            if !wrapper_mode {
+                let (baseline, write_baseline) = baseline_args(&mut orig_args);
+                if baseline.is_some() || write_baseline.is_some() {
+                    exit(run_with_baseline(
+                        &orig_args,
+                        baseline.as_ref().map(PathBuf::as_path),
+                        write_baseline,
+                    ));
+                }
+            }
+
             if !wrapper_mode && should_describe_lints() {
                 describe_lints();
                 exit(0);
@@ -348,23 +725,26 @@ pub fn main() {
 
             if clippy_enabled {
                 args.extend_from_slice(&["--cfg".to_owned(), r#"feature="cargo-clippy""#.to_owned()]);
-                if let Ok(extra_args) = env::var("CLIPPY_ARGS") {
-                    args.extend(extra_args.split("__CLIPPY_HACKERY__").filter_map(|s| {
-                        if s.is_empty() {
-                            None
-                        } else {
-                            Some(s.to_string())
-                        }
-                    }));
-                }
+                let lint_args = lint_args(&args);
+                args.extend(lint_args);
             }
 
-            let mut clippy = ClippyCallbacks;
+            let mut clippy = ClippyCallbacks {
+                timings_format: timings_format(&mut args),
+                ..ClippyCallbacks::default()
+            };
             let mut default = rustc_driver::DefaultCallbacks;
             let callbacks: &mut (dyn rustc_driver::Callbacks + Send) =
                 if clippy_enabled { &mut clippy } else { &mut default };
             let args = args;
-            rustc_driver::run_compiler(&args, callbacks, None, None)
+            let result = rustc_driver::run_compiler(&args, callbacks, None, None);
+
+            if let (true, Some(format)) = (clippy_enabled, clippy.timings_format) {
+                let crate_name = arg_value(&args, "--crate-name", |_| true).unwrap_or("unknown");
+                print_timings(crate_name, &clippy.timings, format);
+            }
+
+            result
         })
         .and_then(|result| result)
         .is_err() as i32,
@@ -1,3 +1,5 @@
+use super::shared::{is_rust_block, LINT_LEVELS};
+
 /// Lint data parsed from the Clippy source code.
 #[derive(Clone, PartialEq, Debug)]
 pub struct Lint {
@@ -6,22 +8,125 @@ pub struct Lint {
     pub desc: &'static str,
     pub deprecation: Option<&'static str>,
     pub module: &'static str,
+    /// The markdown documentation of the lint.
+    pub docs: &'static str,
+}
+
+impl Lint {
+    /// Finds a lint by its name, with or without the `clippy::` prefix. Dashes are accepted in
+    /// place of underscores, like on the command line.
+    pub fn find(name: &str) -> Option<&'static Self> {
+        let name = name.trim_start_matches("clippy::").replace('-', "_");
+        super::ALL_LINTS.iter().find(|lint| lint.name == name)
+    }
+
+    /// Returns the lint groups this lint is registered in, e.g. `["clippy::all", "clippy::style"]`.
+    ///
+    /// `ALL_GROUPS` is generated by `update_lints` from the `register_lint_group` calls.
+    pub fn groups(&self) -> Vec<String> {
+        let mut groups = Vec::new();
+        if self.deprecation.is_none() {
+            if super::ALL_GROUPS.contains(&self.group) {
+                groups.push("clippy::all".to_string());
+            }
+            groups.push(format!("clippy::{}", self.group));
+        }
+        groups
+    }
+
+    /// Whether the UI tests show that this lint emits machine applicable suggestions, which
+    /// `--fix` applies. The lints are listed in `src/lintlist/machine_applicable.txt`.
+    pub fn is_machine_applicable(&self) -> bool {
+        include_str!("machine_applicable.txt")
+            .lines()
+            .any(|name| name == self.name)
+    }
+
+    /// Returns the `clippy.toml` keys that configure this lint.
+    pub fn conf_keys(&self) -> impl Iterator<Item = &'static ConfKey> + '_ {
+        super::CONF_KEYS
+            .iter()
+            .filter(move |key| key.lints.contains(&self.name))
+    }
+
+    /// Returns the documentation of the lint for `--explain`.
+    ///
+    /// Like in rustdoc, lines of Rust code blocks that start with `# ` are hidden.
+    pub fn explain(&self) -> String {
+        let level = LINT_LEVELS
+            .iter()
+            .find(|(group, _)| *group == self.group)
+            .map_or("allow", |(_, level)| level.as_str());
+        let mut explanation = format!("clippy::{} ({}, {} by default)\n\n", self.name, self.group, level);
+
+        let mut rust_block = None;
+        for line in self.docs.lines() {
+            if line.starts_with("```") {
+                rust_block = match rust_block {
+                    Some(_) => None,
+                    None => Some(is_rust_block(&line[3..])),
+                };
+            } else if rust_block == Some(true) && (line == "#" || line.starts_with("# ")) {
+                continue;
+            }
+            explanation.push_str(line);
+            explanation.push('\n');
+        }
+
+        explanation
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Ord, Eq)]
-pub enum Level {
-    Allow,
-    Warn,
-    Deny,
+/// A `clippy.toml` key from `define_Conf!` in `clippy_lints/src/utils/conf.rs`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfKey {
+    pub name: &'static str,
+    /// The names of the lints the key applies to.
+    pub lints: &'static [&'static str],
+    pub doc: &'static str,
 }
 
-pub const LINT_LEVELS: [(&str, Level); 8] = [
-    ("correctness", Level::Deny),
-    ("style", Level::Warn),
-    ("complexity", Level::Warn),
-    ("perf", Level::Warn),
-    ("restriction", Level::Allow),
-    ("pedantic", Level::Allow),
-    ("nursery", Level::Allow),
-    ("cargo", Level::Allow),
-];
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explain() {
+        let lint = Lint {
+            name: "needless_return",
+            group: "style",
+            desc: "using a return statement like `return expr;` where an expression would suffice",
+            deprecation: None,
+            module: "returns",
+            docs: "**Example:**\n```rust\n# fn main() {\nreturn;\n# }\n```\n```toml\n# comment\n```",
+        };
+        assert_eq!(
+            "clippy::needless_return (style, warn by default)\n\n\
+             **Example:**\n```rust\nreturn;\n```\n```toml\n# comment\n```\n",
+            lint.explain()
+        );
+    }
+
+    #[test]
+    fn find() {
+        assert_eq!(
+            Some("needless_return"),
+            Lint::find("clippy::needless-return").map(|lint| lint.name)
+        );
+        assert_eq!(None, Lint::find("E0308"));
+    }
+
+    #[test]
+    fn groups() {
+        let lint = Lint::find("needless_return").unwrap();
+        assert_eq!(vec!["clippy::all", "clippy::style"], lint.groups());
+        assert_eq!(Vec::<String>::new(), super::super::DEPRECATED_LINTS[0].groups());
+    }
+
+    #[test]
+    fn conf_keys() {
+        let lint = Lint::find("too_many_lines").unwrap();
+        let keys: Vec<_> = lint.conf_keys().map(|key| key.name).collect();
+        assert_eq!(vec!["too-many-lines-threshold"], keys);
+    }
+}
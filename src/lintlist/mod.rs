@@ -1,18 +1,28 @@
 //! This file is managed by `util/dev update_lints`. Do not edit.
 
+// the documentation of some lints isn't ASCII
+#![allow(clippy::non_ascii_literal)]
+
 pub mod lint;
-pub use lint::Level;
+mod shared;
+pub use lint::ConfKey;
 pub use lint::Lint;
-pub use lint::LINT_LEVELS;
+pub use shared::Level;
+pub use shared::LINT_LEVELS;
+
+// begin clippy::all groups, do not remove this comment, it’s used in `update_lints`
+pub const ALL_GROUPS: [&str; 4] = ["complexity", "correctness", "perf", "style"];
+// end clippy::all groups, do not remove this comment, it’s used in `update_lints`
 
 // begin lint list, do not remove this comment, it’s used in `update_lints`
-pub const ALL_LINTS: [Lint; 313] = [
+pub const ALL_LINTS: [Lint; 316] = [
     Lint {
         name: "absurd_extreme_comparisons",
         group: "correctness",
         desc: "a comparison with a maximum or minimum value that is always true or false",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for comparisons where one side of the relation is\neither the minimum or maximum value for its type and warns if it involves a\ncase that is always true or always false. Only integer and boolean types are\nchecked.\n\n**Why is this bad?** An expression like `min <= x` may misleadingly imply\nthat it is possible for `x` to be less than the minimum. Expressions like\n`max < x` are probably mistakes.\n\n**Known problems:** For `usize` the size of the current compile target will\nbe assumed (e.g., 64 bits on 64 bit systems). This means code that uses such\na comparison to detect target pointer width will trigger this lint. One can\nuse `mem::sizeof` and compare its value or conditional compilation\nattributes\nlike `#[cfg(target_pointer_width = \"64\")] ..` instead.\n\n**Example:**\n\n```rust\nlet vec: Vec<isize> = vec![];\nif vec.len() <= 0 {}\nif 100 > std::i32::MAX {}\n```",
     },
     Lint {
         name: "almost_swapped",
@@ -20,6 +30,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`foo = bar; bar = foo` sequence",
         deprecation: None,
         module: "swap",
+        docs: "**What it does:** Checks for `foo = bar; bar = foo` sequences.\n\n**Why is this bad?** This looks like a failed attempt to swap.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let mut a = 1;\n# let mut b = 2;\na = b;\nb = a;\n```\nCould be written as:\n```rust\n# let mut a = 1;\n# let mut b = 2;\nstd::mem::swap(&mut a, &mut b);\n```",
     },
     Lint {
         name: "approx_constant",
@@ -27,6 +38,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "the approximate of a known float constant (in `std::fXX::consts`)",
         deprecation: None,
         module: "approx_const",
+        docs: "**What it does:** Checks for floating point literals that approximate\nconstants which are defined in\n[`std::f32::consts`](https://doc.rust-lang.org/stable/std/f32/consts/#constants)\nor\n[`std::f64::consts`](https://doc.rust-lang.org/stable/std/f64/consts/#constants),\nrespectively, suggesting to use the predefined constant.\n\n**Why is this bad?** Usually, the definition in the standard library is more\nprecise than what people come up with. If you find that your definition is\nactually more precise, please [file a Rust\nissue](https://github.com/rust-lang/rust/issues).\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = 3.14;\n```",
     },
     Lint {
         name: "assertions_on_constants",
@@ -34,6 +46,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`assert!(true)` / `assert!(false)` will be optimized out by the compiler, and should probably be replaced by a `panic!()` or `unreachable!()`",
         deprecation: None,
         module: "assertions_on_constants",
+        docs: "**What it does:** Checks for `assert!(true)` and `assert!(false)` calls.\n\n**Why is this bad?** Will be optimized out by the compiler or should probably be replaced by a\npanic!() or unreachable!()\n\n**Known problems:** None\n\n**Example:**\n```rust,ignore\nassert!(false)\n// or\nassert!(true)\n// or\nconst B: bool = false;\nassert!(B)\n```",
     },
     Lint {
         name: "assign_op_pattern",
@@ -41,6 +54,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "assigning the result of an operation on a variable to that same variable",
         deprecation: None,
         module: "assign_ops",
+        docs: "**What it does:** Checks for `a = a op b` or `a = b commutative_op a`\npatterns.\n\n**Why is this bad?** These can be written as the shorter `a op= b`.\n\n**Known problems:** While forbidden by the spec, `OpAssign` traits may have\nimplementations that differ from the regular `Op` impl.\n\n**Example:**\n```rust\nlet mut a = 5;\nlet b = 0;\n// ...\na = a + b;\n```",
     },
     Lint {
         name: "bad_bit_mask",
@@ -48,6 +62,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "expressions of the form `_ & mask == select` that will only ever return `true` or `false`",
         deprecation: None,
         module: "bit_mask",
+        docs: "**What it does:** Checks for incompatible bit masks in comparisons.\n\nThe formula for detecting if an expression of the type `_ <bit_op> m\n<cmp_op> c` (where `<bit_op>` is one of {`&`, `|`} and `<cmp_op>` is one of\n{`!=`, `>=`, `>`, `!=`, `>=`, `>`}) can be determined from the following\ntable:\n\n|Comparison  |Bit Op|Example     |is always|Formula               |\n|------------|------|------------|---------|----------------------|\n|`==` or `!=`| `&`  |`x & 2 == 3`|`false`  |`c & m != c`          |\n|`<`  or `>=`| `&`  |`x & 2 < 3` |`true`   |`m < c`               |\n|`>`  or `<=`| `&`  |`x & 1 > 1` |`false`  |`m <= c`              |\n|`==` or `!=`| `|`  |`x | 1 == 0`|`false`  |`c | m != c`          |\n|`<`  or `>=`| `|`  |`x | 1 < 1` |`false`  |`m >= c`              |\n|`<=` or `>` | `|`  |`x | 1 > 0` |`true`   |`m > c`               |\n\n**Why is this bad?** If the bits that the comparison cares about are always\nset to zero or one by the bit mask, the comparison is constant `true` or\n`false` (depending on mask, compared value, and operators).\n\nSo the code is actively misleading, and the only reason someone would write\nthis intentionally is to win an underhanded Rust contest or create a\ntest-case for this lint.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let x = 1;\nif (x & 1 == 2) { }\n```",
     },
     Lint {
         name: "blacklisted_name",
@@ -55,6 +70,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of a blacklisted/placeholder name",
         deprecation: None,
         module: "blacklisted_name",
+        docs: "**What it does:** Checks for usage of blacklisted names for variables, such\nas `foo`.\n\n**Why is this bad?** These names are usually placeholder names and should be\navoided.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet foo = 3.14;\n```",
     },
     Lint {
         name: "block_in_if_condition_expr",
@@ -62,6 +78,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "braces that can be eliminated in conditions, e.g., `if { true } ...`",
         deprecation: None,
         module: "block_in_if_condition",
+        docs: "**What it does:** Checks for `if` conditions that use blocks to contain an\nexpression.\n\n**Why is this bad?** It isn't really Rust style, same as using parentheses\nto contain expressions.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nif { true } { /* ... */ }\n```",
     },
     Lint {
         name: "block_in_if_condition_stmt",
@@ -69,6 +86,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "complex blocks in conditions, e.g., `if { let x = true; x } ...`",
         deprecation: None,
         module: "block_in_if_condition",
+        docs: "**What it does:** Checks for `if` conditions that use blocks containing\nstatements, or conditions that use closures with blocks.\n\n**Why is this bad?** Using blocks in the condition makes it hard to read.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nif { let x = somefunc(); x } {}\n// or\nif somefunc(|x| { x == 47 }) {}\n```",
     },
     Lint {
         name: "bool_comparison",
@@ -76,6 +94,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "comparing a variable to a boolean, e.g., `if x == true` or `if x != true`",
         deprecation: None,
         module: "needless_bool",
+        docs: "**What it does:** Checks for expressions of the form `x == true`,\n`x != true` and order comparisons such as `x < true` (or vice versa) and\nsuggest using the variable directly.\n\n**Why is this bad?** Unnecessary code.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nif x == true {} // could be `if x { }`\n```",
     },
     Lint {
         name: "borrow_interior_mutable_const",
@@ -83,6 +102,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "referencing const with interior mutability",
         deprecation: None,
         module: "non_copy_const",
+        docs: "**What it does:** Checks if `const` items which is interior mutable (e.g.,\ncontains a `Cell`, `Mutex`, `AtomicXxxx`, etc.) has been borrowed directly.\n\n**Why is this bad?** Consts are copied everywhere they are referenced, i.e.,\nevery time you refer to the const a fresh instance of the `Cell` or `Mutex`\nor `AtomicXxxx` will be created, which defeats the whole purpose of using\nthese types in the first place.\n\nThe `const` value should be stored inside a `static` item.\n\n**Known problems:** None\n\n**Example:**\n```rust\nuse std::sync::atomic::{AtomicUsize, Ordering::SeqCst};\nconst CONST_ATOM: AtomicUsize = AtomicUsize::new(12);\n\n// Bad.\nCONST_ATOM.store(6, SeqCst); // the content of the atomic is unchanged\nassert_eq!(CONST_ATOM.load(SeqCst), 12); // because the CONST_ATOM in these lines are distinct\n\n// Good.\nstatic STATIC_ATOM: AtomicUsize = CONST_ATOM;\nSTATIC_ATOM.store(9, SeqCst);\nassert_eq!(STATIC_ATOM.load(SeqCst), 9); // use a `static` item to refer to the same instance\n```",
     },
     Lint {
         name: "borrowed_box",
@@ -90,6 +110,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a borrow of a boxed type",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for use of `&Box<T>` anywhere in the code.\n\n**Why is this bad?** Any `&Box<T>` can also be a `&T`, which is more\ngeneral.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nfn foo(bar: &Box<T>) { ... }\n```\n\nBetter:\n\n```rust,ignore\nfn foo(bar: &T) { ... }\n```",
     },
     Lint {
         name: "box_vec",
@@ -97,6 +118,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of `Box<Vec<T>>`, vector elements are already on the heap",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for use of `Box<Vec<_>>` anywhere in the code.\n\n**Why is this bad?** `Vec` already keeps its contents in a separate area on\nthe heap. So if you `Box` it, you just add another level of indirection\nwithout any benefit whatsoever.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nstruct X {\n    values: Box<Vec<Foo>>,\n}\n```\n\nBetter:\n\n```rust,ignore\nstruct X {\n    values: Vec<Foo>,\n}\n```",
     },
     Lint {
         name: "boxed_local",
@@ -104,6 +126,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Box<T>` where unnecessary",
         deprecation: None,
         module: "escape",
+        docs: "**What it does:** Checks for usage of `Box<T>` where an unboxed `T` would\nwork fine.\n\n**Why is this bad?** This is an unnecessary allocation, and bad for\nperformance. It is only necessary to allocate if you wish to move the box\ninto something.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# fn foo(bar: usize) {}\nlet x = Box::new(1);\nfoo(*x);\nprintln!(\"{}\", *x);\n```",
     },
     Lint {
         name: "builtin_type_shadow",
@@ -111,6 +134,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "shadowing a builtin type",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Warns if a generic shadows a built-in type.\n\n**Why is this bad?** This gives surprising type errors.\n\n**Known problems:** None.\n\n**Example:**\n\n```ignore\nimpl<u32> Foo<u32> {\n    fn impl_func(&self) -> u32 {\n        42\n    }\n}\n```",
     },
     Lint {
         name: "cargo_common_metadata",
@@ -118,6 +142,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "common metadata is defined in `Cargo.toml`",
         deprecation: None,
         module: "cargo_common_metadata",
+        docs: "**What it does:** Checks to see if all common metadata is defined in\n`Cargo.toml`. See: https://rust-lang-nursery.github.io/api-guidelines/documentation.html#cargotoml-includes-all-common-metadata-c-metadata\n\n**Why is this bad?** It will be more difficult for users to discover the\npurpose of the crate, and key information related to it.\n\n**Known problems:** None.\n\n**Example:**\n```toml\n# This `Cargo.toml` is missing an authors field:\n[package]\nname = \"clippy\"\nversion = \"0.0.212\"\ndescription = \"A bunch of helpful lints to avoid common pitfalls in Rust\"\nrepository = \"https://github.com/rust-lang/rust-clippy\"\nreadme = \"README.md\"\nlicense = \"MIT/Apache-2.0\"\nkeywords = [\"clippy\", \"lint\", \"plugin\"]\ncategories = [\"development-tools\", \"development-tools::cargo-plugins\"]\n```",
     },
     Lint {
         name: "cast_lossless",
@@ -125,6 +150,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "casts using `as` that are known to be lossless, e.g., `x as u64` where `x: u8`",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts between numerical types that may\nbe replaced by safe conversion functions.\n\n**Why is this bad?** Rust's `as` keyword will perform many kinds of\nconversions, including silently lossy conversions. Conversion functions such\nas `i32::from` will only perform lossless conversions. Using the conversion\nfunctions prevents conversions from turning into silent lossy conversions if\nthe types of the input expressions ever change, and make it easier for\npeople reading the code to know that the conversion is lossless.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn as_u64(x: u8) -> u64 {\n    x as u64\n}\n```\n\nUsing `::from` would look like this:\n\n```rust\nfn as_u64(x: u8) -> u64 {\n    u64::from(x)\n}\n```",
     },
     Lint {
         name: "cast_possible_truncation",
@@ -132,6 +158,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "casts that may cause truncation of the value, e.g., `x as u8` where `x: u32`, or `x as i32` where `x: f32`",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts between numerical types that may\ntruncate large values. This is expected behavior, so the cast is `Allow` by\ndefault. Values that are known to fit into the target type, like in\n`(x & 0xff) as u8`, are not linted.\n\n**Why is this bad?** In some problem domains, it is good practice to avoid\ntruncation. This lint can be activated to help assess where additional\nchecks could be beneficial.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn as_u8(x: u64) -> u8 {\n    x as u8\n}\n```",
     },
     Lint {
         name: "cast_possible_wrap",
@@ -139,6 +166,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "casts that may cause wrapping around the value, e.g., `x as i32` where `x: u32` and `x > i32::MAX`",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts from an unsigned type to a signed type of\nthe same size. Performing such a cast is a 'no-op' for the compiler,\ni.e., nothing is changed at the bit level, and the binary representation of\nthe value is reinterpreted. This can cause wrapping if the value is too big\nfor the target signed type. However, the cast works as defined, so this lint\nis `Allow` by default.\n\n**Why is this bad?** While such a cast is not bad in itself, the results can\nbe surprising when this is not the intended behavior, as demonstrated by the\nexample below.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nstd::u32::MAX as i32; // will yield a value of `-1`\n```",
     },
     Lint {
         name: "cast_precision_loss",
@@ -146,6 +174,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "casts that cause loss of precision, e.g., `x as f32` where `x: u64`",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts from any numerical to a float type where\nthe receiving type cannot store all values from the original type without\nrounding errors. This possible rounding is to be expected, so this lint is\n`Allow` by default.\n\nBasically, this warns on casting any integer with 32 or more bits to `f32`\nor any 64-bit integer to `f64`.\n\n**Why is this bad?** It's not bad at all. But in some applications it can be\nhelpful to know where precision loss can take place. This lint can help find\nthose places in the code.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = std::u64::MAX;\nx as f64;\n```",
     },
     Lint {
         name: "cast_ptr_alignment",
@@ -153,6 +182,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "cast from a pointer to a more-strictly-aligned pointer",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts from a less-strictly-aligned pointer to a\nmore-strictly-aligned pointer\n\n**Why is this bad?** Dereferencing the resulting pointer may be undefined\nbehavior.\n\n**Known problems:** Using `std::ptr::read_unaligned` and `std::ptr::write_unaligned` or similar\non the resulting pointer is fine.\n\n**Example:**\n```rust\nlet _ = (&1u8 as *const u8) as *const u16;\nlet _ = (&mut 1u8 as *mut u8) as *mut u16;\n```",
     },
     Lint {
         name: "cast_ref_to_mut",
@@ -160,6 +190,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a cast of reference to a mutable pointer",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts of `&T` to `&mut T` anywhere in the code.\n\n**Why is this bad?** It’s basically guaranteed to be undefined behaviour.\n`UnsafeCell` is the only way to obtain aliasable data that is considered\nmutable.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nfn x(r: &i32) {\n    unsafe {\n        *(r as *const _ as *mut _) += 1;\n    }\n}\n```\n\nInstead consider using interior mutability types.\n\n```rust\nuse std::cell::UnsafeCell;\n\nfn x(r: &UnsafeCell<i32>) {\n    unsafe {\n        *r.get() += 1;\n    }\n}\n```",
     },
     Lint {
         name: "cast_sign_loss",
@@ -167,6 +198,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "casts from signed types to unsigned types, e.g., `x as u32` where `x: i32`",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts from a signed to an unsigned numerical\ntype. In this case, negative values wrap around to large positive values,\nwhich can be quite surprising in practice. However, as the cast works as\ndefined, this lint is `Allow` by default.\n\n**Why is this bad?** Possibly surprising results. You can activate this lint\nas a one-time check to see where numerical wrapping can arise.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet y: i8 = -1;\ny as u128; // will return 18446744073709551615\n```",
     },
     Lint {
         name: "char_lit_as_u8",
@@ -174,6 +206,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "casting a character literal to u8 truncates",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for expressions where a character literal is cast\nto `u8` and suggests using a byte literal instead.\n\n**Why is this bad?** In general, casting values to smaller types is\nerror-prone and should be avoided where possible. In the particular case of\nconverting a character literal to u8, it is easy to avoid by just using a\nbyte literal instead. As an added bonus, `b'a'` is even slightly shorter\nthan `'a' as u8`.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\n'x' as u8\n```\n\nA better version, using the byte literal:\n\n```rust,ignore\nb'x'\n```",
     },
     Lint {
         name: "chars_last_cmp",
@@ -181,6 +214,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `.chars().last()` or `.chars().next_back()` to check if a string ends with a char",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `.chars().last()` or\n`.chars().next_back()` on a `str` to check if it ends with a given char.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`_.ends_with(_)`.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nname.chars().last() == Some('_') || name.chars().next_back() == Some('-')\n```",
     },
     Lint {
         name: "chars_next_cmp",
@@ -188,6 +222,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `.chars().next()` to check if a string starts with a char",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `.chars().next()` on a `str` to check\nif it starts with a given char.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`_.starts_with(_)`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet name = \"foo\";\nif name.chars().next() == Some('_') {};\n```\nCould be written as\n```rust\nlet name = \"foo\";\nif name.starts_with('_') {};\n```",
     },
     Lint {
         name: "checked_conversions",
@@ -195,6 +230,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`try_from` could replace manual bounds checking when casting",
         deprecation: None,
         module: "checked_conversions",
+        docs: "**What it does:** Checks for explicit bounds checking when casting.\n\n**Why is this bad?** Reduces the readability of statements & is error prone.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let foo: u32 = 5;\n# let _ =\nfoo <= i32::max_value() as u32\n# ;\n```\n\nCould be written:\n\n```rust\n# use std::convert::TryFrom;\n# let foo = 1;\n# let _ =\ni32::try_from(foo).is_ok()\n# ;\n```",
     },
     Lint {
         name: "clone_double_ref",
@@ -202,6 +238,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `clone` on `&&T`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `.clone()` on an `&&T`.\n\n**Why is this bad?** Cloning an `&&T` copies the inner `&T`, instead of\ncloning the underlying `T`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn main() {\n    let x = vec![1];\n    let y = &&x;\n    let z = y.clone();\n    println!(\"{:p} {:p}\", *y, z); // prints out the same pointer\n}\n```",
     },
     Lint {
         name: "clone_on_copy",
@@ -209,6 +246,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `clone` on a `Copy` type",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `.clone()` on a `Copy` type.\n\n**Why is this bad?** The only reason `Copy` types implement `Clone` is for\ngenerics, not for using the `clone` method on a concrete type.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n42u64.clone();\n```",
     },
     Lint {
         name: "clone_on_ref_ptr",
@@ -216,6 +254,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using \'clone\' on a ref-counted pointer",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `.clone()` on a ref-counted pointer,\n(`Rc`, `Arc`, `rc::Weak`, or `sync::Weak`), and suggests calling Clone via unified\nfunction syntax instead (e.g., `Rc::clone(foo)`).\n\n**Why is this bad?** Calling '.clone()' on an Rc, Arc, or Weak\ncan obscure the fact that only the pointer is being cloned, not the underlying\ndata.\n\n**Example:**\n```rust\n# use std::rc::Rc;\nlet x = Rc::new(1);\nx.clone();\n```",
     },
     Lint {
         name: "cmp_nan",
@@ -223,6 +262,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "comparisons to NAN, which will always return false, probably not intended",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Checks for comparisons to NaN.\n\n**Why is this bad?** NaN does not compare meaningfully to anything – not\neven itself – so those comparisons are simply wrong.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use core::f32::NAN;\n# let x = 1.0;\n\nif x == NAN { }\n```",
     },
     Lint {
         name: "cmp_null",
@@ -230,6 +270,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "comparing a pointer to a null pointer, suggesting to use `.is_null()` instead.",
         deprecation: None,
         module: "ptr",
+        docs: "**What it does:** This lint checks for equality comparisons with `ptr::null`\n\n**Why is this bad?** It's easier and more readable to use the inherent\n`.is_null()`\nmethod instead\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nif x == ptr::null {\n    ..\n}\n```",
     },
     Lint {
         name: "cmp_owned",
@@ -237,6 +278,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "creating owned instances for comparing with others, e.g., `x == \"foo\".to_string()`",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Checks for conversions to owned values just for the sake\nof a comparison.\n\n**Why is this bad?** The comparison can operate on a reference, so creating\nan owned value effectively throws it away directly afterwards, which is\nneedlessly consuming code and heap space.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let x = \"foo\";\n# let y = String::from(\"foo\");\nif x.to_owned() == y {}\n```\nCould be written as\n```rust\n# let x = \"foo\";\n# let y = String::from(\"foo\");\nif x == y {}\n```",
     },
     Lint {
         name: "cognitive_complexity",
@@ -244,6 +286,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "functions that should be split up into multiple functions",
         deprecation: None,
         module: "cognitive_complexity",
+        docs: "**What it does:** Checks for methods with high cognitive complexity.\n\n**Why is this bad?** Methods of high cognitive complexity tend to be hard to\nboth read and maintain. Also LLVM will tend to optimize small methods better.\n\n**Known problems:** Sometimes it's hard to find a way to reduce the\ncomplexity.\n\n**Example:** No. You'll see it when you get the warning.",
     },
     Lint {
         name: "collapsible_if",
@@ -251,6 +294,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`if`s that can be collapsed (e.g., `if x { if y { ... } }` and `else { if x { ... } }`)",
         deprecation: None,
         module: "collapsible_if",
+        docs: "**What it does:** Checks for nested `if` statements which can be collapsed\nby `&&`-combining their conditions and for `else { if ... }` expressions\nthat\ncan be collapsed to `else if ...`.\n\n**Why is this bad?** Each `if`-statement adds one level of nesting, which\nmakes code look more complex than it really is.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nif x {\n    if y {\n        …\n    }\n}\n\n// or\n\nif x {\n    …\n} else {\n    if y {\n        …\n    }\n}\n```\n\nShould be written:\n\n```rust.ignore\nif x && y {\n    …\n}\n\n// or\n\nif x {\n    …\n} else if y {\n    …\n}\n```",
     },
     Lint {
         name: "copy_iterator",
@@ -258,6 +302,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "implementing `Iterator` on a `Copy` type",
         deprecation: None,
         module: "copy_iterator",
+        docs: "**What it does:** Checks for types that implement `Copy` as well as\n`Iterator`.\n\n**Why is this bad?** Implicit copies can be confusing when working with\niterator combinators.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\n#[derive(Copy, Clone)]\nstruct Countdown(u8);\n\nimpl Iterator for Countdown {\n    // ...\n}\n\nlet a: Vec<_> = my_iterator.take(1).collect();\nlet b: Vec<_> = my_iterator.collect();\n```",
     },
     Lint {
         name: "crosspointer_transmute",
@@ -265,6 +310,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes that have to or from types that are a pointer to the other",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes between a type `T` and `*T`.\n\n**Why is this bad?** It's easy to mistakenly transmute between a type and a\npointer to that type.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\ncore::intrinsics::transmute(t) // where the result type is the same as\n                               // `*t` or `&t`'s\n```",
     },
     Lint {
         name: "dbg_macro",
@@ -272,6 +318,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`dbg!` macro is intended as a debugging tool",
         deprecation: None,
         module: "dbg_macro",
+        docs: "**What it does:** Checks for usage of dbg!() macro.\n\n**Why is this bad?** `dbg!` macro is intended as a debugging tool. It\nshould not be in version control.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\n// Bad\ndbg!(true)\n\n// Good\ntrue\n```",
     },
     Lint {
         name: "decimal_literal_representation",
@@ -279,6 +326,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using decimal representation when hexadecimal would be better",
         deprecation: None,
         module: "literal_representation",
+        docs: "**What it does:** Warns if there is a better representation for a numeric literal.\n\n**Why is this bad?** Especially for big powers of 2 a hexadecimal representation is more\nreadable than a decimal representation.\n\n**Known problems:** None.\n\n**Example:**\n\n`255` => `0xFF`\n`65_535` => `0xFFFF`\n`4_042_322_160` => `0xF0F0_F0F0`",
     },
     Lint {
         name: "declare_interior_mutable_const",
@@ -286,6 +334,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "declaring const with interior mutability",
         deprecation: None,
         module: "non_copy_const",
+        docs: "**What it does:** Checks for declaration of `const` items which is interior\nmutable (e.g., contains a `Cell`, `Mutex`, `AtomicXxxx`, etc.).\n\n**Why is this bad?** Consts are copied everywhere they are referenced, i.e.,\nevery time you refer to the const a fresh instance of the `Cell` or `Mutex`\nor `AtomicXxxx` will be created, which defeats the whole purpose of using\nthese types in the first place.\n\nThe `const` should better be replaced by a `static` item if a global\nvariable is wanted, or replaced by a `const fn` if a constructor is wanted.\n\n**Known problems:** A \"non-constant\" const item is a legacy way to supply an\ninitialized value to downstream `static` items (e.g., the\n`std::sync::ONCE_INIT` constant). In this case the use of `const` is legit,\nand this lint should be suppressed.\n\n**Example:**\n```rust\nuse std::sync::atomic::{AtomicUsize, Ordering::SeqCst};\n\n// Bad.\nconst CONST_ATOM: AtomicUsize = AtomicUsize::new(12);\nCONST_ATOM.store(6, SeqCst); // the content of the atomic is unchanged\nassert_eq!(CONST_ATOM.load(SeqCst), 12); // because the CONST_ATOM in these lines are distinct\n\n// Good.\nstatic STATIC_ATOM: AtomicUsize = AtomicUsize::new(15);\nSTATIC_ATOM.store(9, SeqCst);\nassert_eq!(STATIC_ATOM.load(SeqCst), 9); // use a `static` item to refer to the same instance\n```",
     },
     Lint {
         name: "default_trait_access",
@@ -293,6 +342,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "checks for literal calls to Default::default()",
         deprecation: None,
         module: "default_trait_access",
+        docs: "**What it does:** Checks for literal calls to `Default::default()`.\n\n**Why is this bad?** It's more clear to the reader to use the name of the type whose default is\nbeing gotten than the generic `Default`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n// Bad\nlet s: String = Default::default();\n\n// Good\nlet s = String::default();\n```",
     },
     Lint {
         name: "deprecated_cfg_attr",
@@ -300,6 +350,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of `cfg_attr(rustfmt)` instead of `tool_attributes`",
         deprecation: None,
         module: "attrs",
+        docs: "**What it does:** Checks for `#[cfg_attr(rustfmt, rustfmt_skip)]` and suggests to replace it\nwith `#[rustfmt::skip]`.\n\n**Why is this bad?** Since tool_attributes ([rust-lang/rust#44690](https://github.com/rust-lang/rust/issues/44690))\nare stable now, they should be used instead of the old `cfg_attr(rustfmt)` attributes.\n\n**Known problems:** This lint doesn't detect crate level inner attributes, because they get\nprocessed before the PreExpansionPass lints get executed. See\n[#3123](https://github.com/rust-lang/rust-clippy/pull/3123#issuecomment-422321765)\n\n**Example:**\n\nBad:\n```rust\n#[cfg_attr(rustfmt, rustfmt_skip)]\nfn main() { }\n```\n\nGood:\n```rust\n#[rustfmt::skip]\nfn main() { }\n```",
     },
     Lint {
         name: "deprecated_semver",
@@ -307,6 +358,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of `#[deprecated(since = \"x\")]` where x is not semver",
         deprecation: None,
         module: "attrs",
+        docs: "**What it does:** Checks for `#[deprecated]` annotations with a `since`\nfield that is not a valid semantic version.\n\n**Why is this bad?** For checking the version of the deprecation, it must be\na valid semver. Failing that, the contained information is useless.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n#[deprecated(since = \"forever\")]\nfn something_else() { /* ... */ }\n```",
     },
     Lint {
         name: "deref_addrof",
@@ -314,6 +366,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of `*&` or `*&mut` in an expression",
         deprecation: None,
         module: "reference",
+        docs: "**What it does:** Checks for usage of `*&` and `*&mut` in expressions.\n\n**Why is this bad?** Immediately dereferencing a reference is no-op and\nmakes the code less clear.\n\n**Known problems:** Multiple dereference/addrof pairs are not handled so\nthe suggested fix for `x = **&&y` is `x = *&y`, which is still incorrect.\n\n**Example:**\n```rust,ignore\nlet a = f(*&mut b);\nlet c = *&d;\n```",
     },
     Lint {
         name: "derive_hash_xor_eq",
@@ -321,6 +374,23 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "deriving `Hash` but implementing `PartialEq` explicitly",
         deprecation: None,
         module: "derive",
+        docs: "**What it does:** Checks for deriving `Hash` but implementing `PartialEq`\nexplicitly or vice versa.\n\n**Why is this bad?** The implementation of these traits must agree (for\nexample for use with `HashMap`) so it’s probably a bad idea to use a\ndefault-generated `Hash` implementation with an explicitly defined\n`PartialEq`. In particular, the following must hold for any type:\n\n```text\nk1 == k2 ⇒ hash(k1) == hash(k2)\n```\n\n**Known problems:** None.\n\n**Example:**\n```ignore\n#[derive(Hash)]\nstruct Foo;\n\nimpl PartialEq for Foo {\n    ...\n}\n```",
+    },
+    Lint {
+        name: "disallowed_methods",
+        group: "restriction",
+        desc: "use of a function or method that is disallowed in `clippy.toml`",
+        deprecation: None,
+        module: "disallowed_methods",
+        docs: "**What it does:** Denies the functions and methods configured in the\n`disallowed-methods` list of `clippy.toml`.\n\n**Why is this bad?** Some functions are undesirable in certain contexts,\ne.g. `std::thread::sleep` in async code or `std::env::set_var` in\nmultithreaded programs.\n\n**Known problems:** Paths can only refer to items of the crates the linted\ncrate depends on. Paths that can't be resolved are ignored.\n\n**Example:**\n\nWith the following configuration:\n```toml\ndisallowed-methods = [\n    { path = \"std::env::set_var\" },\n    { path = \"std::thread::sleep\", reason = \"use `Timer::delay` in async code\" },\n]\n```\n\n```rust,ignore\nstd::env::set_var(\"RUST_LOG\", \"info\");\n```",
+    },
+    Lint {
+        name: "disallowed_types",
+        group: "restriction",
+        desc: "use of a type that is disallowed in `clippy.toml`",
+        deprecation: None,
+        module: "disallowed_types",
+        docs: "**What it does:** Denies the types configured in the `disallowed-types`\nlist of `clippy.toml`.\n\n**Why is this bad?** Some types are undesirable in certain contexts, e.g.\n`HashMap` in crates whose output must not depend on the iteration order.\n\n**Known problems:** Paths can only refer to items of the crates the linted\ncrate depends on. Paths that can't be resolved are ignored.\n\n**Example:**\n\nWith the following configuration:\n```toml\ndisallowed-types = [\n    { path = \"std::collections::HashMap\", reason = \"use `BTreeMap` for a deterministic output\" },\n]\n```\n\n```rust,ignore\nuse std::collections::HashMap;\n\nstruct Index {\n    words: HashMap<String, usize>,\n}\n```",
     },
     Lint {
         name: "diverging_sub_expression",
@@ -328,6 +398,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "whether an expression contains a diverging sub expression",
         deprecation: None,
         module: "eval_order_dependence",
+        docs: "**What it does:** Checks for diverging calls that are not match arms or\nstatements.\n\n**Why is this bad?** It is often confusing to read. In addition, the\nsub-expression evaluation order for Rust is not well documented.\n\n**Known problems:** Someone might want to use `some_bool || panic!()` as a\nshorthand.\n\n**Example:**\n```rust,no_run\n# fn b() -> bool { true }\n# fn c() -> bool { true }\nlet a = b() || panic!() || c();\n// `c()` is dead, `panic!()` is only called if `b()` returns `false`\nlet x = (a, b, c, panic!());\n// can simply be replaced by `panic!()`\n```",
     },
     Lint {
         name: "doc_markdown",
@@ -335,6 +406,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "presence of `_`, `::` or camel-case outside backticks in documentation",
         deprecation: None,
         module: "doc",
+        docs: "**What it does:** Checks for the presence of `_`, `::` or camel-case words\noutside ticks in documentation.\n\n**Why is this bad?** *Rustdoc* supports markdown formatting, `_`, `::` and\ncamel-case probably indicates some code which should be included between\nticks. `_` can also be used for emphasis in markdown, this lint tries to\nconsider that.\n\n**Known problems:** Lots of bad docs won’t be fixed, what the lint checks\nfor is limited, and there are still false positives.\n\n**Examples:**\n```rust\n/// Do something with the foo_bar parameter. See also\n/// that::other::module::foo.\n// ^ `foo_bar` and `that::other::module::foo` should be ticked.\nfn doit(foo_bar: usize) {}\n```",
     },
     Lint {
         name: "double_comparisons",
@@ -342,6 +414,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "unnecessary double comparisons that can be simplified",
         deprecation: None,
         module: "double_comparison",
+        docs: "**What it does:** Checks for double comparisons that could be simplified to a single expression.\n\n\n**Why is this bad?** Readability.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let x = 1;\n# let y = 2;\nif x == y || x < y {}\n```\n\nCould be written as:\n\n```rust\n# let x = 1;\n# let y = 2;\nif x <= y {}\n```",
     },
     Lint {
         name: "double_neg",
@@ -349,6 +422,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`--x`, which is a double negation of `x` and not a pre-decrement as in C/C++",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Detects expressions of the form `--x`.\n\n**Why is this bad?** It can mislead C/C++ programmers to think `x` was\ndecremented.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet mut x = 3;\n--x;\n```",
     },
     Lint {
         name: "double_parens",
@@ -356,6 +430,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Warn on unnecessary double parentheses",
         deprecation: None,
         module: "double_parens",
+        docs: "**What it does:** Checks for unnecessary double parentheses.\n\n**Why is this bad?** This makes code harder to read and might indicate a\nmistake.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# fn foo(bar: usize) {}\n((0));\nfoo((0));\n((1, 2));\n```",
     },
     Lint {
         name: "drop_bounds",
@@ -363,6 +438,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Bounds of the form `T: Drop` are useless",
         deprecation: None,
         module: "drop_bounds",
+        docs: "**What it does:** Checks for generics with `std::ops::Drop` as bounds.\n\n**Why is this bad?** `Drop` bounds do not really accomplish anything.\nA type may have compiler-generated drop glue without implementing the\n`Drop` trait itself. The `Drop` trait also only has one method,\n`Drop::drop`, and that function is by fiat not callable in user code.\nSo there is really no use case for using `Drop` in trait bounds.\n\nThe most likely use case of a drop bound is to distinguish between types\nthat have destructors and types that don't. Combined with specialization,\na naive coder would write an implementation that assumed a type could be\ntrivially dropped, then write a specialization for `T: Drop` that actually\ncalls the destructor. Except that doing so is not correct; String, for\nexample, doesn't actually implement Drop, but because String contains a\nVec, assuming it can be trivially dropped will leak memory.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn foo<T: Drop>() {}\n```",
     },
     Lint {
         name: "drop_copy",
@@ -370,6 +446,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "calls to `std::mem::drop` with a value that implements Copy",
         deprecation: None,
         module: "drop_forget_ref",
+        docs: "**What it does:** Checks for calls to `std::mem::drop` with a value\nthat derives the Copy trait\n\n**Why is this bad?** Calling `std::mem::drop` [does nothing for types that\nimplement Copy](https://doc.rust-lang.org/std/mem/fn.drop.html), since the\nvalue will be copied and moved into the function on invocation.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x: i32 = 42; // i32 implements Copy\nstd::mem::drop(x) // A copy of x is passed to the function, leaving the\n                  // original unaffected\n```",
     },
     Lint {
         name: "drop_ref",
@@ -377,6 +454,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "calls to `std::mem::drop` with a reference instead of an owned value",
         deprecation: None,
         module: "drop_forget_ref",
+        docs: "**What it does:** Checks for calls to `std::mem::drop` with a reference\ninstead of an owned value.\n\n**Why is this bad?** Calling `drop` on a reference will only drop the\nreference itself, which is a no-op. It will not call the `drop` method (from\nthe `Drop` trait implementation) on the underlying referenced value, which\nis likely what was intended.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nlet mut lock_guard = mutex.lock();\nstd::mem::drop(&lock_guard) // Should have been drop(lock_guard), mutex\n// still locked\noperation_that_requires_mutex_to_be_unlocked();\n```",
     },
     Lint {
         name: "duplicate_underscore_argument",
@@ -384,6 +462,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "function arguments having names which only differ by an underscore",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Checks for function arguments having the similar names\ndiffering by an underscore.\n\n**Why is this bad?** It affects code readability.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn foo(a: i32, _a: i32) {}\n```",
     },
     Lint {
         name: "duration_subsec",
@@ -391,6 +470,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "checks for calculation of subsecond microseconds or milliseconds",
         deprecation: None,
         module: "duration_subsec",
+        docs: "**What it does:** Checks for calculation of subsecond microseconds or milliseconds\nfrom other `Duration` methods.\n\n**Why is this bad?** It's more concise to call `Duration::subsec_micros()` or\n`Duration::subsec_millis()` than to calculate them.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use std::time::Duration;\nlet dur = Duration::new(5, 0);\nlet _micros = dur.subsec_nanos() / 1_000;\nlet _millis = dur.subsec_nanos() / 1_000_000;\n```",
     },
     Lint {
         name: "else_if_without_else",
@@ -398,6 +478,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "if expression with an `else if`, but without a final `else` branch",
         deprecation: None,
         module: "else_if_without_else",
+        docs: "**What it does:** Checks for usage of if expressions with an `else if` branch,\nbut without a final `else` branch.\n\n**Why is this bad?** Some coding guidelines require this (e.g., MISRA-C:2004 Rule 14.10).\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# fn a() {}\n# fn b() {}\n# let x: i32 = 1;\nif x.is_positive() {\n    a();\n} else if x.is_negative() {\n    b();\n}\n```\n\nCould be written:\n\n```rust\n# fn a() {}\n# fn b() {}\n# let x: i32 = 1;\nif x.is_positive() {\n    a();\n} else if x.is_negative() {\n    b();\n} else {\n    // We don't care about zero.\n}\n```",
     },
     Lint {
         name: "empty_enum",
@@ -405,6 +486,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "enum with no variants",
         deprecation: None,
         module: "empty_enum",
+        docs: "**What it does:** Checks for `enum`s with no variants.\n\n**Why is this bad?** Enum's with no variants should be replaced with `!`,\nthe uninhabited type,\nor a wrapper around it.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nenum Test {}\n```",
     },
     Lint {
         name: "empty_line_after_outer_attr",
@@ -412,6 +494,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "empty line after outer attribute",
         deprecation: None,
         module: "attrs",
+        docs: "**What it does:** Checks for empty lines after outer attributes\n\n**Why is this bad?**\nMost likely the attribute was meant to be an inner attribute using a '!'.\nIf it was meant to be an outer attribute, then the following item\nshould not be separated by empty lines.\n\n**Known problems:** Can cause false positives.\n\nFrom the clippy side it's difficult to detect empty lines between an attributes and the\nfollowing item because empty lines and comments are not part of the AST. The parsing\ncurrently works for basic cases but is not perfect.\n\n**Example:**\n```rust\n// Good (as inner attribute)\n#![inline(always)]\n\nfn this_is_fine() { }\n\n// Bad\n#[inline(always)]\n\nfn not_quite_good_code() { }\n\n// Good (as outer attribute)\n#[inline(always)]\nfn this_is_fine_too() { }\n```",
     },
     Lint {
         name: "empty_loop",
@@ -419,6 +502,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "empty `loop {}`, which should block or sleep",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for empty `loop` expressions.\n\n**Why is this bad?** Those busy loops burn CPU cycles without doing\nanything. Think of the environment and either block on something or at least\nmake the thread sleep for some microseconds.\n\n**Known problems:** None.\n\n**Example:**\n```no_run\nloop {}\n```",
     },
     Lint {
         name: "enum_clike_unportable_variant",
@@ -426,6 +510,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "C-like enums that are `repr(isize/usize)` and have values that don\'t fit into an `i32`",
         deprecation: None,
         module: "enum_clike",
+        docs: "**What it does:** Checks for C-like enumerations that are\n`repr(isize/usize)` and have values that don't fit into an `i32`.\n\n**Why is this bad?** This will truncate the variant value on 32 bit\narchitectures, but works fine on 64 bit.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n#[repr(usize)]\nenum NonPortable {\n    X = 0x1_0000_0000,\n    Y = 0,\n}\n```",
     },
     Lint {
         name: "enum_glob_use",
@@ -433,6 +518,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use items that import all variants of an enum",
         deprecation: None,
         module: "enum_glob_use",
+        docs: "**What it does:** Checks for `use Enum::*`.\n\n**Why is this bad?** It is usually better style to use the prefixed name of\nan enumeration variant, rather than importing variants.\n\n**Known problems:** Old-style enumerations that prefix the variants are\nstill around.\n\n**Example:**\n```rust\nuse std::cmp::Ordering::*;\n```",
     },
     Lint {
         name: "enum_variant_names",
@@ -440,6 +526,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "enums where all variants share a prefix/postfix",
         deprecation: None,
         module: "enum_variants",
+        docs: "**What it does:** Detects enumeration variants that are prefixed or suffixed\nby the same characters.\n\n**Why is this bad?** Enumeration variant names should specify their variant,\nnot repeat the enumeration name.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nenum Cake {\n    BlackForestCake,\n    HummingbirdCake,\n    BattenbergCake,\n}\n```",
     },
     Lint {
         name: "eq_op",
@@ -447,6 +534,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "equal operands on both sides of a comparison or bitwise combination (e.g., `x == x`)",
         deprecation: None,
         module: "eq_op",
+        docs: "**What it does:** Checks for equal operands to comparison, logical and\nbitwise, difference and division binary operators (`==`, `>`, etc., `&&`,\n`||`, `&`, `|`, `^`, `-` and `/`).\n\n**Why is this bad?** This is usually just a typo or a copy and paste error.\n\n**Known problems:** False negatives: We had some false positives regarding\ncalls (notably [racer](https://github.com/phildawes/racer) had one instance\nof `x.pop() && x.pop()`), so we removed matching any function or method\ncalls. We may introduce a whitelist of known pure functions in the future.\n\n**Example:**\n```rust\n# let x = 1;\nif x + 1 == x + 1 {}\n```",
     },
     Lint {
         name: "erasing_op",
@@ -454,6 +542,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using erasing operations, e.g., `x * 0` or `y & 0`",
         deprecation: None,
         module: "erasing_op",
+        docs: "**What it does:** Checks for erasing operations, e.g., `x * 0`.\n\n**Why is this bad?** The whole expression can be replaced by zero.\nThis is most likely not the intended outcome and should probably be\ncorrected\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = 1;\n0 / x;\n0 * x;\nx & 0;\n```",
     },
     Lint {
         name: "eval_order_dependence",
@@ -461,6 +550,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "whether a variable read occurs before a write depends on sub-expression evaluation order",
         deprecation: None,
         module: "eval_order_dependence",
+        docs: "**What it does:** Checks for a read and a write to the same variable where\nwhether the read occurs before or after the write depends on the evaluation\norder of sub-expressions.\n\n**Why is this bad?** It is often confusing to read. In addition, the\nsub-expression evaluation order for Rust is not well documented.\n\n**Known problems:** Code which intentionally depends on the evaluation\norder, or which is correct for any evaluation order.\n\n**Example:**\n```rust\nlet mut x = 0;\nlet a = {\n    x = 1;\n    1\n} + x;\n// Unclear whether a is 1 or 2.\n```",
     },
     Lint {
         name: "excessive_precision",
@@ -468,6 +558,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "excessive precision for float literal",
         deprecation: None,
         module: "excessive_precision",
+        docs: "**What it does:** Checks for float literals with a precision greater\nthan that supported by the underlying type\n\n**Why is this bad?** Rust will truncate the literal silently.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\n// Bad\nlet v: f32 = 0.123_456_789_9;\nprintln!(\"{}\", v); //  0.123_456_789\n\n// Good\nlet v: f64 = 0.123_456_789_9;\nprintln!(\"{}\", v); //  0.123_456_789_9\n```",
     },
     Lint {
         name: "expect_fun_call",
@@ -475,6 +566,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using any `expect` method with a function call",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for calls to `.expect(&format!(...))`, `.expect(foo(..))`,\netc., and suggests to use `unwrap_or_else` instead\n\n**Why is this bad?** The function will always be called.\n\n**Known problems:** If the function has side-effects, not calling it will\nchange the semantics of the program, but you shouldn't rely on that anyway.\n\n**Example:**\n```rust\n# let foo = Some(String::new());\n# let err_code = \"418\";\n# let err_msg = \"I'm a teapot\";\nfoo.expect(&format!(\"Err {}: {}\", err_code, err_msg));\n```\nor\n```rust\n# let foo = Some(String::new());\n# let err_code = \"418\";\n# let err_msg = \"I'm a teapot\";\nfoo.expect(format!(\"Err {}: {}\", err_code, err_msg).as_str());\n```\nthis can instead be written:\n```rust\n# let foo = Some(String::new());\n# let err_code = \"418\";\n# let err_msg = \"I'm a teapot\";\nfoo.unwrap_or_else(|| panic!(\"Err {}: {}\", err_code, err_msg));\n```",
     },
     Lint {
         name: "expl_impl_clone_on_copy",
@@ -482,6 +574,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "implementing `Clone` explicitly on `Copy` types",
         deprecation: None,
         module: "derive",
+        docs: "**What it does:** Checks for explicit `Clone` implementations for `Copy`\ntypes.\n\n**Why is this bad?** To avoid surprising behaviour, these traits should\nagree and the behaviour of `Copy` cannot be overridden. In almost all\nsituations a `Copy` type should have a `Clone` implementation that does\nnothing more than copy the object, which is what `#[derive(Copy, Clone)]`\ngets you.\n\n**Known problems:** Bounds of generic types are sometimes wrong: https://github.com/rust-lang/rust/issues/26925\n\n**Example:**\n```rust,ignore\n#[derive(Copy)]\nstruct Foo;\n\nimpl Clone for Foo {\n    // ..\n}\n```",
     },
     Lint {
         name: "explicit_counter_loop",
@@ -489,6 +582,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "for-looping with an explicit counter when `_.enumerate()` would do",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks `for` loops over slices with an explicit counter\nand suggests the use of `.enumerate()`.\n\n**Why is it bad?** Using `.enumerate()` makes the intent more clear,\ndeclutters the code and may be faster in some instances.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let v = vec![1];\n# fn bar(bar: usize, baz: usize) {}\nlet mut i = 0;\nfor item in &v {\n    bar(i, *item);\n    i += 1;\n}\n```\nCould be written as\n```rust\n# let v = vec![1];\n# fn bar(bar: usize, baz: usize) {}\nfor (i, item) in v.iter().enumerate() { bar(i, *item); }\n```",
     },
     Lint {
         name: "explicit_into_iter_loop",
@@ -496,6 +590,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "for-looping over `_.into_iter()` when `_` would do",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for loops on `y.into_iter()` where `y` will do, and\nsuggests the latter.\n\n**Why is this bad?** Readability.\n\n**Known problems:** None\n\n**Example:**\n```rust\n# let y = vec![1];\n// with `y` a `Vec` or slice:\nfor x in y.into_iter() {\n    // ..\n}\n```\ncan be rewritten to\n```rust\n# let y = vec![1];\nfor x in y {\n    // ..\n}\n```",
     },
     Lint {
         name: "explicit_iter_loop",
@@ -503,6 +598,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "for-looping over `_.iter()` or `_.iter_mut()` when `&_` or `&mut _` would do",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for loops on `x.iter()` where `&x` will do, and\nsuggests the latter.\n\n**Why is this bad?** Readability.\n\n**Known problems:** False negatives. We currently only warn on some known\ntypes.\n\n**Example:**\n```rust\n// with `y` a `Vec` or slice:\n# let y = vec![1];\nfor x in y.iter() {\n    // ..\n}\n```\ncan be rewritten to\n```rust\n# let y = vec![1];\nfor x in &y {\n    // ..\n}\n```",
     },
     Lint {
         name: "explicit_write",
@@ -510,6 +606,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using the `write!()` family of functions instead of the `print!()` family of functions, when using the latter would work",
         deprecation: None,
         module: "explicit_write",
+        docs: "**What it does:** Checks for usage of `write!()` / `writeln()!` which can be\nreplaced with `(e)print!()` / `(e)println!()`\n\n**Why is this bad?** Using `(e)println! is clearer and more concise\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use std::io::Write;\n# let bar = \"furchtbar\";\n// this would be clearer as `eprintln!(\"foo: {:?}\", bar);`\nwriteln!(&mut std::io::stderr(), \"foo: {:?}\", bar).unwrap();\n```",
     },
     Lint {
         name: "extra_unused_lifetimes",
@@ -517,6 +614,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "unused lifetimes in function definitions",
         deprecation: None,
         module: "lifetimes",
+        docs: "**What it does:** Checks for lifetimes in generics that are never used\nanywhere else.\n\n**Why is this bad?** The additional lifetimes make the code look more\ncomplicated, while there is nothing out of the ordinary going on. Removing\nthem leads to more readable code.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn unused_lifetime<'a>(x: u8) {\n    // ..\n}\n```",
     },
     Lint {
         name: "fallible_impl_from",
@@ -524,6 +622,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Warn on impls of `From<..>` that contain `panic!()` or `unwrap()`",
         deprecation: None,
         module: "fallible_impl_from",
+        docs: "**What it does:** Checks for impls of `From<..>` that contain `panic!()` or `unwrap()`\n\n**Why is this bad?** `TryFrom` should be used if there's a possibility of failure.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nstruct Foo(i32);\nimpl From<String> for Foo {\n    fn from(s: String) -> Self {\n        Foo(s.parse().unwrap())\n    }\n}\n```",
     },
     Lint {
         name: "filter_map",
@@ -531,6 +630,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using combinations of `filter`, `map`, `filter_map` and `flat_map` which can usually be written as a single method call",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.filter(_).map(_)`,\n`_.filter(_).flat_map(_)`, `_.filter_map(_).flat_map(_)` and similar.\n\n**Why is this bad?** Readability, this can be written more concisely as a\nsingle method call.\n\n**Known problems:** Often requires a condition + Option/Iterator creation\ninside the closure.\n\n**Example:**\n```rust\nlet vec = vec![1];\nvec.iter().filter(|x| **x == 0).map(|x| *x * 2);\n```",
     },
     Lint {
         name: "filter_map_next",
@@ -538,6 +638,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using combination of `filter_map` and `next` which can usually be written as a single method call",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.filter_map(_).next()`.\n\n**Why is this bad?** Readability, this can be written more concisely as a\nsingle method call.\n\n**Known problems:** None\n\n**Example:**\n```rust\n (0..3).filter_map(|x| if x == 2 { Some(x) } else { None }).next();\n```\nCan be written as\n\n```rust\n (0..3).find_map(|x| if x == 2 { Some(x) } else { None });\n```",
     },
     Lint {
         name: "filter_next",
@@ -545,6 +646,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `filter(p).next()`, which is more succinctly expressed as `.find(p)`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.filter(_).next()`.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`_.find(_)`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let vec = vec![1];\nvec.iter().filter(|x| **x == 0).next();\n```\nCould be written as\n```rust\n# let vec = vec![1];\nvec.iter().find(|x| **x == 0);\n```",
     },
     Lint {
         name: "find_map",
@@ -552,6 +654,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a combination of `find` and `map` can usually be written as a single method call",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.find(_).map(_)`.\n\n**Why is this bad?** Readability, this can be written more concisely as a\nsingle method call.\n\n**Known problems:** Often requires a condition + Option/Iterator creation\ninside the closure.\n\n**Example:**\n```rust\n (0..3).find(|x| *x == 2).map(|x| x * 2);\n```\nCan be written as\n```rust\n (0..3).find_map(|x| if x == 2 { Some(x * 2) } else { None });\n```",
     },
     Lint {
         name: "flat_map_identity",
@@ -559,6 +662,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "call to `flat_map` where `flatten` is sufficient",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `flat_map(|x| x)`.\n\n**Why is this bad?** Readability, this can be written more concisely by using `flatten`.\n\n**Known problems:** None\n\n**Example:**\n```rust\n# let iter = vec![vec![0]].into_iter();\niter.flat_map(|x| x);\n```\nCan be written as\n```rust\n# let iter = vec![vec![0]].into_iter();\niter.flatten();\n```",
     },
     Lint {
         name: "float_arithmetic",
@@ -566,6 +670,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "any floating-point arithmetic statement",
         deprecation: None,
         module: "arithmetic",
+        docs: "**What it does:** Checks for float arithmetic.\n\n**Why is this bad?** For some embedded systems or kernel development, it\ncan be useful to rule out floating-point numbers.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let a = 0.0;\na + 1.0;\n```",
     },
     Lint {
         name: "float_cmp",
@@ -573,6 +678,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `==` or `!=` on float values instead of comparing difference with an epsilon",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Checks for (in-)equality comparisons on floating-point\nvalues (apart from zero), except in functions called `*eq*` (which probably\nimplement equality for a type involving floats).\n\n**Why is this bad?** Floating point calculations are usually imprecise, so\nasking if two values are *exactly* equal is asking for trouble. For a good\nguide on what to do, see [the floating point\nguide](http://www.floating-point-gui.de/errors/comparison).\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = 1.2331f64;\nlet y = 1.2332f64;\nif y == 1.23f64 { }\nif y != x {} // where both are floats\n```",
     },
     Lint {
         name: "float_cmp_const",
@@ -580,6 +686,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `==` or `!=` on float constants instead of comparing difference with an epsilon",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Checks for (in-)equality comparisons on floating-point\nvalue and constant, except in functions called `*eq*` (which probably\nimplement equality for a type involving floats).\n\n**Why is this bad?** Floating point calculations are usually imprecise, so\nasking if two values are *exactly* equal is asking for trouble. For a good\nguide on what to do, see [the floating point\nguide](http://www.floating-point-gui.de/errors/comparison).\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x: f64 = 1.0;\nconst ONE: f64 = 1.00;\nx == ONE;  // where both are floats\n```",
     },
     Lint {
         name: "fn_to_numeric_cast",
@@ -587,6 +694,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "casting a function pointer to a numeric type other than usize",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts of function pointers to something other than usize\n\n**Why is this bad?**\nCasting a function pointer to anything other than usize/isize is not portable across\narchitectures, because you end up losing bits if the target type is too small or end up with a\nbunch of extra bits that waste space and add more instructions to the final binary than\nstrictly necessary for the problem\n\nCasting to isize also doesn't make sense since there are no signed addresses.\n\n**Example**\n\n```rust\n// Bad\nfn fun() -> i32 { 1 }\nlet a = fun as i64;\n\n// Good\nfn fun2() -> i32 { 1 }\nlet a = fun2 as usize;\n```",
     },
     Lint {
         name: "fn_to_numeric_cast_with_truncation",
@@ -594,6 +702,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "casting a function pointer to a numeric type not wide enough to store the address",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts of a function pointer to a numeric type not wide enough to\nstore address.\n\n**Why is this bad?**\nSuch a cast discards some bits of the function's address. If this is intended, it would be more\nclearly expressed by casting to usize first, then casting the usize to the intended type (with\na comment) to perform the truncation.\n\n**Example**\n\n```rust\n// Bad\nfn fn1() -> i16 {\n    1\n};\nlet _ = fn1 as i32;\n\n// Better: Cast to usize first, then comment with the reason for the truncation\nfn fn2() -> i16 {\n    1\n};\nlet fn_ptr = fn2 as usize;\nlet fn_ptr_truncated = fn_ptr as i32;\n```",
     },
     Lint {
         name: "for_kv_map",
@@ -601,6 +710,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "looping on a map using `iter` when `keys` or `values` would do",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for iterating a map (`HashMap` or `BTreeMap`) and\nignoring either the keys or values.\n\n**Why is this bad?** Readability. There are `keys` and `values` methods that\ncan be used to express that don't need the values or keys.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nfor (k, _) in &map {\n    ..\n}\n```\n\ncould be replaced by\n\n```ignore\nfor k in map.keys() {\n    ..\n}\n```",
     },
     Lint {
         name: "for_loop_over_option",
@@ -608,6 +718,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "for-looping over an `Option`, which is more clearly expressed as an `if let`",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for `for` loops over `Option` values.\n\n**Why is this bad?** Readability. This is more clearly expressed as an `if\nlet`.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nfor x in option {\n    ..\n}\n```\n\nThis should be\n```ignore\nif let Some(x) = option {\n    ..\n}\n```",
     },
     Lint {
         name: "for_loop_over_result",
@@ -615,6 +726,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "for-looping over a `Result`, which is more clearly expressed as an `if let`",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for `for` loops over `Result` values.\n\n**Why is this bad?** Readability. This is more clearly expressed as an `if\nlet`.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nfor x in result {\n    ..\n}\n```\n\nThis should be\n```ignore\nif let Ok(x) = result {\n    ..\n}\n```",
     },
     Lint {
         name: "forget_copy",
@@ -622,6 +734,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "calls to `std::mem::forget` with a value that implements Copy",
         deprecation: None,
         module: "drop_forget_ref",
+        docs: "**What it does:** Checks for calls to `std::mem::forget` with a value that\nderives the Copy trait\n\n**Why is this bad?** Calling `std::mem::forget` [does nothing for types that\nimplement Copy](https://doc.rust-lang.org/std/mem/fn.drop.html) since the\nvalue will be copied and moved into the function on invocation.\n\nAn alternative, but also valid, explanation is that Copy types do not\nimplement\nthe Drop trait, which means they have no destructors. Without a destructor,\nthere\nis nothing for `std::mem::forget` to ignore.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x: i32 = 42; // i32 implements Copy\nstd::mem::forget(x) // A copy of x is passed to the function, leaving the\n                    // original unaffected\n```",
     },
     Lint {
         name: "forget_ref",
@@ -629,6 +742,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "calls to `std::mem::forget` with a reference instead of an owned value",
         deprecation: None,
         module: "drop_forget_ref",
+        docs: "**What it does:** Checks for calls to `std::mem::forget` with a reference\ninstead of an owned value.\n\n**Why is this bad?** Calling `forget` on a reference will only forget the\nreference itself, which is a no-op. It will not forget the underlying\nreferenced\nvalue, which is likely what was intended.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = Box::new(1);\nstd::mem::forget(&x) // Should have been forget(x), x will still be dropped\n```",
     },
     Lint {
         name: "get_last_with_len",
@@ -636,6 +750,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Using `x.get(x.len() - 1)` when `x.last()` is correct and simpler",
         deprecation: None,
         module: "get_last_with_len",
+        docs: "**What it does:** Checks for using `x.get(x.len() - 1)` instead of\n`x.last()`.\n\n**Why is this bad?** Using `x.last()` is easier to read and has the same\nresult.\n\nNote that using `x[x.len() - 1]` is semantically different from\n`x.last()`.  Indexing into the array will panic on out-of-bounds\naccesses, while `x.get()` and `x.last()` will return `None`.\n\nThere is another lint (get_unwrap) that covers the case of using\n`x.get(index).unwrap()` instead of `x[index]`.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\n// Bad\nlet x = vec![2, 3, 5];\nlet last_element = x.get(x.len() - 1);\n\n// Good\nlet x = vec![2, 3, 5];\nlet last_element = x.last();\n```",
     },
     Lint {
         name: "get_unwrap",
@@ -643,6 +758,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `.get().unwrap()` or `.get_mut().unwrap()` when using `[]` would work instead",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for use of `.get().unwrap()` (or\n`.get_mut().unwrap`) on a standard library type which implements `Index`\n\n**Why is this bad?** Using the Index trait (`[]`) is more clear and more\nconcise.\n\n**Known problems:** Not a replacement for error handling: Using either\n`.unwrap()` or the Index trait (`[]`) carries the risk of causing a `panic`\nif the value being accessed is `None`. If the use of `.get().unwrap()` is a\ntemporary placeholder for dealing with the `Option` type, then this does\nnot mitigate the need for error handling. If there is a chance that `.get()`\nwill be `None` in your program, then it is advisable that the `None` case\nis handled in a future refactor instead of using `.unwrap()` or the Index\ntrait.\n\n**Example:**\n```rust\nlet mut some_vec = vec![0, 1, 2, 3];\nlet last = some_vec.get(3).unwrap();\n*some_vec.get_mut(0).unwrap() = 1;\n```\nThe correct use would be:\n```rust\nlet mut some_vec = vec![0, 1, 2, 3];\nlet last = some_vec[3];\nsome_vec[0] = 1;\n```",
     },
     Lint {
         name: "identity_conversion",
@@ -650,6 +766,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using always-identical `Into`/`From`/`IntoIter` conversions",
         deprecation: None,
         module: "identity_conversion",
+        docs: "**What it does:** Checks for always-identical `Into`/`From`/`IntoIter` conversions.\n\n**Why is this bad?** Redundant code.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n// format!() returns a `String`\nlet s: String = format!(\"hello\").into();\n```",
     },
     Lint {
         name: "identity_op",
@@ -657,6 +774,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using identity operations, e.g., `x + 0` or `y / 1`",
         deprecation: None,
         module: "identity_op",
+        docs: "**What it does:** Checks for identity operations, e.g., `x + 0`.\n\n**Why is this bad?** This code can be removed without changing the\nmeaning. So it just obscures what's going on. Delete it mercilessly.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let x = 1;\nx / 1 + 0 * 1 - 0 | 0;\n```",
     },
     Lint {
         name: "if_let_some_result",
@@ -664,6 +782,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of `ok()` in `if let Some(pat)` statements is unnecessary, match on `Ok(pat)` instead",
         deprecation: None,
         module: "ok_if_let",
+        docs: "**What it does:*** Checks for unnecessary `ok()` in if let.\n\n**Why is this bad?** Calling `ok()` in if let is unnecessary, instead match\non `Ok(pat)`\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nfor result in iter {\n    if let Some(bench) = try!(result).parse().ok() {\n        vec.push(bench)\n    }\n}\n```\nCould be written:\n\n```ignore\nfor result in iter {\n    if let Ok(bench) = try!(result).parse() {\n        vec.push(bench)\n    }\n}\n```",
     },
     Lint {
         name: "if_not_else",
@@ -671,6 +790,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`if` branches that could be swapped so no negation operation is necessary on the condition",
         deprecation: None,
         module: "if_not_else",
+        docs: "**What it does:** Checks for usage of `!` or `!=` in an if condition with an\nelse branch.\n\n**Why is this bad?** Negations reduce the readability of statements.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let v: Vec<usize> = vec![];\n# fn a() {}\n# fn b() {}\nif !v.is_empty() {\n    a()\n} else {\n    b()\n}\n```\n\nCould be written:\n\n```rust\n# let v: Vec<usize> = vec![];\n# fn a() {}\n# fn b() {}\nif v.is_empty() {\n    b()\n} else {\n    a()\n}\n```",
     },
     Lint {
         name: "if_same_then_else",
@@ -678,6 +798,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "if with the same *then* and *else* blocks",
         deprecation: None,
         module: "copies",
+        docs: "**What it does:** Checks for `if/else` with the same body as the *then* part\nand the *else* part.\n\n**Why is this bad?** This is probably a copy & paste error.\n\n**Known problems:** Hopefully none.\n\n**Example:**\n```ignore\nlet foo = if … {\n    42\n} else {\n    42\n};\n```",
     },
     Lint {
         name: "ifs_same_cond",
@@ -685,6 +806,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "consecutive `ifs` with the same condition",
         deprecation: None,
         module: "copies",
+        docs: "**What it does:** Checks for consecutive `if`s with the same condition.\n\n**Why is this bad?** This is probably a copy & paste error.\n\n**Known problems:** Hopefully none.\n\n**Example:**\n```ignore\nif a == b {\n    …\n} else if a == b {\n    …\n}\n```\n\nNote that this lint ignores all conditions with a function call as it could\nhave side effects:\n\n```ignore\nif foo() {\n    …\n} else if foo() { // not linted\n    …\n}\n```",
     },
     Lint {
         name: "implicit_hasher",
@@ -692,6 +814,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "missing generalization over different hashers",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for public `impl` or `fn` missing generalization\nover different hashers and implicitly defaulting to the default hashing\nalgorithm (SipHash).\n\n**Why is this bad?** `HashMap` or `HashSet` with custom hashers cannot be\nused with them.\n\n**Known problems:** Suggestions for replacing constructors can contain\nfalse-positives. Also applying suggestions can require modification of other\npieces of code, possibly including external crates.\n\n**Example:**\n```rust\n# use std::collections::HashMap;\n# use std::hash::{Hash, BuildHasher};\n# trait Serialize {};\nimpl<K: Hash + Eq, V> Serialize for HashMap<K, V> { }\n\npub fn foo(map: &mut HashMap<i32, i32>) { }\n```\ncould be rewritten as\n```rust\n# use std::collections::HashMap;\n# use std::hash::{Hash, BuildHasher};\n# trait Serialize {};\nimpl<K: Hash + Eq, V, S: BuildHasher> Serialize for HashMap<K, V, S> { }\n\npub fn foo<S: BuildHasher>(map: &mut HashMap<i32, i32, S>) { }\n```",
     },
     Lint {
         name: "implicit_return",
@@ -699,6 +822,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use a return statement like `return expr` instead of an expression",
         deprecation: None,
         module: "implicit_return",
+        docs: "**What it does:** Checks for missing return statements at the end of a block.\n\n**Why is this bad?** Actually omitting the return keyword is idiomatic Rust code. Programmers\ncoming from other languages might prefer the expressiveness of `return`. It's possible to miss\nthe last returning statement because the only difference is a missing `;`. Especially in bigger\ncode with multiple return paths having a `return` keyword makes it easier to find the\ncorresponding statements.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn foo(x: usize) -> usize {\n    x\n}\n```\nadd return\n```rust\nfn foo(x: usize) -> usize {\n    return x;\n}\n```",
     },
     Lint {
         name: "inconsistent_digit_grouping",
@@ -706,6 +830,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "integer literals with digits grouped inconsistently",
         deprecation: None,
         module: "literal_representation",
+        docs: "**What it does:** Warns if an integral or floating-point constant is\ngrouped inconsistently with underscores.\n\n**Why is this bad?** Readers may incorrectly interpret inconsistently\ngrouped digits.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\nlet x: u64 = 618_64_9189_73_511;\n```",
     },
     Lint {
         name: "indexing_slicing",
@@ -713,6 +838,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "indexing/slicing usage",
         deprecation: None,
         module: "indexing_slicing",
+        docs: "**What it does:** Checks for usage of indexing or slicing. Arrays are special cases, this lint\ndoes report on arrays if we can tell that slicing operations are in bounds and does not\nlint on constant `usize` indexing on arrays because that is handled by rustc's `const_err` lint.\n\n**Why is this bad?** Indexing and slicing can panic at runtime and there are\nsafe alternatives.\n\n**Known problems:** Hopefully none.\n\n**Example:**\n```rust,no_run\n// Vector\nlet x = vec![0; 5];\n\n// Bad\nx[2];\n&x[2..100];\n&x[2..];\n&x[..100];\n\n// Good\nx.get(2);\nx.get(2..100);\nx.get(2..);\nx.get(..100);\n```\n\n```rust,no_run\n// Array\nlet y = [0, 1, 2, 3];\n\n// Bad\n&y[10..100];\n&y[10..];\n&y[..100];\n\n// Good\n&y[2..];\n&y[..2];\n&y[0..3];\ny.get(10);\ny.get(10..100);\ny.get(10..);\ny.get(..100);\n```",
     },
     Lint {
         name: "ineffective_bit_mask",
@@ -720,6 +846,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "expressions where a bit mask will be rendered useless by a comparison, e.g., `(x | 1) > 2`",
         deprecation: None,
         module: "bit_mask",
+        docs: "**What it does:** Checks for bit masks in comparisons which can be removed\nwithout changing the outcome. The basic structure can be seen in the\nfollowing table:\n\n|Comparison| Bit Op  |Example    |equals |\n|----------|---------|-----------|-------|\n|`>` / `<=`|`|` / `^`|`x | 2 > 3`|`x > 3`|\n|`<` / `>=`|`|` / `^`|`x ^ 1 < 4`|`x < 4`|\n\n**Why is this bad?** Not equally evil as [`bad_bit_mask`](#bad_bit_mask),\nbut still a bit misleading, because the bit mask is ineffective.\n\n**Known problems:** False negatives: This lint will only match instances\nwhere we have figured out the math (which is for a power-of-two compared\nvalue). This means things like `x | 1 >= 7` (which would be better written\nas `x >= 6`) will not be reported (but bit masks like this are fairly\nuncommon).\n\n**Example:**\n```rust\n# let x = 1;\nif (x | 1 > 3) {  }\n```",
     },
     Lint {
         name: "infallible_destructuring_match",
@@ -727,6 +854,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a match statement with a single infallible arm instead of a `let`",
         deprecation: None,
         module: "infallible_destructuring_match",
+        docs: "**What it does:** Checks for matches being used to destructure a single-variant enum\nor tuple struct where a `let` will suffice.\n\n**Why is this bad?** Just readability – `let` doesn't nest, whereas a `match` does.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nenum Wrapper {\n    Data(i32),\n}\n\nlet wrapper = Wrapper::Data(42);\n\nlet data = match wrapper {\n    Wrapper::Data(i) => i,\n};\n```\n\nThe correct use would be:\n```rust\nenum Wrapper {\n    Data(i32),\n}\n\nlet wrapper = Wrapper::Data(42);\nlet Wrapper::Data(data) = wrapper;\n```",
     },
     Lint {
         name: "infinite_iter",
@@ -734,6 +862,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "infinite iteration",
         deprecation: None,
         module: "infinite_iter",
+        docs: "**What it does:** Checks for iteration that is guaranteed to be infinite.\n\n**Why is this bad?** While there may be places where this is acceptable\n(e.g., in event streams), in most cases this is simply an error.\n\n**Known problems:** None.\n\n**Example:**\n```no_run\nuse std::iter;\n\niter::repeat(1_u8).collect::<Vec<_>>();\n```",
     },
     Lint {
         name: "inherent_to_string",
@@ -741,6 +870,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "type implements inherent method `to_string()`, but should instead implement the `Display` trait",
         deprecation: None,
         module: "inherent_to_string",
+        docs: "**What id does:** Checks for the definition of inherent methods with a signature of `to_string(&self) -> String`.\n\n**Why is this bad?** This method is also implicitly defined if a type implements the `Display` trait. As the functionality of `Display` is much more versatile, it should be preferred.\n\n**Known problems:** None\n\n** Example:**\n\n```rust\n// Bad\npub struct A;\n\nimpl A {\n    pub fn to_string(&self) -> String {\n        \"I am A\".to_string()\n    }\n}\n```\n\n```rust\n// Good\nuse std::fmt;\n\npub struct A;\n\nimpl fmt::Display for A {\n    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {\n        write!(f, \"I am A\")\n    }\n}\n```",
     },
     Lint {
         name: "inherent_to_string_shadow_display",
@@ -748,6 +878,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "type implements inherent method `to_string()`, which gets shadowed by the implementation of the `Display` trait ",
         deprecation: None,
         module: "inherent_to_string",
+        docs: "**What id does:** Checks for the definition of inherent methods with a signature of `to_string(&self) -> String` and if the type implementing this method also implements the `Display` trait.\n\n**Why is this bad?** This method is also implicitly defined if a type implements the `Display` trait. The less versatile inherent method will then shadow the implementation introduced by `Display`.\n\n**Known problems:** None\n\n** Example:**\n\n```rust\n// Bad\nuse std::fmt;\n\npub struct A;\n\nimpl A {\n    pub fn to_string(&self) -> String {\n        \"I am A\".to_string()\n    }\n}\n\nimpl fmt::Display for A {\n    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {\n        write!(f, \"I am A, too\")\n    }\n}\n```\n\n```rust\n// Good\nuse std::fmt;\n\npub struct A;\n\nimpl fmt::Display for A {\n    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {\n        write!(f, \"I am A\")\n    }\n}\n```",
     },
     Lint {
         name: "inline_always",
@@ -755,6 +886,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of `#[inline(always)]`",
         deprecation: None,
         module: "attrs",
+        docs: "**What it does:** Checks for items annotated with `#[inline(always)]`,\nunless the annotated function is empty or simply panics.\n\n**Why is this bad?** While there are valid uses of this annotation (and once\nyou know when to use it, by all means `allow` this lint), it's a common\nnewbie-mistake to pepper one's code with it.\n\nAs a rule of thumb, before slapping `#[inline(always)]` on a function,\nmeasure if that additional function call really affects your runtime profile\nsufficiently to make up for the increase in compile time.\n\n**Known problems:** False positives, big time. This lint is meant to be\ndeactivated by everyone doing serious performance work. This means having\ndone the measurement.\n\n**Example:**\n```ignore\n#[inline(always)]\nfn not_quite_hot_code(..) { ... }\n```",
     },
     Lint {
         name: "inline_fn_without_body",
@@ -762,6 +894,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of `#[inline]` on trait methods without bodies",
         deprecation: None,
         module: "inline_fn_without_body",
+        docs: "**What it does:** Checks for `#[inline]` on trait methods without bodies\n\n**Why is this bad?** Only implementations of trait methods may be inlined.\nThe inline attribute is ignored for trait methods without bodies.\n\n**Known problems:** None.\n\n**Example:**\n```rust\ntrait Animal {\n    #[inline]\n    fn name(&self) -> &'static str;\n}\n```",
     },
     Lint {
         name: "int_plus_one",
@@ -769,6 +902,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "instead of using x >= y + 1, use x > y",
         deprecation: None,
         module: "int_plus_one",
+        docs: "**What it does:** Checks for usage of `x >= y + 1` or `x - 1 >= y` (and `<=`) in a block\n\n\n**Why is this bad?** Readability -- better to use `> y` instead of `>= y + 1`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let x = 1;\n# let y = 1;\nif x >= y + 1 {}\n```\n\nCould be written as:\n\n```rust\n# let x = 1;\n# let y = 1;\nif x > y {}\n```",
     },
     Lint {
         name: "integer_arithmetic",
@@ -776,6 +910,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "any integer arithmetic statement",
         deprecation: None,
         module: "arithmetic",
+        docs: "**What it does:** Checks for plain integer arithmetic.\n\n**Why is this bad?** This is only checked against overflow in debug builds.\nIn some applications one wants explicitly checked, wrapping or saturating\narithmetic.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let a = 0;\na + 1;\n```",
     },
     Lint {
         name: "integer_division",
@@ -783,6 +918,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "integer division may cause loss of precision",
         deprecation: None,
         module: "integer_division",
+        docs: "**What it does:** Checks for division of integers\n\n**Why is this bad?** When outside of some very specific algorithms,\ninteger division is very often a mistake because it discards the\nremainder.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn main() {\n    let x = 3 / 2;\n    println!(\"{}\", x);\n}\n```",
     },
     Lint {
         name: "into_iter_on_array",
@@ -790,6 +926,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `.into_iter()` on an array",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for `into_iter` calls on types which should be replaced by `iter` or\n`iter_mut`.\n\n**Why is this bad?** Arrays and `PathBuf` do not yet have an `into_iter` method which move out\ntheir content into an iterator. Auto-referencing resolves the `into_iter` call to its reference\ninstead, like `<&[T; N] as IntoIterator>::into_iter`, which just iterates over item references\nlike calling `iter` would. Furthermore, when the standard library actually\n[implements the `into_iter` method](https://github.com/rust-lang/rust/issues/25725) which moves\nthe content out of the array, the original use of `into_iter` got inferred with the wrong type\nand the code will be broken.\n\n**Known problems:** None\n\n**Example:**\n\n```rust\nlet _ = [1, 2, 3].into_iter().map(|x| *x).collect::<Vec<u32>>();\n```\nCould be written as:\n```rust\nlet _ = [1, 2, 3].iter().map(|x| *x).collect::<Vec<u32>>();\n```",
     },
     Lint {
         name: "into_iter_on_ref",
@@ -797,6 +934,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `.into_iter()` on a reference",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for `into_iter` calls on references which should be replaced by `iter`\nor `iter_mut`.\n\n**Why is this bad?** Readability. Calling `into_iter` on a reference will not move out its\ncontent into the resulting iterator, which is confusing. It is better just call `iter` or\n`iter_mut` directly.\n\n**Known problems:** None\n\n**Example:**\n\n```rust\nlet _ = (&vec![3, 4, 5]).into_iter();\n```",
     },
     Lint {
         name: "invalid_regex",
@@ -804,6 +942,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "invalid regular expressions",
         deprecation: None,
         module: "regex",
+        docs: "**What it does:** Checks [regex](https://crates.io/crates/regex) creation\n(with `Regex::new`,`RegexBuilder::new` or `RegexSet::new`) for correct\nregex syntax.\n\n**Why is this bad?** This will lead to a runtime panic.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nRegex::new(\"|\")\n```",
     },
     Lint {
         name: "invalid_upcast_comparisons",
@@ -811,6 +950,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a comparison involving an upcast which is always true or false",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for comparisons where the relation is always either\ntrue or false, but where one side has been upcast so that the comparison is\nnecessary. Only integer types are checked.\n\n**Why is this bad?** An expression like `let x : u8 = ...; (x as u32) > 300`\nwill mistakenly imply that it is possible for `x` to be outside the range of\n`u8`.\n\n**Known problems:**\nhttps://github.com/rust-lang/rust-clippy/issues/886\n\n**Example:**\n```rust\nlet x: u8 = 1;\n(x as u32) > 300;\n```",
     },
     Lint {
         name: "items_after_statements",
@@ -818,6 +958,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "blocks where an item comes after a statement",
         deprecation: None,
         module: "items_after_statements",
+        docs: "**What it does:** Checks for items declared after some statement in a block.\n\n**Why is this bad?** Items live for the entire scope they are declared\nin. But statements are processed in order. This might cause confusion as\nit's hard to figure out which item is meant in a statement.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn foo() {\n    println!(\"cake\");\n}\n\nfn main() {\n    foo(); // prints \"foo\"\n    fn foo() {\n        println!(\"foo\");\n    }\n    foo(); // prints \"foo\"\n}\n```",
     },
     Lint {
         name: "iter_cloned_collect",
@@ -825,6 +966,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `.cloned().collect()` on slice to create a `Vec`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for the use of `.cloned().collect()` on slice to\ncreate a `Vec`.\n\n**Why is this bad?** `.to_vec()` is clearer\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet s = [1, 2, 3, 4, 5];\nlet s2: Vec<isize> = s[..].iter().cloned().collect();\n```\nThe better use would be:\n```rust\nlet s = [1, 2, 3, 4, 5];\nlet s2: Vec<isize> = s.to_vec();\n```",
     },
     Lint {
         name: "iter_next_loop",
@@ -832,6 +974,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "for-looping over `_.next()` which is probably not intended",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for loops on `x.next()`.\n\n**Why is this bad?** `next()` returns either `Some(value)` if there was a\nvalue, or `None` otherwise. The insidious thing is that `Option<_>`\nimplements `IntoIterator`, so that possibly one value will be iterated,\nleading to some hard to find bugs. No one will want to write such code\n[except to win an Underhanded Rust\nContest](https://www.reddit.com/r/rust/comments/3hb0wm/underhanded_rust_contest/cu5yuhr).\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nfor x in y.next() {\n    ..\n}\n```",
     },
     Lint {
         name: "iter_nth",
@@ -839,6 +982,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `.iter().nth()` on a standard library type with O(1) element access",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for use of `.iter().nth()` (and the related\n`.iter_mut().nth()`) on standard library types with O(1) element access.\n\n**Why is this bad?** `.get()` and `.get_mut()` are more efficient and more\nreadable.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet some_vec = vec![0, 1, 2, 3];\nlet bad_vec = some_vec.iter().nth(3);\nlet bad_slice = &some_vec[..].iter().nth(3);\n```\nThe correct use would be:\n```rust\nlet some_vec = vec![0, 1, 2, 3];\nlet bad_vec = some_vec.get(3);\nlet bad_slice = &some_vec[..].get(3);\n```",
     },
     Lint {
         name: "iter_skip_next",
@@ -846,6 +990,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `.skip(x).next()` on an iterator",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for use of `.skip(x).next()` on iterators.\n\n**Why is this bad?** `.nth(x)` is cleaner\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet some_vec = vec![0, 1, 2, 3];\nlet bad_vec = some_vec.iter().skip(3).next();\nlet bad_slice = &some_vec[..].iter().skip(3).next();\n```\nThe correct use would be:\n```rust\nlet some_vec = vec![0, 1, 2, 3];\nlet bad_vec = some_vec.iter().nth(3);\nlet bad_slice = &some_vec[..].iter().nth(3);\n```",
     },
     Lint {
         name: "iterator_step_by_zero",
@@ -853,6 +998,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Iterator::step_by(0)`, which produces an infinite iterator",
         deprecation: None,
         module: "ranges",
+        docs: "**What it does:** Checks for calling `.step_by(0)` on iterators,\nwhich never terminates.\n\n**Why is this bad?** This very much looks like an oversight, since with\n`loop { .. }` there is an obvious better way to endlessly loop.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nfor x in (5..5).step_by(0) {\n    ..\n}\n```",
     },
     Lint {
         name: "just_underscores_and_digits",
@@ -860,6 +1006,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "unclear name",
         deprecation: None,
         module: "non_expressive_names",
+        docs: "**What it does:** Checks if you have variables whose name consists of just\nunderscores and digits.\n\n**Why is this bad?** It's hard to memorize what a variable means without a\ndescriptive name.\n\n**Known problems:** None?\n\n**Example:**\n```rust\nlet _1 = 1;\nlet ___1 = 1;\nlet __1___2 = 11;\n```",
     },
     Lint {
         name: "large_digit_groups",
@@ -867,6 +1014,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "grouping digits into groups that are too large",
         deprecation: None,
         module: "literal_representation",
+        docs: "**What it does:** Warns if the digits of an integral or floating-point\nconstant are grouped into groups that\nare too large.\n\n**Why is this bad?** Negatively impacts readability.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\nlet x: u64 = 6186491_8973511;\n```",
     },
     Lint {
         name: "large_enum_variant",
@@ -874,6 +1022,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "large size difference between variants on an enum",
         deprecation: None,
         module: "large_enum_variant",
+        docs: "**What it does:** Checks for large size differences between variants on\n`enum`s.\n\n**Why is this bad?** Enum size is bounded by the largest variant. Having a\nlarge variant\ncan penalize the memory layout of that enum.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nenum Test {\n    A(i32),\n    B([i32; 8000]),\n}\n```",
     },
     Lint {
         name: "len_without_is_empty",
@@ -881,6 +1030,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "traits or impls with a public `len` method but no corresponding `is_empty` method",
         deprecation: None,
         module: "len_zero",
+        docs: "**What it does:** Checks for items that implement `.len()` but not\n`.is_empty()`.\n\n**Why is this bad?** It is good custom to have both methods, because for\nsome data structures, asking about the length will be a costly operation,\nwhereas `.is_empty()` can usually answer in constant time. Also it used to\nlead to false positives on the [`len_zero`](#len_zero) lint – currently that\nlint will ignore such entities.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nimpl X {\n    pub fn len(&self) -> usize {\n        ..\n    }\n}\n```",
     },
     Lint {
         name: "len_zero",
@@ -888,6 +1038,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "checking `.len() == 0` or `.len() > 0` (or similar) when `.is_empty()` could be used instead",
         deprecation: None,
         module: "len_zero",
+        docs: "**What it does:** Checks for getting the length of something via `.len()`\njust to compare to zero, and suggests using `.is_empty()` where applicable.\n\n**Why is this bad?** Some structures can answer `.is_empty()` much faster\nthan calculating their length. Notably, for slices, getting the length\nrequires a subtraction whereas `.is_empty()` is just a comparison. So it is\ngood to get into the habit of using `.is_empty()`, and having it is cheap.\nBesides, it makes the intent clearer than a manual comparison.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nif x.len() == 0 {\n    ..\n}\nif y.len() != 0 {\n    ..\n}\n```\ninstead use\n```ignore\nif x.is_empty() {\n    ..\n}\nif !y.is_empty() {\n    ..\n}\n```",
     },
     Lint {
         name: "let_and_return",
@@ -895,6 +1046,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "creating a let-binding and then immediately returning it like `let x = expr; x` at the end of a block",
         deprecation: None,
         module: "returns",
+        docs: "**What it does:** Checks for `let`-bindings, which are subsequently\nreturned.\n\n**Why is this bad?** It is just extraneous code. Remove it to make your code\nmore rusty.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn foo() -> String {\n    let x = String::new();\n    x\n}\n```\ninstead, use\n```\nfn foo() -> String {\n    String::new()\n}\n```",
     },
     Lint {
         name: "let_unit_value",
@@ -902,6 +1054,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "creating a let binding to a value of unit type, which usually can\'t be used afterwards",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for binding a unit value.\n\n**Why is this bad?** A unit value cannot usefully be used anywhere. So\nbinding one is kind of pointless.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = {\n    1;\n};\n```",
     },
     Lint {
         name: "linkedlist",
@@ -909,6 +1062,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of LinkedList, usually a vector is faster, or a more specialized data structure like a VecDeque",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for usage of any `LinkedList`, suggesting to use a\n`Vec` or a `VecDeque` (formerly called `RingBuf`).\n\n**Why is this bad?** Gankro says:\n\n> The TL;DR of `LinkedList` is that it's built on a massive amount of\npointers and indirection.\n> It wastes memory, it has terrible cache locality, and is all-around slow.\n`RingBuf`, while\n> \"only\" amortized for push/pop, should be faster in the general case for\nalmost every possible\n> workload, and isn't even amortized at all if you can predict the capacity\nyou need.\n>\n> `LinkedList`s are only really good if you're doing a lot of merging or\nsplitting of lists.\n> This is because they can just mangle some pointers instead of actually\ncopying the data. Even\n> if you're doing a lot of insertion in the middle of the list, `RingBuf`\ncan still be better\n> because of how expensive it is to seek to the middle of a `LinkedList`.\n\n**Known problems:** False positives – the instances where using a\n`LinkedList` makes sense are few and far between, but they can still happen.\n\n**Example:**\n```rust\n# use std::collections::LinkedList;\nlet x: LinkedList<usize> = LinkedList::new();\n```",
     },
     Lint {
         name: "logic_bug",
@@ -916,6 +1070,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "boolean expressions that contain terminals which can be eliminated",
         deprecation: None,
         module: "booleans",
+        docs: "**What it does:** Checks for boolean expressions that contain terminals that\ncan be eliminated.\n\n**Why is this bad?** This is most likely a logic bug.\n\n**Known problems:** Ignores short circuiting behavior.\n\n**Example:**\n```ignore\nif a && b || a { ... }\n```\nThe `b` is unnecessary, the expression is equivalent to `if a`.",
     },
     Lint {
         name: "main_recursion",
@@ -923,6 +1078,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "recursion using the entrypoint",
         deprecation: None,
         module: "main_recursion",
+        docs: "**What it does:** Checks for recursion using the entrypoint.\n\n**Why is this bad?** Apart from special setups (which we could detect following attributes like #![no_std]),\nrecursing into main() seems like an unintuitive antipattern we should be able to detect.\n\n**Known problems:** None.\n\n**Example:**\n```no_run\nfn main() {\n    main();\n}\n```",
     },
     Lint {
         name: "manual_memcpy",
@@ -930,6 +1086,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "manually copying items between slices",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for for-loops that manually copy items between\nslices that could be optimized by having a memcpy.\n\n**Why is this bad?** It is not as fast as a memcpy.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let src = vec![1];\n# let mut dst = vec![0; 65];\nfor i in 0..src.len() {\n    dst[i + 64] = src[i];\n}\n```\nCould be written as:\n```rust\n# let src = vec![1];\n# let mut dst = vec![0; 65];\ndst[64..(src.len() + 64)].clone_from_slice(&src[..]);\n```",
     },
     Lint {
         name: "manual_saturating_arithmetic",
@@ -937,6 +1094,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`.chcked_add/sub(x).unwrap_or(MAX/MIN)`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for `.checked_add/sub(x).unwrap_or(MAX/MIN)`.\n\n**Why is this bad?** These can be written simply with `saturating_add/sub` methods.\n\n**Example:**\n\n```rust\n# let y: u32 = 0;\n# let x: u32 = 100;\nlet add = x.checked_add(y).unwrap_or(u32::max_value());\nlet sub = x.checked_sub(y).unwrap_or(u32::min_value());\n```\n\ncan be written using dedicated methods for saturating addition/subtraction as:\n\n```rust\n# let y: u32 = 0;\n# let x: u32 = 100;\nlet add = x.saturating_add(y);\nlet sub = x.saturating_sub(y);\n```",
     },
     Lint {
         name: "manual_swap",
@@ -944,6 +1102,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "manual swap of two variables",
         deprecation: None,
         module: "swap",
+        docs: "**What it does:** Checks for manual swapping.\n\n**Why is this bad?** The `std::mem::swap` function exposes the intent better\nwithout deinitializing or copying either variable.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet mut a = 42;\nlet mut b = 1337;\n\nlet t = b;\nb = a;\na = t;\n```\nUse std::mem::swap():\n```rust\nlet mut a = 1;\nlet mut b = 2;\nstd::mem::swap(&mut a, &mut b);\n```",
     },
     Lint {
         name: "many_single_char_names",
@@ -951,6 +1110,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "too many single character bindings",
         deprecation: None,
         module: "non_expressive_names",
+        docs: "**What it does:** Checks for too many variables whose name consists of a\nsingle character.\n\n**Why is this bad?** It's hard to memorize what a variable means without a\ndescriptive name.\n\n**Known problems:** None?\n\n**Example:**\n```ignore\nlet (a, b, c, d, e, f, g) = (...);\n```",
     },
     Lint {
         name: "map_clone",
@@ -958,6 +1118,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `iterator.map(|x| x.clone())`, or dereferencing closures for `Copy` types",
         deprecation: None,
         module: "map_clone",
+        docs: "**What it does:** Checks for usage of `iterator.map(|x| x.clone())` and suggests\n`iterator.cloned()` instead\n\n**Why is this bad?** Readability, this can be written more concisely\n\n**Known problems:** None\n\n**Example:**\n\n```rust\nlet x = vec![42, 43];\nlet y = x.iter();\nlet z = y.map(|i| *i);\n```\n\nThe correct use would be:\n\n```rust\nlet x = vec![42, 43];\nlet y = x.iter();\nlet z = y.cloned();\n```",
     },
     Lint {
         name: "map_entry",
@@ -965,6 +1126,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of `contains_key` followed by `insert` on a `HashMap` or `BTreeMap`",
         deprecation: None,
         module: "entry",
+        docs: "**What it does:** Checks for uses of `contains_key` + `insert` on `HashMap`\nor `BTreeMap`.\n\n**Why is this bad?** Using `entry` is more efficient.\n\n**Known problems:** Some false negatives, eg.:\n```rust\n# use std::collections::HashMap;\n# let mut map = HashMap::new();\n# let v = 1;\n# let k = 1;\nif !map.contains_key(&k) {\n    map.insert(k.clone(), v);\n}\n```\n\n**Example:**\n```rust\n# use std::collections::HashMap;\n# let mut map = HashMap::new();\n# let k = 1;\n# let v = 1;\nif !map.contains_key(&k) {\n    map.insert(k, v);\n}\n```\ncan both be rewritten as:\n```rust\n# use std::collections::HashMap;\n# let mut map = HashMap::new();\n# let k = 1;\n# let v = 1;\nmap.entry(k).or_insert(v);\n```",
     },
     Lint {
         name: "map_flatten",
@@ -972,6 +1134,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using combinations of `flatten` and `map` which can usually be written as a single method call",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.map(_).flatten(_)`,\n\n**Why is this bad?** Readability, this can be written more concisely as a\nsingle method call.\n\n**Known problems:**\n\n**Example:**\n```rust\nlet vec = vec![vec![1]];\nvec.iter().map(|x| x.iter()).flatten();\n```",
     },
     Lint {
         name: "match_as_ref",
@@ -979,6 +1142,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a match on an Option value instead of using `as_ref()` or `as_mut`",
         deprecation: None,
         module: "matches",
+        docs: "**What it does:** Checks for match which is used to add a reference to an\n`Option` value.\n\n**Why is this bad?** Using `as_ref()` or `as_mut()` instead is shorter.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x: Option<()> = None;\nlet r: Option<&()> = match x {\n    None => None,\n    Some(ref v) => Some(v),\n};\n```",
     },
     Lint {
         name: "match_bool",
@@ -986,6 +1150,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a match on a boolean expression instead of an `if..else` block",
         deprecation: None,
         module: "matches",
+        docs: "**What it does:** Checks for matches where match expression is a `bool`. It\nsuggests to replace the expression with an `if...else` block.\n\n**Why is this bad?** It makes the code less readable.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# fn foo() {}\n# fn bar() {}\nlet condition: bool = true;\nmatch condition {\n    true => foo(),\n    false => bar(),\n}\n```\nUse if/else instead:\n```rust\n# fn foo() {}\n# fn bar() {}\nlet condition: bool = true;\nif condition {\n    foo();\n} else {\n    bar();\n}\n```",
     },
     Lint {
         name: "match_overlapping_arm",
@@ -993,6 +1158,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a match with overlapping arms",
         deprecation: None,
         module: "matches",
+        docs: "**What it does:** Checks for overlapping match arms.\n\n**Why is this bad?** It is likely to be an error and if not, makes the code\nless obvious.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = 5;\nmatch x {\n    1...10 => println!(\"1 ... 10\"),\n    5...15 => println!(\"5 ... 15\"),\n    _ => (),\n}\n```",
     },
     Lint {
         name: "match_ref_pats",
@@ -1000,6 +1166,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a match or `if let` with all arms prefixed with `&` instead of deref-ing the match expression",
         deprecation: None,
         module: "matches",
+        docs: "**What it does:** Checks for matches where all arms match a reference,\nsuggesting to remove the reference and deref the matched expression\ninstead. It also checks for `if let &foo = bar` blocks.\n\n**Why is this bad?** It just makes the code less readable. That reference\ndestructuring adds nothing to the code.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nmatch x {\n    &A(ref y) => foo(y),\n    &B => bar(),\n    _ => frob(&x),\n}\n```",
     },
     Lint {
         name: "match_same_arms",
@@ -1007,6 +1174,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`match` with identical arm bodies",
         deprecation: None,
         module: "copies",
+        docs: "**What it does:** Checks for `match` with identical arm bodies.\n\n**Why is this bad?** This is probably a copy & paste error. If arm bodies\nare the same on purpose, you can factor them\n[using `|`](https://doc.rust-lang.org/book/patterns.html#multiple-patterns).\n\n**Known problems:** False positive possible with order dependent `match`\n(see issue\n[#860](https://github.com/rust-lang/rust-clippy/issues/860)).\n\n**Example:**\n```rust,ignore\nmatch foo {\n    Bar => bar(),\n    Quz => quz(),\n    Baz => bar(), // <= oops\n}\n```\n\nThis should probably be\n```rust,ignore\nmatch foo {\n    Bar => bar(),\n    Quz => quz(),\n    Baz => baz(), // <= fixed\n}\n```\n\nor if the original code was not a typo:\n```rust,ignore\nmatch foo {\n    Bar | Baz => bar(), // <= shows the intent better\n    Quz => quz(),\n}\n```",
     },
     Lint {
         name: "match_wild_err_arm",
@@ -1014,6 +1182,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a match with `Err(_)` arm and take drastic actions",
         deprecation: None,
         module: "matches",
+        docs: "**What it does:** Checks for arm which matches all errors with `Err(_)`\nand take drastic actions like `panic!`.\n\n**Why is this bad?** It is generally a bad practice, just like\ncatching all exceptions in java with `catch(Exception)`\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x: Result<i32, &str> = Ok(3);\nmatch x {\n    Ok(_) => println!(\"ok\"),\n    Err(_) => panic!(\"err\"),\n}\n```",
     },
     Lint {
         name: "maybe_infinite_iter",
@@ -1021,6 +1190,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "possible infinite iteration",
         deprecation: None,
         module: "infinite_iter",
+        docs: "**What it does:** Checks for iteration that may be infinite.\n\n**Why is this bad?** While there may be places where this is acceptable\n(e.g., in event streams), in most cases this is simply an error.\n\n**Known problems:** The code may have a condition to stop iteration, but\nthis lint is not clever enough to analyze it.\n\n**Example:**\n```rust\nlet infinite_iter = 0..;\n[0..].iter().zip(infinite_iter.take_while(|x| *x > 5));\n```",
     },
     Lint {
         name: "mem_discriminant_non_enum",
@@ -1028,6 +1198,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "calling mem::descriminant on non-enum type",
         deprecation: None,
         module: "mem_discriminant",
+        docs: "**What it does:** Checks for calls of `mem::discriminant()` on a non-enum type.\n\n**Why is this bad?** The value of `mem::discriminant()` on non-enum types\nis unspecified.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nuse std::mem;\n\nmem::discriminant(&\"hello\");\nmem::discriminant(&&Some(2));\n```",
     },
     Lint {
         name: "mem_forget",
@@ -1035,6 +1206,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`mem::forget` usage on `Drop` types, likely to cause memory leaks",
         deprecation: None,
         module: "mem_forget",
+        docs: "**What it does:** Checks for usage of `std::mem::forget(t)` where `t` is\n`Drop`.\n\n**Why is this bad?** `std::mem::forget(t)` prevents `t` from running its\ndestructor, possibly causing leaks.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use std::mem;\n# use std::rc::Rc;\nmem::forget(Rc::new(55))\n```",
     },
     Lint {
         name: "mem_replace_option_with_none",
@@ -1042,6 +1214,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "replacing an `Option` with `None` instead of `take()`",
         deprecation: None,
         module: "mem_replace",
+        docs: "**What it does:** Checks for `mem::replace()` on an `Option` with\n`None`.\n\n**Why is this bad?** `Option` already has the method `take()` for\ntaking its current value (Some(..) or None) and replacing it with\n`None`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nuse std::mem;\n\nlet mut an_option = Some(0);\nlet replaced = mem::replace(&mut an_option, None);\n```\nIs better expressed with:\n```rust\nlet mut an_option = Some(0);\nlet taken = an_option.take();\n```",
     },
     Lint {
         name: "min_max",
@@ -1049,6 +1222,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`min(_, max(_, _))` (or vice versa) with bounds clamping the result to a constant",
         deprecation: None,
         module: "minmax",
+        docs: "**What it does:** Checks for expressions where `std::cmp::min` and `max` are\nused to clamp values, but switched so that the result is constant.\n\n**Why is this bad?** This is in all probability not the intended outcome. At\nthe least it hurts readability of the code.\n\n**Known problems:** None\n\n**Example:**\n```ignore\nmin(0, max(100, x))\n```\nIt will always be equal to `0`. Probably the author meant to clamp the value\nbetween 0 and 100, but has erroneously swapped `min` and `max`.",
     },
     Lint {
         name: "misrefactored_assign_op",
@@ -1056,6 +1230,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "having a variable on both sides of an assign op",
         deprecation: None,
         module: "assign_ops",
+        docs: "**What it does:** Checks for `a op= a op b` or `a op= b op a` patterns.\n\n**Why is this bad?** Most likely these are bugs where one meant to write `a\nop= b`.\n\n**Known problems:** Clippy cannot know for sure if `a op= a op b` should have\nbeen `a = a op a op b` or `a = a op b`/`a op= b`. Therefore, it suggests both.\nIf `a op= a op b` is really the correct behaviour it should be\nwritten as `a = a op a op b` as it's less confusing.\n\n**Example:**\n```rust\nlet mut a = 5;\nlet b = 2;\n// ...\na += a + b;\n```",
     },
     Lint {
         name: "missing_const_for_fn",
@@ -1063,6 +1238,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Lint functions definitions that could be made `const fn`",
         deprecation: None,
         module: "missing_const_for_fn",
+        docs: "**What it does:**\n\nSuggests the use of `const` in functions and methods where possible.\n\n**Why is this bad?**\n\nNot having the function const prevents callers of the function from being const as well.\n\n**Known problems:**\n\nConst functions are currently still being worked on, with some features only being available\non nightly. This lint does not consider all edge cases currently and the suggestions may be\nincorrect if you are using this lint on stable.\n\nAlso, the lint only runs one pass over the code. Consider these two non-const functions:\n\n```rust\nfn a() -> i32 {\n    0\n}\nfn b() -> i32 {\n    a()\n}\n```\n\nWhen running Clippy, the lint will only suggest to make `a` const, because `b` at this time\ncan't be const as it calls a non-const function. Making `a` const and running Clippy again,\nwill suggest to make `b` const, too.\n\n**Example:**\n\n```rust\n# struct Foo {\n#     random_number: usize,\n# }\n# impl Foo {\nfn new() -> Self {\n    Self { random_number: 42 }\n}\n# }\n```\n\nCould be a const fn:\n\n```rust\n# struct Foo {\n#     random_number: usize,\n# }\n# impl Foo {\nconst fn new() -> Self {\n    Self { random_number: 42 }\n}\n# }\n```",
     },
     Lint {
         name: "missing_docs_in_private_items",
@@ -1070,6 +1246,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "detects missing documentation for public and private members",
         deprecation: None,
         module: "missing_doc",
+        docs: "**What it does:** Warns if there is missing doc for any documentable item\n(public or private).\n\n**Why is this bad?** Doc is good. *rustc* has a `MISSING_DOCS`\nallowed-by-default lint for\npublic members, but has no way to enforce documentation of private items.\nThis lint fixes that.\n\n**Known problems:** None.",
     },
     Lint {
         name: "missing_inline_in_public_items",
@@ -1077,6 +1254,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "detects missing #[inline] attribute for public callables (functions, trait methods, methods...)",
         deprecation: None,
         module: "missing_inline",
+        docs: "**What it does:** it lints if an exported function, method, trait method with default impl,\nor trait method impl is not `#[inline]`.\n\n**Why is this bad?** In general, it is not. Functions can be inlined across\ncrates when that's profitable as long as any form of LTO is used. When LTO is disabled,\nfunctions that are not `#[inline]` cannot be inlined across crates. Certain types of crates\nmight intend for most of the methods in their public API to be able to be inlined across\ncrates even when LTO is disabled. For these types of crates, enabling this lint might make\nsense. It allows the crate to require all exported methods to be `#[inline]` by default, and\nthen opt out for specific methods where this might not make sense.\n\n**Known problems:** None.\n\n**Example:**\n```rust\npub fn foo() {} // missing #[inline]\nfn ok() {} // ok\n#[inline] pub fn bar() {} // ok\n#[inline(always)] pub fn baz() {} // ok\n\npub trait Bar {\n  fn bar(); // ok\n  fn def_bar() {} // missing #[inline]\n}\n\nstruct Baz;\nimpl Baz {\n   fn private() {} // ok\n}\n\nimpl Bar for Baz {\n  fn bar() {} // ok - Baz is not exported\n}\n\npub struct PubBaz;\nimpl PubBaz {\n   fn private() {} // ok\n   pub fn not_ptrivate() {} // missing #[inline]\n}\n\nimpl Bar for PubBaz {\n   fn bar() {} // missing #[inline]\n   fn def_bar() {} // missing #[inline]\n}\n```",
     },
     Lint {
         name: "mistyped_literal_suffixes",
@@ -1084,6 +1262,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "mistyped literal suffix",
         deprecation: None,
         module: "literal_representation",
+        docs: "**What it does:** Warns for mistyped suffix in literals\n\n**Why is this bad?** This is most probably a typo\n\n**Known problems:**\n\t\t- Recommends a signed suffix, even though the number might be too big and an unsigned\n\t\tsuffix is required\n\t\t- Does not match on `_128` since that is a valid grouping for decimal and octal numbers\n\n**Example:**\n\n```rust\n2_32;\n```",
     },
     Lint {
         name: "mixed_case_hex_literals",
@@ -1091,6 +1270,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "hex literals whose letter digits are not consistently upper- or lowercased",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Warns on hexadecimal literals with mixed-case letter\ndigits.\n\n**Why is this bad?** It looks confusing.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet y = 0x1a9BAcD;\n```",
     },
     Lint {
         name: "module_inception",
@@ -1098,6 +1278,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "modules that have the same name as their parent module",
         deprecation: None,
         module: "enum_variants",
+        docs: "**What it does:** Checks for modules that have the same name as their\nparent module\n\n**Why is this bad?** A typical beginner mistake is to have `mod foo;` and\nagain `mod foo { ..\n}` in `foo.rs`.\nThe expectation is that items inside the inner `mod foo { .. }` are then\navailable\nthrough `foo::x`, but they are only available through\n`foo::foo::x`.\nIf this is done on purpose, it would be better to choose a more\nrepresentative module name.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\n// lib.rs\nmod foo;\n// foo.rs\nmod foo {\n    ...\n}\n```",
     },
     Lint {
         name: "module_name_repetitions",
@@ -1105,6 +1286,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "type names prefixed/postfixed with their containing module\'s name",
         deprecation: None,
         module: "enum_variants",
+        docs: "**What it does:** Detects type names that are prefixed or suffixed by the\ncontaining module's name.\n\n**Why is this bad?** It requires the user to type the module name twice.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nmod cake {\n    struct BlackForestCake;\n}\n```",
     },
     Lint {
         name: "modulo_one",
@@ -1112,6 +1294,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "taking a number modulo 1, which always returns 0",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Checks for getting the remainder of a division by one.\n\n**Why is this bad?** The result can only ever be zero. No one will write\nsuch code deliberately, unless trying to win an Underhanded Rust\nContest. Even for that contest, it's probably a bad idea. Use something more\nunderhanded.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let x = 1;\nlet a = x % 1;\n```",
     },
     Lint {
         name: "multiple_crate_versions",
@@ -1119,6 +1302,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "multiple versions of the same crate being used",
         deprecation: None,
         module: "multiple_crate_versions",
+        docs: "**What it does:** Checks to see if multiple versions of a crate are being\nused.\n\n**Why is this bad?** This bloats the size of targets, and can lead to\nconfusing error messages when structs or traits are used interchangeably\nbetween different versions of a crate.\n\n**Known problems:** Because this can be caused purely by the dependencies\nthemselves, it's not always possible to fix this issue.\n\n**Example:**\n```toml\n# This will pull in both winapi v0.3.4 and v0.2.8, triggering a warning.\n[dependencies]\nctrlc = \"3.1.0\"\nansi_term = \"0.11.0\"\n```",
     },
     Lint {
         name: "multiple_inherent_impl",
@@ -1126,6 +1310,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Multiple inherent impl that could be grouped",
         deprecation: None,
         module: "inherent_impl",
+        docs: "**What it does:** Checks for multiple inherent implementations of a struct\n\n**Why is this bad?** Splitting the implementation of a type makes the code harder to navigate.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nstruct X;\nimpl X {\n    fn one() {}\n}\nimpl X {\n    fn other() {}\n}\n```\n\nCould be written:\n\n```rust\nstruct X;\nimpl X {\n    fn one() {}\n    fn other() {}\n}\n```",
     },
     Lint {
         name: "mut_from_ref",
@@ -1133,6 +1318,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "fns that create mutable refs from immutable ref args",
         deprecation: None,
         module: "ptr",
+        docs: "**What it does:** This lint checks for functions that take immutable\nreferences and return\nmutable ones.\n\n**Why is this bad?** This is trivially unsound, as one can create two\nmutable references\nfrom the same (immutable!) source. This\n[error](https://github.com/rust-lang/rust/issues/39465)\nactually lead to an interim Rust release 1.15.1.\n\n**Known problems:** To be on the conservative side, if there's at least one\nmutable reference\nwith the output lifetime, this lint will not trigger. In practice, this\ncase is unlikely anyway.\n\n**Example:**\n```ignore\nfn foo(&Foo) -> &mut Bar { .. }\n```",
     },
     Lint {
         name: "mut_mut",
@@ -1140,6 +1326,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of double-mut refs, e.g., `&mut &mut ...`",
         deprecation: None,
         module: "mut_mut",
+        docs: "**What it does:** Checks for instances of `mut mut` references.\n\n**Why is this bad?** Multiple `mut`s don't add anything meaningful to the\nsource. This is either a copy'n'paste error, or it shows a fundamental\nmisunderstanding of references.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let mut y = 1;\nlet x = &mut &mut y;\n```",
     },
     Lint {
         name: "mut_range_bound",
@@ -1147,6 +1334,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "for loop over a range where one of the bounds is a mutable variable",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for loops which have a range bound that is a mutable variable\n\n**Why is this bad?** One might think that modifying the mutable variable changes the loop bounds\n\n**Known problems:** None\n\n**Example:**\n```rust\nlet mut foo = 42;\nfor i in 0..foo {\n    foo -= 1;\n    println!(\"{}\", i); // prints numbers from 0 to 42, not 0 to 21\n}\n```",
     },
     Lint {
         name: "mutex_atomic",
@@ -1154,6 +1342,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a mutex where an atomic value could be used instead",
         deprecation: None,
         module: "mutex_atomic",
+        docs: "**What it does:** Checks for usages of `Mutex<X>` where an atomic will do.\n\n**Why is this bad?** Using a mutex just to make access to a plain bool or\nreference sequential is shooting flies with cannons.\n`std::sync::atomic::AtomicBool` and `std::sync::atomic::AtomicPtr` are leaner and\nfaster.\n\n**Known problems:** This lint cannot detect if the mutex is actually used\nfor waiting before a critical section.\n\n**Example:**\n```rust\n# use std::sync::Mutex;\n# let y = 1;\nlet x = Mutex::new(&y);\n```",
     },
     Lint {
         name: "mutex_integer",
@@ -1161,6 +1350,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a mutex for an integer type",
         deprecation: None,
         module: "mutex_atomic",
+        docs: "**What it does:** Checks for usages of `Mutex<X>` where `X` is an integral\ntype.\n\n**Why is this bad?** Using a mutex just to make access to a plain integer\nsequential is\nshooting flies with cannons. `std::sync::atomic::AtomicUsize` is leaner and faster.\n\n**Known problems:** This lint cannot detect if the mutex is actually used\nfor waiting before a critical section.\n\n**Example:**\n```rust\n# use std::sync::Mutex;\nlet x = Mutex::new(0usize);\n```",
     },
     Lint {
         name: "naive_bytecount",
@@ -1168,6 +1358,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of naive `<slice>.filter(|&x| x == y).count()` to count byte values",
         deprecation: None,
         module: "bytecount",
+        docs: "**What it does:** Checks for naive byte counts\n\n**Why is this bad?** The [`bytecount`](https://crates.io/crates/bytecount)\ncrate has methods to count your bytes faster, especially for large slices.\n\n**Known problems:** If you have predominantly small slices, the\n`bytecount::count(..)` method may actually be slower. However, if you can\nensure that less than 2³²-1 matches arise, the `naive_count_32(..)` can be\nfaster in those cases.\n\n**Example:**\n\n```rust\n# let vec = vec![1_u8];\n&vec.iter().filter(|x| **x == 0u8).count(); // use bytecount::count instead\n```",
     },
     Lint {
         name: "needless_bool",
@@ -1175,6 +1366,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "if-statements with plain booleans in the then- and else-clause, e.g., `if p { true } else { false }`",
         deprecation: None,
         module: "needless_bool",
+        docs: "**What it does:** Checks for expressions of the form `if c { true } else {\nfalse }`\n(or vice versa) and suggest using the condition directly.\n\n**Why is this bad?** Redundant code.\n\n**Known problems:** Maybe false positives: Sometimes, the two branches are\npainstakingly documented (which we, of course, do not detect), so they *may*\nhave some value. Even then, the documentation can be rewritten to match the\nshorter code.\n\n**Example:**\n```rust,ignore\nif x {\n    false\n} else {\n    true\n}\n```\nCould be written as\n```rust,ignore\n!x\n```",
     },
     Lint {
         name: "needless_borrow",
@@ -1182,6 +1374,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "taking a reference that is going to be automatically dereferenced",
         deprecation: None,
         module: "needless_borrow",
+        docs: "**What it does:** Checks for address of operations (`&`) that are going to\nbe dereferenced immediately by the compiler.\n\n**Why is this bad?** Suggests that the receiver of the expression borrows\nthe expression.\n\n**Example:**\n```rust\nlet x: &i32 = &&&&&&5;\n```\n\n**Known problems:** None.",
     },
     Lint {
         name: "needless_borrowed_reference",
@@ -1189,6 +1382,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "taking a needless borrowed reference",
         deprecation: None,
         module: "needless_borrowed_ref",
+        docs: "**What it does:** Checks for useless borrowed references.\n\n**Why is this bad?** It is mostly useless and make the code look more\ncomplex than it\nactually is.\n\n**Known problems:** It seems that the `&ref` pattern is sometimes useful.\nFor instance in the following snippet:\n```rust,ignore\nenum Animal {\n    Cat(u64),\n    Dog(u64),\n}\n\nfn foo(a: &Animal, b: &Animal) {\n    match (a, b) {\n        (&Animal::Cat(v), k) | (k, &Animal::Cat(v)) => (), // lifetime mismatch error\n        (&Animal::Dog(ref c), &Animal::Dog(_)) => ()\n    }\n}\n```\nThere is a lifetime mismatch error for `k` (indeed a and b have distinct\nlifetime).\nThis can be fixed by using the `&ref` pattern.\nHowever, the code can also be fixed by much cleaner ways\n\n**Example:**\n```rust\nlet mut v = Vec::<String>::new();\nlet _ = v.iter_mut().filter(|&ref a| a.is_empty());\n```\nThis closure takes a reference on something that has been matched as a\nreference and\nde-referenced.\nAs such, it could just be |a| a.is_empty()",
     },
     Lint {
         name: "needless_collect",
@@ -1196,6 +1390,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "collecting an iterator when collect is not needed",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for functions collecting an iterator when collect\nis not needed.\n\n**Why is this bad?** `collect` causes the allocation of a new data structure,\nwhen this allocation may not be needed.\n\n**Known problems:**\nNone\n\n**Example:**\n```rust\n# let iterator = vec![1].into_iter();\nlet len = iterator.clone().collect::<Vec<_>>().len();\n// should be\nlet len = iterator.count();\n```",
     },
     Lint {
         name: "needless_continue",
@@ -1203,6 +1398,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`continue` statements that can be replaced by a rearrangement of code",
         deprecation: None,
         module: "needless_continue",
+        docs: "**What it does:** The lint checks for `if`-statements appearing in loops\nthat contain a `continue` statement in either their main blocks or their\n`else`-blocks, when omitting the `else`-block possibly with some\nrearrangement of code can make the code easier to understand.\n\n**Why is this bad?** Having explicit `else` blocks for `if` statements\ncontaining `continue` in their THEN branch adds unnecessary branching and\nnesting to the code. Having an else block containing just `continue` can\nalso be better written by grouping the statements following the whole `if`\nstatement within the THEN block and omitting the else block completely.\n\n**Known problems:** None\n\n**Example:**\n```rust\n# fn condition() -> bool { false }\n# fn update_condition() {}\n# let x = false;\nwhile condition() {\n    update_condition();\n    if x {\n        // ...\n    } else {\n        continue;\n    }\n    println!(\"Hello, world\");\n}\n```\n\nCould be rewritten as\n\n```rust\n# fn condition() -> bool { false }\n# fn update_condition() {}\n# let x = false;\nwhile condition() {\n    update_condition();\n    if x {\n        // ...\n        println!(\"Hello, world\");\n    }\n}\n```\n\nAs another example, the following code\n\n```rust\n# fn waiting() -> bool { false }\nloop {\n    if waiting() {\n        continue;\n    } else {\n        // Do something useful\n    }\n    # break;\n}\n```\nCould be rewritten as\n\n```rust\n# fn waiting() -> bool { false }\nloop {\n    if waiting() {\n        continue;\n    }\n    // Do something useful\n    # break;\n}\n```",
     },
     Lint {
         name: "needless_lifetimes",
@@ -1210,6 +1406,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using explicit lifetimes for references in function arguments when elision rules would allow omitting them",
         deprecation: None,
         module: "lifetimes",
+        docs: "**What it does:** Checks for lifetime annotations which can be removed by\nrelying on lifetime elision.\n\n**Why is this bad?** The additional lifetimes make the code look more\ncomplicated, while there is nothing out of the ordinary going on. Removing\nthem leads to more readable code.\n\n**Known problems:** Potential false negatives: we bail out if the function\nhas a `where` clause where lifetimes are mentioned.\n\n**Example:**\n```rust\nfn in_and_out<'a>(x: &'a u8, y: u8) -> &'a u8 {\n    x\n}\n```",
     },
     Lint {
         name: "needless_pass_by_value",
@@ -1217,6 +1414,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "functions taking arguments by value, but not consuming them in its body",
         deprecation: None,
         module: "needless_pass_by_value",
+        docs: "**What it does:** Checks for functions taking arguments by value, but not\nconsuming them in its\nbody.\n\n**Why is this bad?** Taking arguments by reference is more flexible and can\nsometimes avoid\nunnecessary allocations.\n\n**Known problems:**\n* This lint suggests taking an argument by reference,\nhowever sometimes it is better to let users decide the argument type\n(by using `Borrow` trait, for example), depending on how the function is used.\n\n**Example:**\n```rust\nfn foo(v: Vec<i32>) {\n    assert_eq!(v.len(), 42);\n}\n```\n\n```rust\n// should be\nfn foo(v: &[i32]) {\n    assert_eq!(v.len(), 42);\n}\n```",
     },
     Lint {
         name: "needless_range_loop",
@@ -1224,6 +1422,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "for-looping over a range of indices where an iterator over items would do",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for looping over the range of `0..len` of some\ncollection just to get the values by index.\n\n**Why is this bad?** Just iterating the collection itself makes the intent\nmore clear and is probably faster.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet vec = vec!['a', 'b', 'c'];\nfor i in 0..vec.len() {\n    println!(\"{}\", vec[i]);\n}\n```\nCould be written as:\n```rust\nlet vec = vec!['a', 'b', 'c'];\nfor i in vec {\n    println!(\"{}\", i);\n}\n```",
     },
     Lint {
         name: "needless_return",
@@ -1231,6 +1430,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a return statement like `return expr;` where an expression would suffice",
         deprecation: None,
         module: "returns",
+        docs: "**What it does:** Checks for return statements at the end of a block.\n\n**Why is this bad?** Removing the `return` and semicolon will make the code\nmore rusty.\n\n**Known problems:** If the computation returning the value borrows a local\nvariable, removing the `return` may run afoul of the borrow checker.\n\n**Example:**\n```rust\nfn foo(x: usize) -> usize {\n    return x;\n}\n```\nsimplify to\n```rust\nfn foo(x: usize) -> usize {\n    x\n}\n```",
     },
     Lint {
         name: "needless_update",
@@ -1238,6 +1438,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Foo { ..base }` when there are no missing fields",
         deprecation: None,
         module: "needless_update",
+        docs: "**What it does:** Checks for needlessly including a base struct on update\nwhen all fields are changed anyway.\n\n**Why is this bad?** This will cost resources (because the base has to be\nsomewhere), and make the code less readable.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# struct Point {\n#     x: i32,\n#     y: i32,\n#     z: i32,\n# }\n# let zero_point = Point { x: 0, y: 0, z: 0 };\nPoint {\n    x: 1,\n    y: 1,\n    ..zero_point\n};\n```",
     },
     Lint {
         name: "neg_cmp_op_on_partial_ord",
@@ -1245,6 +1446,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "The use of negated comparison operators on partially ordered types may produce confusing code.",
         deprecation: None,
         module: "neg_cmp_op_on_partial_ord",
+        docs: "**What it does:**\nChecks for the usage of negated comparison operators on types which only implement\n`PartialOrd` (e.g., `f64`).\n\n**Why is this bad?**\nThese operators make it easy to forget that the underlying types actually allow not only three\npotential Orderings (Less, Equal, Greater) but also a fourth one (Uncomparable). This is\nespecially easy to miss if the operator based comparison result is negated.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\nuse std::cmp::Ordering;\n\n// Bad\nlet a = 1.0;\nlet b = std::f64::NAN;\n\nlet _not_less_or_equal = !(a <= b);\n\n// Good\nlet a = 1.0;\nlet b = std::f64::NAN;\n\nlet _not_less_or_equal = match a.partial_cmp(&b) {\n    None | Some(Ordering::Greater) => true,\n    _ => false,\n};\n```",
     },
     Lint {
         name: "neg_multiply",
@@ -1252,6 +1454,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "multiplying integers with -1",
         deprecation: None,
         module: "neg_multiply",
+        docs: "**What it does:** Checks for multiplication by -1 as a form of negation.\n\n**Why is this bad?** It's more readable to just negate.\n\n**Known problems:** This only catches integers (for now).\n\n**Example:**\n```ignore\nx * -1\n```",
     },
     Lint {
         name: "never_loop",
@@ -1259,6 +1462,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "any loop that will always `break` or `return`",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for loops that will always `break`, `return` or\n`continue` an outer loop.\n\n**Why is this bad?** This loop never loops, all it does is obfuscating the\ncode.\n\n**Known problems:** None\n\n**Example:**\n```rust\nloop {\n    ..;\n    break;\n}\n```",
     },
     Lint {
         name: "new_ret_no_self",
@@ -1266,6 +1470,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "not returning `Self` in a `new` method",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for `new` not returning `Self`.\n\n**Why is this bad?** As a convention, `new` methods are used to make a new\ninstance of a type.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nimpl Foo {\n    fn new(..) -> NotAFoo {\n    }\n}\n```",
     },
     Lint {
         name: "new_without_default",
@@ -1273,6 +1478,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`fn new() -> Self` method without `Default` implementation",
         deprecation: None,
         module: "new_without_default",
+        docs: "**What it does:** Checks for types with a `fn new() -> Self` method and no\nimplementation of\n[`Default`](https://doc.rust-lang.org/std/default/trait.Default.html).\n\nIt detects both the case when a manual\n[`Default`](https://doc.rust-lang.org/std/default/trait.Default.html)\nimplementation is required and also when it can be created with\n`#[derive(Default)]`\n\n**Why is this bad?** The user might expect to be able to use\n[`Default`](https://doc.rust-lang.org/std/default/trait.Default.html) as the\ntype can be constructed without arguments.\n\n**Known problems:** Hopefully none.\n\n**Example:**\n\n```ignore\nstruct Foo(Bar);\n\nimpl Foo {\n    fn new() -> Self {\n        Foo(Bar::new())\n    }\n}\n```\n\nInstead, use:\n\n```ignore\nstruct Foo(Bar);\n\nimpl Default for Foo {\n    fn default() -> Self {\n        Foo(Bar::new())\n    }\n}\n```\n\nOr, if\n[`Default`](https://doc.rust-lang.org/std/default/trait.Default.html)\ncan be derived by `#[derive(Default)]`:\n\n```rust\nstruct Foo;\n\nimpl Foo {\n    fn new() -> Self {\n        Foo\n    }\n}\n```\n\nInstead, use:\n\n```rust\n#[derive(Default)]\nstruct Foo;\n\nimpl Foo {\n    fn new() -> Self {\n        Foo\n    }\n}\n```\n\nYou can also have `new()` call `Default::default()`.",
     },
     Lint {
         name: "no_effect",
@@ -1280,6 +1486,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "statements with no effect",
         deprecation: None,
         module: "no_effect",
+        docs: "**What it does:** Checks for statements which have no effect.\n\n**Why is this bad?** Similar to dead code, these statements are actually\nexecuted. However, as they have no effect, all they do is make the code less\nreadable.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n0;\n```",
     },
     Lint {
         name: "non_ascii_literal",
@@ -1287,6 +1494,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using any literal non-ASCII chars in a string literal instead of using the `\\\\u` escape",
         deprecation: None,
         module: "unicode",
+        docs: "**What it does:** Checks for non-ASCII characters in string literals.\n\n**Why is this bad?** Yeah, we know, the 90's called and wanted their charset\nback. Even so, there still are editors and other programs out there that\ndon't work well with Unicode. So if the code is meant to be used\ninternationally, on multiple operating systems, or has other portability\nrequirements, activating this lint could be useful.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = String::from(\"€\");\n```\nCould be written as:\n```rust\nlet x = String::from(\"\\u{20ac}\");\n```",
     },
     Lint {
         name: "nonminimal_bool",
@@ -1294,6 +1502,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "boolean expressions that can be written more concisely",
         deprecation: None,
         module: "booleans",
+        docs: "**What it does:** Checks for boolean expressions that can be written more\nconcisely.\n\n**Why is this bad?** Readability of boolean expressions suffers from\nunnecessary duplication.\n\n**Known problems:** Ignores short circuiting behavior of `||` and\n`&&`. Ignores `|`, `&` and `^`.\n\n**Example:**\n```ignore\nif a && true  // should be: if a\nif !(a == b)  // should be: if a != b\n```",
     },
     Lint {
         name: "nonsensical_open_options",
@@ -1301,6 +1510,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "nonsensical combination of options for opening a file",
         deprecation: None,
         module: "open_options",
+        docs: "**What it does:** Checks for duplicate open options as well as combinations\nthat make no sense.\n\n**Why is this bad?** In the best case, the code will be harder to read than\nnecessary. I don't know the worst case.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nuse std::fs::OpenOptions;\n\nOpenOptions::new().read(true).truncate(true);\n```",
     },
     Lint {
         name: "not_unsafe_ptr_arg_deref",
@@ -1308,6 +1518,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "public functions dereferencing raw pointer arguments but not marked `unsafe`",
         deprecation: None,
         module: "functions",
+        docs: "**What it does:** Checks for public functions that dereference raw pointer\narguments but are not marked unsafe.\n\n**Why is this bad?** The function should probably be marked `unsafe`, since\nfor an arbitrary raw pointer, there is no way of telling for sure if it is\nvalid.\n\n**Known problems:**\n\n* It does not check functions recursively so if the pointer is passed to a\nprivate non-`unsafe` function which does the dereferencing, the lint won't\ntrigger.\n* It only checks for arguments whose type are raw pointers, not raw pointers\ngot from an argument in some other way (`fn foo(bar: &[*const u8])` or\n`some_argument.get_raw_ptr()`).\n\n**Example:**\n```rust\npub fn foo(x: *const u8) {\n    println!(\"{}\", unsafe { *x });\n}\n```",
     },
     Lint {
         name: "ok_expect",
@@ -1315,6 +1526,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `ok().expect()`, which gives worse error messages than calling `expect` directly on the Result",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `ok().expect(..)`.\n\n**Why is this bad?** Because you usually call `expect()` on the `Result`\ndirectly to get a better error message.\n\n**Known problems:** The error type needs to implement `Debug`\n\n**Example:**\n```ignore\nx.ok().expect(\"why did I do this again?\")\n```",
     },
     Lint {
         name: "op_ref",
@@ -1322,6 +1534,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "taking a reference to satisfy the type constraints on `==`",
         deprecation: None,
         module: "eq_op",
+        docs: "**What it does:** Checks for arguments to `==` which have their address\ntaken to satisfy a bound\nand suggests to dereference the other argument instead\n\n**Why is this bad?** It is more idiomatic to dereference the other argument.\n\n**Known problems:** None\n\n**Example:**\n```ignore\n&x == y\n```",
     },
     Lint {
         name: "option_and_then_some",
@@ -1329,6 +1542,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Option.and_then(|x| Some(y))`, which is more succinctly expressed as `map(|x| y)`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.and_then(|x| Some(y))`.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`_.map(|x| y)`.\n\n**Known problems:** None\n\n**Example:**\n\n```rust\nlet x = Some(\"foo\");\nlet _ = x.and_then(|s| Some(s.len()));\n```\n\nThe correct use would be:\n\n```rust\nlet x = Some(\"foo\");\nlet _ = x.map(|s| s.len());\n```",
     },
     Lint {
         name: "option_map_or_none",
@@ -1336,6 +1550,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Option.map_or(None, f)`, which is more succinctly expressed as `and_then(f)`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.map_or(None, _)`.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`_.and_then(_)`.\n\n**Known problems:** The order of the arguments is not in execution order.\n\n**Example:**\n```ignore\nopt.map_or(None, |a| a + 1)\n```",
     },
     Lint {
         name: "option_map_unit_fn",
@@ -1343,6 +1558,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `option.map(f)`, where f is a function or closure that returns ()",
         deprecation: None,
         module: "map_unit_fn",
+        docs: "**What it does:** Checks for usage of `option.map(f)` where f is a function\nor closure that returns the unit type.\n\n**Why is this bad?** Readability, this can be written more clearly with\nan if let statement\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\n# fn do_stuff() -> Option<String> { Some(String::new()) }\n# fn log_err_msg(foo: String) -> Option<String> { Some(foo) }\n# fn format_msg(foo: String) -> String { String::new() }\nlet x: Option<String> = do_stuff();\nx.map(log_err_msg);\n# let x: Option<String> = do_stuff();\nx.map(|msg| log_err_msg(format_msg(msg)));\n```\n\nThe correct use would be:\n\n```rust\n# fn do_stuff() -> Option<String> { Some(String::new()) }\n# fn log_err_msg(foo: String) -> Option<String> { Some(foo) }\n# fn format_msg(foo: String) -> String { String::new() }\nlet x: Option<String> = do_stuff();\nif let Some(msg) = x {\n    log_err_msg(msg);\n}\n\n# let x: Option<String> = do_stuff();\nif let Some(msg) = x {\n    log_err_msg(format_msg(msg));\n}\n```",
     },
     Lint {
         name: "option_map_unwrap_or",
@@ -1350,6 +1566,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Option.map(f).unwrap_or(a)`, which is more succinctly expressed as `map_or(a, f)`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.map(_).unwrap_or(_)`.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`_.map_or(_, _)`.\n\n**Known problems:** The order of the arguments is not in execution order\n\n**Example:**\n```rust\n# let x = Some(1);\nx.map(|a| a + 1).unwrap_or(0);\n```",
     },
     Lint {
         name: "option_map_unwrap_or_else",
@@ -1357,6 +1574,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Option.map(f).unwrap_or_else(g)`, which is more succinctly expressed as `map_or_else(g, f)`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `_.map(_).unwrap_or_else(_)`.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`_.map_or_else(_, _)`.\n\n**Known problems:** The order of the arguments is not in execution order.\n\n**Example:**\n```rust\n# let x = Some(1);\n# fn some_function() -> usize { 1 }\nx.map(|a| a + 1).unwrap_or_else(some_function);\n```",
     },
     Lint {
         name: "option_option",
@@ -1364,6 +1582,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of `Option<Option<T>>`",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for use of `Option<Option<_>>` in function signatures and type\ndefinitions\n\n**Why is this bad?** `Option<_>` represents an optional value. `Option<Option<_>>`\nrepresents an optional optional value which is logically the same thing as an optional\nvalue but has an unneeded extra level of wrapping.\n\n**Known problems:** None.\n\n**Example**\n```rust\nfn x() -> Option<Option<u32>> {\n    None\n}\n```",
     },
     Lint {
         name: "option_unwrap_used",
@@ -1371,6 +1590,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Option.unwrap()`, which should at least get a better message using `expect()`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for `.unwrap()` calls on `Option`s.\n\n**Why is this bad?** Usually it is better to handle the `None` case, or to\nat least call `.expect(_)` with a more helpful message. Still, for a lot of\nquick-and-dirty code, `unwrap` is a good choice, which is why this lint is\n`Allow` by default.\n\n**Known problems:** None.\n\n**Example:**\n\nUsing unwrap on an `Option`:\n\n```rust\nlet opt = Some(1);\nopt.unwrap();\n```\n\nBetter:\n\n```rust\nlet opt = Some(1);\nopt.expect(\"more helpful message\");\n```",
     },
     Lint {
         name: "or_fun_call",
@@ -1378,6 +1598,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using any `*or` method with a function call, which suggests `*or_else`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for calls to `.or(foo(..))`, `.unwrap_or(foo(..))`,\netc., and suggests to use `or_else`, `unwrap_or_else`, etc., or\n`unwrap_or_default` instead.\n\n**Why is this bad?** The function will always be called and potentially\nallocate an object acting as the default.\n\n**Known problems:** If the function has side-effects, not calling it will\nchange the semantic of the program, but you shouldn't rely on that anyway.\n\n**Example:**\n```rust\n# let foo = Some(String::new());\nfoo.unwrap_or(String::new());\n```\nthis can instead be written:\n```rust\n# let foo = Some(String::new());\nfoo.unwrap_or_else(String::new);\n```\nor\n```rust\n# let foo = Some(String::new());\nfoo.unwrap_or_default();\n```",
     },
     Lint {
         name: "out_of_bounds_indexing",
@@ -1385,6 +1606,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "out of bounds constant indexing",
         deprecation: None,
         module: "indexing_slicing",
+        docs: "**What it does:** Checks for out of bounds array indexing with a constant\nindex.\n\n**Why is this bad?** This will always panic at runtime.\n\n**Known problems:** Hopefully none.\n\n**Example:**\n```no_run\n# #![allow(const_err)]\nlet x = [1, 2, 3, 4];\n\n// Bad\nx[9];\n&x[2..9];\n\n// Good\nx[0];\nx[3];\n```",
     },
     Lint {
         name: "overflow_check_conditional",
@@ -1392,6 +1614,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "overflow checks inspired by C which are likely to panic",
         deprecation: None,
         module: "overflow_check_conditional",
+        docs: "**What it does:** Detects classic underflow/overflow checks.\n\n**Why is this bad?** Most classic C underflow/overflow checks will fail in\nRust. Users can use functions like `overflowing_*` and `wrapping_*` instead.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let a = 1;\n# let b = 2;\na + b < a;\n```",
     },
     Lint {
         name: "panic_params",
@@ -1399,6 +1622,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "missing parameters in `panic!` calls",
         deprecation: None,
         module: "panic_unimplemented",
+        docs: "**What it does:** Checks for missing parameters in `panic!`.\n\n**Why is this bad?** Contrary to the `format!` family of macros, there are\ntwo forms of `panic!`: if there are no parameters given, the first argument\nis not a format string and used literally. So while `format!(\"{}\")` will\nfail to compile, `panic!(\"{}\")` will not.\n\n**Known problems:** None.\n\n**Example:**\n```no_run\npanic!(\"This `panic!` is probably missing a parameter there: {}\");\n```",
     },
     Lint {
         name: "panicking_unwrap",
@@ -1406,6 +1630,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "checks for calls of unwrap[_err]() that will always fail",
         deprecation: None,
         module: "unwrap",
+        docs: "**What it does:** Checks for calls of `unwrap[_err]()` that will always fail.\n\n**Why is this bad?** If panicking is desired, an explicit `panic!()` should be used.\n\n**Known problems:** This lint only checks `if` conditions not assignments.\nSo something like `let x: Option<()> = None; x.unwrap();` will not be recognized.\n\n**Example:**\n```rust\n# let option = Some(0);\n# fn do_something_with(_x: usize) {}\nif option.is_none() {\n    do_something_with(option.unwrap())\n}\n```\n\nThis code will always panic. The if condition should probably be inverted.",
     },
     Lint {
         name: "partialeq_ne_impl",
@@ -1413,6 +1638,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "re-implementing `PartialEq::ne`",
         deprecation: None,
         module: "partialeq_ne_impl",
+        docs: "**What it does:** Checks for manual re-implementations of `PartialEq::ne`.\n\n**Why is this bad?** `PartialEq::ne` is required to always return the\nnegated result of `PartialEq::eq`, which is exactly what the default\nimplementation does. Therefore, there should never be any need to\nre-implement it.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nstruct Foo;\n\nimpl PartialEq for Foo {\n   fn eq(&self, other: &Foo) -> bool { true }\n   fn ne(&self, other: &Foo) -> bool { !(self == other) }\n}\n```",
     },
     Lint {
         name: "path_buf_push_overwrite",
@@ -1420,6 +1646,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "calling `push` with file system root on `PathBuf` can overwrite it",
         deprecation: None,
         module: "path_buf_push_overwrite",
+        docs: "**What it does:*** Checks for [push](https://doc.rust-lang.org/std/path/struct.PathBuf.html#method.push)\ncalls on `PathBuf` that can cause overwrites.\n\n**Why is this bad?** Calling `push` with a root path at the start can overwrite the\nprevious defined path.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nuse std::path::PathBuf;\n\nlet mut x = PathBuf::from(\"/foo\");\nx.push(\"/bar\");\nassert_eq!(x, PathBuf::from(\"/bar\"));\n```\nCould be written:\n\n```rust\nuse std::path::PathBuf;\n\nlet mut x = PathBuf::from(\"/foo\");\nx.push(\"bar\");\nassert_eq!(x, PathBuf::from(\"/foo/bar\"));\n```",
     },
     Lint {
         name: "possible_missing_comma",
@@ -1427,6 +1654,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "possible missing comma in array",
         deprecation: None,
         module: "formatting",
+        docs: "**What it does:** Checks for possible missing comma in an array. It lints if\nan array element is a binary operator expression and it lies on two lines.\n\n**Why is this bad?** This could lead to unexpected results.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nlet a = &[\n    -1, -2, -3 // <= no comma here\n    -4, -5, -6\n];\n```",
     },
     Lint {
         name: "precedence",
@@ -1434,6 +1662,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "operations where precedence may be unclear",
         deprecation: None,
         module: "precedence",
+        docs: "**What it does:** Checks for operations where precedence may be unclear\nand suggests to add parentheses. Currently it catches the following:\n* mixed usage of arithmetic and bit shifting/combining operators without\nparentheses\n* a \"negative\" numeric literal (which is really a unary `-` followed by a\nnumeric literal)\n  followed by a method call\n\n**Why is this bad?** Not everyone knows the precedence of those operators by\nheart, so expressions like these may trip others trying to reason about the\ncode.\n\n**Known problems:** None.\n\n**Example:**\n* `1 << 2 + 3` equals 32, while `(1 << 2) + 3` equals 7\n* `-1i32.abs()` equals -1, while `(-1i32).abs()` equals 1",
     },
     Lint {
         name: "print_literal",
@@ -1441,6 +1670,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "printing a literal with a format string",
         deprecation: None,
         module: "write",
+        docs: "**What it does:** This lint warns about the use of literals as `print!`/`println!` args.\n\n**Why is this bad?** Using literals as `println!` args is inefficient\n(c.f., https://github.com/matthiaskrgr/rust-str-bench) and unnecessary\n(i.e., just put the literal in the format string)\n\n**Known problems:** Will also warn with macro calls as arguments that expand to literals\n-- e.g., `println!(\"{}\", env!(\"FOO\"))`.\n\n**Example:**\n```rust\nprintln!(\"{}\", \"foo\");\n```\nuse the literal without formatting:\n```rust\nprintln!(\"foo\");\n```",
     },
     Lint {
         name: "print_stdout",
@@ -1448,6 +1678,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "printing on stdout",
         deprecation: None,
         module: "write",
+        docs: "**What it does:** Checks for printing on *stdout*. The purpose of this lint\nis to catch debugging remnants.\n\n**Why is this bad?** People often print on *stdout* while debugging an\napplication and might forget to remove those prints afterward.\n\n**Known problems:** Only catches `print!` and `println!` calls.\n\n**Example:**\n```rust\nprintln!(\"Hello world!\");\n```",
     },
     Lint {
         name: "print_with_newline",
@@ -1455,6 +1686,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `print!()` with a format string that ends in a single newline",
         deprecation: None,
         module: "write",
+        docs: "**What it does:** This lint warns when you use `print!()` with a format\nstring that\nends in a newline.\n\n**Why is this bad?** You should use `println!()` instead, which appends the\nnewline.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let name = \"World\";\nprint!(\"Hello {}!\\n\", name);\n```\nuse println!() instead\n```rust\n# let name = \"World\";\nprintln!(\"Hello {}!\", name);\n```",
     },
     Lint {
         name: "println_empty_string",
@@ -1462,6 +1694,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `println!(\"\")` with an empty string",
         deprecation: None,
         module: "write",
+        docs: "**What it does:** This lint warns when you use `println!(\"\")` to\nprint a newline.\n\n**Why is this bad?** You should use `println!()`, which is simpler.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nprintln!(\"\");\n```",
     },
     Lint {
         name: "ptr_arg",
@@ -1469,6 +1702,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "fn arguments of the type `&Vec<...>` or `&String`, suggesting to use `&[...]` or `&str` instead, respectively",
         deprecation: None,
         module: "ptr",
+        docs: "**What it does:** This lint checks for function arguments of type `&String`\nor `&Vec` unless the references are mutable. It will also suggest you\nreplace `.clone()` calls with the appropriate `.to_owned()`/`to_string()`\ncalls.\n\n**Why is this bad?** Requiring the argument to be of the specific size\nmakes the function less useful for no benefit; slices in the form of `&[T]`\nor `&str` usually suffice and can be obtained from other types, too.\n\n**Known problems:** The lint does not follow data. So if you have an\nargument `x` and write `let y = x; y.clone()` the lint will not suggest\nchanging that `.clone()` to `.to_owned()`.\n\nOther functions called from this function taking a `&String` or `&Vec`\nargument may also fail to compile if you change the argument. Applying\nthis lint on them will fix the problem, but they may be in other crates.\n\nAlso there may be `fn(&Vec)`-typed references pointing to your function.\nIf you have them, you will get a compiler error after applying this lint's\nsuggestions. You then have the choice to undo your changes or change the\ntype of the reference.\n\nNote that if the function is part of your public interface, there may be\nother crates referencing it you may not be aware. Carefully deprecate the\nfunction before applying the lint suggestions in this case.\n\n**Example:**\n```ignore\nfn foo(&Vec<u32>) { .. }\n```",
     },
     Lint {
         name: "ptr_offset_with_cast",
@@ -1476,6 +1710,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "unneeded pointer offset cast",
         deprecation: None,
         module: "ptr_offset_with_cast",
+        docs: "**What it does:** Checks for usage of the `offset` pointer method with a `usize` casted to an\n`isize`.\n\n**Why is this bad?** If we’re always increasing the pointer address, we can avoid the numeric\ncast by using the `add` method instead.\n\n**Known problems:** None\n\n**Example:**\n```rust\nlet vec = vec![b'a', b'b', b'c'];\nlet ptr = vec.as_ptr();\nlet offset = 1_usize;\n\nunsafe {\n    ptr.offset(offset as isize);\n}\n```\n\nCould be written:\n\n```rust\nlet vec = vec![b'a', b'b', b'c'];\nlet ptr = vec.as_ptr();\nlet offset = 1_usize;\n\nunsafe {\n    ptr.add(offset);\n}\n```",
     },
     Lint {
         name: "pub_enum_variant_names",
@@ -1483,6 +1718,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "enums where all variants share a prefix/postfix",
         deprecation: None,
         module: "enum_variants",
+        docs: "**What it does:** Detects enumeration variants that are prefixed or suffixed\nby the same characters.\n\n**Why is this bad?** Enumeration variant names should specify their variant,\nnot repeat the enumeration name.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nenum Cake {\n    BlackForestCake,\n    HummingbirdCake,\n    BattenbergCake,\n}\n```",
     },
     Lint {
         name: "question_mark",
@@ -1490,6 +1726,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "checks for expressions that could be replaced by the question mark operator",
         deprecation: None,
         module: "question_mark",
+        docs: "**What it does:** Checks for expressions that could be replaced by the question mark operator.\n\n**Why is this bad?** Question mark usage is more idiomatic.\n\n**Known problems:** None\n\n**Example:**\n```ignore\nif option.is_none() {\n    return None;\n}\n```\n\nCould be written:\n\n```ignore\noption?;\n```",
     },
     Lint {
         name: "range_minus_one",
@@ -1497,6 +1734,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`x..=(y-1)` reads better as `x..y`",
         deprecation: None,
         module: "ranges",
+        docs: "**What it does:** Checks for inclusive ranges where 1 is subtracted from\nthe upper bound, e.g., `x..=(y-1)`.\n\n**Why is this bad?** The code is more readable with an exclusive range\nlike `x..y`.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nfor x..=(y-1) { .. }\n```\nCould be written as\n```rust,ignore\nfor x..y { .. }\n```",
     },
     Lint {
         name: "range_plus_one",
@@ -1504,6 +1742,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`x..(y+1)` reads better as `x..=y`",
         deprecation: None,
         module: "ranges",
+        docs: "**What it does:** Checks for exclusive ranges where 1 is added to the\nupper bound, e.g., `x..(y+1)`.\n\n**Why is this bad?** The code is more readable with an inclusive range\nlike `x..=y`.\n\n**Known problems:** Will add unnecessary pair of parentheses when the\nexpression is not wrapped in a pair but starts with a opening parenthesis\nand ends with a closing one.\nI.e., `let _ = (f()+1)..(f()+1)` results in `let _ = ((f()+1)..=f())`.\n\n**Example:**\n```rust,ignore\nfor x..(y+1) { .. }\n```\nCould be written as\n```rust,ignore\nfor x..=y { .. }\n```",
     },
     Lint {
         name: "range_zip_with_len",
@@ -1511,6 +1750,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "zipping iterator with a range when `enumerate()` would do",
         deprecation: None,
         module: "ranges",
+        docs: "**What it does:** Checks for zipping a collection with the range of\n`0.._.len()`.\n\n**Why is this bad?** The code is better expressed with `.enumerate()`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let x = vec![1];\nx.iter().zip(0..x.len());\n```\nCould be written as\n```rust\n# let x = vec![1];\nx.iter().enumerate();\n```",
     },
     Lint {
         name: "redundant_clone",
@@ -1518,6 +1758,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`clone()` of an owned value that is going to be dropped immediately",
         deprecation: None,
         module: "redundant_clone",
+        docs: "**What it does:** Checks for a redudant `clone()` (and its relatives) which clones an owned\nvalue that is going to be dropped without further use.\n\n**Why is this bad?** It is not always possible for the compiler to eliminate useless\nallocations and deallocations generated by redundant `clone()`s.\n\n**Known problems:** Suggestions made by this lint could require NLL to be enabled.\n\n**Example:**\n```rust\n# use std::path::Path;\n# #[derive(Clone)]\n# struct Foo;\n# impl Foo {\n#     fn new() -> Self { Foo {} }\n# }\n# fn call(x: Foo) {}\n{\n    let x = Foo::new();\n    call(x.clone());\n    call(x.clone()); // this can just pass `x`\n}\n\n[\"lorem\", \"ipsum\"].join(\" \").to_string();\n\nPath::new(\"/a/b\").join(\"c\").to_path_buf();\n```",
     },
     Lint {
         name: "redundant_closure",
@@ -1525,6 +1766,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "redundant closures, i.e., `|a| foo(a)` (which can be written as just `foo`)",
         deprecation: None,
         module: "eta_reduction",
+        docs: "**What it does:** Checks for closures which just call another function where\nthe function can be called directly. `unsafe` functions or calls where types\nget adjusted are ignored.\n\n**Why is this bad?** Needlessly creating a closure adds code for no benefit\nand gives the optimizer more work.\n\n**Known problems:** If creating the closure inside the closure has a side-\neffect then moving the closure creation out will change when that side-\neffect runs.\nSee rust-lang/rust-clippy#1439 for more details.\n\n**Example:**\n```rust,ignore\nxs.map(|x| foo(x))\n```\nwhere `foo(_)` is a plain function that takes the exact argument type of\n`x`.",
     },
     Lint {
         name: "redundant_closure_call",
@@ -1532,6 +1774,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "throwaway closures called in the expression they are defined",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Detects closures called in the same expression where they\nare defined.\n\n**Why is this bad?** It is unnecessarily adding to the expression's\ncomplexity.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\n(|| 42)()\n```",
     },
     Lint {
         name: "redundant_closure_for_method_calls",
@@ -1539,6 +1782,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "redundant closures for method calls",
         deprecation: None,
         module: "eta_reduction",
+        docs: "**What it does:** Checks for closures which only invoke a method on the closure\nargument and can be replaced by referencing the method directly.\n\n**Why is this bad?** It's unnecessary to create the closure.\n\n**Known problems:** rust-lang/rust-clippy#3071, rust-lang/rust-clippy#4002,\nrust-lang/rust-clippy#3942\n\n\n**Example:**\n```rust,ignore\nSome('a').map(|s| s.to_uppercase());\n```\nmay be rewritten as\n```rust,ignore\nSome('a').map(char::to_uppercase);\n```",
     },
     Lint {
         name: "redundant_field_names",
@@ -1546,6 +1790,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "checks for fields in struct literals where shorthands could be used",
         deprecation: None,
         module: "redundant_field_names",
+        docs: "**What it does:** Checks for fields in struct literals where shorthands\ncould be used.\n\n**Why is this bad?** If the field and variable names are the same,\nthe field name is redundant.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet bar: u8 = 123;\n\nstruct Foo {\n    bar: u8,\n}\n\nlet foo = Foo { bar: bar };\n```\nthe last line can be simplified to\n```ignore\nlet foo = Foo { bar };\n```",
     },
     Lint {
         name: "redundant_pattern",
@@ -1553,6 +1798,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `name @ _` in a pattern",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Checks for patterns in the form `name @ _`.\n\n**Why is this bad?** It's almost always more readable to just use direct\nbindings.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let v = Some(\"abc\");\n\nmatch v {\n    Some(x) => (),\n    y @ _ => (), // easier written as `y`,\n}\n```",
     },
     Lint {
         name: "redundant_pattern_matching",
@@ -1560,6 +1806,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use the proper utility function avoiding an `if let`",
         deprecation: None,
         module: "redundant_pattern_matching",
+        docs: "**What it does:** Lint for redundant pattern matching over `Result` or\n`Option`\n\n**Why is this bad?** It's more concise and clear to just use the proper\nutility function\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\nif let Ok(_) = Ok::<i32, i32>(42) {}\nif let Err(_) = Err::<i32, i32>(42) {}\nif let None = None::<()> {}\nif let Some(_) = Some(42) {}\nmatch Ok::<i32, i32>(42) {\n    Ok(_) => true,\n    Err(_) => false,\n};\n```\n\nThe more idiomatic use would be:\n\n```rust\nif Ok::<i32, i32>(42).is_ok() {}\nif Err::<i32, i32>(42).is_err() {}\nif None::<()>.is_none() {}\nif Some(42).is_some() {}\nOk::<i32, i32>(42).is_ok();\n```",
     },
     Lint {
         name: "redundant_static_lifetimes",
@@ -1567,6 +1814,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Using explicit `\'static` lifetime for constants or statics when elision rules would allow omitting them.",
         deprecation: None,
         module: "redundant_static_lifetimes",
+        docs: "**What it does:** Checks for constants and statics with an explicit `'static` lifetime.\n\n**Why is this bad?** Adding `'static` to every reference can create very\ncomplicated types.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nconst FOO: &'static [(&'static str, &'static str, fn(&Bar) -> bool)] =\n&[...]\nstatic FOO: &'static [(&'static str, &'static str, fn(&Bar) -> bool)] =\n&[...]\n```\nThis code can be rewritten as\n```ignore\n const FOO: &[(&str, &str, fn(&Bar) -> bool)] = &[...]\n static FOO: &[(&str, &str, fn(&Bar) -> bool)] = &[...]\n```",
     },
     Lint {
         name: "ref_in_deref",
@@ -1574,6 +1822,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Use of reference in auto dereference expression.",
         deprecation: None,
         module: "reference",
+        docs: "**What it does:** Checks for references in expressions that use\nauto dereference.\n\n**Why is this bad?** The reference is a no-op and is automatically\ndereferenced by the compiler and makes the code less clear.\n\n**Example:**\n```rust\nstruct Point(u32, u32);\nlet point = Point(30, 20);\nlet x = (&point).0;\n```",
     },
     Lint {
         name: "regex_macro",
@@ -1581,6 +1830,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of `regex!(_)` instead of `Regex::new(_)`",
         deprecation: None,
         module: "regex",
+        docs: "**What it does:** Checks for usage of `regex!(_)` which (as of now) is\nusually slower than `Regex::new(_)` unless called in a loop (which is a bad\nidea anyway).\n\n**Why is this bad?** Performance, at least for now. The macro version is\nlikely to catch up long-term, but for now the dynamic version is faster.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nregex!(\"foo|bar\")\n```",
     },
     Lint {
         name: "replace_consts",
@@ -1588,6 +1838,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Lint usages of standard library `const`s that could be replaced by `const fn`s",
         deprecation: None,
         module: "replace_consts",
+        docs: "**What it does:** Checks for usage of `ATOMIC_X_INIT`, `ONCE_INIT`, and\n`uX/iX::MIN/MAX`.\n\n**Why is this bad?** `const fn`s exist\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use core::sync::atomic::{ATOMIC_ISIZE_INIT, AtomicIsize};\nstatic FOO: AtomicIsize = ATOMIC_ISIZE_INIT;\n```\n\nCould be written:\n\n```rust\n# use core::sync::atomic::AtomicIsize;\nstatic FOO: AtomicIsize = AtomicIsize::new(0);\n```",
     },
     Lint {
         name: "result_map_unit_fn",
@@ -1595,6 +1846,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `result.map(f)`, where f is a function or closure that returns ()",
         deprecation: None,
         module: "map_unit_fn",
+        docs: "**What it does:** Checks for usage of `result.map(f)` where f is a function\nor closure that returns the unit type.\n\n**Why is this bad?** Readability, this can be written more clearly with\nan if let statement\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\n# fn do_stuff() -> Result<String, String> { Ok(String::new()) }\n# fn log_err_msg(foo: String) -> Result<String, String> { Ok(foo) }\n# fn format_msg(foo: String) -> String { String::new() }\nlet x: Result<String, String> = do_stuff();\nx.map(log_err_msg);\n# let x: Result<String, String> = do_stuff();\nx.map(|msg| log_err_msg(format_msg(msg)));\n```\n\nThe correct use would be:\n\n```rust\n# fn do_stuff() -> Result<String, String> { Ok(String::new()) }\n# fn log_err_msg(foo: String) -> Result<String, String> { Ok(foo) }\n# fn format_msg(foo: String) -> String { String::new() }\nlet x: Result<String, String> = do_stuff();\nif let Ok(msg) = x {\n    log_err_msg(msg);\n};\n# let x: Result<String, String> = do_stuff();\nif let Ok(msg) = x {\n    log_err_msg(format_msg(msg));\n};\n```",
     },
     Lint {
         name: "result_map_unwrap_or_else",
@@ -1602,6 +1854,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Result.map(f).unwrap_or_else(g)`, which is more succinctly expressed as `.ok().map_or_else(g, f)`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `result.map(_).unwrap_or_else(_)`.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`result.ok().map_or_else(_, _)`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let x: Result<usize, ()> = Ok(1);\n# fn some_function(foo: ()) -> usize { 1 }\nx.map(|a| a + 1).unwrap_or_else(some_function);\n```",
     },
     Lint {
         name: "result_unwrap_used",
@@ -1609,6 +1862,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `Result.unwrap()`, which might be better handled",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for `.unwrap()` calls on `Result`s.\n\n**Why is this bad?** `result.unwrap()` will let the thread panic on `Err`\nvalues. Normally, you want to implement more sophisticated error handling,\nand propagate errors upwards with `try!`.\n\nEven if you want to panic on errors, not all `Error`s implement good\nmessages on display. Therefore, it may be beneficial to look at the places\nwhere they may get displayed. Activate this lint to do just that.\n\n**Known problems:** None.\n\n**Example:**\nUsing unwrap on an `Option`:\n\n```rust\nlet res: Result<usize, ()> = Ok(1);\nres.unwrap();\n```\n\nBetter:\n\n```rust\nlet res: Result<usize, ()> = Ok(1);\nres.expect(\"more helpful message\");\n```",
     },
     Lint {
         name: "reverse_range_loop",
@@ -1616,6 +1870,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "iteration over an empty range, such as `10..0` or `5..5`",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for loops over ranges `x..y` where both `x` and `y`\nare constant and `x` is greater or equal to `y`, unless the range is\nreversed or has a negative `.step_by(_)`.\n\n**Why is it bad?** Such loops will either be skipped or loop until\nwrap-around (in debug code, this may `panic!()`). Both options are probably\nnot intended.\n\n**Known problems:** The lint cannot catch loops over dynamically defined\nranges. Doing this would require simulating all possible inputs and code\npaths through the program, which would be complex and error-prone.\n\n**Example:**\n```ignore\nfor x in 5..10 - 5 {\n    ..\n} // oops, stray `-`\n```",
     },
     Lint {
         name: "search_is_some",
@@ -1623,6 +1878,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using an iterator search followed by `is_some()`, which is more succinctly expressed as a call to `any()`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for an iterator search (such as `find()`,\n`position()`, or `rposition()`) followed by a call to `is_some()`.\n\n**Why is this bad?** Readability, this can be written more concisely as\n`_.any(_)`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# let vec = vec![1];\nvec.iter().find(|x| **x == 0).is_some();\n```\nCould be written as\n```rust\n# let vec = vec![1];\nvec.iter().any(|x| *x == 0);\n```",
     },
     Lint {
         name: "serde_api_misuse",
@@ -1630,6 +1886,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "various things that will negatively affect your serde experience",
         deprecation: None,
         module: "serde_api",
+        docs: "**What it does:** Checks for mis-uses of the serde API.\n\n**Why is this bad?** Serde is very finnicky about how its API should be\nused, but the type system can't be used to enforce it (yet?).\n\n**Known problems:** None.\n\n**Example:** Implementing `Visitor::visit_string` but not\n`Visitor::visit_str`.",
     },
     Lint {
         name: "shadow_reuse",
@@ -1637,6 +1894,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "rebinding a name to an expression that re-uses the original value, e.g., `let x = x + 1`",
         deprecation: None,
         module: "shadow",
+        docs: "**What it does:** Checks for bindings that shadow other bindings already in\nscope, while reusing the original value.\n\n**Why is this bad?** Not too much, in fact it's a common pattern in Rust\ncode. Still, some argue that name shadowing like this hurts readability,\nbecause a value may be bound to different things depending on position in\nthe code.\n\n**Known problems:** This lint, as the other shadowing related lints,\ncurrently only catches very simple patterns.\n\n**Example:**\n```rust\nlet x = 2;\nlet x = x + 1;\n```\nuse different variable name:\n```rust\nlet x = 2;\nlet y = x + 1;\n```",
     },
     Lint {
         name: "shadow_same",
@@ -1644,6 +1902,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "rebinding a name to itself, e.g., `let mut x = &mut x`",
         deprecation: None,
         module: "shadow",
+        docs: "**What it does:** Checks for bindings that shadow other bindings already in\nscope, while just changing reference level or mutability.\n\n**Why is this bad?** Not much, in fact it's a very common pattern in Rust\ncode. Still, some may opt to avoid it in their code base, they can set this\nlint to `Warn`.\n\n**Known problems:** This lint, as the other shadowing related lints,\ncurrently only catches very simple patterns.\n\n**Example:**\n```rust\n# let x = 1;\nlet x = &x;\n```",
     },
     Lint {
         name: "shadow_unrelated",
@@ -1651,6 +1910,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "rebinding a name without even using the original value",
         deprecation: None,
         module: "shadow",
+        docs: "**What it does:** Checks for bindings that shadow other bindings already in\nscope, either without a initialization or with one that does not even use\nthe original value.\n\n**Why is this bad?** Name shadowing can hurt readability, especially in\nlarge code bases, because it is easy to lose track of the active binding at\nany place in the code. This can be alleviated by either giving more specific\nnames to bindings or introducing more scopes to contain the bindings.\n\n**Known problems:** This lint, as the other shadowing related lints,\ncurrently only catches very simple patterns.\n\n**Example:**\n```rust\n# let y = 1;\n# let z = 2;\nlet x = y;\nlet x = z; // shadows the earlier binding\n```",
     },
     Lint {
         name: "short_circuit_statement",
@@ -1658,6 +1918,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a short circuit boolean condition as a statement",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Checks for the use of short circuit boolean conditions as\na\nstatement.\n\n**Why is this bad?** Using a short circuit boolean condition as a statement\nmay hide the fact that the second part is executed or not depending on the\noutcome of the first part.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nf() && g(); // We should write `if f() { g(); }`.\n```",
     },
     Lint {
         name: "should_implement_trait",
@@ -1665,6 +1926,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "defining a method that should be implementing a std trait",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for methods that should live in a trait\nimplementation of a `std` trait (see [llogiq's blog\npost](http://llogiq.github.io/2015/07/30/traits.html) for further\ninformation) instead of an inherent implementation.\n\n**Why is this bad?** Implementing the traits improve ergonomics for users of\nthe code, often with very little cost. Also people seeing a `mul(...)`\nmethod\nmay expect `*` to work equally, so you should have good reason to disappoint\nthem.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nstruct X;\nimpl X {\n    fn add(&self, other: &X) -> X {\n        ..\n    }\n}\n```",
     },
     Lint {
         name: "similar_names",
@@ -1672,6 +1934,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "similarly named items and bindings",
         deprecation: None,
         module: "non_expressive_names",
+        docs: "**What it does:** Checks for names that are very similar and thus confusing.\n\n**Why is this bad?** It's hard to distinguish between names that differ only\nby a single character.\n\n**Known problems:** None?\n\n**Example:**\n```ignore\nlet checked_exp = something;\nlet checked_expr = something_else;\n```",
     },
     Lint {
         name: "single_char_pattern",
@@ -1679,6 +1942,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a single-character str where a char could be used, e.g., `_.split(\"x\")`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for string methods that receive a single-character\n`str` as an argument, e.g., `_.split(\"x\")`.\n\n**Why is this bad?** Performing these methods using a `char` is faster than\nusing a `str`.\n\n**Known problems:** Does not catch multi-byte unicode characters.\n\n**Example:**\n`_.split(\"x\")` could be `_.split('x')`",
     },
     Lint {
         name: "single_match",
@@ -1686,6 +1950,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a match statement with a single nontrivial arm (i.e., where the other arm is `_ => {}`) instead of `if let`",
         deprecation: None,
         module: "matches",
+        docs: "**What it does:** Checks for matches with a single arm where an `if let`\nwill usually suffice.\n\n**Why is this bad?** Just readability – `if let` nests less than a `match`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# fn bar(stool: &str) {}\n# let x = Some(\"abc\");\nmatch x {\n    Some(ref foo) => bar(foo),\n    _ => (),\n}\n```",
     },
     Lint {
         name: "single_match_else",
@@ -1693,6 +1958,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a match statement with two arms where the second arm\'s pattern is a placeholder instead of a specific match pattern",
         deprecation: None,
         module: "matches",
+        docs: "**What it does:** Checks for matches with two arms where an `if let else` will\nusually suffice.\n\n**Why is this bad?** Just readability – `if let` nests less than a `match`.\n\n**Known problems:** Personal style preferences may differ.\n\n**Example:**\n\nUsing `match`:\n\n```rust\n# fn bar(foo: &usize) {}\n# let other_ref: usize = 1;\n# let x: Option<&usize> = Some(&1);\nmatch x {\n    Some(ref foo) => bar(foo),\n    _ => bar(&other_ref),\n}\n```\n\nUsing `if let` with `else`:\n\n```rust\n# fn bar(foo: &usize) {}\n# let other_ref: usize = 1;\n# let x: Option<&usize> = Some(&1);\nif let Some(ref foo) = x {\n    bar(foo);\n} else {\n    bar(&other_ref);\n}\n```",
     },
     Lint {
         name: "slow_vector_initialization",
@@ -1700,6 +1966,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "slow vector initialization",
         deprecation: None,
         module: "slow_vector_initialization",
+        docs: "**What it does:** Checks slow zero-filled vector initialization\n\n**Why is this bad?** These structures are non-idiomatic and less efficient than simply using\n`vec![0; len]`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use core::iter::repeat;\n# let len = 4;\nlet mut vec1 = Vec::with_capacity(len);\nvec1.resize(len, 0);\n\nlet mut vec2 = Vec::with_capacity(len);\nvec2.extend(repeat(0).take(len))\n```",
     },
     Lint {
         name: "string_add",
@@ -1707,6 +1974,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `x + ..` where x is a `String` instead of `push_str()`",
         deprecation: None,
         module: "strings",
+        docs: "**What it does:** Checks for all instances of `x + _` where `x` is of type\n`String`, but only if [`string_add_assign`](#string_add_assign) does *not*\nmatch.\n\n**Why is this bad?** It's not bad in and of itself. However, this particular\n`Add` implementation is asymmetric (the other operand need not be `String`,\nbut `x` does), while addition as mathematically defined is symmetric, also\nthe `String::push_str(_)` function is a perfectly good replacement.\nTherefore, some dislike it and wish not to have it in their code.\n\nThat said, other people think that string addition, having a long tradition\nin other languages is actually fine, which is why we decided to make this\nparticular lint `allow` by default.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\nlet x = \"Hello\".to_owned();\nx + \", World\";\n```",
     },
     Lint {
         name: "string_add_assign",
@@ -1714,6 +1982,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `x = x + ..` where x is a `String` instead of `push_str()`",
         deprecation: None,
         module: "strings",
+        docs: "**What it does:** Checks for string appends of the form `x = x + y` (without\n`let`!).\n\n**Why is this bad?** It's not really bad, but some people think that the\n`.push_str(_)` method is more readable.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\nlet mut x = \"Hello\".to_owned();\nx = x + \", World\";\n```",
     },
     Lint {
         name: "string_extend_chars",
@@ -1721,6 +1990,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `x.extend(s.chars())` where s is a `&str` or `String`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for the use of `.extend(s.chars())` where s is a\n`&str` or `String`.\n\n**Why is this bad?** `.push_str(s)` is clearer\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet abc = \"abc\";\nlet def = String::from(\"def\");\nlet mut s = String::new();\ns.extend(abc.chars());\ns.extend(def.chars());\n```\nThe correct use would be:\n```rust\nlet abc = \"abc\";\nlet def = String::from(\"def\");\nlet mut s = String::new();\ns.push_str(abc);\ns.push_str(&def);\n```",
     },
     Lint {
         name: "string_lit_as_bytes",
@@ -1728,6 +1998,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "calling `as_bytes` on a string literal instead of using a byte string literal",
         deprecation: None,
         module: "strings",
+        docs: "**What it does:** Checks for the `as_bytes` method called on string literals\nthat contain only ASCII characters.\n\n**Why is this bad?** Byte string literals (e.g., `b\"foo\"`) can be used\ninstead. They are shorter but less discoverable than `as_bytes()`.\n\n**Known Problems:** None.\n\n**Example:**\n```rust\nlet bs = \"a byte string\".as_bytes();\n```",
     },
     Lint {
         name: "suspicious_arithmetic_impl",
@@ -1735,6 +2006,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "suspicious use of operators in impl of arithmetic trait",
         deprecation: None,
         module: "suspicious_trait_impl",
+        docs: "**What it does:** Lints for suspicious operations in impls of arithmetic operators, e.g.\nsubtracting elements in an Add impl.\n\n**Why this is bad?** This is probably a typo or copy-and-paste error and not intended.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nimpl Add for Foo {\n    type Output = Foo;\n\n    fn add(self, other: Foo) -> Foo {\n        Foo(self.0 - other.0)\n    }\n}\n```",
     },
     Lint {
         name: "suspicious_assignment_formatting",
@@ -1742,6 +2014,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "suspicious formatting of `*=`, `-=` or `!=`",
         deprecation: None,
         module: "formatting",
+        docs: "**What it does:** Checks for use of the non-existent `=*`, `=!` and `=-`\noperators.\n\n**Why is this bad?** This is either a typo of `*=`, `!=` or `-=` or\nconfusing.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\na =- 42; // confusing, should it be `a -= 42` or `a = -42`?\n```",
     },
     Lint {
         name: "suspicious_else_formatting",
@@ -1749,6 +2022,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "suspicious formatting of `else`",
         deprecation: None,
         module: "formatting",
+        docs: "**What it does:** Checks for formatting of `else`. It lints if the `else`\nis followed immediately by a newline or the `else` seems to be missing.\n\n**Why is this bad?** This is probably some refactoring remnant, even if the\ncode is correct, it might look confusing.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nif foo {\n} { // looks like an `else` is missing here\n}\n\nif foo {\n} if bar { // looks like an `else` is missing here\n}\n\nif foo {\n} else\n\n{ // this is the `else` block of the previous `if`, but should it be?\n}\n\nif foo {\n} else\n\nif bar { // this is the `else` block of the previous `if`, but should it be?\n}\n```",
     },
     Lint {
         name: "suspicious_map",
@@ -1756,6 +2030,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "suspicious usage of map",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for calls to `map` followed by a `count`.\n\n**Why is this bad?** It looks suspicious. Maybe `map` was confused with `filter`.\nIf the `map` call is intentional, this should be rewritten.\n\n**Known problems:** None\n\n**Example:**\n\n```rust\nlet _ = (0..3).map(|x| x + 2).count();\n```",
     },
     Lint {
         name: "suspicious_op_assign_impl",
@@ -1763,6 +2038,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "suspicious use of operators in impl of OpAssign trait",
         deprecation: None,
         module: "suspicious_trait_impl",
+        docs: "**What it does:** Lints for suspicious operations in impls of OpAssign, e.g.\nsubtracting elements in an AddAssign impl.\n\n**Why this is bad?** This is probably a typo or copy-and-paste error and not intended.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nimpl AddAssign for Foo {\n    fn add_assign(&mut self, other: Foo) {\n        *self = *self - other;\n    }\n}\n```",
     },
     Lint {
         name: "temporary_assignment",
@@ -1770,6 +2046,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "assignments to temporaries",
         deprecation: None,
         module: "temporary_assignment",
+        docs: "**What it does:** Checks for construction of a structure or tuple just to\nassign a value in it.\n\n**Why is this bad?** Readability. If the structure is only created to be\nupdated, why not write the structure you want in the first place?\n\n**Known problems:** None.\n\n**Example:**\n```rust\n(0, 0).0 = 1\n```",
     },
     Lint {
         name: "temporary_cstring_as_ptr",
@@ -1777,6 +2054,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "getting the inner pointer of a temporary `CString`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for getting the inner pointer of a temporary\n`CString`.\n\n**Why is this bad?** The inner pointer of a `CString` is only valid as long\nas the `CString` is alive.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nlet c_str = CString::new(\"foo\").unwrap().as_ptr();\nunsafe {\n    call_some_ffi_func(c_str);\n}\n```\nHere `c_str` point to a freed address. The correct use would be:\n```rust,ignore\nlet c_str = CString::new(\"foo\").unwrap();\nunsafe {\n    call_some_ffi_func(c_str.as_ptr());\n}\n```",
     },
     Lint {
         name: "too_many_arguments",
@@ -1784,6 +2062,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "functions with too many arguments",
         deprecation: None,
         module: "functions",
+        docs: "**What it does:** Checks for functions with too many parameters.\n\n**Why is this bad?** Functions with lots of parameters are considered bad\nstyle and reduce readability (“what does the 5th parameter mean?”). Consider\ngrouping some parameters into a new type.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# struct Color;\nfn foo(x: u32, y: u32, name: &str, c: Color, w: f32, h: f32, a: f32, b: f32) {\n    // ..\n}\n```",
     },
     Lint {
         name: "too_many_lines",
@@ -1791,6 +2070,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "functions with too many lines",
         deprecation: None,
         module: "functions",
+        docs: "**What it does:** Checks for functions with a large amount of lines.\n\n**Why is this bad?** Functions with a lot of lines are harder to understand\ndue to having to look at a larger amount of code to understand what the\nfunction is doing. Consider splitting the body of the function into\nmultiple functions.\n\n**Known problems:** None.\n\n**Example:**\n``` rust\nfn im_too_long() {\nprintln!(\"\");\n// ... 100 more LoC\nprintln!(\"\");\n}\n```",
     },
     Lint {
         name: "toplevel_ref_arg",
@@ -1798,6 +2078,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "an entire binding declared as `ref`, in a function argument or a `let` statement",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Checks for function arguments and let bindings denoted as\n`ref`.\n\n**Why is this bad?** The `ref` declaration makes the function take an owned\nvalue, but turns the argument into a reference (which means that the value\nis destroyed when exiting the function). This adds not much value: either\ntake a reference type, or take an owned value and create references in the\nbody.\n\nFor let bindings, `let x = &foo;` is preferred over `let ref x = foo`. The\ntype of `x` is more obvious with the former.\n\n**Known problems:** If the argument is dereferenced within the function,\nremoving the `ref` will lead to errors. This can be fixed by removing the\ndereferences, e.g., changing `*x` to `x` within the function.\n\n**Example:**\n```rust\nfn foo(ref x: u8) -> bool {\n    true\n}\n```",
     },
     Lint {
         name: "transmute_bytes_to_str",
@@ -1805,6 +2086,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes from a `&[u8]` to a `&str`",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes from a `&[u8]` to a `&str`.\n\n**Why is this bad?** Not every byte slice is a valid UTF-8 string.\n\n**Known problems:**\n- [`from_utf8`] which this lint suggests using is slower than `transmute`\nas it needs to validate the input.\nIf you are certain that the input is always a valid UTF-8,\nuse [`from_utf8_unchecked`] which is as fast as `transmute`\nbut has a semantically meaningful name.\n- You might want to handle errors returned from [`from_utf8`] instead of calling `unwrap`.\n\n[`from_utf8`]: https://doc.rust-lang.org/std/str/fn.from_utf8.html\n[`from_utf8_unchecked`]: https://doc.rust-lang.org/std/str/fn.from_utf8_unchecked.html\n\n**Example:**\n```rust\nlet b: &[u8] = &[1_u8, 2_u8];\n\n// Bad\nunsafe {\n    let _: &str = std::mem::transmute(b); // where b: &[u8]\n}\n\n// should be:\nlet _ = std::str::from_utf8(b).unwrap();\n```",
     },
     Lint {
         name: "transmute_int_to_bool",
@@ -1812,6 +2094,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes from an integer to a `bool`",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes from an integer to a `bool`.\n\n**Why is this bad?** This might result in an invalid in-memory representation of a `bool`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet x = 1_u8;\n\n// Bad\nunsafe {\n    let _: bool = std::mem::transmute(x); // where x: u8\n}\n\n// should be:\nlet _: bool = x != 0;\n```",
     },
     Lint {
         name: "transmute_int_to_char",
@@ -1819,6 +2102,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes from an integer to a `char`",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes from an integer to a `char`.\n\n**Why is this bad?** Not every integer is a Unicode scalar value.\n\n**Known problems:**\n- [`from_u32`] which this lint suggests using is slower than `transmute`\nas it needs to validate the input.\nIf you are certain that the input is always a valid Unicode scalar value,\nuse [`from_u32_unchecked`] which is as fast as `transmute`\nbut has a semantically meaningful name.\n- You might want to handle `None` returned from [`from_u32`] instead of calling `unwrap`.\n\n[`from_u32`]: https://doc.rust-lang.org/std/char/fn.from_u32.html\n[`from_u32_unchecked`]: https://doc.rust-lang.org/std/char/fn.from_u32_unchecked.html\n\n**Example:**\n```rust\nlet x = 1_u32;\n\n// Bad\nunsafe {\n    let _: char = std::mem::transmute(x); // where x: u32\n}\n\n// should be:\nlet _ = std::char::from_u32(x).unwrap();\n```",
     },
     Lint {
         name: "transmute_int_to_float",
@@ -1826,6 +2110,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes from an integer to a float",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes from an integer to a float.\n\n**Why is this bad?** Transmutes are dangerous and error-prone, whereas `from_bits` is intuitive\nand safe.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nunsafe {\n    let _: f32 = std::mem::transmute(1_u32); // where x: u32\n}\n\n// should be:\nlet _: f32 = f32::from_bits(1_u32);\n```",
     },
     Lint {
         name: "transmute_ptr_to_ptr",
@@ -1833,6 +2118,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes from a pointer to a pointer / a reference to a reference",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes from a pointer to a pointer, or\nfrom a reference to a reference.\n\n**Why is this bad?** Transmutes are dangerous, and these can instead be\nwritten as casts.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet ptr = &1u32 as *const u32;\n\n// Bad\nunsafe {\n    // pointer-to-pointer transmute\n    let _: *const f32 = std::mem::transmute(ptr);\n    // ref-ref transmute\n    let _: &f32 = std::mem::transmute(&1u32);\n}\n// These can be respectively written:\nlet _ = ptr as *const f32;\nlet _ = unsafe{ &*(&1u32 as *const u32 as *const f32) };\n```",
     },
     Lint {
         name: "transmute_ptr_to_ref",
@@ -1840,6 +2126,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes from a pointer to a reference type",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes from a pointer to a reference.\n\n**Why is this bad?** This can always be rewritten with `&` and `*`.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nunsafe {\n    let _: &T = std::mem::transmute(p); // where p: *const T\n}\n\n// can be written:\nlet _: &T = &*p;\n```",
     },
     Lint {
         name: "transmuting_null",
@@ -1847,6 +2134,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes from a null pointer to a reference, which is undefined behavior",
         deprecation: None,
         module: "transmuting_null",
+        docs: "**What it does:** Checks for transmute calls which would receive a null pointer.\n\n**Why is this bad?** Transmuting a null pointer is undefined behavior.\n\n**Known problems:** Not all cases can be detected at the moment of this writing.\nFor example, variables which hold a null pointer and are then fed to a `transmute`\ncall, aren't detectable yet.\n\n**Example:**\n```rust\nlet null_ref: &u64 = unsafe { std::mem::transmute(0 as *const u64) };\n```",
     },
     Lint {
         name: "trivial_regex",
@@ -1854,6 +2142,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "trivial regular expressions",
         deprecation: None,
         module: "regex",
+        docs: "**What it does:** Checks for trivial [regex](https://crates.io/crates/regex)\ncreation (with `Regex::new`, `RegexBuilder::new` or `RegexSet::new`).\n\n**Why is this bad?** Matching the regex can likely be replaced by `==` or\n`str::starts_with`, `str::ends_with` or `std::contains` or other `str`\nmethods.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nRegex::new(\"^foobar\")\n```",
     },
     Lint {
         name: "trivially_copy_pass_by_ref",
@@ -1861,6 +2150,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "functions taking small copyable arguments by reference",
         deprecation: None,
         module: "trivially_copy_pass_by_ref",
+        docs: "**What it does:** Checks for functions taking arguments by reference, where\nthe argument type is `Copy` and small enough to be more efficient to always\npass by value.\n\n**Why is this bad?** In many calling conventions instances of structs will\nbe passed through registers if they fit into two or less general purpose\nregisters.\n\n**Known problems:** This lint is target register size dependent, it is\nlimited to 32-bit to try and reduce portability problems between 32 and\n64-bit, but if you are compiling for 8 or 16-bit targets then the limit\nwill be different.\n\nThe configuration option `trivial_copy_size_limit` can be set to override\nthis limit for a project.\n\nThis lint attempts to allow passing arguments by reference if a reference\nto that argument is returned. This is implemented by comparing the lifetime\nof the argument and return value for equality. However, this can cause\nfalse positives in cases involving multiple lifetimes that are bounded by\neach other.\n\n**Example:**\n\n```rust\n// Bad\nfn foo(v: &u32) {}\n```\n\n```rust\n// Better\nfn foo(v: u32) {}\n```",
     },
     Lint {
         name: "try_err",
@@ -1868,6 +2158,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "return errors explicitly rather than hiding them behind a `?`",
         deprecation: None,
         module: "try_err",
+        docs: "**What it does:** Checks for usages of `Err(x)?`.\n\n**Why is this bad?** The `?` operator is designed to allow calls that\ncan fail to be easily chained. For example, `foo()?.bar()` or\n`foo(bar()?)`. Because `Err(x)?` can't be used that way (it will\nalways return), it is more clear to write `return Err(x)`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nfn foo(fail: bool) -> Result<i32, String> {\n    if fail {\n      Err(\"failed\")?;\n    }\n    Ok(0)\n}\n```\nCould be written:\n\n```rust\nfn foo(fail: bool) -> Result<i32, String> {\n    if fail {\n      return Err(\"failed\".into());\n    }\n    Ok(0)\n}\n```",
     },
     Lint {
         name: "type_complexity",
@@ -1875,6 +2166,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of very complex types that might be better factored into `type` definitions",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for types used in structs, parameters and `let`\ndeclarations above a certain complexity threshold.\n\n**Why is this bad?** Too complex types make the code less readable. Consider\nusing a `type` definition to simplify them.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use std::rc::Rc;\nstruct Foo {\n    inner: Rc<Vec<Vec<Box<(u32, u32, u32, u32)>>>>,\n}\n```",
     },
     Lint {
         name: "type_repetition_in_bounds",
@@ -1882,6 +2174,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Types are repeated unnecessary in trait bounds use `+` instead of using `T: _, T: _`",
         deprecation: None,
         module: "trait_bounds",
+        docs: "**What it does:** This lint warns about unnecessary type repetitions in trait bounds\n\n**Why is this bad?** Repeating the type for every bound makes the code\nless readable than combining the bounds\n\n**Example:**\n```rust\npub fn foo<T>(t: T) where T: Copy, T: Clone {}\n```\n\nCould be written as:\n\n```rust\npub fn foo<T>(t: T) where T: Copy + Clone {}\n```",
     },
     Lint {
         name: "unicode_not_nfc",
@@ -1889,6 +2182,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a Unicode literal not in NFC normal form (see [Unicode tr15](http://www.unicode.org/reports/tr15/) for further information)",
         deprecation: None,
         module: "unicode",
+        docs: "**What it does:** Checks for string literals that contain Unicode in a form\nthat is not equal to its\n[NFC-recomposition](http://www.unicode.org/reports/tr15/#Norm_Forms).\n\n**Why is this bad?** If such a string is compared to another, the results\nmay be surprising.\n\n**Known problems** None.\n\n**Example:** You may not see it, but \"a\u{300}\"\" and \"à\"\" aren't the same string. The\nformer when escaped is actually `\"a\\u{300}\"` while the latter is `\"\\u{e0}\"`.",
     },
     Lint {
         name: "unimplemented",
@@ -1896,6 +2190,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`unimplemented!` should not be present in production code",
         deprecation: None,
         module: "panic_unimplemented",
+        docs: "**What it does:** Checks for usage of `unimplemented!`.\n\n**Why is this bad?** This macro should not be present in production code\n\n**Known problems:** None.\n\n**Example:**\n```no_run\nunimplemented!();\n```",
     },
     Lint {
         name: "uninit_assumed_init",
@@ -1903,6 +2198,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`MaybeUninit::uninit().assume_init()`",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for `MaybeUninit::uninit().assume_init()`.\n\n**Why is this bad?** For most types, this is undefined behavior.\n\n**Known problems:** For now, we accept empty tuples and tuples / arrays\nof `MaybeUninit`. There may be other types that allow uninitialized\ndata, but those are not yet rigorously defined.\n\n**Example:**\n\n```rust\n// Beware the UB\nuse std::mem::MaybeUninit;\n\nlet _: usize = unsafe { MaybeUninit::uninit().assume_init() };\n```\n\nNote that the following is OK:\n\n```rust\nuse std::mem::MaybeUninit;\n\nlet _: [MaybeUninit<bool>; 5] = unsafe {\n    MaybeUninit::uninit().assume_init()\n};\n```",
     },
     Lint {
         name: "unit_arg",
@@ -1910,6 +2206,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "passing unit to a function",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for passing a unit value as an argument to a function without using a\nunit literal (`()`).\n\n**Why is this bad?** This is likely the result of an accidental semicolon.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nfoo({\n    let a = bar();\n    baz(a);\n})\n```",
     },
     Lint {
         name: "unit_cmp",
@@ -1917,6 +2214,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "comparing unit values",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for comparisons to unit.\n\n**Why is this bad?** Unit is always equal to itself, and thus is just a\nclumsily written constant. Mostly this happens when someone accidentally\nadds semicolons at the end of the operands.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# fn foo() {};\n# fn bar() {};\n# fn baz() {};\nif {\n    foo();\n} == {\n    bar();\n} {\n    baz();\n}\n```\nis equal to\n```rust\n# fn foo() {};\n# fn bar() {};\n# fn baz() {};\n{\n    foo();\n    bar();\n    baz();\n}\n```",
     },
     Lint {
         name: "unknown_clippy_lints",
@@ -1924,6 +2222,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "unknown_lints for scoped Clippy lints",
         deprecation: None,
         module: "attrs",
+        docs: "**What it does:** Checks for `allow`/`warn`/`deny`/`forbid` attributes with scoped clippy\nlints and if those lints exist in clippy. If there is an uppercase letter in the lint name\n(not the tool name) and a lowercase version of this lint exists, it will suggest to lowercase\nthe lint name.\n\n**Why is this bad?** A lint attribute with a mistyped lint name won't have an effect.\n\n**Known problems:** None.\n\n**Example:**\nBad:\n```rust\n#![warn(if_not_els)]\n#![deny(clippy::All)]\n```\n\nGood:\n```rust\n#![warn(if_not_else)]\n#![deny(clippy::all)]\n```",
     },
     Lint {
         name: "unnecessary_cast",
@@ -1931,6 +2230,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "cast to the same type, e.g., `x as i32` where `x: i32`",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for casts to the same type.\n\n**Why is this bad?** It's just unnecessary.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet _ = 2i32 as i32;\n```",
     },
     Lint {
         name: "unnecessary_filter_map",
@@ -1938,6 +2238,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `filter_map` when a more succinct alternative exists",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for `filter_map` calls which could be replaced by `filter` or `map`.\nMore specifically it checks if the closure provided is only performing one of the\nfilter or map operations and suggests the appropriate option.\n\n**Why is this bad?** Complexity. The intent is also clearer if only a single\noperation is being performed.\n\n**Known problems:** None\n\n**Example:**\n```rust\nlet _ = (0..3).filter_map(|x| if x > 2 { Some(x) } else { None });\n```\nAs there is no transformation of the argument this could be written as:\n```rust\nlet _ = (0..3).filter(|&x| x > 2);\n```\n\n```rust\nlet _ = (0..4).filter_map(i32::checked_abs);\n```\nAs there is no conditional check on the argument this could be written as:\n```rust\nlet _ = (0..4).map(i32::checked_abs);\n```",
     },
     Lint {
         name: "unnecessary_fold",
@@ -1945,6 +2246,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `fold` when a more succinct alternative exists",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for using `fold` when a more succinct alternative exists.\nSpecifically, this checks for `fold`s which could be replaced by `any`, `all`,\n`sum` or `product`.\n\n**Why is this bad?** Readability.\n\n**Known problems:** False positive in pattern guards. Will be resolved once\nnon-lexical lifetimes are stable.\n\n**Example:**\n```rust\nlet _ = (0..3).fold(false, |acc, x| acc || x > 2);\n```\nThis could be written as:\n```rust\nlet _ = (0..3).any(|x| x > 2);\n```",
     },
     Lint {
         name: "unnecessary_mut_passed",
@@ -1952,6 +2254,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "an argument passed as a mutable reference although the callee only demands an immutable reference",
         deprecation: None,
         module: "mut_reference",
+        docs: "**What it does:** Detects giving a mutable reference to a function that only\nrequires an immutable reference.\n\n**Why is this bad?** The immutable reference rules out all other references\nto the value. Also the code misleads about the intent of the call site.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nmy_vec.push(&mut value)\n```",
     },
     Lint {
         name: "unnecessary_operation",
@@ -1959,6 +2262,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "outer expressions with no effect",
         deprecation: None,
         module: "no_effect",
+        docs: "**What it does:** Checks for expression statements that can be reduced to a\nsub-expression.\n\n**Why is this bad?** Expressions by themselves often have no side-effects.\nHaving such expressions reduces readability.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\ncompute_array()[0];\n```",
     },
     Lint {
         name: "unnecessary_unwrap",
@@ -1966,6 +2270,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "checks for calls of unwrap[_err]() that cannot fail",
         deprecation: None,
         module: "unwrap",
+        docs: "**What it does:** Checks for calls of `unwrap[_err]()` that cannot fail.\n\n**Why is this bad?** Using `if let` or `match` is more idiomatic.\n\n**Known problems:** None\n\n**Example:**\n```rust\n# let option = Some(0);\n# fn do_something_with(_x: usize) {}\nif option.is_some() {\n    do_something_with(option.unwrap())\n}\n```\n\nCould be written:\n\n```rust\n# let option = Some(0);\n# fn do_something_with(_x: usize) {}\nif let Some(value) = option {\n    do_something_with(value)\n}\n```",
     },
     Lint {
         name: "unneeded_field_pattern",
@@ -1973,6 +2278,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "struct fields bound to a wildcard instead of using `..`",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Checks for structure field patterns bound to wildcards.\n\n**Why is this bad?** Using `..` instead is shorter and leaves the focus on\nthe fields that are actually bound.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nlet { a: _, b: ref b, c: _ } = ..\n```",
     },
     Lint {
         name: "unreadable_literal",
@@ -1980,6 +2286,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "long integer literal without underscores",
         deprecation: None,
         module: "literal_representation",
+        docs: "**What it does:** Warns if a long integral or floating-point constant does\nnot contain underscores.\n\n**Why is this bad?** Reading long numbers is difficult without separators.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\nlet x: u64 = 61864918973511;\n```",
     },
     Lint {
         name: "unsafe_removed_from_name",
@@ -1987,6 +2294,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`unsafe` removed from API names on import",
         deprecation: None,
         module: "unsafe_removed_from_name",
+        docs: "**What it does:** Checks for imports that remove \"unsafe\" from an item's\nname.\n\n**Why is this bad?** Renaming makes it less clear which traits and\nstructures are unsafe.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nuse std::cell::{UnsafeCell as TotallySafeCell};\n\nextern crate crossbeam;\nuse crossbeam::{spawn_unsafe as spawn};\n```",
     },
     Lint {
         name: "unseparated_literal_suffix",
@@ -1994,6 +2302,15 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "literals whose suffix is not separated by an underscore",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Warns if literal suffixes are not separated by an\nunderscore.\n\n**Why is this bad?** It is much less readable.\n\n**Known problems:** None.\n\n**Example:**\n```rust\nlet y = 123832i32;\n```",
+    },
+    Lint {
+        name: "unused_clippy_allow",
+        group: "restriction",
+        desc: "`#[allow]` attributes for Clippy lints that never trigger",
+        deprecation: None,
+        module: "attrs",
+        docs: "**What it does:** Checks for `#[allow(clippy::...)]` attributes that don't suppress any\nClippy lint.\n\n**Why is this bad?** These attributes are usually left over after the code they were\nadded for changed. They clutter the code and hide new occurrences of the lint.\n\n**Known problems:** Only the code compiled in the current configuration is checked, so an\nattribute for a lint that only triggers with e.g. `--all-features` is reported when the\nfeature is disabled. Attributes for lint groups, like `clippy::pedantic`, are ignored.\n\n**Example:**\n```rust\n#[allow(clippy::too_many_arguments)]\nfn add(a: u32, b: u32) -> u32 {\n    a + b\n}\n```",
     },
     Lint {
         name: "unused_io_amount",
@@ -2001,6 +2318,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "unused written/read amount",
         deprecation: None,
         module: "unused_io_amount",
+        docs: "**What it does:** Checks for unused written/read amount.\n\n**Why is this bad?** `io::Write::write` and `io::Read::read` are not\nguaranteed to\nprocess the entire buffer. They return how many bytes were processed, which\nmight be smaller\nthan a given buffer's length. If you don't need to deal with\npartial-write/read, use\n`write_all`/`read_exact` instead.\n\n**Known problems:** Detects only common patterns.\n\n**Example:**\n```rust,ignore\nuse std::io;\nfn foo<W: io::Write>(w: &mut W) -> io::Result<()> {\n    // must be `w.write_all(b\"foo\")?;`\n    w.write(b\"foo\")?;\n    Ok(())\n}\n```",
     },
     Lint {
         name: "unused_label",
@@ -2008,6 +2326,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "unused labels",
         deprecation: None,
         module: "unused_label",
+        docs: "**What it does:** Checks for unused labels.\n\n**Why is this bad?** Maybe the label should be used in which case there is\nan error in the code or it should be removed.\n\n**Known problems:** Hopefully none.\n\n**Example:**\n```rust,ignore\nfn unused_label() {\n    'label: for i in 1..2 {\n        if i > 4 { continue }\n    }\n```",
     },
     Lint {
         name: "unused_unit",
@@ -2015,6 +2334,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "needless unit expression",
         deprecation: None,
         module: "returns",
+        docs: "**What it does:** Checks for unit (`()`) expressions that can be removed.\n\n**Why is this bad?** Such expressions add no value, but can make the code\nless readable. Depending on formatting they can make a `break` or `return`\nstatement look like a function call.\n\n**Known problems:** The lint currently misses unit return types in types,\ne.g., the `F` in `fn generic_unit<F: Fn() -> ()>(f: F) { .. }`.\n\n**Example:**\n```rust\nfn return_unit() -> () {\n    ()\n}\n```",
     },
     Lint {
         name: "use_debug",
@@ -2022,6 +2342,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of `Debug`-based formatting",
         deprecation: None,
         module: "write",
+        docs: "**What it does:** Checks for use of `Debug` formatting. The purpose of this\nlint is to catch debugging remnants.\n\n**Why is this bad?** The purpose of the `Debug` trait is to facilitate\ndebugging Rust code. It should not be used in user-facing output.\n\n**Example:**\n```rust\n# let foo = \"bar\";\nprintln!(\"{:?}\", foo);\n```",
     },
     Lint {
         name: "use_self",
@@ -2029,6 +2350,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "Unnecessary structure name repetition whereas `Self` is applicable",
         deprecation: None,
         module: "use_self",
+        docs: "**What it does:** Checks for unnecessary repetition of structure name when a\nreplacement with `Self` is applicable.\n\n**Why is this bad?** Unnecessary repetition. Mixed use of `Self` and struct\nname\nfeels inconsistent.\n\n**Known problems:**\n- False positive when using associated types (#2843)\n- False positives in some situations when using generics (#3410)\n\n**Example:**\n```rust\nstruct Foo {}\nimpl Foo {\n    fn new() -> Foo {\n        Foo {}\n    }\n}\n```\ncould be\n```rust\nstruct Foo {}\nimpl Foo {\n    fn new() -> Self {\n        Self {}\n    }\n}\n```",
     },
     Lint {
         name: "used_underscore_binding",
@@ -2036,6 +2358,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a binding which is prefixed with an underscore",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Checks for the use of bindings with a single leading\nunderscore.\n\n**Why is this bad?** A single leading underscore is usually used to indicate\nthat a binding will not be used. Using such a binding breaks this\nexpectation.\n\n**Known problems:** The lint does not work properly with desugaring and\nmacro, it has been allowed in the mean time.\n\n**Example:**\n```rust\nlet _x = 0;\nlet y = _x + 1; // Here we are using `_x`, even though it has a leading\n                // underscore. We should rename `_x` to `x`\n```",
     },
     Lint {
         name: "useless_asref",
@@ -2043,6 +2366,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `as_ref` where the types before and after the call are the same",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for usage of `.as_ref()` or `.as_mut()` where the\ntypes before and after the call are the same.\n\n**Why is this bad?** The call is unnecessary.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# fn do_stuff(x: &[i32]) {}\nlet x: &[i32] = &[1, 2, 3, 4, 5];\ndo_stuff(x.as_ref());\n```\nThe correct use would be:\n```rust\n# fn do_stuff(x: &[i32]) {}\nlet x: &[i32] = &[1, 2, 3, 4, 5];\ndo_stuff(x);\n```",
     },
     Lint {
         name: "useless_attribute",
@@ -2050,6 +2374,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "use of lint attributes on `extern crate` items",
         deprecation: None,
         module: "attrs",
+        docs: "**What it does:** Checks for `extern crate` and `use` items annotated with\nlint attributes.\n\nThis lint whitelists `#[allow(unused_imports)]`, `#[allow(deprecated)]` and\n`#[allow(unreachable_pub)]` on `use` items and `#[allow(unused_imports)]` on\n`extern crate` items with a `#[macro_use]` attribute.\n\n**Why is this bad?** Lint attributes have no effect on crate imports. Most\nlikely a `!` was forgotten.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\n// Bad\n#[deny(dead_code)]\nextern crate foo;\n#[forbid(dead_code)]\nuse foo::bar;\n\n// Ok\n#[allow(unused_imports)]\nuse foo::baz;\n#[allow(unused_imports)]\n#[macro_use]\nextern crate baz;\n```",
     },
     Lint {
         name: "useless_format",
@@ -2057,6 +2382,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "useless use of `format!`",
         deprecation: None,
         module: "format",
+        docs: "**What it does:** Checks for the use of `format!(\"string literal with no\nargument\")` and `format!(\"{}\", foo)` where `foo` is a string.\n\n**Why is this bad?** There is no point of doing that. `format!(\"foo\")` can\nbe replaced by `\"foo\".to_owned()` if you really need a `String`. The even\nworse `&format!(\"foo\")` is often encountered in the wild. `format!(\"{}\",\nfoo)` can be replaced by `foo.clone()` if `foo: String` or `foo.to_owned()`\nif `foo: &str`.\n\n**Known problems:** None.\n\n**Examples:**\n```rust\n# let foo = \"foo\";\nformat!(\"foo\");\nformat!(\"{}\", foo);\n```",
     },
     Lint {
         name: "useless_let_if_seq",
@@ -2064,6 +2390,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "unidiomatic `let mut` declaration followed by initialization in `if`",
         deprecation: None,
         module: "let_if_seq",
+        docs: "**What it does:** Checks for variable declarations immediately followed by a\nconditional affectation.\n\n**Why is this bad?** This is not idiomatic Rust.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nlet foo;\n\nif bar() {\n    foo = 42;\n} else {\n    foo = 0;\n}\n\nlet mut baz = None;\n\nif bar() {\n    baz = Some(42);\n}\n```\n\nshould be written\n\n```rust,ignore\nlet foo = if bar() {\n    42\n} else {\n    0\n};\n\nlet baz = if bar() {\n    Some(42)\n} else {\n    None\n};\n```",
     },
     Lint {
         name: "useless_transmute",
@@ -2071,6 +2398,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes that have the same to and from types or could be a cast/coercion",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes to the original type of the object\nand transmutes that could be a cast.\n\n**Why is this bad?** Readability. The code tricks people into thinking that\nsomething complex is going on.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\ncore::intrinsics::transmute(t); // where the result type is the same as `t`'s\n```",
     },
     Lint {
         name: "useless_vec",
@@ -2078,6 +2406,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "useless `vec!`",
         deprecation: None,
         module: "vec",
+        docs: "**What it does:** Checks for usage of `&vec![..]` when using `&[..]` would\nbe possible.\n\n**Why is this bad?** This is less efficient.\n\n**Known problems:** None.\n\n**Example:**\n```rust,ignore\nfoo(&vec![1, 2])\n```",
     },
     Lint {
         name: "vec_box",
@@ -2085,6 +2414,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of `Vec<Box<T>>` where T: Sized, vector elements are already on the heap",
         deprecation: None,
         module: "types",
+        docs: "**What it does:** Checks for use of `Vec<Box<T>>` where T: Sized anywhere in the code.\n\n**Why is this bad?** `Vec` already keeps its contents in a separate area on\nthe heap. So if you `Box` its contents, you just add another level of indirection.\n\n**Known problems:** Vec<Box<T: Sized>> makes sense if T is a large type (see #3530,\n1st comment).\n\n**Example:**\n```rust\nstruct X {\n    values: Vec<Box<i32>>,\n}\n```\n\nBetter:\n\n```rust\nstruct X {\n    values: Vec<i32>,\n}\n```",
     },
     Lint {
         name: "verbose_bit_mask",
@@ -2092,6 +2422,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "expressions where a bit mask is less readable than the corresponding method call",
         deprecation: None,
         module: "bit_mask",
+        docs: "**What it does:** Checks for bit masks that can be replaced by a call\nto `trailing_zeros`\n\n**Why is this bad?** `x.trailing_zeros() > 4` is much clearer than `x & 15\n== 0`\n\n**Known problems:** llvm generates better code for `x & 15 == 0` on x86\n\n**Example:**\n```rust\n# let x = 1;\nif x & 0x1111 == 0 { }\n```",
     },
     Lint {
         name: "while_immutable_condition",
@@ -2099,6 +2430,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "variables used within while expression are not mutated in the body",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks whether variables used within while loop condition\ncan be (and are) mutated in the body.\n\n**Why is this bad?** If the condition is unchanged, entering the body of the loop\nwill lead to an infinite loop.\n\n**Known problems:** If the `while`-loop is in a closure, the check for mutation of the\ncondition variables in the body can cause false negatives. For example when only `Upvar` `a` is\nin the condition and only `Upvar` `b` gets mutated in the body, the lint will not trigger.\n\n**Example:**\n```rust\nlet i = 0;\nwhile i > 10 {\n    println!(\"let me loop forever!\");\n}\n```",
     },
     Lint {
         name: "while_let_loop",
@@ -2106,6 +2438,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "`loop { if let { ... } else break }`, which can be written as a `while let` loop",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Detects `loop + match` combinations that are easier\nwritten as a `while let` loop.\n\n**Why is this bad?** The `while let` loop is usually shorter and more\nreadable.\n\n**Known problems:** Sometimes the wrong binding is displayed (#383).\n\n**Example:**\n```rust,no_run\n# let y = Some(1);\nloop {\n    let x = match y {\n        Some(x) => x,\n        None => break,\n    };\n    // .. do something with x\n}\n// is easier written as\nwhile let Some(x) = y {\n    // .. do something with x\n};\n```",
     },
     Lint {
         name: "while_let_on_iterator",
@@ -2113,6 +2446,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a while-let loop instead of a for loop on an iterator",
         deprecation: None,
         module: "loops",
+        docs: "**What it does:** Checks for `while let` expressions on iterators.\n\n**Why is this bad?** Readability. A simple `for` loop is shorter and conveys\nthe intent better.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nwhile let Some(val) = iter() {\n    ..\n}\n```",
     },
     Lint {
         name: "wildcard_dependencies",
@@ -2120,6 +2454,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "wildcard dependencies being used",
         deprecation: None,
         module: "wildcard_dependencies",
+        docs: "**What it does:** Checks for wildcard dependencies in the `Cargo.toml`.\n\n**Why is this bad?** [As the edition guide says](https://rust-lang-nursery.github.io/edition-guide/rust-2018/cargo-and-crates-io/crates-io-disallows-wildcard-dependencies.html),\nit is highly unlikely that you work with any possible version of your dependency,\nand wildcard dependencies would cause unnecessary breakage in the ecosystem.\n\n**Known problems:** None.\n\n**Example:**\n\n```toml\n[dependencies]\nregex = \"*\"\n```",
     },
     Lint {
         name: "wildcard_enum_match_arm",
@@ -2127,6 +2462,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "a wildcard enum match arm using `_`",
         deprecation: None,
         module: "matches",
+        docs: "**What it does:** Checks for wildcard enum matches using `_`.\n\n**Why is this bad?** New enum variants added by library updates can be missed.\n\n**Known problems:** Suggested replacements may be incorrect if guards exhaustively cover some\nvariants, and also may not use correct path to enum if it's not present in the current scope.\n\n**Example:**\n```rust\n# enum Foo { A(usize), B(usize) }\n# let x = Foo::B(1);\nmatch x {\n    A => {},\n    _ => {},\n}\n```",
     },
     Lint {
         name: "write_literal",
@@ -2134,6 +2470,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "writing a literal with a format string",
         deprecation: None,
         module: "write",
+        docs: "**What it does:** This lint warns about the use of literals as `write!`/`writeln!` args.\n\n**Why is this bad?** Using literals as `writeln!` args is inefficient\n(c.f., https://github.com/matthiaskrgr/rust-str-bench) and unnecessary\n(i.e., just put the literal in the format string)\n\n**Known problems:** Will also warn with macro calls as arguments that expand to literals\n-- e.g., `writeln!(buf, \"{}\", env!(\"FOO\"))`.\n\n**Example:**\n```rust\n# use std::fmt::Write;\n# let mut buf = String::new();\nwriteln!(buf, \"{}\", \"foo\");\n```",
     },
     Lint {
         name: "write_with_newline",
@@ -2141,6 +2478,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `write!()` with a format string that ends in a single newline",
         deprecation: None,
         module: "write",
+        docs: "**What it does:** This lint warns when you use `write!()` with a format\nstring that\nends in a newline.\n\n**Why is this bad?** You should use `writeln!()` instead, which appends the\nnewline.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use std::fmt::Write;\n# let mut buf = String::new();\n# let name = \"World\";\nwrite!(buf, \"Hello {}!\\n\", name);\n```",
     },
     Lint {
         name: "writeln_empty_string",
@@ -2148,6 +2486,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using `writeln!(buf, \"\")` with an empty string",
         deprecation: None,
         module: "write",
+        docs: "**What it does:** This lint warns when you use `writeln!(buf, \"\")` to\nprint a newline.\n\n**Why is this bad?** You should use `writeln!(buf)`, which is simpler.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n# use std::fmt::Write;\n# let mut buf = String::new();\nwriteln!(buf, \"\");\n```",
     },
     Lint {
         name: "wrong_pub_self_convention",
@@ -2155,6 +2494,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "defining a public method named with an established prefix (like \"into_\") that takes `self` with the wrong convention",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** This is the same as\n[`wrong_self_convention`](#wrong_self_convention), but for public items.\n\n**Why is this bad?** See [`wrong_self_convention`](#wrong_self_convention).\n\n**Known problems:** Actually *renaming* the function may break clients if\nthe function is part of the public interface. In that case, be mindful of\nthe stability guarantees you've given your users.\n\n**Example:**\n```rust\n# struct X;\nimpl<'a> X {\n    pub fn as_str(self) -> &'a str {\n        \"foo\"\n    }\n}\n```",
     },
     Lint {
         name: "wrong_self_convention",
@@ -2162,6 +2502,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "defining a method named with an established prefix (like \"into_\") that takes `self` with the wrong convention",
         deprecation: None,
         module: "methods",
+        docs: "**What it does:** Checks for methods with certain name prefixes and which\ndoesn't match how self is taken. The actual rules are:\n\n|Prefix |`self` taken          |\n|-------|----------------------|\n|`as_`  |`&self` or `&mut self`|\n|`from_`| none                 |\n|`into_`|`self`                |\n|`is_`  |`&self` or none       |\n|`to_`  |`&self`               |\n\n**Why is this bad?** Consistency breeds readability. If you follow the\nconventions, your users won't be surprised that they, e.g., need to supply a\nmutable reference to a `as_..` function.\n\n**Known problems:** None.\n\n**Example:**\n```ignore\nimpl X {\n    fn as_str(self) -> &str {\n        ..\n    }\n}\n```",
     },
     Lint {
         name: "wrong_transmute",
@@ -2169,6 +2510,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "transmutes that are confusing at best, undefined behaviour at worst and always useless",
         deprecation: None,
         module: "transmute",
+        docs: "**What it does:** Checks for transmutes that can't ever be correct on any\narchitecture.\n\n**Why is this bad?** It's basically guaranteed to be undefined behaviour.\n\n**Known problems:** When accessing C, users might want to store pointer\nsized objects in `extradata` arguments to save an allocation.\n\n**Example:**\n```ignore\nlet ptr: *const T = core::intrinsics::transmute('x')\n```",
     },
     Lint {
         name: "zero_divided_by_zero",
@@ -2176,6 +2518,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "usage of `0.0 / 0.0` to obtain NaN instead of std::f32::NaN or std::f64::NaN",
         deprecation: None,
         module: "zero_div_zero",
+        docs: "**What it does:** Checks for `0.0 / 0.0`.\n\n**Why is this bad?** It's less readable than `std::f32::NAN` or\n`std::f64::NAN`.\n\n**Known problems:** None.\n\n**Example:**\n```rust\n0.0f32 / 0.0;\n```",
     },
     Lint {
         name: "zero_prefixed_literal",
@@ -2183,6 +2526,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "integer literals starting with `0`",
         deprecation: None,
         module: "misc_early",
+        docs: "**What it does:** Warns if an integral constant literal starts with `0`.\n\n**Why is this bad?** In some languages (including the infamous C language\nand most of its\nfamily), this marks an octal constant. In Rust however, this is a decimal\nconstant. This could\nbe confusing for both the writer and a reader of the constant.\n\n**Known problems:** None.\n\n**Example:**\n\nIn Rust:\n```rust\nfn main() {\n    let a = 0123;\n    println!(\"{}\", a);\n}\n```\n\nprints `123`, while in C:\n\n```c\n#include <stdio.h>\n\nint main() {\n    int a = 0123;\n    printf(\"%d\\n\", a);\n}\n```\n\nprints `83` (as `83 == 0o123` while `123 == 0o173`).",
     },
     Lint {
         name: "zero_ptr",
@@ -2190,6 +2534,7 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using 0 as *{const, mut} T",
         deprecation: None,
         module: "misc",
+        docs: "**What it does:** Catch casts from `0` to some pointer type\n\n**Why is this bad?** This generally means `null` and is better expressed as\n{`std`, `core`}`::ptr::`{`null`, `null_mut`}.\n\n**Known problems:** None.\n\n**Example:**\n\n```rust\nlet a = 0 as *const u32;\n```",
     },
     Lint {
         name: "zero_width_space",
@@ -2197,6 +2542,227 @@ pub const ALL_LINTS: [Lint; 313] = [
         desc: "using a zero-width space in a string literal, which is confusing",
         deprecation: None,
         module: "unicode",
+        docs: "**What it does:** Checks for the Unicode zero-width space in the code.\n\n**Why is this bad?** Having an invisible character in the code makes for all\nsorts of April fools, but otherwise is very much frowned upon.\n\n**Known problems:** None.\n\n**Example:** You don't see it, but there may be a zero-width space\nsomewhere in this text.",
     },
 ];
 // end lint list, do not remove this comment, it’s used in `update_lints`
+
+// begin deprecated lint list, do not remove this comment, it’s used in `update_lints`
+pub const DEPRECATED_LINTS: [Lint; 13] = [
+    Lint {
+        name: "assign_ops",
+        group: "Deprecated",
+        desc: "using compound assignment operators (e.g., `+=`) is harmless",
+        deprecation: Some(
+            "using compound assignment operators (e.g., `+=`) is harmless",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This lint is too subjective, not having a good reason for being in clippy.\nAdditionally, compound assignment operators may be overloaded separately from their non-assigning\ncounterparts, so this lint may suggest a change in behavior or the code may not compile.",
+    },
+    Lint {
+        name: "extend_from_slice",
+        group: "Deprecated",
+        desc: "`.extend_from_slice(_)` is a faster way to extend a Vec by a slice",
+        deprecation: Some(
+            "`.extend_from_slice(_)` is a faster way to extend a Vec by a slice",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This used to check for `Vec::extend`, which was slower than\n`Vec::extend_from_slice`. Thanks to specialization, this is no longer true.",
+    },
+    Lint {
+        name: "if_let_redundant_pattern_matching",
+        group: "Deprecated",
+        desc: "this lint has been changed to redundant_pattern_matching",
+        deprecation: Some(
+            "this lint has been changed to redundant_pattern_matching",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** The original rule will only lint for `if let`. After\nmaking it support to lint `match`, naming as `if let` is not suitable for it.\nSo, this lint is deprecated.",
+    },
+    Lint {
+        name: "invalid_ref",
+        group: "Deprecated",
+        desc: "superseded by rustc lint `invalid_value`",
+        deprecation: Some(
+            "superseded by rustc lint `invalid_value`",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This lint has been superseded by the warn-by-default\n`invalid_value` rustc lint.",
+    },
+    Lint {
+        name: "misaligned_transmute",
+        group: "Deprecated",
+        desc: "this lint has been split into cast_ptr_alignment and transmute_ptr_to_ptr",
+        deprecation: Some(
+            "this lint has been split into cast_ptr_alignment and transmute_ptr_to_ptr",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This lint should never have applied to non-pointer types, as transmuting\nbetween non-pointer types of differing alignment is well-defined behavior (it's semantically\nequivalent to a memcpy). This lint has thus been refactored into two separate lints:\ncast_ptr_alignment and transmute_ptr_to_ptr.",
+    },
+    Lint {
+        name: "range_step_by_zero",
+        group: "Deprecated",
+        desc: "`iterator.step_by(0)` panics nowadays",
+        deprecation: Some(
+            "`iterator.step_by(0)` panics nowadays",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** `Range::step_by(0)` used to be linted since it's\nan infinite iterator, which is better expressed by `iter::repeat`,\nbut the method has been removed for `Iterator::step_by` which panics\nif given a zero",
+    },
+    Lint {
+        name: "should_assert_eq",
+        group: "Deprecated",
+        desc: "`assert!()` will be more flexible with RFC 2011",
+        deprecation: Some(
+            "`assert!()` will be more flexible with RFC 2011",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This used to check for `assert!(a == b)` and recommend\nreplacement with `assert_eq!(a, b)`, but this is no longer needed after RFC 2011.",
+    },
+    Lint {
+        name: "str_to_string",
+        group: "Deprecated",
+        desc: "using `str::to_string` is common even today and specialization will likely happen soon",
+        deprecation: Some(
+            "using `str::to_string` is common even today and specialization will likely happen soon",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This used to check for `.to_string()` method calls on values\nof type `&str`. This is not unidiomatic and with specialization coming, `to_string` could be\nspecialized to be as efficient as `to_owned`.",
+    },
+    Lint {
+        name: "string_to_string",
+        group: "Deprecated",
+        desc: "using `string::to_string` is common even today and specialization will likely happen soon",
+        deprecation: Some(
+            "using `string::to_string` is common even today and specialization will likely happen soon",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This used to check for `.to_string()` method calls on values\nof type `String`. This is not unidiomatic and with specialization coming, `to_string` could be\nspecialized to be as efficient as `clone`.",
+    },
+    Lint {
+        name: "unsafe_vector_initialization",
+        group: "Deprecated",
+        desc: "the replacement suggested by this lint had substantially different behavior",
+        deprecation: Some(
+            "the replacement suggested by this lint had substantially different behavior",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This lint used to suggest replacing `let mut vec =\nVec::with_capacity(n); vec.set_len(n);` with `let vec = vec![0; n];`. The\nreplacement has very different performance characteristics so the lint is\ndeprecated.",
+    },
+    Lint {
+        name: "unstable_as_mut_slice",
+        group: "Deprecated",
+        desc: "`Vec::as_mut_slice` has been stabilized in 1.7",
+        deprecation: Some(
+            "`Vec::as_mut_slice` has been stabilized in 1.7",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This used to check for `Vec::as_mut_slice`, which was unstable with good\nstable alternatives. `Vec::as_mut_slice` has now been stabilized.",
+    },
+    Lint {
+        name: "unstable_as_slice",
+        group: "Deprecated",
+        desc: "`Vec::as_slice` has been stabilized in 1.7",
+        deprecation: Some(
+            "`Vec::as_slice` has been stabilized in 1.7",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This used to check for `Vec::as_slice`, which was unstable with good\nstable alternatives. `Vec::as_slice` has now been stabilized.",
+    },
+    Lint {
+        name: "unused_collect",
+        group: "Deprecated",
+        desc: "`collect` has been marked as #[must_use] in rustc and that covers all cases of this lint",
+        deprecation: Some(
+            "`collect` has been marked as #[must_use] in rustc and that covers all cases of this lint",
+        ),
+        module: "deprecated_lints",
+        docs: "**What it does:** Nothing. This lint has been deprecated.\n\n**Deprecation reason:** This lint has been superseded by #[must_use] in rustc.",
+    },
+];
+// end deprecated lint list, do not remove this comment, it’s used in `update_lints`
+
+// begin conf list, do not remove this comment, it’s used in `update_lints`
+pub const CONF_KEYS: [ConfKey; 16] = [
+    ConfKey {
+        name: "blacklisted-names",
+        lints: &["blacklisted_name"],
+        doc: "The list of blacklisted names to lint about",
+    },
+    ConfKey {
+        name: "cognitive-complexity-threshold",
+        lints: &["cognitive_complexity"],
+        doc: "The maximum cognitive complexity a function can have",
+    },
+    ConfKey {
+        name: "doc-valid-idents",
+        lints: &["doc_markdown"],
+        doc: "The list of words this lint should not consider as identifiers needing ticks",
+    },
+    ConfKey {
+        name: "too-many-arguments-threshold",
+        lints: &["too_many_arguments"],
+        doc: "The maximum number of argument a function or method can have",
+    },
+    ConfKey {
+        name: "type-complexity-threshold",
+        lints: &["type_complexity"],
+        doc: "The maximum complexity a type can have",
+    },
+    ConfKey {
+        name: "single-char-binding-names-threshold",
+        lints: &["many_single_char_names"],
+        doc: "The maximum number of single char bindings a scope may have",
+    },
+    ConfKey {
+        name: "too-large-for-stack",
+        lints: &["boxed_local"],
+        doc: "The maximum size of objects (in bytes) that will be linted. Larger objects are ok on the heap",
+    },
+    ConfKey {
+        name: "enum-variant-name-threshold",
+        lints: &["enum_variant_names"],
+        doc: "The minimum number of enum variants for the lints about variant names to trigger",
+    },
+    ConfKey {
+        name: "enum-variant-size-threshold",
+        lints: &["large_enum_variant"],
+        doc: "The maximum size of a enum's variant to avoid box suggestion",
+    },
+    ConfKey {
+        name: "verbose-bit-mask-threshold",
+        lints: &["verbose_bit_mask"],
+        doc: "The maximum allowed size of a bit mask before suggesting to use 'trailing_zeros'",
+    },
+    ConfKey {
+        name: "literal-representation-threshold",
+        lints: &["decimal_literal_representation"],
+        doc: "The lower bound for linting decimal literals",
+    },
+    ConfKey {
+        name: "trivial-copy-size-limit",
+        lints: &["trivially_copy_pass_by_ref"],
+        doc: "The maximum size (in bytes) to consider a `Copy` type for passing by value instead of by reference.",
+    },
+    ConfKey {
+        name: "too-many-lines-threshold",
+        lints: &["too_many_lines"],
+        doc: "The maximum number of lines a function or method can have",
+    },
+    ConfKey {
+        name: "msrv",
+        lints: &["manual_saturating_arithmetic", "question_mark", "redundant_static_lifetimes"],
+        doc: "The minimum rust version that the project supports",
+    },
+    ConfKey {
+        name: "disallowed-methods",
+        lints: &["disallowed_methods"],
+        doc: "The list of disallowed functions and methods, written as fully qualified paths. Entries can also be tables with a `path` and a `reason` key",
+    },
+    ConfKey {
+        name: "disallowed-types",
+        lints: &["disallowed_types"],
+        doc: "The list of disallowed types, written as fully qualified paths. Entries can also be tables with a `path` and a `reason` key",
+    },
+];
+// end conf list, do not remove this comment, it’s used in `update_lints`
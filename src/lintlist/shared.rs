@@ -0,0 +1,39 @@
+//! Lint data that `clippy_dev` uses as well. It includes this file, so it can't refer to the
+//! rest of the lint list.
+
+#[derive(PartialOrd, PartialEq, Ord, Eq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Warn => "warn",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+pub const LINT_LEVELS: [(&str, Level); 8] = [
+    ("correctness", Level::Deny),
+    ("style", Level::Warn),
+    ("complexity", Level::Warn),
+    ("perf", Level::Warn),
+    ("restriction", Level::Allow),
+    ("pedantic", Level::Allow),
+    ("nursery", Level::Allow),
+    ("cargo", Level::Allow),
+];
+
+/// Whether a code block with the info string `info` contains Rust code, see
+/// <https://doc.rust-lang.org/rustdoc/documentation-tests.html#attributes>.
+pub fn is_rust_block(info: &str) -> bool {
+    info.split(',').map(str::trim).all(|attr| match attr {
+        "" | "rust" | "ignore" | "should_panic" | "no_run" | "compile_fail" | "edition2018" => true,
+        _ => false,
+    })
+}
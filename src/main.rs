@@ -2,6 +2,23 @@
 
 use rustc_tools_util::*;
 
+use std::env;
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+
+mod baseline;
+mod diagnostics;
+mod diff;
+// Shared with `clippy-driver`, which uses more of it.
+#[allow(dead_code)]
+mod lintlist;
+// `Output::rustc_line` is only used by `clippy-driver`.
+#[allow(dead_code)]
+mod output;
+mod sarif;
+
 const CARGO_CLIPPY_HELP: &str = r#"Checks a package to catch common mistakes and improve your Rust code.
 
 Usage:
@@ -10,6 +27,13 @@ Usage:
 Common options:
     -h, --help               Print this message
     -V, --version            Print version info and exit
+    --explain <LINT>         Print the documentation of a lint
+    --fix                    Automatically apply lint suggestions
+    --allow-dirty            Allow `--fix` to modify files in a dirty working directory
+    --baseline <FILE>        Only report warnings that are not recorded in the baseline file
+    --write-baseline <FILE>  Record all current warnings in a baseline file
+    --diff-base <REV>        Only report warnings on lines changed since the git revision
+    --message-format sarif   Print a SARIF log of all diagnostics instead of rendering them
 
 Other options are the same as `cargo check`.
 
@@ -37,68 +61,250 @@ fn show_version() {
 
 pub fn main() {
     // Check for version and help flags even when invoked as 'cargo-clippy'
-    if std::env::args().any(|a| a == "--help" || a == "-h") {
+    if env::args().any(|a| a == "--help" || a == "-h") {
         show_help();
         return;
     }
 
-    if std::env::args().any(|a| a == "--version" || a == "-V") {
+    if env::args().any(|a| a == "--version" || a == "-V") {
         show_version();
         return;
     }
 
-    if let Err(code) = process(std::env::args().skip(2)) {
-        std::process::exit(code);
+    let mut args = env::args().skip(2);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(name) = option_value(&arg, "--explain", &mut args) {
+            if let Err(code) = explain(&name) {
+                process::exit(code);
+            }
+            return;
+        }
+    }
+
+    if let Err(code) = process(env::args().skip(2)) {
+        process::exit(code);
     }
 }
 
-fn process<I>(mut old_args: I) -> Result<(), i32>
-where
-    I: Iterator<Item = String>,
-{
-    let mut args = vec!["check".to_owned()];
+/// Prints the documentation of a Clippy lint. Anything else, like error codes, is explained by
+/// rustc.
+fn explain(name: &str) -> Result<(), i32> {
+    if let Some(lint) = lintlist::Lint::find(name) {
+        print!("{}", lint.explain());
+        return Ok(());
+    }
 
-    for arg in old_args.by_ref() {
-        if arg == "--" {
-            break;
+    let exit_status = Command::new("rustc")
+        .arg("--explain")
+        .arg(name)
+        .status()
+        .expect("could not run rustc");
+
+    if exit_status.success() {
+        Ok(())
+    } else {
+        Err(exit_status.code().unwrap_or(-1))
+    }
+}
+
+/// The cargo invocation that runs `clippy-driver` over a package.
+struct ClippyCmd {
+    /// `check`, or `fix` when `--fix` is passed.
+    cargo_subcommand: &'static str,
+    /// Arguments forwarded to the cargo subcommand.
+    args: Vec<String>,
+    /// Arguments forwarded to `clippy-driver` through `CLIPPY_ARGS`.
+    clippy_args: String,
+    /// The value of `--message-format`, if it was passed.
+    message_format: Option<String>,
+    baseline: Option<PathBuf>,
+    write_baseline: Option<PathBuf>,
+    /// The git revision passed to `--diff-base`.
+    diff_base: Option<String>,
+}
+
+impl ClippyCmd {
+    fn new<I>(mut old_args: I) -> Self
+    where
+        I: Iterator<Item = String>,
+    {
+        let mut cargo_subcommand = "check";
+        let mut args = vec![];
+        let mut message_format = None;
+        let mut baseline = None;
+        let mut write_baseline = None;
+        let mut diff_base = None;
+
+        while let Some(arg) = old_args.next() {
+            match arg.as_str() {
+                "--fix" => {
+                    cargo_subcommand = "fix";
+                    continue;
+                },
+                "--" => break,
+                _ => {},
+            }
+
+            if let Some(value) = option_value(&arg, "--message-format", &mut old_args) {
+                message_format = Some(value);
+            } else if let Some(value) = option_value(&arg, "--baseline", &mut old_args) {
+                baseline = Some(PathBuf::from(value));
+            } else if let Some(value) = option_value(&arg, "--write-baseline", &mut old_args) {
+                write_baseline = Some(PathBuf::from(value));
+            } else if let Some(value) = option_value(&arg, "--diff-base", &mut old_args) {
+                diff_base = Some(value);
+            } else {
+                args.push(arg);
+            }
+        }
+
+        let clippy_args: String = old_args.map(|arg| format!("{}__CLIPPY_HACKERY__", arg)).collect();
+
+        Self {
+            cargo_subcommand,
+            args,
+            clippy_args,
+            message_format,
+            baseline,
+            write_baseline,
+            diff_base,
         }
-        args.push(arg);
     }
 
-    let clippy_args: String = old_args.map(|arg| format!("{}__CLIPPY_HACKERY__", arg)).collect();
+    /// Checks for options that can't be combined.
+    fn validate(&self) -> Result<(), String> {
+        if self.diff_base.is_some() && self.write_baseline.is_some() {
+            // The baseline would only contain the warnings on the changed lines.
+            return Err("`--write-baseline` can't be combined with `--diff-base`".to_string());
+        }
+        Ok(())
+    }
 
-    let mut path = std::env::current_exe()
-        .expect("current executable path invalid")
-        .with_file_name("clippy-driver");
-    if cfg!(windows) {
-        path.set_extension("exe");
+    /// Whether cargo's messages have to be post-processed by `cargo-clippy`.
+    fn needs_output_processing(&self) -> bool {
+        self.baseline.is_some() || self.write_baseline.is_some() || self.diff_base.is_some() || self.sarif_output()
     }
 
-    let target_dir = std::env::var_os("CLIPPY_DOGFOOD")
-        .map(|_| {
-            std::env::var_os("CARGO_MANIFEST_DIR").map_or_else(
-                || std::ffi::OsString::from("clippy_dogfood"),
-                |d| {
-                    std::path::PathBuf::from(d)
-                        .join("target")
-                        .join("dogfood")
-                        .into_os_string()
-                },
-            )
-        })
-        .map(|p| ("CARGO_TARGET_DIR", p));
+    fn sarif_output(&self) -> bool {
+        self.message_format.as_ref().map_or(false, |format| format == "sarif")
+    }
 
-    // Run the dogfood tests directly on nightly cargo. This is required due
-    // to a bug in rustup.rs when running cargo on custom toolchains. See issue #3118.
-    if std::env::var_os("CLIPPY_DOGFOOD").is_some() && cfg!(windows) {
-        args.insert(0, "+nightly".to_string());
+    /// Whether the user asked for JSON messages rather than human readable ones.
+    fn json_output(&self) -> bool {
+        self.message_format
+            .as_ref()
+            .map_or(false, |format| format.starts_with("json"))
     }
 
-    let exit_status = std::process::Command::new("cargo")
-        .args(&args)
-        .env("RUSTC_WRAPPER", path)
-        .env("CLIPPY_ARGS", clippy_args)
-        .envs(target_dir)
+    /// `cargo fix` registers itself as `RUSTC_WRAPPER`, so in fix mode `clippy-driver` has to be
+    /// passed as the workspace wrapper instead. This also restricts linting to workspace members,
+    /// since suggestions can only be applied to those.
+    fn path_env(&self) -> &'static str {
+        if self.cargo_subcommand == "fix" {
+            "RUSTC_WORKSPACE_WRAPPER"
+        } else {
+            "RUSTC_WRAPPER"
+        }
+    }
+
+    fn path() -> PathBuf {
+        let mut path = env::current_exe()
+            .expect("current executable path invalid")
+            .with_file_name("clippy-driver");
+
+        if cfg!(windows) {
+            path.set_extension("exe");
+        }
+
+        path
+    }
+
+    /// The value of `--manifest-path`, if it was passed.
+    fn manifest_path(&self) -> Option<String> {
+        let mut args = self.args.iter().cloned();
+        while let Some(arg) = args.next() {
+            if let Some(value) = option_value(&arg, "--manifest-path", &mut args) {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn target_dir() -> Option<(&'static str, OsString)> {
+        env::var_os("CLIPPY_DOGFOOD")
+            .map(|_| {
+                env::var_os("CARGO_MANIFEST_DIR").map_or_else(
+                    || OsString::from("clippy_dogfood"),
+                    |d| PathBuf::from(d).join("target").join("dogfood").into_os_string(),
+                )
+            })
+            .map(|p| ("CARGO_TARGET_DIR", p))
+    }
+
+    fn into_std_cmd(self) -> Command {
+        let mut cmd = Command::new("cargo");
+
+        // Run the dogfood tests directly on nightly cargo. This is required due
+        // to a bug in rustup.rs when running cargo on custom toolchains. See issue #3118.
+        if env::var_os("CLIPPY_DOGFOOD").is_some() && cfg!(windows) {
+            cmd.arg("+nightly");
+        }
+
+        cmd.env(self.path_env(), Self::path())
+            .envs(Self::target_dir())
+            .env("CLIPPY_ARGS", &self.clippy_args)
+            .arg(self.cargo_subcommand)
+            .args(&self.args);
+
+        if self.needs_output_processing() {
+            // The rendered diagnostics are printed by `cargo-clippy` itself if the user didn't ask
+            // for JSON.
+            let format = match self.message_format.as_ref().map(String::as_str) {
+                Some(format) if self.json_output() => format,
+                Some("short") => "json-diagnostic-short",
+                _ => "json",
+            };
+            cmd.arg(format!("--message-format={}", format)).stdout(Stdio::piped());
+        } else if let Some(format) = &self.message_format {
+            cmd.arg(format!("--message-format={}", format));
+        }
+
+        cmd
+    }
+}
+
+/// If `arg` is the option `name`, returns its value. The option is assumed to be either
+/// `--name=value` or `--name value`.
+fn option_value(arg: &str, name: &str, args: &mut impl Iterator<Item = String>) -> Option<String> {
+    if arg == name {
+        args.next()
+    } else if arg.starts_with(name) && arg[name.len()..].starts_with('=') {
+        Some(arg[name.len() + 1..].to_string())
+    } else {
+        None
+    }
+}
+
+fn process<I>(old_args: I) -> Result<(), i32>
+where
+    I: Iterator<Item = String>,
+{
+    let cmd = ClippyCmd::new(old_args);
+
+    if let Err(err) = cmd.validate() {
+        eprintln!("error: {}", err);
+        return Err(1);
+    }
+
+    if cmd.needs_output_processing() {
+        return process_output(cmd);
+    }
+
+    let exit_status = cmd
+        .into_std_cmd()
         .spawn()
         .expect("could not run cargo")
         .wait()
@@ -110,3 +316,131 @@ where
         Err(exit_status.code().unwrap_or(-1))
     }
 }
+
+/// Runs cargo and filters the messages it prints through `output::Output`.
+fn process_output(cmd: ClippyCmd) -> Result<(), i32> {
+    let baseline = match &cmd.baseline {
+        Some(path) => match baseline::Baseline::read(path) {
+            Ok(baseline) => Some(baseline),
+            Err(err) => {
+                eprintln!("error: could not read baseline `{}`: {}", path.display(), err);
+                return Err(1);
+            },
+        },
+        None => None,
+    };
+    let changed_lines = match &cmd.diff_base {
+        Some(rev) => match workspace_root(&cmd).and_then(|root| diff::ChangedLines::from_git(rev, &root)) {
+            Ok(changed_lines) => Some(changed_lines),
+            Err(err) => {
+                eprintln!("error: could not compute the lines changed since `{}`: {}", rev, err);
+                return Err(1);
+            },
+        },
+        None => None,
+    };
+    let format = if cmd.sarif_output() {
+        output::Format::Sarif(sarif::Sarif::default())
+    } else if cmd.json_output() {
+        output::Format::Json
+    } else {
+        output::Format::Human
+    };
+    let mut output = output::Output::new(format, baseline, cmd.write_baseline.clone(), changed_lines);
+
+    let mut child = cmd.into_std_cmd().spawn().expect("could not run cargo");
+    let stdout = BufReader::new(child.stdout.take().expect("cargo's stdout is piped"));
+    for line in stdout.lines() {
+        output.line(&line.expect("could not read cargo's output"));
+    }
+
+    let exit_status = child.wait().expect("failed to wait for cargo?");
+    output.finish(exit_status)
+}
+
+/// Returns the directory the file names in diagnostics are relative to.
+fn workspace_root(clippy_cmd: &ClippyCmd) -> Result<PathBuf, String> {
+    let mut cmd = Command::new("cargo");
+    cmd.args(&["metadata", "--format-version", "1", "--no-deps"]);
+    if let Some(manifest_path) = clippy_cmd.manifest_path() {
+        cmd.arg("--manifest-path").arg(manifest_path);
+    }
+
+    let output = cmd.output().map_err(|err| format!("could not run cargo: {}", err))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|err| format!("invalid `cargo metadata` output: {}", err))?;
+    metadata["workspace_root"]
+        .as_str()
+        .map(|root| Path::new(root).to_path_buf())
+        .ok_or_else(|| "`cargo metadata` printed no workspace root".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClippyCmd;
+
+    #[test]
+    fn fix() {
+        let args = "cargo clippy --fix --allow-dirty"
+            .split_whitespace()
+            .map(ToString::to_string);
+        let cmd = ClippyCmd::new(args.skip(2));
+        assert_eq!("fix", cmd.cargo_subcommand);
+        assert_eq!("RUSTC_WORKSPACE_WRAPPER", cmd.path_env());
+        assert_eq!(vec!["--allow-dirty".to_string()], cmd.args);
+    }
+
+    #[test]
+    fn check() {
+        let args = "cargo clippy --all-targets -- -D clippy::pedantic"
+            .split_whitespace()
+            .map(ToString::to_string);
+        let cmd = ClippyCmd::new(args.skip(2));
+        assert_eq!("check", cmd.cargo_subcommand);
+        assert_eq!("RUSTC_WRAPPER", cmd.path_env());
+        assert_eq!(vec!["--all-targets".to_string()], cmd.args);
+        assert_eq!(
+            "-D__CLIPPY_HACKERY__clippy::pedantic__CLIPPY_HACKERY__",
+            cmd.clippy_args
+        );
+    }
+
+    #[test]
+    fn baseline() {
+        let args = "cargo clippy --baseline clippy.baseline --write-baseline=new.baseline --message-format=short"
+            .split_whitespace()
+            .map(ToString::to_string);
+        let cmd = ClippyCmd::new(args.skip(2));
+        assert_eq!(Some("clippy.baseline".into()), cmd.baseline);
+        assert_eq!(Some("new.baseline".into()), cmd.write_baseline);
+        assert_eq!(Some("short"), cmd.message_format.as_ref().map(String::as_str));
+        assert!(cmd.needs_output_processing());
+        assert!(!cmd.json_output());
+        assert!(cmd.args.is_empty());
+    }
+
+    #[test]
+    fn diff_base() {
+        let args = "cargo clippy --diff-base=origin/master --manifest-path sub/Cargo.toml"
+            .split_whitespace()
+            .map(ToString::to_string);
+        let cmd = ClippyCmd::new(args.skip(2));
+        assert_eq!(Some("origin/master"), cmd.diff_base.as_ref().map(String::as_str));
+        assert_eq!(Some("sub/Cargo.toml".to_string()), cmd.manifest_path());
+        assert!(cmd.needs_output_processing());
+        assert!(cmd.validate().is_ok());
+    }
+
+    #[test]
+    fn diff_base_with_write_baseline() {
+        let args = "cargo clippy --diff-base origin/master --write-baseline clippy.baseline"
+            .split_whitespace()
+            .map(ToString::to_string);
+        let cmd = ClippyCmd::new(args.skip(2));
+        assert!(cmd.validate().is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Post-processing of the messages cargo and rustc print, for the `cargo-clippy` and
+//! `clippy-driver` options that neither cargo nor rustc support natively.
+
+use crate::baseline::{self, Baseline, Fingerprint};
+use crate::diagnostics::{CargoMessage, Diagnostic};
+use crate::diff::ChangedLines;
+use crate::sarif::Sarif;
+use rustc_tools_util::VersionInfo;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+/// How diagnostics are reported to the user.
+pub enum Format {
+    /// The rendered diagnostics are printed to stderr.
+    Human,
+    /// Cargo's JSON messages are printed to stdout.
+    Json,
+    /// A SARIF log is printed to stdout once cargo exited.
+    Sarif(Sarif),
+}
+
+pub struct Output {
+    format: Format,
+    baseline: Option<Baseline>,
+    write_baseline: Option<PathBuf>,
+    /// Only lints on these lines are reported, if set.
+    changed_lines: Option<ChangedLines>,
+    /// All Clippy diagnostics seen so far, keyed by their fingerprint and position.
+    ///
+    /// The same diagnostic is emitted once per target when checking e.g. a library and its unit
+    /// tests, so each one must only be counted once against the baseline.
+    seen: HashMap<(Fingerprint, usize, usize), bool>,
+    /// Clippy diagnostics that weren't suppressed by the baseline.
+    new_lints: usize,
+    /// Errors that weren't suppressed.
+    errors: usize,
+    /// Errors that were suppressed by the baseline or `--diff-base`, e.g. because of `-D warnings`.
+    suppressed_errors: usize,
+}
+
+impl Output {
+    pub fn new(
+        format: Format,
+        baseline: Option<Baseline>,
+        write_baseline: Option<PathBuf>,
+        changed_lines: Option<ChangedLines>,
+    ) -> Self {
+        Self {
+            format,
+            baseline,
+            write_baseline,
+            changed_lines,
+            seen: HashMap::new(),
+            new_lints: 0,
+            errors: 0,
+            suppressed_errors: 0,
+        }
+    }
+
+    /// Handles a line of cargo's stdout.
+    pub fn line(&mut self, line: &str) {
+        let message = if let Some(message) = CargoMessage::parse(line) {
+            message
+        } else {
+            println!("{}", line);
+            return;
+        };
+
+        if let Some(diag) = message.diagnostic() {
+            self.diagnostic(diag, line);
+        } else if let Format::Json = self.format {
+            println!("{}", line);
+        }
+    }
+
+    /// Handles a line of the stderr of rustc invoked with `--error-format=json`, as `clippy-driver`
+    /// does.
+    pub fn rustc_line(&mut self, line: &str) {
+        if let Ok(diag) = serde_json::from_str::<Diagnostic>(line) {
+            self.diagnostic(&diag, line);
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+
+    /// Reports `diag` unless it is suppressed. `line` is the JSON message it was parsed from.
+    fn diagnostic(&mut self, diag: &Diagnostic, line: &str) {
+        if self.suppress(diag) {
+            return;
+        }
+
+        match &mut self.format {
+            Format::Human => {
+                // Don't claim that compilation aborted if all errors were suppressed.
+                if !diag.is_error() && diag.level == "error" && self.errors == 0 {
+                    return;
+                }
+                if let Some(rendered) = &diag.rendered {
+                    eprint!("{}", rendered);
+                }
+            },
+            Format::Json => println!("{}", line),
+            Format::Sarif(sarif) => sarif.add(diag),
+        }
+    }
+
+    /// Returns `true` if the diagnostic must not be shown.
+    fn suppress(&mut self, diag: &Diagnostic) -> bool {
+        if let (Some(changed_lines), Some(span)) = (&self.changed_lines, diag.primary_span()) {
+            if diag.is_lint() && !changed_lines.contains(span) {
+                if diag.is_error() {
+                    self.suppressed_errors += 1;
+                }
+                return true;
+            }
+        }
+
+        let (fingerprint, span) = match (Fingerprint::new(diag), diag.primary_span()) {
+            (Some(fingerprint), Some(span)) => (fingerprint, span),
+            (None, _) | (_, None) => {
+                if diag.is_error() {
+                    self.errors += 1;
+                }
+                return false;
+            },
+        };
+
+        let key = (fingerprint, span.line_start, span.column_start);
+        if let Some(&suppressed) = self.seen.get(&key) {
+            return suppressed;
+        }
+
+        let suppressed = self
+            .baseline
+            .as_mut()
+            .map_or(false, |baseline| baseline.suppresses(&key.0));
+        match (suppressed, diag.is_error()) {
+            (true, true) => self.suppressed_errors += 1,
+            (false, true) => self.errors += 1,
+            _ => {},
+        }
+        if !suppressed {
+            self.new_lints += 1;
+        }
+
+        self.seen.insert(key, suppressed);
+        suppressed
+    }
+
+    /// Called once cargo or rustc exited. Returns the exit code of `cargo-clippy` or
+    /// `clippy-driver`.
+    pub fn finish(self, cargo_status: ExitStatus) -> Result<(), i32> {
+        if let Format::Sarif(sarif) = self.format {
+            let version_info = rustc_tools_util::get_version_info!();
+            println!("{:#}", sarif.into_log(&version_info));
+        }
+
+        if let Some(path) = &self.write_baseline {
+            let fingerprints = self.seen.keys().map(|(fingerprint, ..)| fingerprint.clone()).collect();
+            if let Err(err) = baseline::write(path, fingerprints) {
+                eprintln!("error: could not write baseline `{}`: {}", path.display(), err);
+                return Err(1);
+            }
+        }
+
+        if self.baseline.is_some() && self.new_lints > 0 {
+            eprintln!(
+                "error: found {} Clippy warning(s) that are not part of the baseline",
+                self.new_lints
+            );
+            return Err(1);
+        }
+
+        // Compilation only failed because of lints that weren't shown.
+        if !cargo_status.success() && self.errors == 0 && self.suppressed_errors > 0 {
+            return Ok(());
+        }
+
+        if cargo_status.success() {
+            Ok(())
+        } else {
+            Err(cargo_status.code().unwrap_or(-1))
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Conversion of diagnostics into a [SARIF 2.1] log, for `--message-format=sarif`.
+//!
+//! [SARIF 2.1]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use crate::diagnostics::{Diagnostic, DiagnosticSpan};
+use crate::lintlist::{Level, Lint, ALL_LINTS, LINT_LEVELS};
+use rustc_tools_util::VersionInfo;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+const SCHEMA: &str = "https://schemastore.azurewebsites.net/schemas/json/sarif-2.1.0-rtm.5.json";
+const DOCS_LINK: &str = "https://rust-lang.github.io/rust-clippy/master/index.html";
+
+/// Collects the results of a Clippy run.
+#[derive(Default)]
+pub struct Sarif {
+    results: Vec<Value>,
+}
+
+impl Sarif {
+    pub fn add(&mut self, diag: &Diagnostic) {
+        let span = if let Some(span) = diag.primary_span() {
+            span
+        } else {
+            // Diagnostics without a location, like "aborting due to previous error", aren't
+            // results.
+            return;
+        };
+
+        let mut result = json!({
+            "level": match diag.level.as_str() {
+                "error" | "error: internal compiler error" => "error",
+                "warning" => "warning",
+                _ => "note",
+            },
+            "message": { "text": diag.message },
+            "locations": [{ "physicalLocation": physical_location(span) }],
+        });
+
+        if let Some(code) = &diag.code {
+            result["ruleId"] = json!(code.code);
+            if let Some(index) = diag
+                .clippy_lint()
+                .and_then(|name| ALL_LINTS.iter().position(|lint| lint.name == name))
+            {
+                result["ruleIndex"] = json!(index);
+            }
+        }
+
+        let fixes: Vec<_> = diag.children.iter().filter_map(fix).collect();
+        if !fixes.is_empty() {
+            result["fixes"] = json!(fixes);
+        }
+
+        self.results.push(result);
+    }
+
+    /// Returns the complete SARIF log.
+    pub fn into_log(self, version: &VersionInfo) -> Value {
+        json!({
+            "$schema": SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "clippy",
+                        "informationUri": "https://github.com/rust-lang/rust-clippy",
+                        "version": format!("{}.{}.{}", version.major, version.minor, version.patch),
+                        "rules": ALL_LINTS.iter().map(rule).collect::<Vec<_>>(),
+                    },
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": self.results,
+            }],
+        })
+    }
+}
+
+/// Describes a lint as a SARIF `reportingDescriptor`.
+fn rule(lint: &Lint) -> Value {
+    let level = LINT_LEVELS
+        .iter()
+        .find_map(|(group, level)| if *group == lint.group { Some(level) } else { None });
+
+    json!({
+        "id": format!("clippy::{}", lint.name),
+        "name": lint.name,
+        "shortDescription": { "text": lint.desc },
+        "helpUri": format!("{}#{}", DOCS_LINK, lint.name),
+        "defaultConfiguration": {
+            "enabled": level != Some(&Level::Allow),
+            "level": if level == Some(&Level::Deny) { "error" } else { "warning" },
+        },
+        "properties": { "tags": [lint.group] },
+    })
+}
+
+fn physical_location(span: &DiagnosticSpan) -> Value {
+    json!({
+        "artifactLocation": { "uri": uri(span) },
+        "region": region(span),
+    })
+}
+
+fn uri(span: &DiagnosticSpan) -> String {
+    span.file_name.replace('\\', "/")
+}
+
+fn region(span: &DiagnosticSpan) -> Value {
+    json!({
+        "startLine": span.line_start,
+        "startColumn": span.column_start,
+        "endLine": span.line_end,
+        "endColumn": span.column_end,
+    })
+}
+
+/// Converts a suggestion into a SARIF `fix`. Suggestions with placeholders can't be applied as-is,
+/// so they are left out.
+fn fix(suggestion: &Diagnostic) -> Option<Value> {
+    let mut changes: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+
+    for span in &suggestion.spans {
+        let replacement = span.suggested_replacement.as_ref()?;
+        if span.suggestion_applicability.as_ref().map(String::as_str) == Some("HasPlaceholders") {
+            return None;
+        }
+
+        changes.entry(uri(span)).or_default().push(json!({
+            "deletedRegion": region(span),
+            "insertedContent": { "text": replacement },
+        }));
+    }
+
+    if changes.is_empty() {
+        return None;
+    }
+
+    Some(json!({
+        "description": { "text": suggestion.message },
+        "artifactChanges": changes
+            .into_iter()
+            .map(|(uri, replacements)| json!({
+                "artifactLocation": { "uri": uri },
+                "replacements": replacements,
+            }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::CargoMessage;
+
+    const MESSAGE: &str = r#"{
+        "reason": "compiler-message",
+        "message": {
+            "message": "redundant field names in struct initialization",
+            "code": { "code": "clippy::redundant_field_names", "explanation": null },
+            "level": "warning",
+            "spans": [{
+                "file_name": "src/main.rs",
+                "line_start": 4,
+                "line_end": 4,
+                "column_start": 17,
+                "column_end": 21,
+                "is_primary": true,
+                "text": [{ "text": "    Foo { a: a };", "highlight_start": 11, "highlight_end": 15 }]
+            }],
+            "children": [{
+                "message": "replace it with",
+                "code": null,
+                "level": "help",
+                "spans": [{
+                    "file_name": "src/main.rs",
+                    "line_start": 4,
+                    "line_end": 4,
+                    "column_start": 11,
+                    "column_end": 15,
+                    "is_primary": true,
+                    "text": [],
+                    "suggested_replacement": "a",
+                    "suggestion_applicability": "MachineApplicable"
+                }],
+                "children": [],
+                "rendered": null
+            }],
+            "rendered": "warning: redundant field names in struct initialization"
+        }
+    }"#;
+
+    #[test]
+    fn result_with_fix() {
+        let message = CargoMessage::parse(MESSAGE).unwrap();
+        let mut sarif = Sarif::default();
+        sarif.add(message.diagnostic().unwrap());
+
+        let result = &sarif.results[0];
+        assert_eq!(result["ruleId"], "clippy::redundant_field_names");
+        assert_eq!(
+            result["ruleIndex"],
+            ALL_LINTS
+                .iter()
+                .position(|lint| lint.name == "redundant_field_names")
+                .unwrap()
+        );
+        assert_eq!(result["locations"][0]["physicalLocation"]["region"]["startColumn"], 17);
+
+        let change = &result["fixes"][0]["artifactChanges"][0];
+        assert_eq!(change["artifactLocation"]["uri"], "src/main.rs");
+        assert_eq!(change["replacements"][0]["insertedContent"]["text"], "a");
+    }
+}
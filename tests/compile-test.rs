@@ -3,11 +3,14 @@
 use compiletest_rs as compiletest;
 extern crate test;
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
 use std::env::{set_var, var};
 use std::ffi::OsStr;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
 fn clippy_driver_path() -> PathBuf {
     if let Some(path) = option_env!("CLIPPY_DRIVER_PATH") {
@@ -17,6 +20,10 @@ fn clippy_driver_path() -> PathBuf {
     }
 }
 
+fn cargo_clippy_path() -> PathBuf {
+    clippy_driver_path().with_file_name("cargo-clippy")
+}
+
 fn host_libs() -> PathBuf {
     if let Some(path) = option_env!("HOST_LIBS") {
         PathBuf::from(path)
@@ -149,6 +156,413 @@ fn run_ui_toml() {
     }
 }
 
+/// Runs `cargo clippy` on every package in `tests/ui-cargo/<lint>/<test>` and compares the
+/// diagnostics with `src/main.stderr` of the package, for lints that read the Cargo metadata.
+fn run_ui_cargo() {
+    if rustc_test_suite().is_some() || cfg!(windows) {
+        return;
+    }
+
+    let src_base = Path::new("tests/ui-cargo");
+    let build_base = std::env::current_dir()
+        .unwrap()
+        .join("target/debug/test_build_base/ui-cargo");
+    let filter = var("TESTNAME").ok();
+    let mut failures = Vec::new();
+    for lint_dir in sorted_dirs(src_base) {
+        for test_dir in sorted_dirs(&lint_dir) {
+            if !test_dir.join("Cargo.toml").is_file() {
+                continue;
+            }
+            let name = test_dir.strip_prefix(src_base).unwrap().to_path_buf();
+            if let Some(filter) = &filter {
+                if !name.to_string_lossy().contains(filter.as_str()) {
+                    continue;
+                }
+            }
+            if let Err(err) = run_ui_cargo_test(&test_dir, &build_base.join(&name)) {
+                failures.push(format!("{}: {}", name.display(), err));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} ui-cargo tests failed:\n\n{}\n\nTo update the references, run `tests/ui-cargo/update-all-references.sh`",
+        failures.len(),
+        failures.join("\n\n")
+    );
+}
+
+fn sorted_dirs(dir: &Path) -> Vec<PathBuf> {
+    sorted_entries(dir).into_iter().filter(|path| path.is_dir()).collect()
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    entries.sort();
+    entries
+}
+
+fn run_ui_cargo_test(test_dir: &Path, build_dir: &Path) -> Result<(), String> {
+    let target_dir = build_dir.join("target");
+    // Cargo doesn't run Clippy again on a package that is already built.
+    let _ = fs::remove_dir_all(&target_dir);
+    // The path of `cargo-clippy` would be relative to `test_dir` otherwise.
+    let cargo_clippy = std::env::current_dir().unwrap().join(cargo_clippy_path());
+    let output = Command::new(cargo_clippy)
+        .current_dir(test_dir)
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .env("CARGO_NET_OFFLINE", "true")
+        .args(&["clippy", "--message-format=json", "--"])
+        .args(&["-D", "warnings", "-Z", "ui-testing"])
+        .output()
+        .map_err(|err| format!("could not run cargo-clippy: {}", err))?;
+
+    let actual: String = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-message")
+        .filter_map(|message| message["message"]["rendered"].as_str().map(ToString::to_string))
+        .filter(|rendered| !rendered.starts_with("error: aborting due to"))
+        .collect();
+    let expected = fs::read_to_string(test_dir.join("src/main.stderr")).unwrap_or_default();
+
+    let actual_path = build_dir.join("src/main.stderr");
+    let _ = fs::remove_file(&actual_path);
+    if !actual.is_empty() {
+        fs::create_dir_all(actual_path.parent().unwrap()).unwrap();
+        fs::write(&actual_path, &actual).unwrap();
+    }
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected:\n{}\nactual:\n{}\ncargo stderr:\n{}",
+            expected,
+            actual,
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
+
+const KNOWN_SUGGESTION_FAILURES: &str = "tests/suggestions/known_failures.txt";
+const MACHINE_APPLICABLE_LINTS: &str = "src/lintlist/machine_applicable.txt";
+/// Tests that use auxiliary crates or other files can't be compiled from the build directory.
+const SKIPPED_SUGGESTION_TESTS: [&str; 6] = [
+    "// aux-build:",
+    "// ignore-",
+    "#[path",
+    "include!",
+    "include_str!",
+    "include_bytes!",
+];
+
+/// Applies the machine applicable suggestions of every UI test, one lint at a time, and checks that
+/// the result compiles and doesn't emit the lint again. The `.fixed` files of `run-rustfix` tests
+/// must not have machine applicable suggestions left.
+///
+/// Lints that are known to claim a wrong `Applicability` are listed in
+/// `tests/suggestions/known_failures.txt`, the other lints with machine applicable suggestions in
+/// `src/lintlist/machine_applicable.txt`. Run with `BLESS=1` to update these lists.
+#[allow(clippy::too_many_lines)]
+fn run_suggestion_tests() {
+    if rustc_test_suite().is_some() {
+        return;
+    }
+
+    let config = config("ui", "tests/ui".into());
+    let flags: Vec<String> = config
+        .target_rustcflags
+        .as_ref()
+        .unwrap()
+        .split_whitespace()
+        .map(ToString::to_string)
+        .collect();
+    let build_base = config.build_base.join("suggestions");
+    let filter = var("TESTNAME").ok();
+
+    let mut failures: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut machine_applicable = BTreeSet::new();
+    for test in ui_test_files(&config.src_base) {
+        let name = test.strip_prefix(&config.src_base).unwrap().with_extension("");
+        if let Some(filter) = &filter {
+            if !name.to_string_lossy().contains(filter.as_str()) {
+                continue;
+            }
+        }
+        let source = fs::read_to_string(&test).unwrap();
+        if SKIPPED_SUGGESTION_TESTS.iter().any(|skip| source.contains(skip)) {
+            continue;
+        }
+        let mut args = flags.clone();
+        args.extend(compile_flags(&source));
+        let build_dir = build_base.join(&name);
+        fs::create_dir_all(&build_dir).unwrap();
+
+        let output = compile_json(&test, &args, &build_dir);
+        // Tests of code that doesn't compile have no suggestions to check.
+        if !compile_errors(&output).is_empty() {
+            continue;
+        }
+        for (lint, count) in lint_counts(&output) {
+            let suggestions = machine_applicable_suggestions(&output, &lint);
+            if suggestions.is_empty() {
+                continue;
+            }
+            machine_applicable.insert(lint.trim_start_matches("clippy::").to_string());
+            if let Err(err) = check_suggestions(&source, &suggestions, &lint, count, &args, &build_dir) {
+                failures
+                    .entry(lint)
+                    .or_default()
+                    .push(format!("{}: {}", test.display(), err));
+            }
+        }
+
+        let fixed = test.with_extension("fixed");
+        if fixed.exists() {
+            let fixed_path = build_dir.join(format!("{}.rs", name.file_name().unwrap().to_string_lossy()));
+            fs::copy(&fixed, &fixed_path).unwrap();
+            let output = compile_json(&fixed_path, &args, &build_dir);
+            for lint in lint_counts(&output).keys() {
+                if !machine_applicable_suggestions(&output, lint).is_empty() {
+                    failures
+                        .entry(lint.clone())
+                        .or_default()
+                        .push(format!("{}: has machine applicable suggestions left", fixed.display()));
+                }
+            }
+        }
+    }
+
+    let failed: BTreeSet<&str> = failures
+        .keys()
+        .map(|lint| lint.trim_start_matches("clippy::"))
+        .collect();
+    let verified: BTreeSet<&str> = machine_applicable
+        .iter()
+        .map(String::as_str)
+        .filter(|lint| !failed.contains(lint))
+        .collect();
+    if var("BLESS").is_ok() {
+        let list: String = failed.iter().map(|lint| format!("{}\n", lint)).collect();
+        fs::write(KNOWN_SUGGESTION_FAILURES, list).unwrap();
+        let list: String = verified.iter().map(|lint| format!("{}\n", lint)).collect();
+        fs::write(MACHINE_APPLICABLE_LINTS, list).unwrap();
+        return;
+    }
+
+    let known = fs::read_to_string(KNOWN_SUGGESTION_FAILURES).unwrap();
+    let known: BTreeSet<&str> = known.lines().filter(|line| !line.is_empty()).collect();
+    let mut errors = Vec::new();
+    for (lint, lint_failures) in &failures {
+        if !known.contains(lint.trim_start_matches("clippy::")) {
+            errors.push(format!(
+                "`{}` has wrong machine applicable suggestions:\n{}",
+                lint,
+                lint_failures.join("\n")
+            ));
+        }
+    }
+    if filter.is_none() {
+        for lint in known.difference(&failed) {
+            errors.push(format!(
+                "`clippy::{}` works now, remove it from `{}`",
+                lint, KNOWN_SUGGESTION_FAILURES
+            ));
+        }
+        let listed = fs::read_to_string(MACHINE_APPLICABLE_LINTS).unwrap();
+        let listed: BTreeSet<&str> = listed.lines().filter(|line| !line.is_empty()).collect();
+        if listed != verified {
+            errors.push(format!(
+                "`{}` is out of date, run with `BLESS=1` to update it",
+                MACHINE_APPLICABLE_LINTS
+            ));
+        }
+    }
+
+    assert!(errors.is_empty(), "{}\n", errors.join("\n\n"));
+}
+
+/// The arguments of the `// compile-flags:` headers of a test.
+fn compile_flags(source: &str) -> Vec<String> {
+    const HEADER: &str = "// compile-flags:";
+    let mut flags = Vec::new();
+    for line in source.lines().filter(|line| line.starts_with(HEADER)) {
+        flags.extend(line[HEADER.len()..].split_whitespace().map(ToString::to_string));
+    }
+    flags
+}
+
+/// The `.rs` files of the UI tests in `dir` and its subdirectories.
+fn ui_test_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in sorted_entries(dir) {
+        if path.is_dir() {
+            if path.file_name() != Some(OsStr::new("auxiliary")) {
+                files.extend(ui_test_files(&path));
+            }
+        } else if path.extension() == Some(OsStr::new("rs")) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// Applies the machine applicable `suggestions` for `lint`, which is emitted `count` times by the
+/// UI test `source`.
+fn check_suggestions(
+    source: &str,
+    suggestions: &[rustfix::Suggestion],
+    lint: &str,
+    count: usize,
+    args: &[String],
+    build_dir: &Path,
+) -> Result<(), String> {
+    let fixed = rustfix::apply_suggestions(source, suggestions)
+        .map_err(|err| format!("the suggestions can't be applied: {}", err))?;
+    let fixed_path = build_dir.join(format!("{}.rs", lint.trim_start_matches("clippy::")));
+    fs::write(&fixed_path, &fixed).unwrap();
+
+    let fixed_output = compile_json(&fixed_path, args, build_dir);
+    let errors = compile_errors(&fixed_output);
+    if !errors.is_empty() {
+        return Err(format!(
+            "the fixed code in `{}` doesn't compile: {}",
+            fixed_path.display(),
+            errors.join(", ")
+        ));
+    }
+    let left = lint_counts(&fixed_output).get(lint).cloned().unwrap_or(0);
+    // There's one suggestion for each warning with a machine applicable suggestion.
+    let without_suggestion = count - suggestions.len();
+    if left > without_suggestion {
+        return Err(format!(
+            "the fixed code in `{}` still emits the lint {} times, but only {} of the {} warnings had no \
+             machine applicable suggestion",
+            fixed_path.display(),
+            left,
+            without_suggestion,
+            count
+        ));
+    }
+    Ok(())
+}
+
+/// Compiles `file` with Clippy, capping all lints at warnings, and returns the JSON diagnostics.
+fn compile_json(file: &Path, args: &[String], build_dir: &Path) -> String {
+    let output = Command::new(clippy_driver_path())
+        .arg(file)
+        .args(args)
+        .args(&[
+            "--error-format=json",
+            "--emit=metadata",
+            "--cap-lints",
+            "warn",
+            "--out-dir",
+        ])
+        .arg(build_dir)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter_map(|line| {
+            if line.starts_with('{') {
+                Some(format!("{}\n", line))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn diagnostics(output: &str) -> impl Iterator<Item = serde_json::Value> + '_ {
+    output.lines().filter_map(|line| serde_json::from_str(line).ok())
+}
+
+fn compile_errors(output: &str) -> Vec<String> {
+    diagnostics(output)
+        .filter(|diag| diag["level"] == "error")
+        .filter_map(|diag| diag["message"].as_str().map(ToString::to_string))
+        .filter(|message| !message.starts_with("aborting due to"))
+        .collect()
+}
+
+/// How often each Clippy lint is emitted.
+fn lint_counts(output: &str) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for diag in diagnostics(output) {
+        if let Some(code) = diag["code"]["code"].as_str() {
+            if code.starts_with("clippy::") {
+                *counts.entry(code.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// The first machine applicable suggestion of each `lint` warning in `output`.
+///
+/// `rustfix::get_suggestions_from_json` skips suggestions that edit several spans, so they are
+/// collected from the JSON diagnostics here.
+fn machine_applicable_suggestions(output: &str, lint: &str) -> Vec<rustfix::Suggestion> {
+    diagnostics(output)
+        .filter(|diag| diag["code"]["code"] == lint)
+        .filter_map(|diag| {
+            let solution = diag["children"]
+                .as_array()?
+                .iter()
+                .find_map(machine_applicable_solution)?;
+            Some(rustfix::Suggestion {
+                message: diag["message"].as_str()?.to_string(),
+                snippets: Vec::new(),
+                solutions: vec![solution],
+            })
+        })
+        .collect()
+}
+
+/// The edits of the suggestion `child`, if all of them are machine applicable.
+fn machine_applicable_solution(child: &serde_json::Value) -> Option<rustfix::Solution> {
+    let spans = child["spans"].as_array()?;
+    if spans.is_empty()
+        || spans
+            .iter()
+            .any(|span| span["suggestion_applicability"] != "MachineApplicable")
+    {
+        return None;
+    }
+    let replacements = spans
+        .iter()
+        .map(|span| {
+            let number = |key: &str| span[key].as_u64().and_then(|number| usize::try_from(number).ok());
+            let position = |line: &str, column: &str| {
+                Some(rustfix::LinePosition {
+                    line: number(line)?,
+                    column: number(column)?,
+                })
+            };
+            Some(rustfix::Replacement {
+                snippet: rustfix::Snippet {
+                    file_name: span["file_name"].as_str()?.to_string(),
+                    line_range: rustfix::LineRange {
+                        start: position("line_start", "column_start")?,
+                        end: position("line_end", "column_end")?,
+                    },
+                    range: number("byte_start")?..number("byte_end")?,
+                    text: Default::default(),
+                },
+                replacement: span["suggested_replacement"].as_str()?.to_string(),
+            })
+        })
+        .collect::<Option<_>>()?;
+    Some(rustfix::Solution {
+        message: child["message"].as_str()?.to_string(),
+        replacements,
+    })
+}
+
 fn prepare_env() {
     set_var("CLIPPY_DISABLE_DOCS_LINKS", "true");
     set_var("CLIPPY_TESTS", "true");
@@ -160,4 +574,6 @@ fn compile_test() {
     prepare_env();
     run_mode("ui", "tests/ui".into());
     run_ui_toml();
+    run_ui_cargo();
+    run_suggestion_tests();
 }
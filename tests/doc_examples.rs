@@ -0,0 +1,384 @@
+//! Checks the examples in the documentation of every lint: examples of bad code have to emit the
+//! lint, examples of good code have to compile without emitting it.
+//!
+//! Examples that can't be fixed right away are listed in `tests/doc_examples/known_failures.txt`.
+//! Run with `BLESS=1` to update that list.
+
+#![allow(dead_code)]
+
+#[path = "../src/lintlist/mod.rs"]
+mod lintlist;
+
+use lintlist::{Lint, ALL_LINTS};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const KNOWN_FAILURES: &str = "tests/doc_examples/known_failures.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Bad,
+    Good,
+}
+
+#[derive(Debug, PartialEq)]
+struct Example {
+    lint: &'static str,
+    kind: Kind,
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    message: String,
+    code: Option<DiagnosticCode>,
+    level: String,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+#[test]
+fn doc_examples() {
+    if option_env!("RUSTC_TEST_SUITE").is_some() || cfg!(windows) {
+        return;
+    }
+
+    let examples: Vec<(String, Example)> = ALL_LINTS
+        .iter()
+        .flat_map(|lint| {
+            examples(lint)
+                .into_iter()
+                .enumerate()
+                .map(move |(index, example)| (format!("{}:{}", lint.name, index + 1), example))
+        })
+        .collect();
+
+    let build_dir = Path::new("target").join(env!("PROFILE")).join("doc_examples");
+    fs::create_dir_all(&build_dir).unwrap();
+
+    let queue = Arc::new(Mutex::new(examples.into_iter()));
+    let failures = Arc::new(Mutex::new(Vec::new()));
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let failures = Arc::clone(&failures);
+            let build_dir = build_dir.clone();
+            thread::spawn(move || loop {
+                let next = queue.lock().unwrap().next();
+                let (id, example) = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+                if let Err(err) = check(&id, &example, &build_dir) {
+                    failures.lock().unwrap().push((id, err));
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let failures = failures.lock().unwrap();
+    let failed: BTreeSet<&str> = failures.iter().map(|(id, _)| id.as_str()).collect();
+    if env::var("BLESS").is_ok() {
+        let list: String = failed.iter().map(|id| format!("{}\n", id)).collect();
+        fs::write(KNOWN_FAILURES, list).unwrap();
+        return;
+    }
+
+    let known = fs::read_to_string(KNOWN_FAILURES).unwrap();
+    let known: BTreeSet<&str> = known.lines().filter(|line| !line.is_empty()).collect();
+    let mut errors = Vec::new();
+    for (id, err) in failures.iter() {
+        if !known.contains(id.as_str()) {
+            errors.push(format!("{}: {}", id, err));
+        }
+    }
+    for id in known.difference(&failed) {
+        errors.push(format!("{}: works now, remove it from `{}`", id, KNOWN_FAILURES));
+    }
+
+    assert!(
+        errors.is_empty(),
+        "{} lint documentation examples are wrong:\n\n{}\n",
+        errors.len(),
+        errors.join("\n\n")
+    );
+}
+
+/// Compiles `example` with its lint enabled and checks that the lint is emitted for bad code only.
+fn check(id: &str, example: &Example, build_dir: &Path) -> Result<(), String> {
+    let crate_name = id.replace(':', "_");
+    let file = build_dir.join(format!("{}.rs", crate_name));
+    fs::write(&file, &example.code).unwrap();
+
+    let output = Command::new(clippy_driver_path())
+        .arg(&file)
+        .args(&["--crate-name", &crate_name, "--edition", "2018", "--emit=metadata"])
+        .arg("--out-dir")
+        .arg(build_dir)
+        .args(&["--error-format=json", "--cap-lints", "warn"])
+        .args(&["-W", &format!("clippy::{}", example.lint)])
+        .output()
+        .unwrap();
+
+    let diagnostics: Vec<Diagnostic> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .filter_map(|diag| {
+            if diag.level == "error" && !diag.message.starts_with("aborting due to") {
+                Some(diag.message.as_str())
+            } else {
+                None
+            }
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("doesn't compile: {}\n{}", errors.join(", "), example.code));
+    }
+
+    let lint = format!("clippy::{}", example.lint);
+    let emitted = diagnostics
+        .iter()
+        .any(|diag| diag.code.as_ref().map_or(false, |code| code.code == lint));
+    match (example.kind, emitted) {
+        (Kind::Bad, false) => Err(format!("doesn't emit `{}`:\n{}", lint, example.code)),
+        (Kind::Good, true) => Err(format!(
+            "emits `{}`, but is an example of good code:\n{}",
+            lint, example.code
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn clippy_driver_path() -> PathBuf {
+    if let Some(path) = option_env!("CLIPPY_DRIVER_PATH") {
+        PathBuf::from(path)
+    } else {
+        PathBuf::from(concat!("target/", env!("PROFILE"), "/clippy-driver"))
+    }
+}
+
+/// Extracts the examples from the `**Example:**` section of the documentation of `lint`.
+///
+/// The first code block shows bad code. Text after it, like "Could be written as:", introduces
+/// good code, unless it starts with "Bad" or "As another example", or is just "or". A code block
+/// can also contain both, introduced by `// Bad` and `// Good` comments. Code blocks marked
+/// `ignore` or `compile_fail` are skipped.
+fn examples(lint: &'static Lint) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut in_example = false;
+    let mut kind = Kind::Bad;
+    let mut seen_block = false;
+    let mut block: Option<(bool, Vec<&str>)> = None;
+
+    for line in lint.docs.lines() {
+        if let Some((checked, lines)) = &mut block {
+            if line.starts_with("```") {
+                if *checked {
+                    examples.extend(split_block(lint.name, kind, lines));
+                }
+                block = None;
+                seen_block = true;
+            } else {
+                lines.push(line);
+            }
+        } else if line.starts_with("```") {
+            block = Some((in_example && is_checked_block(&line[3..]), Vec::new()));
+        } else if line.starts_with("**") {
+            in_example = line.starts_with("**Example");
+            kind = Kind::Bad;
+            seen_block = false;
+        } else if let Some(marker) = marker(line) {
+            kind = marker;
+        } else if seen_block && !line.trim().is_empty() && !["or", "or:"].contains(&line.trim()) {
+            kind = Kind::Good;
+        }
+    }
+
+    examples
+}
+
+/// Splits a code block at `// Bad` and `// Good` comments. The lines before the first comment are
+/// part of every example, unless the comment introduces another kind of code than the block, like
+/// `// is easier written as` after bad code. Then only the hidden lines are shared.
+fn split_block(lint: &'static str, kind: Kind, lines: &[&str]) -> Vec<Example> {
+    let mut preamble = Vec::new();
+    let mut parts: Vec<(Kind, Vec<&str>)> = Vec::new();
+    for line in lines {
+        let comment = line.trim_start();
+        match (comment.starts_with("//"), marker(comment.trim_start_matches('/'))) {
+            (true, Some(kind)) => parts.push((kind, Vec::new())),
+            _ => match parts.last_mut() {
+                Some((_, part)) => part.push(line),
+                None => preamble.push(*line),
+            },
+        }
+    }
+
+    if parts.is_empty() {
+        return vec![Example {
+            lint,
+            kind,
+            code: program(&preamble),
+        }];
+    }
+    if parts[0].0 != kind {
+        let (hidden, code): (Vec<&str>, Vec<&str>) = preamble.into_iter().partition(|line| hidden_line(line).is_some());
+        parts.insert(0, (kind, code));
+        preamble = hidden;
+    }
+    parts.retain(|(_, part)| part.iter().any(|line| !line.trim().is_empty()));
+    parts
+        .into_iter()
+        .map(|(kind, part)| Example {
+            lint,
+            kind,
+            code: program(&preamble.iter().chain(&part).cloned().collect::<Vec<_>>()),
+        })
+        .collect()
+}
+
+/// Returns whether a line of text introduces bad or good code.
+fn marker(line: &str) -> Option<Kind> {
+    let line = line.trim().to_lowercase();
+    let starts_with_any = |prefixes: &[&str]| prefixes.iter().any(|prefix| line.starts_with(prefix));
+    if starts_with_any(&["bad", "wrong", "as another example"]) {
+        Some(Kind::Bad)
+    } else if starts_with_any(&[
+        "good",
+        "better",
+        "ok",
+        "use instead",
+        "instead",
+        "could be",
+        "can be written",
+        "can be rewritten",
+        "should be",
+        "is easier written",
+        "these can be",
+        "prefer",
+    ]) {
+        Some(Kind::Good)
+    } else {
+        None
+    }
+}
+
+/// Whether a code block with the info string `info` contains Rust code that should compile.
+fn is_checked_block(info: &str) -> bool {
+    info.split(',').map(str::trim).all(|attr| match attr {
+        "" | "rust" | "should_panic" | "no_run" | "edition2018" => true,
+        _ => false,
+    })
+}
+
+/// Returns the code of a line hidden from the documentation, like rustdoc: the line starts with
+/// `# ` or is just `#`, ignoring indentation.
+fn hidden_line(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    if trimmed == "#" {
+        Some("")
+    } else if trimmed.starts_with("# ") {
+        Some(&trimmed[2..])
+    } else {
+        None
+    }
+}
+
+/// Turns the lines of a code block into a program, like rustdoc does: hidden lines are included
+/// and the code is wrapped in `fn main` unless it has one.
+fn program(lines: &[&str]) -> String {
+    let lines: Vec<&str> = lines.iter().map(|&line| hidden_line(line).unwrap_or(line)).collect();
+
+    if lines.iter().any(|line| line.contains("fn main")) {
+        return lines.join("\n") + "\n";
+    }
+    let (crate_attrs, body): (Vec<&str>, Vec<&str>) = lines
+        .into_iter()
+        .partition(|line| line.starts_with("#![") || line.starts_with("extern crate"));
+    let mut program = String::new();
+    for line in crate_attrs {
+        program.push_str(line);
+        program.push('\n');
+    }
+    program.push_str("fn main() {\n");
+    for line in body {
+        program.push_str(line);
+        program.push('\n');
+    }
+    program.push_str("}\n");
+    program
+}
+
+#[test]
+fn extract_examples() {
+    static LINT: Lint = Lint {
+        name: "almost_swapped",
+        group: "correctness",
+        desc: "",
+        deprecation: None,
+        module: "swap",
+        docs: "**What it does:** Checks for `foo = bar; bar = foo` sequences.\n\n\
+               **Example:**\n```rust\n# let mut a = 1;\na = b;\n```\nCould be written as:\n\
+               ```rust\nlet b = 1;\n```\nor\n```rust\n// Bad\nlet a = 1;\n// Good\nlet b = 2;\n```\n\
+               ```ignore\nignored();\n```\n\n\
+               **Example:**\n```rust\n# let c = 3;\nlet a = c;\n// is easier written as\nlet b = c;\n```\n\n\
+               **Example:**\n```rust\nloop {\n    # break;\n}\n```\n\n\
+               **Known problems:**\n```rust\nlet not_an_example = 1;\n```",
+    };
+
+    assert_eq!(
+        vec![
+            Example {
+                lint: "almost_swapped",
+                kind: Kind::Bad,
+                code: "fn main() {\nlet mut a = 1;\na = b;\n}\n".to_string(),
+            },
+            Example {
+                lint: "almost_swapped",
+                kind: Kind::Good,
+                code: "fn main() {\nlet b = 1;\n}\n".to_string(),
+            },
+            Example {
+                lint: "almost_swapped",
+                kind: Kind::Bad,
+                code: "fn main() {\nlet a = 1;\n}\n".to_string(),
+            },
+            Example {
+                lint: "almost_swapped",
+                kind: Kind::Good,
+                code: "fn main() {\nlet b = 2;\n}\n".to_string(),
+            },
+            Example {
+                lint: "almost_swapped",
+                kind: Kind::Bad,
+                code: "fn main() {\nlet c = 3;\nlet a = c;\n}\n".to_string(),
+            },
+            Example {
+                lint: "almost_swapped",
+                kind: Kind::Good,
+                code: "fn main() {\nlet c = 3;\nlet b = c;\n}\n".to_string(),
+            },
+            Example {
+                lint: "almost_swapped",
+                kind: Kind::Bad,
+                code: "fn main() {\nloop {\nbreak;\n}\n}\n".to_string(),
+            },
+        ],
+        examples(&LINT)
+    );
+}
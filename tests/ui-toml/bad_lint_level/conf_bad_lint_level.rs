@@ -0,0 +1,3 @@
+// error-pattern: error reading Clippy's configuration file: invalid level `loud`
+
+fn main() {}
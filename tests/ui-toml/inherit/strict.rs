@@ -0,0 +1,8 @@
+#![warn(clippy::too_many_lines)]
+
+fn too_many_lines() {
+    println!("This is bad.");
+    println!("This is bad.");
+}
+
+fn main() {}
@@ -0,0 +1,3 @@
+// error-pattern: error reading Clippy's configuration file: `invalid.version` is not a valid Rust version
+
+fn main() {}
@@ -0,0 +1,17 @@
+// The lint levels are set in `clippy.toml`.
+
+fn bla() -> bool {
+    unimplemented!()
+}
+
+fn main() {
+    if !bla() {
+        println!("Bugs");
+    } else {
+        println!("Bunny");
+    }
+
+    let _ = 1;
+    fn foo() {}
+    foo();
+}
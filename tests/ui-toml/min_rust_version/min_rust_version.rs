@@ -0,0 +1,15 @@
+// None of these are linted because the suggestions don't compile with Rust 1.16.
+
+const STR: &'static str = "";
+
+fn question_mark(a: Option<u32>) -> Option<u32> {
+    if a.is_none() {
+        return None;
+    }
+    a
+}
+
+fn main() {
+    let _ = STR;
+    let _ = question_mark(None);
+}
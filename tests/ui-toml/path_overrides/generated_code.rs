@@ -0,0 +1,8 @@
+#![warn(clippy::too_many_lines)]
+
+fn too_many_lines() {
+    println!("This is generated.");
+    println!("This is generated.");
+}
+
+fn main() {}
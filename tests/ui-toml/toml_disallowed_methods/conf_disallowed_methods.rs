@@ -0,0 +1,11 @@
+#![warn(clippy::disallowed_methods)]
+
+fn main() {
+    std::env::set_var("KEY", "value");
+
+    let mut v = Vec::new();
+    v.push(1);
+
+    let _ = std::env::var("KEY");
+    let _ = v.len();
+}
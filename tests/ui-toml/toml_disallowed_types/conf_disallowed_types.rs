@@ -0,0 +1,14 @@
+#![warn(clippy::disallowed_types)]
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU32;
+
+pub struct Counters {
+    pub map: HashMap<String, AtomicU32>,
+}
+
+fn main() {
+    let _: Option<HashMap<u8, u8>> = None;
+    let _: Option<std::collections::BTreeMap<u8, u8>> = None;
+}
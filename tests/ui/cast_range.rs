@@ -0,0 +1,45 @@
+#![warn(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_possible_wrap)]
+#![allow(clippy::no_effect, clippy::unnecessary_operation, clippy::if_same_then_else)]
+
+fn masked(x: u32, y: i64) {
+    (x & 0xff) as u8;
+    (x >> 24) as u8;
+    (x % 100) as i8;
+    (y & 0x7fff) as u16;
+    y.max(0) as u64;
+    (x & 0x1ff) as u8;
+}
+
+fn guarded(x: u32, y: i32) {
+    if x < 256 {
+        x as u8;
+    } else {
+        x as u8;
+    }
+    if y >= 0 && y <= 1000 {
+        y as u16;
+        y as u32;
+    }
+    match x {
+        0..=127 => {
+            x as i8;
+        },
+        _ => {
+            x as i8;
+        },
+    }
+    let z = x % 10;
+    z as u8;
+}
+
+fn pointer_sized(x: usize) {
+    (x / 2) as i32;
+    (x >> 1) as u32;
+    (x >> 40) as u32;
+}
+
+fn main() {
+    masked(1, 2);
+    guarded(3, 4);
+    pointer_sized(5);
+}
@@ -93,6 +93,10 @@ fn main() {
     const B: u32 = 10;
     const C: u32 = A / B; // ok, different named constants
     const D: u32 = A / A;
+
+    // commutative operators
+    let (a, b) = (1u32, 2u32);
+    a ^ b ^ 1 == b ^ (1 ^ a);
 }
 
 #[rustfmt::skip]
@@ -6,12 +6,6 @@ fn main() {
     for _v in &vec {
         _index += 1
     }
-
-    let mut _index = 1;
-    _index = 0;
-    for _v in &vec {
-        _index += 1
-    }
 }
 
 mod issue_1219 {
@@ -45,28 +39,6 @@ mod issue_1219 {
             println!("{}", count);
         }
 
-        // should trigger the lint because the count is not conditional
-        let text = "banana";
-        let mut count = 0;
-        for ch in text.chars() {
-            count += 1;
-            if ch == 'a' {
-                continue;
-            }
-            println!("{}", count);
-        }
-
-        // should trigger the lint because the count is not conditional
-        let text = "banana";
-        let mut count = 0;
-        for ch in text.chars() {
-            count += 1;
-            for i in 0..2 {
-                let _ = 123;
-            }
-            println!("{}", count);
-        }
-
         // should not trigger the lint because the count is incremented multiple times
         let text = "banana";
         let mut count = 0;
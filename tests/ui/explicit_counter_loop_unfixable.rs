@@ -0,0 +1,36 @@
+#![warn(clippy::explicit_counter_loop)]
+
+// The suggestions for these loops change the behavior of the code, so they can't be applied
+// automatically.
+
+fn main() {
+    let vec = vec![1, 2, 3, 4];
+
+    // the counter is reassigned before the loop
+    let mut _index = 1;
+    _index = 0;
+    for _v in &vec {
+        _index += 1
+    }
+
+    // the counter is incremented before it is used
+    let text = "banana";
+    let mut count = 0;
+    for ch in text.chars() {
+        count += 1;
+        if ch == 'a' {
+            continue;
+        }
+        println!("{}", count);
+    }
+
+    let text = "banana";
+    let mut count = 0;
+    for ch in text.chars() {
+        count += 1;
+        for i in 0..2 {
+            let _ = 123;
+        }
+        println!("{}", count);
+    }
+}
@@ -1,6 +1,6 @@
 #![warn(clippy::float_cmp_const)]
 #![allow(clippy::float_cmp)]
-#![allow(unused, clippy::no_effect, clippy::unnecessary_operation)]
+#![allow(unused, clippy::no_effect, clippy::unnecessary_operation, clippy::cast_lossless)]
 
 const ONE: f32 = 1.0;
 const TWO: f32 = 2.0;
@@ -19,6 +19,7 @@ fn main() {
     TWO == ONE;
     TWO != ONE;
     ONE + ONE == TWO;
+    ONE as f64 == 1.0;
     let x = 1;
     x as f32 == ONE;
 
@@ -273,4 +273,30 @@ fn func() {
 
 fn f(val: &[u8]) {}
 
+fn commutative(a: u32, b: u32) -> u32 {
+    if a < b {
+        a ^ b ^ 1
+    } else {
+        b ^ (1 ^ a)
+    }
+}
+
+// Calls are never reordered, they could have side effects.
+fn calls_are_not_reordered() -> bool {
+    if true {
+        foo() == !foo()
+    } else {
+        !foo() == foo()
+    }
+}
+
+// Operands with calls aren't swapped either, not even for commutative operators.
+fn calls_are_not_swapped(x: &[u8]) -> usize {
+    if true {
+        x.len() + 1
+    } else {
+        1 + x.len()
+    }
+}
+
 fn main() {}
@@ -22,6 +22,16 @@ fn ifs_same_cond() {
     } else if a == 1 {
     }
 
+    if a + 1 == 2 {
+    } else if 2 == 1 + a {
+        //~ ERROR ifs same condition
+    }
+
+    if a < 2 {
+    } else if 2 > a {
+        //~ ERROR ifs same condition
+    }
+
     // See #659
     if cfg!(feature = "feature1-659") {
         1
@@ -55,4 +55,17 @@ fn main() {
     x[M]; // Ok, should not produce stderr.
     v[N];
     v[M];
+
+    x[index_to % 4]; // Ok, should not produce stderr.
+    if index_to < v.len() {
+        v[index_to]; // Ok, should not produce stderr.
+    }
+    if index_to <= v.len() {
+        v[index_to];
+    }
+    index_param(x, index);
+}
+
+fn index_param(x: [i32; 4], index: usize) {
+    x[index];
 }
@@ -143,4 +143,13 @@ mod issue4244 {
     }
 }
 
+// Operands with calls aren't swapped, not even for commutative operators.
+fn calls_are_not_swapped(x: &[u8], n: u8) -> usize {
+    match n {
+        0 => x.len() + 1,
+        1 => 1 + x.len(),
+        _ => 0,
+    }
+}
+
 fn main() {}
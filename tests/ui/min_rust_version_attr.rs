@@ -0,0 +1,38 @@
+#![feature(custom_inner_attributes)]
+#![clippy::msrv = "1.0"]
+#![allow(dead_code)]
+
+fn question_mark(a: Option<u32>) -> Option<u32> {
+    // no lint, `?` works on `Option` since 1.22
+    if a.is_none() {
+        return None;
+    }
+    a
+}
+
+fn saturating(a: u32) -> u32 {
+    // no lint, `saturating_mul` is stable since 1.7
+    a.checked_mul(2).unwrap_or(u32::max_value())
+}
+
+#[clippy::msrv = "1.22"]
+fn newer_question_mark(a: Option<u32>) -> Option<u32> {
+    if a.is_none() {
+        return None;
+    }
+    a
+}
+
+mod newer {
+    #![clippy::msrv = "1.30.1"]
+
+    fn saturating(a: u32) -> u32 {
+        a.checked_mul(2).unwrap_or(u32::max_value())
+    }
+}
+
+fn main() {
+    let _ = question_mark(None);
+    let _ = saturating(1);
+    let _ = newer_question_mark(None);
+}
@@ -0,0 +1,18 @@
+#![feature(custom_inner_attributes)]
+#![clippy::msrv = "1.0"]
+#![allow(dead_code)]
+
+// Early lint passes are split from `min_rust_version_attr.rs`, since their errors stop
+// compilation before the late lint passes run.
+
+const STR: &'static str = "no lint, `'static` can only be elided since 1.17";
+
+mod newer {
+    #![clippy::msrv = "1.17"]
+
+    const STR: &'static str = "";
+}
+
+fn main() {
+    let _ = STR;
+}
@@ -1,5 +1,11 @@
 #![warn(clippy::out_of_bounds_indexing)]
-#![allow(clippy::no_effect, clippy::unnecessary_operation)]
+#![allow(
+    clippy::no_effect,
+    clippy::unnecessary_operation,
+    clippy::identity_conversion,
+    clippy::op_ref,
+    exceeding_bitshifts
+)]
 
 fn main() {
     let x = [1, 2, 3, 4];
@@ -19,4 +25,33 @@ fn main() {
 
     &x[0..].get(..3); // Ok, should not produce stderr.
     &x[0..3]; // Ok, should not produce stderr.
+
+    const LEN: u8 = 5;
+    const SIZES: [usize; 2] = [4, 5];
+    &x[..LEN as usize];
+    &x[..len()];
+    &x[..SIZES[1]];
+    &x[..LEN as usize - 1]; // Ok, should not produce stderr.
+    &x[..(300u16 as u8) as usize];
+    &x[(-1i8 as u8) as usize..];
+    &x[..(u8::from(X) << 1) as usize];
+    &x[..(u8::from(X) << 9) as usize]; // Ok, the shift overflows and isn't evaluated.
+    &x[..CONFIG.len];
+    &x[PAIR.1..];
+    &x[..if &LEN > &4 { 5 } else { 0 }];
+    &x[..Wrapper(5).0]; // Ok, constructors aren't evaluated.
+}
+
+const fn len() -> usize {
+    5
 }
+
+struct Config {
+    len: usize,
+}
+
+struct Wrapper(usize);
+
+const CONFIG: Config = Config { len: 5 };
+const PAIR: (usize, usize) = (1, 5);
+const X: u8 = 3;
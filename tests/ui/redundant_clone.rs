@@ -56,3 +56,24 @@ fn cannot_move_from_type_with_drop() -> String {
     let s = TypeWithDrop { x: String::new() };
     s.x.clone() // removing this `clone()` summons E0509
 }
+
+fn borrowed_while_cloned() {
+    let s = String::new();
+    let r = &s;
+    let _t = s.clone(); // ok, `s` is still borrowed by `r`
+    let _ = r.len();
+}
+
+fn clone_in_loop(v: &mut Vec<String>) {
+    let s = String::new();
+    for _ in 0..3 {
+        v.push(s.clone()); // ok, `s` is cloned again in the next iteration
+    }
+}
+
+fn redundant_clone_in_loop(v: &mut Vec<String>) {
+    for i in 0..3 {
+        let s = i.to_string();
+        v.push(s.clone());
+    }
+}
@@ -0,0 +1,31 @@
+#![warn(clippy::unused_clippy_allow)]
+#![allow(dead_code, clippy::blacklisted_name)]
+
+#[allow(clippy::too_many_arguments)]
+fn one_arg(_a: u8) {}
+
+#[allow(clippy::too_many_arguments)]
+fn many_args(_a: u8, _b: u8, _c: u8, _d: u8, _e: u8, _f: u8, _g: u8, _h: u8) {}
+
+#[allow(unused, clippy::needless_return, clippy::too_many_arguments)]
+fn partially_used() -> u8 {
+    return 1;
+}
+
+// the attribute on `inner` suppresses the lint
+#[allow(clippy::needless_return)]
+mod outer {
+    #[allow(clippy::needless_return)]
+    fn inner() -> u8 {
+        return 1;
+    }
+}
+
+// lint groups are not checked
+#[allow(clippy::pedantic)]
+fn group() {}
+
+fn main() {
+    let foo = 1;
+    let _ = foo;
+}